@@ -0,0 +1 @@
+drop table denylist_audit_log;
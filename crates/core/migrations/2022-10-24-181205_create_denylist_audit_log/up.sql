@@ -0,0 +1,12 @@
+create table denylist_audit_log (
+  id          uuid        primary key default gen_random_uuid(),
+  list        text        not null check (list in ('store', 'listing')),
+  address     varchar(48) not null,
+  action      text        not null check (action in ('add', 'remove')),
+  hard_ban    boolean,
+  reason      text        not null,
+  actor       text        not null,
+  created_at  timestamp   not null
+);
+
+create index denylist_audit_log_address_idx on denylist_audit_log (address, created_at desc);
@@ -0,0 +1,9 @@
+drop table image_derivative_failures;
+
+drop index files_source_uri_idx;
+
+delete from files where source_uri is not null;
+
+alter table files
+  drop column source_uri,
+  drop column width;
@@ -0,0 +1,15 @@
+alter table files
+  add column source_uri text    null,
+  add column width      integer null;
+
+create index files_source_uri_idx
+  on files (metadata_address, source_uri)
+  where source_uri is not null;
+
+create table image_derivative_failures (
+  metadata_address  varchar(48) primary key not null,
+  source_uri        text        not null,
+  attempts          integer     not null,
+  error             text        not null,
+  updated_at        timestamp   not null
+);
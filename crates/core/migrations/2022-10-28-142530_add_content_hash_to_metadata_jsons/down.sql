@@ -0,0 +1,5 @@
+alter table metadata_jsons
+  drop column content_hash,
+  drop column content_source,
+  drop column cid_verified,
+  drop column content_changed_at;
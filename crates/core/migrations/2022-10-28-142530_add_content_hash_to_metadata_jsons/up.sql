@@ -0,0 +1,5 @@
+alter table metadata_jsons
+  add column content_hash       bytea     null,
+  add column content_source     text      null check (content_source in ('ipfs', 'arweave', 'http')),
+  add column cid_verified       boolean   null,
+  add column content_changed_at timestamp null;
@@ -0,0 +1,11 @@
+create table metadata_json_refetches (
+  metadata_address  varchar(48) primary key not null,
+  interval_seconds  integer     not null,
+  next_fetch_at     timestamp   not null,
+  pending           boolean     not null default false,
+  last_fetched_at   timestamp   null,
+  last_changed_at   timestamp   null
+);
+
+create index metadata_json_refetches_next_fetch_at_idx
+  on metadata_json_refetches (next_fetch_at);
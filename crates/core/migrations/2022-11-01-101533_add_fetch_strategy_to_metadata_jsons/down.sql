@@ -0,0 +1,2 @@
+alter table metadata_jsons
+  drop column fetch_strategy;
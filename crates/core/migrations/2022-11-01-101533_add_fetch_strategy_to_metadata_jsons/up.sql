@@ -0,0 +1,2 @@
+alter table metadata_jsons
+  add column fetch_strategy text null;
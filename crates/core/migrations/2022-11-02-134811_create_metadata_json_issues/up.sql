@@ -0,0 +1,19 @@
+create table metadata_json_issues (
+  id                uuid        primary key default gen_random_uuid(),
+  metadata_address  varchar(48) not null,
+  code              text        not null check (code in (
+    'parse_error',
+    'missing_image',
+    'invalid_seller_fee_basis_points',
+    'creator_share_mismatch',
+    'invalid_file',
+    'unsupported_category'
+  )),
+  path              text        not null,
+  message           text        not null,
+  slot              bigint      not null,
+  write_version     bigint      not null
+);
+
+create index metadata_json_issues_metadata_address_idx
+  on metadata_json_issues (metadata_address);
@@ -0,0 +1,7 @@
+drop index attributes_trait_type_value_numeric_idx;
+
+alter table attributes
+  drop column value_type,
+  drop column display_type,
+  drop column value_numeric,
+  drop column max_value;
@@ -0,0 +1,9 @@
+alter table attributes
+  add column value_type    text             null check (value_type in ('string', 'number', 'boolean')),
+  add column display_type  text             null,
+  add column value_numeric double precision null,
+  add column max_value     double precision null;
+
+create index attributes_trait_type_value_numeric_idx
+  on attributes (trait_type, value_numeric)
+  where value_numeric is not null;
@@ -0,0 +1,10 @@
+drop table store_config_json_issues;
+
+alter table store_config_jsons
+  drop column schema_version,
+  drop column primary_color,
+  drop column background_color,
+  drop column text_color,
+  drop column title_font,
+  drop column text_font,
+  drop column custom_domain;
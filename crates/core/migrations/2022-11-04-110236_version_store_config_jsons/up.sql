@@ -0,0 +1,18 @@
+alter table store_config_jsons
+  add column schema_version   integer not null default 1,
+  add column primary_color    text    null,
+  add column background_color text    null,
+  add column text_color       text    null,
+  add column title_font       text    null,
+  add column text_font        text    null,
+  add column custom_domain    text    null;
+
+create table store_config_json_issues (
+  id              uuid        primary key default gen_random_uuid(),
+  config_address  varchar(48) not null,
+  path            text        not null,
+  message         text        not null
+);
+
+create index store_config_json_issues_config_address_idx
+  on store_config_json_issues (config_address);
@@ -0,0 +1,9 @@
+drop trigger rarity_metadata_burned on metadatas;
+drop function rarity_metadata_burned();
+drop trigger rarity_collection_keys_changed on metadata_collection_keys;
+drop function rarity_collection_keys_changed();
+drop trigger rarity_attributes_changed on attributes;
+drop function rarity_attributes_changed();
+drop function rarity_mark_collection_dirty(varchar);
+drop table rarity_dirty_collections;
+drop table nft_rarities;
@@ -0,0 +1,105 @@
+create table nft_rarities (
+  metadata_address   varchar(48)      not null,
+  method             text             not null check (method in ('statistical', 'information_content', 'trait_normalized')),
+  collection_address varchar(48)      not null,
+  score              double precision not null,
+  rank               integer          not null,
+  updated_at         timestamp        not null default now(),
+  primary key (metadata_address, method)
+);
+
+create index nft_rarities_collection_address_method_rank_idx
+  on nft_rarities (collection_address, method, rank);
+
+-- Verified collections whose rarity scores need to be recomputed
+create table rarity_dirty_collections (
+  collection_address varchar(48) primary key not null,
+  marked_at          timestamp   not null default now()
+);
+
+insert into rarity_dirty_collections (collection_address)
+  select distinct collection_address
+  from metadata_collection_keys
+  where verified;
+
+create or replace function rarity_mark_collection_dirty(address varchar)
+  returns void
+  as
+$$
+begin
+  insert into rarity_dirty_collections (collection_address)
+    values (address)
+    on conflict (collection_address) do update
+      set marked_at = now();
+end;
+$$ language plpgsql;
+
+create or replace function rarity_attributes_changed()
+  returns trigger
+  as
+$$
+declare
+  addr varchar;
+begin
+  if tg_op = 'DELETE' then
+    addr := old.metadata_address;
+  else
+    addr := new.metadata_address;
+  end if;
+
+  perform rarity_mark_collection_dirty(collection_address)
+    from metadata_collection_keys
+    where metadata_address = addr and verified;
+
+  return null;
+end;
+$$ language plpgsql;
+
+create trigger rarity_attributes_changed
+  after insert or update or delete
+  on attributes
+  for each row
+  execute procedure rarity_attributes_changed();
+
+create or replace function rarity_collection_keys_changed()
+  returns trigger
+  as
+$$
+begin
+  if tg_op <> 'INSERT' and old.verified then
+    perform rarity_mark_collection_dirty(old.collection_address);
+  end if;
+
+  if tg_op <> 'DELETE' and new.verified then
+    perform rarity_mark_collection_dirty(new.collection_address);
+  end if;
+
+  return null;
+end;
+$$ language plpgsql;
+
+create trigger rarity_collection_keys_changed
+  after insert or update or delete
+  on metadata_collection_keys
+  for each row
+  execute procedure rarity_collection_keys_changed();
+
+create or replace function rarity_metadata_burned()
+  returns trigger
+  as
+$$
+begin
+  perform rarity_mark_collection_dirty(collection_address)
+    from metadata_collection_keys
+    where metadata_address = new.address and verified;
+
+  return null;
+end;
+$$ language plpgsql;
+
+create trigger rarity_metadata_burned
+  after update of burned_at
+  on metadatas
+  for each row
+  when (old.burned_at is distinct from new.burned_at)
+  execute procedure rarity_metadata_burned();
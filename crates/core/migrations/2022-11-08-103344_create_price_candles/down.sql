@@ -0,0 +1 @@
+drop table price_candles;
@@ -0,0 +1,20 @@
+create table price_candles (
+  collection_address varchar(48) not null,
+  auction_house      varchar(48) not null,
+  resolution         text        not null check (resolution in ('1m', '1h', '1d')),
+  bucket_start       timestamp   not null,
+  open               bigint      not null,
+  high               bigint      not null,
+  low                bigint      not null,
+  close              bigint      not null,
+  volume             numeric     not null,
+  sales              bigint      not null,
+  -- Times of the sales setting open and close, used to merge out-of-order
+  -- sales and to combine candles across auction houses
+  opened_at          timestamp   not null,
+  closed_at          timestamp   not null,
+  primary key (collection_address, resolution, bucket_start, auction_house)
+);
+
+create index price_candles_auction_house_resolution_bucket_start_idx
+  on price_candles (auction_house, resolution, bucket_start);
@@ -0,0 +1 @@
+drop table collection_buyer_buckets;
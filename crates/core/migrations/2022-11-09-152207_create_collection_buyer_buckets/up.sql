@@ -0,0 +1,10 @@
+-- Distinct buyers of each verified collection per time bucket, used to count
+-- unique buyers over arbitrary windows
+create table collection_buyer_buckets (
+  collection_address varchar(48) not null,
+  resolution         text        not null check (resolution in ('1m', '1h', '1d')),
+  bucket_start       timestamp   not null,
+  buyer              varchar(48) not null,
+  sales              bigint      not null,
+  primary key (collection_address, resolution, bucket_start, buyer)
+);
@@ -0,0 +1,11 @@
+alter table price_candles
+  drop column currency_mint,
+  drop column normalized_open,
+  drop column normalized_high,
+  drop column normalized_low,
+  drop column normalized_close,
+  drop column normalized_volume;
+
+drop function normalize_price(varchar, numeric, timestamp);
+drop table currency_rates;
+drop table currencies;
@@ -0,0 +1,53 @@
+-- SPL tokens used as auction house treasury mints
+create table currencies (
+  mint     varchar(48) primary key not null,
+  symbol   text        not null,
+  decimals smallint    not null check (decimals >= 0)
+);
+
+insert into currencies (mint, symbol, decimals) values
+  ('So11111111111111111111111111111111111111112', 'SOL', 9),
+  ('EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v', 'USDC', 6);
+
+-- Historical prices of one whole token in SOL, effective from the given time
+-- until the next rate for the same mint
+create table currency_rates (
+  mint         varchar(48) not null,
+  effective_at timestamp   not null,
+  sol_price    numeric     not null check (sol_price >= 0),
+  primary key (mint, effective_at)
+);
+
+-- Convert an amount in the smallest unit of the given mint to lamports of
+-- SOL using the rate in effect at the given time, or null if the mint or its
+-- rate at that time is unknown
+create or replace function normalize_price(currency varchar, amount numeric, at timestamp)
+  returns numeric
+  stable
+  as
+$$
+  select case
+    when currency = 'So11111111111111111111111111111111111111112' then amount
+    else (
+      select round(amount * r.sol_price * 1000000000 / power(10::numeric, c.decimals))
+      from currencies c
+      inner join currency_rates r on r.mint = c.mint
+      where c.mint = currency and r.effective_at <= at
+      order by r.effective_at desc
+      limit 1
+    )
+  end;
+$$ language sql;
+
+alter table price_candles
+  add column currency_mint     varchar(48),
+  add column normalized_open   numeric,
+  add column normalized_high   numeric,
+  add column normalized_low    numeric,
+  add column normalized_close  numeric,
+  add column normalized_volume numeric;
+
+update price_candles pc
+  set currency_mint = ah.treasury_mint
+  from auction_houses ah
+  where ah.address = pc.auction_house;
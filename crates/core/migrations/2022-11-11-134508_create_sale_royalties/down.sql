@@ -0,0 +1,9 @@
+drop trigger sale_royalties_execute_sale_inserted on execute_sale_instructions;
+drop function sale_royalties_execute_sale_inserted();
+
+drop trigger sale_royalties_purchase_inserted on purchases;
+drop function sale_royalties_purchase_inserted();
+
+drop table sale_royalties;
+
+alter table execute_sale_instructions drop column creator_accounts;
@@ -0,0 +1,95 @@
+-- Creator accounts passed to auction house ExecuteSale, which pays each of
+-- them their share of the royalty
+alter table execute_sale_instructions
+  add column creator_accounts varchar(48)[] not null default '{}';
+
+-- The royalty owed to each creator for each sale, and the amount paid where
+-- it can be determined
+create table sale_royalties (
+  purchase_id         uuid        not null,
+  creator_address     varchar(48) not null,
+  metadata_address    varchar(48) not null,
+  auction_house       varchar(48) not null,
+  marketplace_program varchar(48) not null,
+  currency_mint       varchar(48),
+  sold_at             timestamp   not null,
+  share               integer     not null,
+  expected            bigint      not null,
+  -- Null if the payment could not be determined
+  paid                bigint,
+  primary key (purchase_id, creator_address)
+);
+
+create index sale_royalties_creator_address_sold_at_idx
+  on sale_royalties (creator_address, sold_at);
+
+create index sale_royalties_metadata_address_idx
+  on sale_royalties (metadata_address);
+
+create index sale_royalties_marketplace_program_sold_at_idx
+  on sale_royalties (marketplace_program, sold_at);
+
+-- Record the royalties owed for a new sale, split between the verified
+-- creators of the NFT as the auction house program pays them
+create or replace function sale_royalties_purchase_inserted()
+  returns trigger
+  as
+$$
+begin
+  insert into sale_royalties (
+    purchase_id, creator_address, metadata_address, auction_house,
+    marketplace_program, currency_mint, sold_at, share, expected, paid
+  )
+  select new.id, mc.creator_address, new.metadata, new.auction_house,
+         new.marketplace_program, ah.treasury_mint, new.created_at, mc.share,
+         div(div(new.price::numeric * md.seller_fee_basis_points, 10000) * mc.share, 100)::bigint,
+         null
+  from metadatas md
+  inner join metadata_creators mc
+    on mc.metadata_address = md.address and mc.share > 0
+  left join auction_houses ah on ah.address = new.auction_house
+  where md.address = new.metadata and md.seller_fee_basis_points > 0
+  on conflict do nothing;
+
+  return null;
+end;
+$$ language plpgsql;
+
+create trigger sale_royalties_purchase_inserted
+  after insert
+  on purchases
+  for each row
+  execute procedure sale_royalties_purchase_inserted();
+
+-- Auction house ExecuteSale fails unless every creator is passed and paid,
+-- so a recorded instruction settles the royalties of its sale
+create or replace function sale_royalties_execute_sale_inserted()
+  returns trigger
+  as
+$$
+begin
+  if cardinality(new.creator_accounts) = 0 then
+    return null;
+  end if;
+
+  update sale_royalties sr
+    set paid = case when sr.creator_address = any(new.creator_accounts)
+                 then sr.expected else 0 end
+    from purchases p
+    where sr.purchase_id = p.id
+      and p.buyer = new.buyer
+      and p.seller = new.seller
+      and p.auction_house = new.auction_house
+      and p.metadata = new.metadata
+      and p.token_size = new.token_size
+      and p.price = new.buyer_price;
+
+  return null;
+end;
+$$ language plpgsql;
+
+create trigger sale_royalties_execute_sale_inserted
+  after insert
+  on execute_sale_instructions
+  for each row
+  execute procedure sale_royalties_execute_sale_inserted();
@@ -0,0 +1,15 @@
+drop trigger price_candles_wash_trade_inserted on wash_trades;
+drop function price_candles_wash_trade_inserted();
+
+drop trigger wash_trades_purchase_inserted on purchases;
+drop function wash_trades_purchase_inserted();
+
+drop function detect_wash_trades(uuid);
+drop function flag_wash_trade(uuid, text[]);
+
+alter table price_candles
+  drop column flagged_volume,
+  drop column flagged_sales,
+  drop column flagged_normalized_volume;
+
+drop table wash_trades;
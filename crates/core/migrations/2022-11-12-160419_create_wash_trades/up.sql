@@ -0,0 +1,155 @@
+-- Sales suspected of being wash trades, with the reasons each was flagged
+create table wash_trades (
+  purchase_id uuid      primary key not null,
+  reasons     text[]    not null check (
+    cardinality(reasons) > 0
+    and reasons <@ array['self_trade', 'round_trip', 'linked_wallets', 'zero_fee']
+  ),
+  detected_at timestamp not null
+);
+
+-- Volume and sale counts of the flagged sales in each candle, so flagged
+-- volume can be excluded without scanning purchases
+alter table price_candles
+  add column flagged_volume            numeric not null default 0,
+  add column flagged_sales             bigint  not null default 0,
+  -- Null if any flagged sale in the bucket could not be normalized
+  add column flagged_normalized_volume numeric default 0;
+
+-- Record the reasons a sale was flagged, merging them with any reasons it
+-- was already flagged for
+create or replace function flag_wash_trade(purchase uuid, new_reasons text[])
+  returns void
+  as
+$$
+begin
+  insert into wash_trades (purchase_id, reasons, detected_at)
+  values (purchase, new_reasons, now())
+  on conflict (purchase_id) do update
+    set reasons = array(
+      select distinct r
+      from unnest(wash_trades.reasons || excluded.reasons) as r
+      order by r
+    );
+end;
+$$ language plpgsql;
+
+-- Check a sale for signs of wash trading:
+--  - self_trade: the buyer and seller are the same wallet
+--  - round_trip: the buyer sold the same NFT within the previous seven days;
+--    every sale of the NFT since then is flagged along with it
+--  - linked_wallets: the buyer and seller are connected in the wallet graph
+--  - zero_fee: the sale was made through an auction house charging no fee
+create or replace function detect_wash_trades(purchase uuid)
+  returns void
+  as
+$$
+declare
+  p purchases%rowtype;
+  reasons text[] := '{}';
+  loop_start timestamp;
+begin
+  select * into p from purchases where id = purchase;
+
+  if not found then
+    return;
+  end if;
+
+  if p.buyer = p.seller then
+    reasons := reasons || 'self_trade'::text;
+  end if;
+
+  select max(prev.created_at) into loop_start
+    from purchases prev
+    where prev.metadata = p.metadata
+      and prev.seller = p.buyer
+      and prev.id <> p.id
+      and prev.created_at <= p.created_at
+      and prev.created_at >= p.created_at - interval '7 days';
+
+  if loop_start is not null and p.buyer <> p.seller then
+    reasons := reasons || 'round_trip'::text;
+
+    perform flag_wash_trade(lp.id, array['round_trip'])
+      from purchases lp
+      where lp.metadata = p.metadata
+        and lp.id <> p.id
+        and lp.created_at >= loop_start
+        and lp.created_at <= p.created_at;
+  end if;
+
+  if exists (
+    select 1
+    from graph_connections gc
+    where (gc.from_account = p.buyer and gc.to_account = p.seller)
+      or (gc.from_account = p.seller and gc.to_account = p.buyer)
+  ) then
+    reasons := reasons || 'linked_wallets'::text;
+  end if;
+
+  if exists (
+    select 1
+    from auction_houses ah
+    where ah.address = p.auction_house and ah.seller_fee_basis_points = 0
+  ) then
+    reasons := reasons || 'zero_fee'::text;
+  end if;
+
+  if cardinality(reasons) > 0 then
+    perform flag_wash_trade(p.id, reasons);
+  end if;
+end;
+$$ language plpgsql;
+
+create or replace function wash_trades_purchase_inserted()
+  returns trigger
+  as
+$$
+begin
+  perform detect_wash_trades(new.id);
+
+  return null;
+end;
+$$ language plpgsql;
+
+-- Fires after price_candles_purchase_inserted, so the candles of the new
+-- sale exist by the time it is flagged
+create trigger wash_trades_purchase_inserted
+  after insert
+  on purchases
+  for each row
+  execute procedure wash_trades_purchase_inserted();
+
+-- Move a newly-flagged sale's volume into the flagged columns of its
+-- candles.  Merging further reasons into an existing flag is an update and
+-- does not fire this.
+create or replace function price_candles_wash_trade_inserted()
+  returns trigger
+  as
+$$
+begin
+  update price_candles pc
+    set flagged_volume = pc.flagged_volume + p.price,
+        flagged_sales = pc.flagged_sales + 1,
+        flagged_normalized_volume = pc.flagged_normalized_volume
+          + normalize_price(ah.treasury_mint, p.price, p.created_at)
+    from purchases p
+    inner join metadata_collection_keys mck
+      on mck.metadata_address = p.metadata and mck.verified
+    left join auction_houses ah on ah.address = p.auction_house
+    cross join (values ('1m', 'minute'), ('1h', 'hour'), ('1d', 'day')) as r (resolution, unit)
+    where p.id = new.purchase_id
+      and pc.collection_address = mck.collection_address
+      and pc.auction_house = p.auction_house
+      and pc.resolution = r.resolution
+      and pc.bucket_start = date_trunc(r.unit, p.created_at);
+
+  return null;
+end;
+$$ language plpgsql;
+
+create trigger price_candles_wash_trade_inserted
+  after insert
+  on wash_trades
+  for each row
+  execute procedure price_candles_wash_trade_inserted();
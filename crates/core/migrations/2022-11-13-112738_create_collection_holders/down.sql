@@ -0,0 +1,13 @@
+drop trigger collection_holders_metadata_burned on metadatas;
+drop function collection_holders_metadata_burned();
+
+drop trigger collection_holders_collection_keys_changed on metadata_collection_keys;
+drop function collection_holders_collection_keys_changed();
+
+drop trigger collection_holders_owner_changed on current_metadata_owners;
+drop function collection_holders_owner_changed();
+
+drop function collection_holders_adjust(varchar, varchar, bigint, timestamp);
+
+drop table collection_holder_days;
+drop table collection_holders;
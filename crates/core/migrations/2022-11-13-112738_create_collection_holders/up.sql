@@ -0,0 +1,170 @@
+-- The number of NFTs of each verified collection held by each wallet
+create table collection_holders (
+  collection_address varchar(48) not null,
+  owner_address      varchar(48) not null,
+  nft_count          bigint      not null check (nft_count > 0),
+  primary key (collection_address, owner_address)
+);
+
+create index collection_holders_collection_address_nft_count_idx
+  on collection_holders (collection_address, nft_count desc);
+
+-- Wallets which started or stopped holding NFTs of each verified collection
+-- per day
+create table collection_holder_days (
+  collection_address varchar(48) not null,
+  day                date        not null,
+  new_holders        bigint      not null default 0,
+  churned_holders    bigint      not null default 0,
+  primary key (collection_address, day)
+);
+
+insert into collection_holders (collection_address, owner_address, nft_count)
+  select mck.collection_address, cmo.owner_address, count(*)
+  from metadata_collection_keys mck
+  inner join metadatas md on md.address = mck.metadata_address
+  inner join current_metadata_owners cmo on cmo.mint_address = md.mint_address
+  where mck.verified and md.burned_at is null
+  group by mck.collection_address, cmo.owner_address;
+
+-- Add to the NFTs of a collection held by a wallet, recording the wallet as a
+-- new or churned holder on the given day if it started or stopped holding any
+create or replace function collection_holders_adjust(
+  collection varchar,
+  owner varchar,
+  delta bigint,
+  at timestamp
+)
+  returns void
+  as
+$$
+declare
+  held bigint;
+begin
+  select nft_count into held
+    from collection_holders
+    where collection_address = collection and owner_address = owner
+    for update;
+
+  held := coalesce(held, 0);
+
+  if held + delta > 0 then
+    insert into collection_holders (collection_address, owner_address, nft_count)
+      values (collection, owner, held + delta)
+      on conflict (collection_address, owner_address) do update
+        set nft_count = excluded.nft_count;
+  else
+    delete from collection_holders
+      where collection_address = collection and owner_address = owner;
+  end if;
+
+  if held <= 0 and held + delta > 0 then
+    insert into collection_holder_days (collection_address, day, new_holders)
+      values (collection, at::date, 1)
+      on conflict (collection_address, day) do update
+        set new_holders = collection_holder_days.new_holders + 1;
+  elsif held > 0 and held + delta <= 0 then
+    insert into collection_holder_days (collection_address, day, churned_holders)
+      values (collection, at::date, 1)
+      on conflict (collection_address, day) do update
+        set churned_holders = collection_holder_days.churned_holders + 1;
+  end if;
+end;
+$$ language plpgsql;
+
+create or replace function collection_holders_owner_changed()
+  returns trigger
+  as
+$$
+begin
+  if tg_op = 'UPDATE' and old.owner_address = new.owner_address
+    and old.mint_address = new.mint_address then
+    return null;
+  end if;
+
+  if tg_op <> 'INSERT' then
+    perform collection_holders_adjust(mck.collection_address, old.owner_address, -1, now()::timestamp)
+      from metadatas md
+      inner join metadata_collection_keys mck
+        on mck.metadata_address = md.address and mck.verified
+      where md.mint_address = old.mint_address and md.burned_at is null;
+  end if;
+
+  if tg_op <> 'DELETE' then
+    perform collection_holders_adjust(mck.collection_address, new.owner_address, 1, new.updated_at)
+      from metadatas md
+      inner join metadata_collection_keys mck
+        on mck.metadata_address = md.address and mck.verified
+      where md.mint_address = new.mint_address and md.burned_at is null;
+  end if;
+
+  return null;
+end;
+$$ language plpgsql;
+
+create trigger collection_holders_owner_changed
+  after insert or update or delete
+  on current_metadata_owners
+  for each row
+  execute procedure collection_holders_owner_changed();
+
+create or replace function collection_holders_collection_keys_changed()
+  returns trigger
+  as
+$$
+begin
+  if tg_op = 'UPDATE' and old.verified = new.verified
+    and old.collection_address = new.collection_address
+    and old.metadata_address = new.metadata_address then
+    return null;
+  end if;
+
+  if tg_op <> 'INSERT' and old.verified then
+    perform collection_holders_adjust(old.collection_address, cmo.owner_address, -1, now()::timestamp)
+      from metadatas md
+      inner join current_metadata_owners cmo on cmo.mint_address = md.mint_address
+      where md.address = old.metadata_address and md.burned_at is null;
+  end if;
+
+  if tg_op <> 'DELETE' and new.verified then
+    perform collection_holders_adjust(new.collection_address, cmo.owner_address, 1, now()::timestamp)
+      from metadatas md
+      inner join current_metadata_owners cmo on cmo.mint_address = md.mint_address
+      where md.address = new.metadata_address and md.burned_at is null;
+  end if;
+
+  return null;
+end;
+$$ language plpgsql;
+
+create trigger collection_holders_collection_keys_changed
+  after insert or update or delete
+  on metadata_collection_keys
+  for each row
+  execute procedure collection_holders_collection_keys_changed();
+
+create or replace function collection_holders_metadata_burned()
+  returns trigger
+  as
+$$
+begin
+  perform collection_holders_adjust(
+      mck.collection_address,
+      cmo.owner_address,
+      case when new.burned_at is null then 1 else -1 end,
+      coalesce(new.burned_at, now()::timestamp)
+    )
+    from metadata_collection_keys mck
+    inner join current_metadata_owners cmo on cmo.mint_address = new.mint_address
+    where mck.metadata_address = new.address and mck.verified;
+
+  return null;
+end;
+$$ language plpgsql;
+
+create trigger collection_holders_metadata_burned
+  after update of burned_at
+  on metadatas
+  for each row
+  when ((old.burned_at is null) <> (new.burned_at is null))
+  execute procedure collection_holders_metadata_burned();
@@ -0,0 +1,19 @@
+-- Every owner of each NFT mint, appended as token account updates are
+-- indexed.  Each row is valid from its slot until the slot of the next row for
+-- the same mint.
+create table metadata_owner_history (
+  mint_address          varchar(48) not null,
+  slot                  bigint      not null,
+  owner_address         varchar(48) not null,
+  token_account_address varchar(48) not null,
+  recorded_at           timestamp   not null default now(),
+  primary key (mint_address, slot)
+);
+
+create index metadata_owner_history_owner_address_idx
+  on metadata_owner_history (owner_address);
+
+insert into metadata_owner_history
+  (mint_address, slot, owner_address, token_account_address, recorded_at)
+  select mint_address, slot, owner_address, token_account_address, updated_at
+  from current_metadata_owners;
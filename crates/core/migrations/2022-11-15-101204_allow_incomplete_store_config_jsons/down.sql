@@ -0,0 +1,6 @@
+delete from store_config_jsons
+where subdomain is null or owner_address is null;
+
+alter table store_config_jsons
+  alter column subdomain set not null,
+  alter column owner_address set not null;
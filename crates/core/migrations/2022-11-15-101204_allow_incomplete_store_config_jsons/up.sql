@@ -0,0 +1,3 @@
+alter table store_config_jsons
+  alter column subdomain drop not null,
+  alter column owner_address drop not null;
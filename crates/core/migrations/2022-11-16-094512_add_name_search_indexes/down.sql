@@ -0,0 +1,3 @@
+drop index if exists collections_name_tsvector_idx;
+
+drop index if exists metadatas_name_tsvector_idx;
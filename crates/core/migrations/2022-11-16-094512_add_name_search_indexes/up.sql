@@ -0,0 +1,5 @@
+create index if not exists metadatas_name_tsvector_idx
+  on metadatas using gin (to_tsvector('english', name));
+
+create index if not exists collections_name_tsvector_idx
+  on collections using gin (to_tsvector('english', name));
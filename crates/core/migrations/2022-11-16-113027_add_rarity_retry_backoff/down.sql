@@ -0,0 +1,4 @@
+alter table rarity_dirty_collections
+  drop column last_error,
+  drop column retry_at,
+  drop column failed_attempts;
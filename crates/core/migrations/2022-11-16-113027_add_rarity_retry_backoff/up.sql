@@ -0,0 +1,6 @@
+-- Collections which fail to score are retried with exponential backoff
+-- instead of blocking the queue of collections marked after them
+alter table rarity_dirty_collections
+  add column failed_attempts integer   not null default 0,
+  add column retry_at        timestamp null,
+  add column last_error      text      null;
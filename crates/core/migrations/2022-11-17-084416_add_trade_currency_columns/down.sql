@@ -0,0 +1,42 @@
+drop trigger currencies_set_trade_currency on currencies;
+drop function currencies_set_trade_currency();
+
+drop trigger auction_houses_set_trade_currency on auction_houses;
+drop function auction_houses_set_trade_currency();
+
+drop trigger listings_set_trade_currency on listings;
+drop trigger purchases_set_trade_currency on purchases;
+drop function set_trade_currency();
+
+create or replace function price_candles_wash_trade_inserted()
+  returns trigger
+  as
+$$
+begin
+  update price_candles pc
+    set flagged_volume = pc.flagged_volume + p.price,
+        flagged_sales = pc.flagged_sales + 1,
+        flagged_normalized_volume = pc.flagged_normalized_volume
+          + normalize_price(ah.treasury_mint, p.price, p.created_at)
+    from purchases p
+    inner join metadata_collection_keys mck
+      on mck.metadata_address = p.metadata and mck.verified
+    left join auction_houses ah on ah.address = p.auction_house
+    cross join (values ('1m', 'minute'), ('1h', 'hour'), ('1d', 'day')) as r (resolution, unit)
+    where p.id = new.purchase_id
+      and pc.collection_address = mck.collection_address
+      and pc.auction_house = p.auction_house
+      and pc.resolution = r.resolution
+      and pc.bucket_start = date_trunc(r.unit, p.created_at);
+
+  return null;
+end;
+$$ language plpgsql;
+
+alter table listings
+  drop column currency_mint,
+  drop column currency_decimals;
+
+alter table purchases
+  drop column currency_mint,
+  drop column currency_decimals;
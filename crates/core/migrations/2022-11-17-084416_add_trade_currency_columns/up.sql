@@ -0,0 +1,139 @@
+-- The currency each sale and listing is priced in, stored alongside the
+-- price so it can be read without the auction house, which may not be
+-- indexed yet
+alter table purchases
+  add column currency_mint     varchar(48),
+  add column currency_decimals smallint;
+
+alter table listings
+  add column currency_mint     varchar(48),
+  add column currency_decimals smallint;
+
+update purchases p
+  set currency_mint = ah.treasury_mint
+  from auction_houses ah
+  where ah.address = p.auction_house;
+
+update listings l
+  set currency_mint = ah.treasury_mint
+  from auction_houses ah
+  where ah.address = l.auction_house;
+
+update purchases p
+  set currency_decimals = c.decimals
+  from currencies c
+  where c.mint = p.currency_mint;
+
+update listings l
+  set currency_decimals = c.decimals
+  from currencies c
+  where c.mint = l.currency_mint;
+
+-- Fill in the currency of a sale or listing from its auction house and the
+-- known currencies where the indexer did not provide it
+create or replace function set_trade_currency()
+  returns trigger
+  as
+$$
+begin
+  if new.currency_mint is null then
+    select treasury_mint into new.currency_mint
+      from auction_houses
+      where address = new.auction_house;
+  end if;
+
+  if new.currency_decimals is null then
+    select decimals into new.currency_decimals
+      from currencies
+      where mint = new.currency_mint;
+  end if;
+
+  return new;
+end;
+$$ language plpgsql;
+
+create trigger purchases_set_trade_currency
+  before insert or update
+  on purchases
+  for each row
+  execute procedure set_trade_currency();
+
+create trigger listings_set_trade_currency
+  before insert or update
+  on listings
+  for each row
+  execute procedure set_trade_currency();
+
+-- Fill in the currency of sales and listings indexed before their auction
+-- house
+create or replace function auction_houses_set_trade_currency()
+  returns trigger
+  as
+$$
+begin
+  update purchases
+    set currency_mint = new.treasury_mint
+    where auction_house = new.address and currency_mint is null;
+
+  update listings
+    set currency_mint = new.treasury_mint
+    where auction_house = new.address and currency_mint is null;
+
+  return null;
+end;
+$$ language plpgsql;
+
+create trigger auction_houses_set_trade_currency
+  after insert or update of treasury_mint
+  on auction_houses
+  for each row
+  execute procedure auction_houses_set_trade_currency();
+
+-- Fill in the decimals of sales and listings priced in a newly-added
+-- currency
+create or replace function currencies_set_trade_currency()
+  returns trigger
+  as
+$$
+begin
+  update purchases
+    set currency_decimals = new.decimals
+    where currency_mint = new.mint and currency_decimals is distinct from new.decimals;
+
+  update listings
+    set currency_decimals = new.decimals
+    where currency_mint = new.mint and currency_decimals is distinct from new.decimals;
+
+  return null;
+end;
+$$ language plpgsql;
+
+create trigger currencies_set_trade_currency
+  after insert or update of decimals
+  on currencies
+  for each row
+  execute procedure currencies_set_trade_currency();
+
+create or replace function price_candles_wash_trade_inserted()
+  returns trigger
+  as
+$$
+begin
+  update price_candles pc
+    set flagged_volume = pc.flagged_volume + p.price,
+        flagged_sales = pc.flagged_sales + 1,
+        flagged_normalized_volume = pc.flagged_normalized_volume
+          + normalize_price(p.currency_mint, p.price, p.created_at)
+    from purchases p
+    inner join metadata_collection_keys mck
+      on mck.metadata_address = p.metadata and mck.verified
+    cross join (values ('1m', 'minute'), ('1h', 'hour'), ('1d', 'day')) as r (resolution, unit)
+    where p.id = new.purchase_id
+      and pc.collection_address = mck.collection_address
+      and pc.auction_house = p.auction_house
+      and pc.resolution = r.resolution
+      and pc.bucket_start = date_trunc(r.unit, p.created_at);
+
+  return null;
+end;
+$$ language plpgsql;
@@ -0,0 +1,63 @@
+drop index sale_royalties_unchecked_sold_at_idx;
+
+alter table sale_royalties
+  drop column checked_at;
+
+-- Record the royalties owed for a new sale, split between the verified
+-- creators of the NFT as the auction house program pays them
+create or replace function sale_royalties_purchase_inserted()
+  returns trigger
+  as
+$$
+begin
+  insert into sale_royalties (
+    purchase_id, creator_address, metadata_address, auction_house,
+    marketplace_program, currency_mint, sold_at, share, expected, paid
+  )
+  select new.id, mc.creator_address, new.metadata, new.auction_house,
+         new.marketplace_program, ah.treasury_mint, new.created_at, mc.share,
+         div(div(new.price::numeric * md.seller_fee_basis_points, 10000) * mc.share, 100)::bigint,
+         null
+  from metadatas md
+  inner join metadata_creators mc
+    on mc.metadata_address = md.address and mc.share > 0
+  left join auction_houses ah on ah.address = new.auction_house
+  where md.address = new.metadata and md.seller_fee_basis_points > 0
+  on conflict do nothing;
+
+  return null;
+end;
+$$ language plpgsql;
+
+-- Auction house ExecuteSale fails unless every creator is passed and paid,
+-- so a recorded instruction settles the royalties of its sale
+create or replace function sale_royalties_execute_sale_inserted()
+  returns trigger
+  as
+$$
+begin
+  if cardinality(new.creator_accounts) = 0 then
+    return null;
+  end if;
+
+  update sale_royalties sr
+    set paid = case when sr.creator_address = any(new.creator_accounts)
+                 then sr.expected else 0 end
+    from purchases p
+    where sr.purchase_id = p.id
+      and p.buyer = new.buyer
+      and p.seller = new.seller
+      and p.auction_house = new.auction_house
+      and p.metadata = new.metadata
+      and p.token_size = new.token_size
+      and p.price = new.buyer_price;
+
+  return null;
+end;
+$$ language plpgsql;
+
+create trigger sale_royalties_execute_sale_inserted
+  after insert
+  on execute_sale_instructions
+  for each row
+  execute procedure sale_royalties_execute_sale_inserted();
@@ -0,0 +1,41 @@
+-- Royalty payments are read from the balance changes of each sale's
+-- transaction by the royalties worker, rather than inferred from the creator
+-- accounts passed to ExecuteSale
+drop trigger sale_royalties_execute_sale_inserted on execute_sale_instructions;
+drop function sale_royalties_execute_sale_inserted();
+
+alter table sale_royalties
+  -- When the royalties worker looked up the sale's transaction, or null if
+  -- it has not yet
+  add column checked_at timestamp;
+
+update sale_royalties set paid = null;
+
+create index sale_royalties_unchecked_sold_at_idx
+  on sale_royalties (sold_at)
+  where checked_at is null;
+
+-- Record the royalties owed for a new sale, split between the verified
+-- creators of the NFT as the auction house program pays them
+create or replace function sale_royalties_purchase_inserted()
+  returns trigger
+  as
+$$
+begin
+  insert into sale_royalties (
+    purchase_id, creator_address, metadata_address, auction_house,
+    marketplace_program, currency_mint, sold_at, share, expected, paid
+  )
+  select new.id, mc.creator_address, new.metadata, new.auction_house,
+         new.marketplace_program, new.currency_mint, new.created_at, mc.share,
+         div(div(new.price::numeric * md.seller_fee_basis_points, 10000) * mc.share, 100)::bigint,
+         null
+  from metadatas md
+  inner join metadata_creators mc
+    on mc.metadata_address = md.address and mc.share > 0
+  where md.address = new.metadata and md.seller_fee_basis_points > 0
+  on conflict do nothing;
+
+  return null;
+end;
+$$ language plpgsql;
@@ -0,0 +1,99 @@
+-- Wallet graph connections are social follows rather than transfers between
+-- wallets, so they are no longer a reason to flag a sale.  Sales flagged for
+-- no other reason are unflagged, and their volume moved back out of the
+-- flagged columns of their candles.
+with unflagged as (
+  delete from wash_trades
+  where reasons = array['linked_wallets']
+  returning purchase_id
+)
+update price_candles pc
+  set flagged_volume = pc.flagged_volume - f.volume,
+      flagged_sales = pc.flagged_sales - f.sales,
+      flagged_normalized_volume = pc.flagged_normalized_volume - f.normalized_volume
+  from (
+    select mck.collection_address, p.auction_house, r.resolution,
+           date_trunc(r.unit, p.created_at) as bucket_start,
+           sum(p.price) as volume,
+           count(*) as sales,
+           sum(normalize_price(p.currency_mint, p.price, p.created_at)) as normalized_volume
+    from unflagged u
+    inner join purchases p on p.id = u.purchase_id
+    inner join metadata_collection_keys mck
+      on mck.metadata_address = p.metadata and mck.verified
+    cross join (values ('1m', 'minute'), ('1h', 'hour'), ('1d', 'day')) as r (resolution, unit)
+    group by mck.collection_address, p.auction_house, r.resolution,
+             date_trunc(r.unit, p.created_at)
+  ) as f
+  where pc.collection_address = f.collection_address
+    and pc.auction_house = f.auction_house
+    and pc.resolution = f.resolution
+    and pc.bucket_start = f.bucket_start;
+
+update wash_trades
+  set reasons = array_remove(reasons, 'linked_wallets')
+  where 'linked_wallets' = any(reasons);
+
+alter table wash_trades
+  drop constraint wash_trades_reasons_check,
+  add constraint wash_trades_reasons_check check (
+    cardinality(reasons) > 0
+    and reasons <@ array['self_trade', 'round_trip', 'zero_fee']
+  );
+
+-- Check a sale for signs of wash trading:
+--  - self_trade: the buyer and seller are the same wallet
+--  - round_trip: the buyer sold the same NFT within the previous seven days;
+--    every sale of the NFT since then is flagged along with it
+--  - zero_fee: the sale was made through an auction house charging no fee
+create or replace function detect_wash_trades(purchase uuid)
+  returns void
+  as
+$$
+declare
+  p purchases%rowtype;
+  reasons text[] := '{}';
+  loop_start timestamp;
+begin
+  select * into p from purchases where id = purchase;
+
+  if not found then
+    return;
+  end if;
+
+  if p.buyer = p.seller then
+    reasons := reasons || 'self_trade'::text;
+  end if;
+
+  select max(prev.created_at) into loop_start
+    from purchases prev
+    where prev.metadata = p.metadata
+      and prev.seller = p.buyer
+      and prev.id <> p.id
+      and prev.created_at <= p.created_at
+      and prev.created_at >= p.created_at - interval '7 days';
+
+  if loop_start is not null and p.buyer <> p.seller then
+    reasons := reasons || 'round_trip'::text;
+
+    perform flag_wash_trade(lp.id, array['round_trip'])
+      from purchases lp
+      where lp.metadata = p.metadata
+        and lp.id <> p.id
+        and lp.created_at >= loop_start
+        and lp.created_at <= p.created_at;
+  end if;
+
+  if exists (
+    select 1
+    from auction_houses ah
+    where ah.address = p.auction_house and ah.seller_fee_basis_points = 0
+  ) then
+    reasons := reasons || 'zero_fee'::text;
+  end if;
+
+  if cardinality(reasons) > 0 then
+    perform flag_wash_trade(p.id, reasons);
+  end if;
+end;
+$$ language plpgsql;
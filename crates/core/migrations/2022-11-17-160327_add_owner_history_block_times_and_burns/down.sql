@@ -0,0 +1,3 @@
+drop table metadata_burns;
+
+alter table metadata_owner_history drop column block_time;
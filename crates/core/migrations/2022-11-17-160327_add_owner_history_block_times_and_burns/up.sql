@@ -0,0 +1,24 @@
+alter table metadata_owner_history
+  -- The time the block of the slot was produced, or null until it is filled
+  -- in by the block time worker
+  add column block_time timestamp;
+
+-- The burn of each NFT mint, so ownership can be looked up as of a slot
+-- without counting burned NFTs
+create table metadata_burns (
+  mint_address varchar(48) primary key not null,
+  slot         bigint      not null,
+  -- The time the block of the slot was produced, or null until it is filled
+  -- in by the block time worker
+  block_time   timestamp,
+  recorded_at  timestamp   not null default now()
+);
+
+create index metadata_burns_slot_idx on metadata_burns (slot);
+
+-- Burns were recorded by setting the slot of the burned metadata
+insert into metadata_burns (mint_address, slot, recorded_at)
+  select distinct on (mint_address) mint_address, slot, burned_at
+  from metadatas
+  where burned_at is not null
+  order by mint_address, burned_at;
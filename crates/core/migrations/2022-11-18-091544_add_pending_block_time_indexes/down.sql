@@ -0,0 +1,3 @@
+drop index metadata_burns_pending_block_time_idx;
+
+drop index metadata_owner_history_pending_block_time_idx;
@@ -0,0 +1,9 @@
+-- Block times are filled in after the fact by the block time worker, which
+-- looks up the slots still missing one
+create index metadata_owner_history_pending_block_time_idx
+  on metadata_owner_history (slot)
+  where block_time is null;
+
+create index metadata_burns_pending_block_time_idx
+  on metadata_burns (slot)
+  where block_time is null;
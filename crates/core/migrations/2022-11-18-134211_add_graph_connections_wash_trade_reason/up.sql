@@ -0,0 +1,78 @@
+-- Sales between wallets connected in the wallet graph are flagged again.
+-- Existing sales are not re-checked here; run the wash trade backfill to flag
+-- them.
+alter table wash_trades
+  drop constraint wash_trades_reasons_check,
+  add constraint wash_trades_reasons_check check (
+    cardinality(reasons) > 0
+    and reasons <@ array['self_trade', 'round_trip', 'graph_connections', 'zero_fee']
+  );
+
+-- Check a sale for signs of wash trading:
+--  - self_trade: the buyer and seller are the same wallet
+--  - round_trip: the buyer sold the same NFT within the previous seven days;
+--    every sale of the NFT since then is flagged along with it
+--  - graph_connections: the buyer and seller are connected in the wallet
+--    graph, unless the connection was dropped before the sale
+--  - zero_fee: the sale was made through an auction house charging no fee
+create or replace function detect_wash_trades(purchase uuid)
+  returns void
+  as
+$$
+declare
+  p purchases%rowtype;
+  reasons text[] := '{}';
+  loop_start timestamp;
+begin
+  select * into p from purchases where id = purchase;
+
+  if not found then
+    return;
+  end if;
+
+  if p.buyer = p.seller then
+    reasons := reasons || 'self_trade'::text;
+  end if;
+
+  select max(prev.created_at) into loop_start
+    from purchases prev
+    where prev.metadata = p.metadata
+      and prev.seller = p.buyer
+      and prev.id <> p.id
+      and prev.created_at <= p.created_at
+      and prev.created_at >= p.created_at - interval '7 days';
+
+  if loop_start is not null and p.buyer <> p.seller then
+    reasons := reasons || 'round_trip'::text;
+
+    perform flag_wash_trade(lp.id, array['round_trip'])
+      from purchases lp
+      where lp.metadata = p.metadata
+        and lp.id <> p.id
+        and lp.created_at >= loop_start
+        and lp.created_at <= p.created_at;
+  end if;
+
+  if exists (
+    select 1
+    from graph_connections gc
+    where ((gc.from_account = p.buyer and gc.to_account = p.seller)
+        or (gc.from_account = p.seller and gc.to_account = p.buyer))
+      and (gc.disconnected_at is null or gc.disconnected_at > p.created_at)
+  ) then
+    reasons := reasons || 'graph_connections'::text;
+  end if;
+
+  if exists (
+    select 1
+    from auction_houses ah
+    where ah.address = p.auction_house and ah.seller_fee_basis_points = 0
+  ) then
+    reasons := reasons || 'zero_fee'::text;
+  end if;
+
+  if cardinality(reasons) > 0 then
+    perform flag_wash_trade(p.id, reasons);
+  end if;
+end;
+$$ language plpgsql;
@@ -0,0 +1,32 @@
+drop function refresh_collection_trend_volumes();
+
+drop index price_candles_resolution_bucket_start_idx;
+
+alter table collection_trends
+  drop column _1d_unflagged_sales_count,
+  drop column _7d_unflagged_sales_count,
+  drop column _30d_unflagged_sales_count,
+  drop column _1d_normalized_volume,
+  drop column _prev_1d_normalized_volume,
+  drop column _1d_unflagged_normalized_volume,
+  drop column _prev_1d_unflagged_normalized_volume,
+  drop column _1d_flagged_volume,
+  drop column _prev_1d_flagged_volume,
+  drop column _1d_flagged_sales,
+  drop column _prev_1d_flagged_sales,
+  drop column _7d_normalized_volume,
+  drop column _prev_7d_normalized_volume,
+  drop column _7d_unflagged_normalized_volume,
+  drop column _prev_7d_unflagged_normalized_volume,
+  drop column _7d_flagged_volume,
+  drop column _prev_7d_flagged_volume,
+  drop column _7d_flagged_sales,
+  drop column _prev_7d_flagged_sales,
+  drop column _30d_normalized_volume,
+  drop column _prev_30d_normalized_volume,
+  drop column _30d_unflagged_normalized_volume,
+  drop column _prev_30d_unflagged_normalized_volume,
+  drop column _30d_flagged_volume,
+  drop column _prev_30d_flagged_volume,
+  drop column _30d_flagged_sales,
+  drop column _prev_30d_flagged_sales;
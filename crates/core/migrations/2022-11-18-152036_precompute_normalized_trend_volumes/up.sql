@@ -0,0 +1,122 @@
+-- Volumes of each collection over the periods of collection_trends,
+-- normalized to lamports of SOL with and without sales flagged as wash
+-- trades, along with the native volume and sales flagged.  These are
+-- refreshed from the price candles by the trends worker, since the other
+-- columns are maintained outside the indexer.
+alter table collection_trends
+  add column _1d_normalized_volume numeric,
+  add column _prev_1d_normalized_volume numeric,
+  add column _1d_unflagged_normalized_volume numeric,
+  add column _prev_1d_unflagged_normalized_volume numeric,
+  add column _1d_flagged_volume numeric not null default 0,
+  add column _prev_1d_flagged_volume numeric not null default 0,
+  add column _1d_flagged_sales numeric not null default 0,
+  add column _prev_1d_flagged_sales numeric not null default 0,
+  add column _7d_normalized_volume numeric,
+  add column _prev_7d_normalized_volume numeric,
+  add column _7d_unflagged_normalized_volume numeric,
+  add column _prev_7d_unflagged_normalized_volume numeric,
+  add column _7d_flagged_volume numeric not null default 0,
+  add column _prev_7d_flagged_volume numeric not null default 0,
+  add column _7d_flagged_sales numeric not null default 0,
+  add column _prev_7d_flagged_sales numeric not null default 0,
+  add column _30d_normalized_volume numeric,
+  add column _prev_30d_normalized_volume numeric,
+  add column _30d_unflagged_normalized_volume numeric,
+  add column _prev_30d_unflagged_normalized_volume numeric,
+  add column _30d_flagged_volume numeric not null default 0,
+  add column _prev_30d_flagged_volume numeric not null default 0,
+  add column _30d_flagged_sales numeric not null default 0,
+  add column _prev_30d_flagged_sales numeric not null default 0;
+
+alter table collection_trends
+  add column _1d_unflagged_sales_count numeric
+    generated always as (_1d_sales_count - _1d_flagged_sales) stored,
+  add column _7d_unflagged_sales_count numeric
+    generated always as (_7d_sales_count - _7d_flagged_sales) stored,
+  add column _30d_unflagged_sales_count numeric
+    generated always as (_30d_sales_count - _30d_flagged_sales) stored;
+
+create index price_candles_resolution_bucket_start_idx
+  on price_candles (resolution, bucket_start);
+
+-- Recompute the columns above from the hourly and daily price candles
+-- covering each period, which are aligned to the start of an hour or day.
+-- Normalized volumes are null where a sale could not be converted.
+create or replace function refresh_collection_trend_volumes()
+  returns void
+  as
+$$
+  with periods (period, resolution, start_at, prev_start_at) as (
+    values ('1d', '1h', date_trunc('hour', (now() at time zone 'utc') - interval '1 day'),
+                        date_trunc('hour', (now() at time zone 'utc') - interval '2 days')),
+           ('7d', '1h', date_trunc('hour', (now() at time zone 'utc') - interval '7 days'),
+                        date_trunc('hour', (now() at time zone 'utc') - interval '14 days')),
+           ('30d', '1d', date_trunc('day', (now() at time zone 'utc') - interval '30 days'),
+                         date_trunc('day', (now() at time zone 'utc') - interval '60 days'))
+  ), candles as (
+    select pc.collection_address, p.period, pc.bucket_start >= p.start_at as current,
+           pc.normalized_volume,
+           pc.normalized_volume - coalesce(pc.flagged_normalized_volume, 0)
+             as unflagged_normalized_volume,
+           pc.flagged_volume, pc.flagged_sales
+    from periods p
+    inner join price_candles pc
+      on pc.resolution = p.resolution and pc.bucket_start >= p.prev_start_at
+  ), volumes as (
+    select collection_address as collection, period,
+           case when coalesce(bool_and(normalized_volume is not null) filter (where current), true)
+             then coalesce(sum(normalized_volume) filter (where current), 0)
+           end as normalized_volume,
+           case when bool_and(normalized_volume is not null) filter (where not current)
+             then sum(normalized_volume) filter (where not current)
+           end as prev_normalized_volume,
+           case when coalesce(bool_and(normalized_volume is not null) filter (where current), true)
+             then coalesce(sum(unflagged_normalized_volume) filter (where current), 0)
+           end as unflagged_normalized_volume,
+           case when bool_and(normalized_volume is not null) filter (where not current)
+             then sum(unflagged_normalized_volume) filter (where not current)
+           end as prev_unflagged_normalized_volume,
+           sum(flagged_volume) filter (where current) as flagged_volume,
+           sum(flagged_volume) filter (where not current) as prev_flagged_volume,
+           sum(flagged_sales) filter (where current) as flagged_sales,
+           sum(flagged_sales) filter (where not current) as prev_flagged_sales
+    from candles
+    group by collection_address, period
+  )
+  update collection_trends ct
+    set _1d_normalized_volume = case when v1d.collection is null then 0 else v1d.normalized_volume end,
+        _prev_1d_normalized_volume = v1d.prev_normalized_volume,
+        _1d_unflagged_normalized_volume =
+          case when v1d.collection is null then 0 else v1d.unflagged_normalized_volume end,
+        _prev_1d_unflagged_normalized_volume = v1d.prev_unflagged_normalized_volume,
+        _1d_flagged_volume = coalesce(v1d.flagged_volume, 0),
+        _prev_1d_flagged_volume = coalesce(v1d.prev_flagged_volume, 0),
+        _1d_flagged_sales = coalesce(v1d.flagged_sales, 0),
+        _prev_1d_flagged_sales = coalesce(v1d.prev_flagged_sales, 0),
+        _7d_normalized_volume = case when v7d.collection is null then 0 else v7d.normalized_volume end,
+        _prev_7d_normalized_volume = v7d.prev_normalized_volume,
+        _7d_unflagged_normalized_volume =
+          case when v7d.collection is null then 0 else v7d.unflagged_normalized_volume end,
+        _prev_7d_unflagged_normalized_volume = v7d.prev_unflagged_normalized_volume,
+        _7d_flagged_volume = coalesce(v7d.flagged_volume, 0),
+        _prev_7d_flagged_volume = coalesce(v7d.prev_flagged_volume, 0),
+        _7d_flagged_sales = coalesce(v7d.flagged_sales, 0),
+        _prev_7d_flagged_sales = coalesce(v7d.prev_flagged_sales, 0),
+        _30d_normalized_volume = case when v30d.collection is null then 0 else v30d.normalized_volume end,
+        _prev_30d_normalized_volume = v30d.prev_normalized_volume,
+        _30d_unflagged_normalized_volume =
+          case when v30d.collection is null then 0 else v30d.unflagged_normalized_volume end,
+        _prev_30d_unflagged_normalized_volume = v30d.prev_unflagged_normalized_volume,
+        _30d_flagged_volume = coalesce(v30d.flagged_volume, 0),
+        _prev_30d_flagged_volume = coalesce(v30d.prev_flagged_volume, 0),
+        _30d_flagged_sales = coalesce(v30d.flagged_sales, 0),
+        _prev_30d_flagged_sales = coalesce(v30d.prev_flagged_sales, 0)
+  from collection_trends t
+  left join volumes v1d on v1d.collection = t.collection and v1d.period = '1d'
+  left join volumes v7d on v7d.collection = t.collection and v7d.period = '7d'
+  left join volumes v30d on v30d.collection = t.collection and v30d.period = '30d'
+  where t.collection = ct.collection;
+$$ language sql;
+
+select refresh_collection_trend_volumes();
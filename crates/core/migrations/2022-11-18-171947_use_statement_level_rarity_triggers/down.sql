@@ -0,0 +1,94 @@
+drop trigger rarity_metadatas_burned on metadatas;
+drop function rarity_metadatas_burned();
+drop trigger rarity_collection_keys_deleted on metadata_collection_keys;
+drop trigger rarity_collection_keys_updated on metadata_collection_keys;
+drop trigger rarity_collection_keys_inserted on metadata_collection_keys;
+drop function rarity_collection_keys_updated();
+drop function rarity_collection_keys_changed();
+drop trigger rarity_attributes_deleted on attributes;
+drop trigger rarity_attributes_updated on attributes;
+drop trigger rarity_attributes_inserted on attributes;
+drop function rarity_attributes_updated();
+drop function rarity_attributes_changed();
+
+create or replace function rarity_mark_collection_dirty(address varchar)
+  returns void
+  as
+$$
+begin
+  insert into rarity_dirty_collections (collection_address)
+    values (address)
+    on conflict (collection_address) do update
+      set marked_at = now();
+end;
+$$ language plpgsql;
+
+create or replace function rarity_attributes_changed()
+  returns trigger
+  as
+$$
+declare
+  addr varchar;
+begin
+  if tg_op = 'DELETE' then
+    addr := old.metadata_address;
+  else
+    addr := new.metadata_address;
+  end if;
+
+  perform rarity_mark_collection_dirty(collection_address)
+    from metadata_collection_keys
+    where metadata_address = addr and verified;
+
+  return null;
+end;
+$$ language plpgsql;
+
+create trigger rarity_attributes_changed
+  after insert or update or delete
+  on attributes
+  for each row
+  execute procedure rarity_attributes_changed();
+
+create or replace function rarity_collection_keys_changed()
+  returns trigger
+  as
+$$
+begin
+  if tg_op <> 'INSERT' and old.verified then
+    perform rarity_mark_collection_dirty(old.collection_address);
+  end if;
+
+  if tg_op <> 'DELETE' and new.verified then
+    perform rarity_mark_collection_dirty(new.collection_address);
+  end if;
+
+  return null;
+end;
+$$ language plpgsql;
+
+create trigger rarity_collection_keys_changed
+  after insert or update or delete
+  on metadata_collection_keys
+  for each row
+  execute procedure rarity_collection_keys_changed();
+
+create or replace function rarity_metadata_burned()
+  returns trigger
+  as
+$$
+begin
+  perform rarity_mark_collection_dirty(collection_address)
+    from metadata_collection_keys
+    where metadata_address = new.address and verified;
+
+  return null;
+end;
+$$ language plpgsql;
+
+create trigger rarity_metadata_burned
+  after update of burned_at
+  on metadatas
+  for each row
+  when (old.burned_at is distinct from new.burned_at)
+  execute procedure rarity_metadata_burned();
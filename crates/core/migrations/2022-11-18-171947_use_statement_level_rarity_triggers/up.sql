@@ -0,0 +1,155 @@
+-- Mark collections dirty once per statement from its transition tables
+-- rather than once per changed row, since the attributes of an NFT are
+-- replaced in bulk whenever its metadata JSON is indexed.  Updates only mark
+-- collections if a field used for scoring changed.
+drop trigger rarity_metadata_burned on metadatas;
+drop function rarity_metadata_burned();
+drop trigger rarity_collection_keys_changed on metadata_collection_keys;
+drop function rarity_collection_keys_changed();
+drop trigger rarity_attributes_changed on attributes;
+drop function rarity_attributes_changed();
+drop function rarity_mark_collection_dirty(varchar);
+
+create or replace function rarity_attributes_changed()
+  returns trigger
+  as
+$$
+begin
+  insert into rarity_dirty_collections (collection_address)
+    select distinct k.collection_address
+    from changed_attributes a
+    inner join metadata_collection_keys k
+      on k.metadata_address = a.metadata_address and k.verified
+    on conflict (collection_address) do update
+      set marked_at = now();
+
+  return null;
+end;
+$$ language plpgsql;
+
+create or replace function rarity_attributes_updated()
+  returns trigger
+  as
+$$
+begin
+  insert into rarity_dirty_collections (collection_address)
+    select distinct k.collection_address
+    from old_attributes o
+    inner join new_attributes n on n.id = o.id
+    inner join metadata_collection_keys k
+      on k.metadata_address in (o.metadata_address, n.metadata_address) and k.verified
+    where (o.metadata_address, o.trait_type, o.value)
+      is distinct from (n.metadata_address, n.trait_type, n.value)
+    on conflict (collection_address) do update
+      set marked_at = now();
+
+  return null;
+end;
+$$ language plpgsql;
+
+create trigger rarity_attributes_inserted
+  after insert
+  on attributes
+  referencing new table as changed_attributes
+  for each statement
+  execute procedure rarity_attributes_changed();
+
+create trigger rarity_attributes_updated
+  after update
+  on attributes
+  referencing old table as old_attributes new table as new_attributes
+  for each statement
+  execute procedure rarity_attributes_updated();
+
+create trigger rarity_attributes_deleted
+  after delete
+  on attributes
+  referencing old table as changed_attributes
+  for each statement
+  execute procedure rarity_attributes_changed();
+
+create or replace function rarity_collection_keys_changed()
+  returns trigger
+  as
+$$
+begin
+  insert into rarity_dirty_collections (collection_address)
+    select distinct collection_address
+    from changed_keys
+    where verified
+    on conflict (collection_address) do update
+      set marked_at = now();
+
+  return null;
+end;
+$$ language plpgsql;
+
+create or replace function rarity_collection_keys_updated()
+  returns trigger
+  as
+$$
+begin
+  insert into rarity_dirty_collections (collection_address)
+    select distinct collection_address
+    from (
+      (select metadata_address, collection_address from old_keys where verified
+       except
+       select metadata_address, collection_address from new_keys where verified)
+      union
+      (select metadata_address, collection_address from new_keys where verified
+       except
+       select metadata_address, collection_address from old_keys where verified)
+    ) as k
+    on conflict (collection_address) do update
+      set marked_at = now();
+
+  return null;
+end;
+$$ language plpgsql;
+
+create trigger rarity_collection_keys_inserted
+  after insert
+  on metadata_collection_keys
+  referencing new table as changed_keys
+  for each statement
+  execute procedure rarity_collection_keys_changed();
+
+create trigger rarity_collection_keys_updated
+  after update
+  on metadata_collection_keys
+  referencing old table as old_keys new table as new_keys
+  for each statement
+  execute procedure rarity_collection_keys_updated();
+
+create trigger rarity_collection_keys_deleted
+  after delete
+  on metadata_collection_keys
+  referencing old table as changed_keys
+  for each statement
+  execute procedure rarity_collection_keys_changed();
+
+create or replace function rarity_metadatas_burned()
+  returns trigger
+  as
+$$
+begin
+  insert into rarity_dirty_collections (collection_address)
+    select distinct k.collection_address
+    from old_metadatas o
+    inner join new_metadatas n on n.address = o.address
+    inner join metadata_collection_keys k
+      on k.metadata_address = n.address and k.verified
+    where o.burned_at is distinct from n.burned_at
+    on conflict (collection_address) do update
+      set marked_at = now();
+
+  return null;
+end;
+$$ language plpgsql;
+
+create trigger rarity_metadatas_burned
+  after update
+  on metadatas
+  referencing old table as old_metadatas new table as new_metadatas
+  for each statement
+  execute procedure rarity_metadatas_burned();
@@ -0,0 +1,17 @@
+drop trigger price_candles_listing_inserted on listings;
+drop function price_candles_listing_inserted();
+drop trigger price_candles_purchase_inserted on purchases;
+drop function price_candles_purchase_inserted();
+
+delete from price_candles where sales = 0;
+
+alter table price_candles
+  drop column normalized_listing_low,
+  drop column listing_low,
+  drop column listings,
+  alter column closed_at set not null,
+  alter column opened_at set not null,
+  alter column close set not null,
+  alter column low set not null,
+  alter column high set not null,
+  alter column open set not null;
@@ -0,0 +1,119 @@
+-- Listings created within each candle, along with the lowest price among
+-- them.  Buckets with listings but no sales have no open, high, low, or close.
+alter table price_candles
+  alter column open drop not null,
+  alter column high drop not null,
+  alter column low drop not null,
+  alter column close drop not null,
+  alter column opened_at drop not null,
+  alter column closed_at drop not null,
+  add column listings               bigint  not null default 0,
+  add column listing_low            bigint,
+  add column normalized_listing_low numeric;
+
+create or replace function price_candles_purchase_inserted()
+  returns trigger
+  as
+$$
+declare
+  normalized numeric;
+begin
+  normalized := normalize_price(new.currency_mint, new.price, new.created_at);
+
+  insert into price_candles (
+    collection_address, auction_house, resolution, bucket_start,
+    open, high, low, close, volume, sales, opened_at, closed_at,
+    currency_mint, normalized_open, normalized_high, normalized_low,
+    normalized_close, normalized_volume
+  )
+  select mck.collection_address, new.auction_house, r.resolution,
+         date_trunc(r.unit, new.created_at),
+         new.price, new.price, new.price, new.price, new.price, 1,
+         new.created_at, new.created_at,
+         new.currency_mint, normalized, normalized, normalized, normalized, normalized
+  from metadata_collection_keys mck
+  cross join (values ('1m', 'minute'), ('1h', 'hour'), ('1d', 'day')) as r (resolution, unit)
+  where mck.metadata_address = new.metadata and mck.verified
+  on conflict (collection_address, resolution, bucket_start, auction_house) do update
+    set open = case when price_candles.opened_at is null
+                   or excluded.opened_at < price_candles.opened_at
+                 then excluded.open else price_candles.open end,
+        opened_at = least(price_candles.opened_at, excluded.opened_at),
+        high = greatest(price_candles.high, excluded.high),
+        low = least(price_candles.low, excluded.low),
+        close = case when price_candles.closed_at is null
+                    or excluded.closed_at >= price_candles.closed_at
+                  then excluded.close else price_candles.close end,
+        closed_at = greatest(price_candles.closed_at, excluded.closed_at),
+        volume = price_candles.volume + excluded.volume,
+        sales = price_candles.sales + excluded.sales,
+        currency_mint = coalesce(price_candles.currency_mint, excluded.currency_mint),
+        normalized_open = case when price_candles.opened_at is null
+                              or excluded.opened_at < price_candles.opened_at
+                            then excluded.normalized_open else price_candles.normalized_open end,
+        normalized_high = greatest(price_candles.normalized_high, excluded.normalized_high),
+        normalized_low = least(price_candles.normalized_low, excluded.normalized_low),
+        normalized_close = case when price_candles.closed_at is null
+                               or excluded.closed_at >= price_candles.closed_at
+                             then excluded.normalized_close else price_candles.normalized_close end,
+        -- Unknown if any sale in the bucket could not be normalized
+        normalized_volume = price_candles.normalized_volume + excluded.normalized_volume;
+
+  insert into collection_buyer_buckets (
+    collection_address, resolution, bucket_start, buyer, sales
+  )
+  select mck.collection_address, r.resolution,
+         date_trunc(r.unit, new.created_at), new.buyer, 1
+  from metadata_collection_keys mck
+  cross join (values ('1m', 'minute'), ('1h', 'hour'), ('1d', 'day')) as r (resolution, unit)
+  where mck.metadata_address = new.metadata and mck.verified
+  on conflict (collection_address, resolution, bucket_start, buyer) do update
+    set sales = collection_buyer_buckets.sales + excluded.sales;
+
+  return null;
+end;
+$$ language plpgsql;
+
+create trigger price_candles_purchase_inserted
+  after insert
+  on purchases
+  for each row
+  execute procedure price_candles_purchase_inserted();
+
+create or replace function price_candles_listing_inserted()
+  returns trigger
+  as
+$$
+declare
+  normalized numeric;
+begin
+  normalized := normalize_price(new.currency_mint, new.price, new.created_at);
+
+  insert into price_candles (
+    collection_address, auction_house, resolution, bucket_start,
+    volume, sales, currency_mint, normalized_volume,
+    listings, listing_low, normalized_listing_low
+  )
+  select mck.collection_address, new.auction_house, r.resolution,
+         date_trunc(r.unit, new.created_at),
+         0, 0, new.currency_mint, 0,
+         1, new.price, normalized
+  from metadata_collection_keys mck
+  cross join (values ('1m', 'minute'), ('1h', 'hour'), ('1d', 'day')) as r (resolution, unit)
+  where mck.metadata_address = new.metadata and mck.verified
+  on conflict (collection_address, resolution, bucket_start, auction_house) do update
+    set listings = price_candles.listings + excluded.listings,
+        listing_low = least(price_candles.listing_low, excluded.listing_low),
+        normalized_listing_low = least(price_candles.normalized_listing_low,
+                                       excluded.normalized_listing_low),
+        currency_mint = coalesce(price_candles.currency_mint, excluded.currency_mint);
+
+  return null;
+end;
+$$ language plpgsql;
+
+create trigger price_candles_listing_inserted
+  after insert
+  on listings
+  for each row
+  execute procedure price_candles_listing_inserted();
@@ -165,6 +165,40 @@ impl<'a> AssetIdentifier<'a> {
             }))
     }
 
+    /// Format this asset ID as a URL on a public IPFS or Arweave gateway,
+    /// bypassing the Holaplex asset proxy.  Returns `None` if the ID was
+    /// unparseable or ambiguous.
+    #[must_use]
+    pub fn gateway_url(
+        &self,
+        hint: Option<AssetHint>,
+        ipfs_gateway: &Url,
+        arweave_gateway: &Url,
+    ) -> Option<Url> {
+        let (mut url, segments, path) = match (&self.arweave, &self.ipfs, hint) {
+            (Some(_), Some(_), None) | (None, None, _) => return None,
+            (Some((txid, path)), None, _)
+            | (Some((txid, path)), Some(_), Some(AssetHint::Arweave)) => (
+                arweave_gateway.clone(),
+                vec![base64::encode_config(&txid.0, base64::URL_SAFE_NO_PAD)],
+                path,
+            ),
+            (None, Some((cid, path)), _) | (Some(_), Some((cid, path)), Some(AssetHint::Ipfs)) => (
+                ipfs_gateway.clone(),
+                vec!["ipfs".into(), cid.to_string()],
+                path,
+            ),
+        };
+
+        url.path_segments_mut()
+            .ok()?
+            .pop_if_empty()
+            .extend(segments)
+            .extend(path.split('/').filter(|s| !s.is_empty()));
+
+        Some(url)
+    }
+
     fn fingerprint_ipfs(cid: &Cid, path: &str) -> Vec<u8> {
         if path.is_empty() {
             use cid::multihash::StatefulHasher;
@@ -206,6 +240,115 @@ impl<'a> AssetIdentifier<'a> {
     }
 }
 
+/// Compute the SHA-256 hash of a downloaded asset's content
+#[must_use]
+pub fn content_hash(data: &[u8]) -> Vec<u8> {
+    use cid::multihash::StatefulHasher;
+
+    let mut h = cid::multihash::Sha2_256::default();
+
+    h.update(data);
+
+    h.finalize().as_ref().to_vec()
+}
+
+/// Check downloaded content against the multihash of the IPFS CID it was
+/// requested by.
+///
+/// Returns `None` if the content cannot be verified locally, e.g. because the
+/// content was requested by a path within a directory CID, the CID uses an
+/// unsupported codec or hash function, or the content is too large to have
+/// been stored as a single `UnixFS` chunk.
+#[must_use]
+pub fn verify_ipfs_content(cid: &Cid, path: &str, data: &[u8]) -> Option<bool> {
+    const RAW: u64 = 0x55;
+    const DAG_PB: u64 = 0x70;
+    const SHA2_256: u64 = 0x12;
+    // Default chunk size used by `ipfs add`
+    const MAX_CHUNK: usize = 256 * 1024;
+
+    if !path.is_empty() || cid.hash().code() != SHA2_256 {
+        return None;
+    }
+
+    let expected = cid.hash().digest();
+
+    match cid.codec() {
+        RAW => Some(content_hash(data) == expected),
+        DAG_PB if data.len() <= MAX_CHUNK => {
+            Some(content_hash(&unixfs_file_node(data)) == expected)
+        },
+        _ => None,
+    }
+}
+
+/// Encode a single-chunk file as a `dag-pb` `PBNode` wrapping a `UnixFS`
+/// `Data` message, as produced by `ipfs add` without raw leaves
+fn unixfs_file_node(data: &[u8]) -> Vec<u8> {
+    fn varint(buf: &mut Vec<u8>, mut n: u64) {
+        while n >= 0x80 {
+            #[allow(clippy::cast_possible_truncation)]
+            buf.push((n as u8) | 0x80);
+            n >>= 7;
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        buf.push(n as u8);
+    }
+
+    fn bytes_field(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+        varint(buf, (field << 3) | 2);
+        varint(buf, bytes.len() as u64);
+        buf.extend_from_slice(bytes);
+    }
+
+    fn varint_field(buf: &mut Vec<u8>, field: u64, n: u64) {
+        varint(buf, field << 3);
+        varint(buf, n);
+    }
+
+    // UnixFS Data { Type = File, Data, filesize }
+    let mut unixfs = Vec::with_capacity(data.len() + 16);
+    varint_field(&mut unixfs, 1, 2);
+    if !data.is_empty() {
+        bytes_field(&mut unixfs, 2, data);
+    }
+    varint_field(&mut unixfs, 3, data.len() as u64);
+
+    // PBNode { Data }
+    let mut node = Vec::with_capacity(unixfs.len() + 8);
+    bytes_field(&mut node, 1, &unixfs);
+
+    node
+}
+
+#[cfg(test)]
+mod test {
+    use cid::multihash::{Code, MultihashDigest};
+
+    use super::*;
+
+    #[test]
+    fn verify_ipfs_content() {
+        let data = b"hello world\n";
+        let v0: Cid = "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o"
+            .try_into()
+            .unwrap();
+
+        assert_eq!(super::verify_ipfs_content(&v0, "", data), Some(true));
+        assert_eq!(
+            super::verify_ipfs_content(&v0, "", b"hello world"),
+            Some(false)
+        );
+        assert_eq!(super::verify_ipfs_content(&v0, "foo.json", data), None);
+
+        let raw = Cid::new_v1(0x55, Code::Sha2_256.digest(data));
+
+        assert_eq!(super::verify_ipfs_content(&raw, "", data), Some(true));
+        assert_eq!(super::verify_ipfs_content(&raw, "", b""), Some(false));
+    }
+}
+
 #[cfg(feature = "asset-cdn")]
 mod cdn {
     use super::{AssetHint, AssetIdentifier, Url};
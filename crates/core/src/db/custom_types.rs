@@ -372,6 +372,10 @@ pub enum NftSort {
     /// sort results by ListedAt
     #[strum(serialize = "ListedAt")]
     ListedAt,
+
+    /// sort results by Rarity
+    #[strum(serialize = "Rarity")]
+    Rarity,
 }
 
 /// `ProposalV2State`
@@ -613,3 +617,40 @@ impl FromSql<PayoutOperation, Pg> for PayoutOperationEnum {
         from_bytes(bytes)
     }
 }
+
+/// A reason a sale was flagged in the `wash_trades` table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum WashTradeReason {
+    /// The buyer and seller are the same wallet
+    SelfTrade,
+    /// The NFT was bought back by a wallet which sold it within the previous
+    /// seven days
+    RoundTrip,
+    /// The buyer and seller are connected in the wallet graph
+    GraphConnections,
+    /// The sale was made through an auction house charging no fee
+    ZeroFee,
+}
+
+/// The kind of an event in the provenance of an NFT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum NftEventKind {
+    /// The NFT was minted
+    Mint,
+    /// The NFT was listed for sale on an auction house
+    Listing,
+    /// An offer was made for the NFT on an auction house
+    Offer,
+    /// The NFT was sold on an auction house
+    Sale,
+    /// The NFT moved to a new owner
+    Transfer,
+    /// The NFT was claimed from a Cardinal token manager
+    CardinalClaim,
+    /// Rent was paid for a Genopets habitat
+    GenopetsRent,
+    /// The NFT was burned
+    Burn,
+}
@@ -29,7 +29,7 @@ pub use diesel::{
 };
 use diesel::{pg, r2d2};
 pub use diesel_full_text_search::{
-    websearch_to_tsquery, TsQuery, TsQueryExtensions, TsVector, TsVectorExtensions,
+    to_tsvector, websearch_to_tsquery, TsQuery, TsQueryExtensions, TsVector, TsVectorExtensions,
 };
 pub use sea_query::Order;
 
@@ -93,14 +93,18 @@ impl fmt::Debug for ConnectResult {
 }
 
 /// Arguments for establishing a database connection
-#[derive(Debug, clap::Args)]
+///
+/// Both a read-only and a writable URL may be supplied for services which
+/// open pools in both modes; [`connect`] selects the URL matching the
+/// requested [`ConnectMode`].
+#[derive(Debug, Clone, clap::Args)]
 pub struct ConnectArgs {
     /// Connection string for a read-only database
-    #[clap(long, env, conflicts_with("database-write-url"))]
+    #[clap(long, env)]
     database_read_url: Option<String>,
 
     /// Connection string for a writable database
-    #[clap(long, env, conflicts_with("database-read-url"))]
+    #[clap(long, env)]
     database_write_url: Option<String>,
 
     /// Fallback database connection string
@@ -5,9 +5,10 @@
 use std::borrow::Cow;
 
 use bigdecimal::BigDecimal;
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use diesel::sql_types::{
-    Array, BigInt, Bool, Int4, Int8, Nullable, Numeric, Text, Timestamp, Timestamptz, VarChar,
+    Array, BigInt, Bool, Double, Int4, Int8, Nullable, Numeric, Text, Timestamp, Timestamptz,
+    VarChar,
 };
 use uuid::Uuid;
 
@@ -643,6 +644,159 @@ pub struct CollectionTrend {
     pub thirty_day_marketcap_change: i64,
 }
 
+/// Statistics of a collection over a trailing window, computed from the
+/// `price_candles` and `collection_buyer_buckets` rollups
+#[derive(Debug, Clone, QueryableByName)]
+pub struct CollectionWindowStats {
+    /// Total sales volume within the window, or `None` if the sales were
+    /// made in more than one currency
+    #[sql_type = "Nullable<Numeric>"]
+    #[column_name = "window_volume"]
+    pub volume: Option<BigDecimal>,
+
+    /// Total sales volume within the window in lamports of SOL, or `None` if
+    /// any sale could not be converted
+    #[sql_type = "Nullable<Numeric>"]
+    #[column_name = "window_normalized_volume"]
+    pub normalized_volume: Option<BigDecimal>,
+
+    /// The mint of the currency the native volumes are denominated in, or
+    /// `None` if the sales were made in more than one currency
+    #[sql_type = "Nullable<VarChar>"]
+    #[column_name = "window_currency_mint"]
+    pub currency_mint: Option<String>,
+
+    /// Number of sales within the window
+    #[sql_type = "Int8"]
+    #[column_name = "window_sales_count"]
+    pub sales_count: i64,
+
+    /// Number of distinct buyers within the window
+    #[sql_type = "Int8"]
+    #[column_name = "window_unique_buyers"]
+    pub unique_buyers: i64,
+
+    /// Total sales volume within the preceding window of the same length, or
+    /// `None` if there were no sales
+    #[sql_type = "Nullable<Numeric>"]
+    #[column_name = "window_prev_volume"]
+    pub prev_volume: Option<BigDecimal>,
+
+    /// Total sales volume within the preceding window of the same length in
+    /// lamports of SOL, or `None` if there were no sales or any sale could
+    /// not be converted
+    #[sql_type = "Nullable<Numeric>"]
+    #[column_name = "window_prev_normalized_volume"]
+    pub prev_normalized_volume: Option<BigDecimal>,
+
+    /// Number of sales within the preceding window of the same length, or
+    /// `None` if there were no sales
+    #[sql_type = "Nullable<Int8>"]
+    #[column_name = "window_prev_sales_count"]
+    pub prev_sales_count: Option<i64>,
+
+    /// Percent change in normalized volume from the preceding window
+    #[sql_type = "Nullable<Int8>"]
+    #[column_name = "window_volume_change"]
+    pub volume_change: Option<i64>,
+
+    /// Percent change in sales count from the preceding window
+    #[sql_type = "Nullable<Int8>"]
+    #[column_name = "window_sales_count_change"]
+    pub sales_count_change: Option<i64>,
+
+    /// Lowest price among listings open at any point within the window and
+    /// sales within it, or `None` if there were neither
+    #[sql_type = "Nullable<Numeric>"]
+    #[column_name = "window_floor_price"]
+    pub floor_price: Option<BigDecimal>,
+
+    /// Floor price within the window times the number of NFTs in the
+    /// collection
+    #[sql_type = "Nullable<Numeric>"]
+    #[column_name = "window_marketcap"]
+    pub marketcap: Option<BigDecimal>,
+}
+
+/// Volumes of a collection over the periods of the `collection_trends`
+/// table, normalized to lamports of SOL.  Each volume is `None` if any sale
+/// within its period could not be converted.
+#[derive(Debug, Clone, QueryableByName)]
+pub struct CollectionNormalizedVolumes {
+    /// Collection 1 day volume in lamports of SOL
+    #[sql_type = "Nullable<Numeric>"]
+    #[column_name = "_1d_normalized_volume"]
+    pub one_day_volume: Option<BigDecimal>,
+
+    /// Collection 7 days volume in lamports of SOL
+    #[sql_type = "Nullable<Numeric>"]
+    #[column_name = "_7d_normalized_volume"]
+    pub seven_day_volume: Option<BigDecimal>,
+
+    /// Collection 30 days volume in lamports of SOL
+    #[sql_type = "Nullable<Numeric>"]
+    #[column_name = "_30d_normalized_volume"]
+    pub thirty_day_volume: Option<BigDecimal>,
+
+    /// Collection previous 1 day volume in lamports of SOL
+    #[sql_type = "Nullable<Numeric>"]
+    #[column_name = "_prev_1d_normalized_volume"]
+    pub prev_one_day_volume: Option<BigDecimal>,
+
+    /// Collection previous 7 days volume in lamports of SOL
+    #[sql_type = "Nullable<Numeric>"]
+    #[column_name = "_prev_7d_normalized_volume"]
+    pub prev_seven_day_volume: Option<BigDecimal>,
+
+    /// Collection previous 30 days volume in lamports of SOL
+    #[sql_type = "Nullable<Numeric>"]
+    #[column_name = "_prev_30d_normalized_volume"]
+    pub prev_thirty_day_volume: Option<BigDecimal>,
+
+    /// Percent change in 1 day normalized volume
+    #[sql_type = "Nullable<Int8>"]
+    #[column_name = "_1d_normalized_volume_change"]
+    pub one_day_volume_change: Option<i64>,
+
+    /// Percent change in 7 days normalized volume
+    #[sql_type = "Nullable<Int8>"]
+    #[column_name = "_7d_normalized_volume_change"]
+    pub seven_day_volume_change: Option<i64>,
+
+    /// Percent change in 30 days normalized volume
+    #[sql_type = "Nullable<Int8>"]
+    #[column_name = "_30d_normalized_volume_change"]
+    pub thirty_day_volume_change: Option<i64>,
+}
+
+/// A row in a `trends` query
+#[derive(Debug, Clone, QueryableByName)]
+pub struct NormalizedCollectionTrend {
+    /// The collection's row in the `collection_trends` table
+    #[diesel(embed)]
+    pub trend: CollectionTrend,
+
+    /// The collection's volumes normalized to lamports of SOL
+    #[diesel(embed)]
+    pub normalized: CollectionNormalizedVolumes,
+}
+
+/// A row in a `windowed_trends` query
+#[derive(Debug, Clone, QueryableByName)]
+pub struct WindowedCollectionTrend {
+    /// The collection's row in the `collection_trends` table
+    #[diesel(embed)]
+    pub trend: CollectionTrend,
+
+    /// The collection's volumes normalized to lamports of SOL
+    #[diesel(embed)]
+    pub normalized: CollectionNormalizedVolumes,
+
+    /// The collection's statistics over the requested window
+    #[diesel(embed)]
+    pub window: CollectionWindowStats,
+}
+
 /// Collection nfts/holders count
 #[derive(Debug, Clone, Queryable, QueryableByName)]
 pub struct CollectionCount {
@@ -858,6 +1012,19 @@ pub struct MetadataJson<'a> {
     pub write_version: i64,
     /// Metadata name
     pub name: Option<Cow<'a, str>>,
+    /// SHA-256 hash of the fetched document
+    pub content_hash: Option<Cow<'a, [u8]>>,
+    /// Where the document was fetched from, one of `ipfs`, `arweave`, or
+    /// `http`
+    pub content_source: Option<Cow<'a, str>>,
+    /// Whether the document matched the multihash of its IPFS CID, or null if
+    /// it could not be checked
+    pub cid_verified: Option<bool>,
+    /// The first time the document was observed to change while its URI
+    /// remained the same
+    pub content_changed_at: Option<NaiveDateTime>,
+    /// The name of the fetch strategy which retrieved the document
+    pub fetch_strategy: Option<Cow<'a, str>>,
 }
 
 /// A row in the `files` table
@@ -874,6 +1041,11 @@ pub struct File<'a> {
     pub slot: i64,
     /// The write version of the most recent update for this account
     pub write_version: i64,
+    /// For generated image derivatives, the original image URI this file was
+    /// derived from
+    pub source_uri: Option<Cow<'a, str>>,
+    /// For generated image derivatives, the width bucket of this file
+    pub width: Option<i32>,
 }
 
 /// A row in the `attributes` table
@@ -893,6 +1065,15 @@ pub struct MetadataAttributeWrite<'a> {
     pub slot: i64,
     /// The write version of the most recent update for this account
     pub write_version: i64,
+    /// The type of the attribute value, one of `string`, `number`, or
+    /// `boolean`
+    pub value_type: Option<Cow<'a, str>>,
+    /// The display type hint for the attribute, e.g. `date` or `boost_number`
+    pub display_type: Option<Cow<'a, str>>,
+    /// The numeric attribute value, if the value is a number
+    pub value_numeric: Option<f64>,
+    /// The maximum value of a numeric attribute, if specified
+    pub max_value: Option<f64>,
 }
 
 /// A row in the `attributes` table
@@ -912,6 +1093,15 @@ pub struct MetadataAttribute<'a> {
     pub slot: i64,
     /// The write version of the most recent update for this account
     pub write_version: i64,
+    /// The type of the attribute value, one of `string`, `number`, or
+    /// `boolean`
+    pub value_type: Option<Cow<'a, str>>,
+    /// The display type hint for the attribute, e.g. `date` or `boost_number`
+    pub display_type: Option<Cow<'a, str>>,
+    /// The numeric attribute value, if the value is a number
+    pub value_numeric: Option<f64>,
+    /// The maximum value of a numeric attribute, if specified
+    pub max_value: Option<f64>,
 }
 
 /// A row in the `files` table
@@ -929,6 +1119,11 @@ pub struct MetadataFile<'a> {
     pub slot: i64,
     /// The write version of the most recent update for this account
     pub write_version: i64,
+    /// For generated image derivatives, the original image URI this file was
+    /// derived from
+    pub source_uri: Option<Cow<'a, str>>,
+    /// For generated image derivatives, the width bucket of this file
+    pub width: Option<i32>,
 }
 
 /// A row in the `metadata_collections` table
@@ -998,12 +1193,26 @@ pub struct StoreConfigJson<'a> {
     pub logo_url: Cow<'a, str>,
     /// Storefront banner URL
     pub banner_url: Cow<'a, str>,
-    /// Storefront submain
-    pub subdomain: Cow<'a, str>,
-    /// Storefront owner address
-    pub owner_address: Cow<'a, str>,
+    /// Storefront subdomain, if the document specifies one
+    pub subdomain: Option<Cow<'a, str>>,
+    /// Storefront owner address, if the document specifies one
+    pub owner_address: Option<Cow<'a, str>>,
     /// Storefront address
     pub store_address: Option<Cow<'a, str>>,
+    /// Version of the store config JSON schema the document was parsed with
+    pub schema_version: i32,
+    /// Theme primary color
+    pub primary_color: Option<Cow<'a, str>>,
+    /// Theme background color
+    pub background_color: Option<Cow<'a, str>>,
+    /// Theme text color
+    pub text_color: Option<Cow<'a, str>>,
+    /// Theme font for titles
+    pub title_font: Option<Cow<'a, str>>,
+    /// Theme font for body text
+    pub text_font: Option<Cow<'a, str>>,
+    /// Custom domain serving the storefront
+    pub custom_domain: Option<Cow<'a, str>>,
 }
 
 /// A row in the `auction_houses` table
@@ -1403,6 +1612,87 @@ pub struct PricePoint {
     /// The date for which the price was requested
     #[sql_type = "Timestamp"]
     pub date: NaiveDateTime,
+
+    /// The requested price normalized to lamports of SOL, or `None` if any
+    /// of the prices it was computed from could not be converted
+    #[sql_type = "Nullable<Int8>"]
+    pub normalized_price: Option<i64>,
+}
+
+/// A row in a `collection_candles` query, summarizing the sales of a
+/// collection within a single time bucket
+#[derive(Debug, Clone, QueryableByName)]
+pub struct PriceCandle {
+    /// The start of the time bucket
+    #[sql_type = "Timestamp"]
+    pub bucket_start: NaiveDateTime,
+
+    /// The price of the first sale in the bucket, or `None` if the bucket
+    /// contains sales in more than one currency
+    #[sql_type = "Nullable<Int8>"]
+    pub open: Option<i64>,
+
+    /// The highest sale price in the bucket, or `None` if the bucket
+    /// contains sales in more than one currency
+    #[sql_type = "Nullable<Int8>"]
+    pub high: Option<i64>,
+
+    /// The lowest sale price in the bucket, or `None` if the bucket contains
+    /// sales in more than one currency
+    #[sql_type = "Nullable<Int8>"]
+    pub low: Option<i64>,
+
+    /// The price of the last sale in the bucket, or `None` if the bucket
+    /// contains sales in more than one currency
+    #[sql_type = "Nullable<Int8>"]
+    pub close: Option<i64>,
+
+    /// The total price of all sales in the bucket, or `None` if the bucket
+    /// contains sales in more than one currency
+    #[sql_type = "Nullable<Numeric>"]
+    pub volume: Option<BigDecimal>,
+
+    /// The mint of the currency all native prices are denominated in, or
+    /// `None` if the bucket contains sales in more than one currency
+    #[sql_type = "Nullable<VarChar>"]
+    pub currency_mint: Option<String>,
+
+    /// The normalized price of the first sale in the bucket
+    #[sql_type = "Nullable<Int8>"]
+    pub normalized_open: Option<i64>,
+
+    /// The highest normalized sale price in the bucket
+    #[sql_type = "Nullable<Int8>"]
+    pub normalized_high: Option<i64>,
+
+    /// The lowest normalized sale price in the bucket
+    #[sql_type = "Nullable<Int8>"]
+    pub normalized_low: Option<i64>,
+
+    /// The normalized price of the last sale in the bucket
+    #[sql_type = "Nullable<Int8>"]
+    pub normalized_close: Option<i64>,
+
+    /// The total normalized price of all sales in the bucket
+    #[sql_type = "Nullable<Numeric>"]
+    pub normalized_volume: Option<BigDecimal>,
+
+    /// The number of sales in the bucket
+    #[sql_type = "Int8"]
+    pub sales: i64,
+
+    /// The number of listings created in the bucket
+    #[sql_type = "Int8"]
+    pub listings: i64,
+
+    /// The lowest price of the listings created in the bucket, or `None` if
+    /// the bucket contains trades in more than one currency
+    #[sql_type = "Nullable<Int8>"]
+    pub listing_low: Option<i64>,
+
+    /// The lowest normalized price of the listings created in the bucket
+    #[sql_type = "Nullable<Int8>"]
+    pub normalized_listing_low: Option<i64>,
 }
 
 /// A row in a `metadatas::count_by_marketplace` query, representing stats for
@@ -2582,6 +2872,8 @@ pub struct ExecuteSaleInstruction<'a> {
     pub created_at: NaiveDateTime,
     /// Solana slot number
     pub slot: i64,
+    /// Creator accounts passed to the instruction to receive royalties
+    pub creator_accounts: Vec<String>,
 }
 /// A row in the `cancel_instructions` table
 #[derive(Debug, Clone, Queryable, Insertable, AsChangeset)]
@@ -2782,6 +3074,12 @@ pub struct Purchase<'a> {
     pub write_version: Option<i64>,
     /// Marketplace program address
     pub marketplace_program: Cow<'a, str>,
+    /// The mint of the currency the price is denominated in, filled in from
+    /// the auction house if not given
+    pub currency_mint: Option<Cow<'a, str>>,
+    /// The number of decimals of the currency the price is denominated in,
+    /// filled in from the `currencies` table if not given
+    pub currency_decimals: Option<i16>,
 }
 
 /// A row in the `listings` table
@@ -2822,6 +3120,12 @@ pub struct Listing<'a> {
     pub marketplace_program: Cow<'a, str>,
     /// Timestamp when the listing expires
     pub expiry: Option<NaiveDateTime>,
+    /// The mint of the currency the price is denominated in, filled in from
+    /// the auction house if not given
+    pub currency_mint: Option<Cow<'a, str>>,
+    /// The number of decimals of the currency the price is denominated in,
+    /// filled in from the `currencies` table if not given
+    pub currency_decimals: Option<i16>,
 }
 
 /// A row in the `cardinal_entries` table
@@ -3458,3 +3762,474 @@ pub struct DolphinStats1D<'a> {
     pub last_listed_1d: i64,
     pub last_volume_1d: i64,
 }
+
+/// A row in the `denylist_audit_log` table
+#[derive(Debug, Clone, Queryable, Insertable)]
+#[table_name = "denylist_audit_log"]
+pub struct DenylistAuditEntry<'a> {
+    /// Random Uuid primary key, generated by the database on insert
+    #[diesel(deserialize_as = "Uuid")]
+    pub id: Option<Uuid>,
+    /// The denylist modified, either `store` or `listing`
+    pub list: Cow<'a, str>,
+    /// The storefront owner or listing address added or removed
+    pub address: Cow<'a, str>,
+    /// The change made, either `add` or `remove`
+    pub action: Cow<'a, str>,
+    /// The hard-ban flag set by an `add`, or null for a `remove`
+    pub hard_ban: Option<bool>,
+    /// The moderator-supplied justification for the change
+    pub reason: Cow<'a, str>,
+    /// The name of the admin who made the change
+    pub actor: Cow<'a, str>,
+    /// The time the change was made
+    pub created_at: NaiveDateTime,
+}
+
+/// A row in the `image_derivative_failures` table
+#[derive(Debug, Clone, Queryable, Insertable, AsChangeset)]
+#[diesel(treat_none_as_null = true)]
+pub struct ImageDerivativeFailure<'a> {
+    /// Address of the metadata whose image could not be processed
+    pub metadata_address: Cow<'a, str>,
+    /// The image URI that failed to process
+    pub source_uri: Cow<'a, str>,
+    /// Number of consecutive failed attempts for this image URI
+    pub attempts: i32,
+    /// The most recent error encountered
+    pub error: Cow<'a, str>,
+    /// The time of the most recent attempt
+    pub updated_at: NaiveDateTime,
+}
+
+/// A row in the `metadata_json_issues` table
+#[derive(Debug, Clone, Queryable, Insertable)]
+pub struct MetadataJsonIssue<'a> {
+    /// Random Uuid primary key, generated by the database on insert
+    #[diesel(deserialize_as = "Uuid")]
+    pub id: Option<Uuid>,
+    /// Address of the metadata whose JSON document has the issue
+    pub metadata_address: Cow<'a, str>,
+    /// Machine-readable issue type, e.g. `missing_image`
+    pub code: Cow<'a, str>,
+    /// Location of the issue within the document, e.g. `properties.files[0].uri`
+    pub path: Cow<'a, str>,
+    /// Human-readable description of the issue
+    pub message: Cow<'a, str>,
+    /// The slot of the document the issue was found in
+    pub slot: i64,
+    /// The write version of the document the issue was found in
+    pub write_version: i64,
+}
+
+/// A row in the `metadata_json_refetches` table
+#[derive(Debug, Clone, Queryable, Insertable, AsChangeset)]
+#[diesel(treat_none_as_null = true)]
+#[table_name = "metadata_json_refetches"]
+pub struct MetadataJsonRefetch<'a> {
+    /// Address of the metadata whose JSON document is periodically re-fetched
+    pub metadata_address: Cow<'a, str>,
+    /// Current time between re-fetches, in seconds
+    pub interval_seconds: i32,
+    /// The time after which the document is next due to be re-fetched
+    pub next_fetch_at: NaiveDateTime,
+    /// True if a re-fetch has been dispatched but not yet completed
+    pub pending: bool,
+    /// The time the document was last fetched successfully
+    pub last_fetched_at: Option<NaiveDateTime>,
+    /// The time the document was last observed to change
+    pub last_changed_at: Option<NaiveDateTime>,
+}
+
+/// A row in the `store_config_json_issues` table
+#[derive(Debug, Clone, Queryable, Insertable)]
+pub struct StoreConfigJsonIssue<'a> {
+    /// Random Uuid primary key, generated by the database on insert
+    #[diesel(deserialize_as = "Uuid")]
+    pub id: Option<Uuid>,
+    /// Address of the store config whose JSON document has the issue
+    pub config_address: Cow<'a, str>,
+    /// Location of the issue within the document, e.g. `theme.logo.url`
+    pub path: Cow<'a, str>,
+    /// Human-readable description of the issue
+    pub message: Cow<'a, str>,
+}
+
+/// A row in the `nft_rarities` table
+#[derive(Debug, Clone, Queryable, Insertable, AsChangeset)]
+#[table_name = "nft_rarities"]
+pub struct NftRarity<'a> {
+    /// Address of the scored metadata account
+    pub metadata_address: Cow<'a, str>,
+    /// The scoring method, as named by [`RarityMethod`](crate::rarity::RarityMethod)
+    pub method: Cow<'a, str>,
+    /// Address of the verified collection the NFT was ranked within
+    pub collection_address: Cow<'a, str>,
+    /// The rarity score of the NFT, interpreted according to the method
+    pub score: f64,
+    /// The rank of the NFT within its collection, starting from 1 for the
+    /// rarest
+    pub rank: i32,
+    /// The time the score was last computed
+    pub updated_at: NaiveDateTime,
+}
+
+/// A row in the `currencies` table
+#[derive(Debug, Clone, Queryable, Insertable, AsChangeset)]
+#[table_name = "currencies"]
+pub struct Currency<'a> {
+    /// Address of the SPL token mint
+    pub mint: Cow<'a, str>,
+    /// Display symbol of the token
+    pub symbol: Cow<'a, str>,
+    /// Number of decimal places in one whole token
+    pub decimals: i16,
+}
+
+/// A row in the `currency_rates` table
+#[derive(Debug, Clone, Queryable, Insertable, AsChangeset)]
+pub struct CurrencyRate<'a> {
+    /// Address of the SPL token mint
+    pub mint: Cow<'a, str>,
+    /// The time from which this rate applies, until the next rate for the
+    /// same mint
+    pub effective_at: NaiveDateTime,
+    /// The price of one whole token in SOL
+    pub sol_price: BigDecimal,
+}
+
+/// A row in the `sale_royalties` table
+#[derive(Debug, Clone, Queryable, Insertable, AsChangeset)]
+#[diesel(treat_none_as_null = true)]
+#[table_name = "sale_royalties"]
+pub struct SaleRoyalty<'a> {
+    /// The sale the royalty is owed for
+    pub purchase_id: Uuid,
+    /// Address of the creator owed the royalty
+    pub creator_address: Cow<'a, str>,
+    /// Address of the metadata account of the sold NFT
+    pub metadata_address: Cow<'a, str>,
+    /// Address of the auction house the sale was made through
+    pub auction_house: Cow<'a, str>,
+    /// Address of the marketplace program the sale was made through
+    pub marketplace_program: Cow<'a, str>,
+    /// The mint of the currency the sale was made in, if known
+    pub currency_mint: Option<Cow<'a, str>>,
+    /// The time of the sale
+    pub sold_at: NaiveDateTime,
+    /// The creator's share of the royalty, in percent
+    pub share: i32,
+    /// The royalty owed to the creator, in the smallest unit of the currency
+    pub expected: i64,
+    /// The royalty paid to the creator, or `None` if it could not be
+    /// determined
+    pub paid: Option<i64>,
+    /// When the sale's transaction was checked for the payment, or `None` if
+    /// it has not been
+    pub checked_at: Option<NaiveDateTime>,
+}
+
+/// A row in a `royalties` query, summarizing the royalties owed and paid
+/// for a set of sales
+#[derive(Debug, Clone, QueryableByName)]
+pub struct RoyaltyStats {
+    /// The marketplace program the sales were made through, if grouped by
+    /// marketplace
+    #[sql_type = "Nullable<VarChar>"]
+    pub marketplace_program: Option<String>,
+
+    /// The number of sales owing royalties
+    #[sql_type = "Int8"]
+    pub sales: i64,
+
+    /// The number of sales whose royalty payments could be determined
+    #[sql_type = "Int8"]
+    pub verified_sales: i64,
+
+    /// The mint of the currency the native amounts are denominated in, or
+    /// `None` if the sales were made in more than one currency
+    #[sql_type = "Nullable<VarChar>"]
+    pub currency_mint: Option<String>,
+
+    /// Total royalties owed, or `None` if the sales were made in more than one
+    /// currency
+    #[sql_type = "Nullable<Numeric>"]
+    pub expected: Option<BigDecimal>,
+
+    /// Total royalties owed for verified sales, or `None` if the sales were
+    /// made in more than one currency
+    #[sql_type = "Nullable<Numeric>"]
+    pub verified_expected: Option<BigDecimal>,
+
+    /// Total royalties paid for verified sales, or `None` if the sales were
+    /// made in more than one currency
+    #[sql_type = "Nullable<Numeric>"]
+    pub paid: Option<BigDecimal>,
+
+    /// Total royalties owed in lamports of SOL, or `None` if any could not be
+    /// converted
+    #[sql_type = "Nullable<Numeric>"]
+    pub normalized_expected: Option<BigDecimal>,
+
+    /// Total royalties paid for verified sales in lamports of SOL, or `None`
+    /// if any could not be converted
+    #[sql_type = "Nullable<Numeric>"]
+    pub normalized_paid: Option<BigDecimal>,
+
+    /// The fraction of royalties owed for verified sales which were paid
+    #[sql_type = "Nullable<Double>"]
+    pub paid_ratio: Option<f64>,
+}
+
+/// A row in a `portfolio::holdings` query, representing an NFT held by a
+/// wallet
+#[derive(Debug, Clone, QueryableByName)]
+pub struct PortfolioHolding {
+    /// Address of the metadata of the held NFT
+    #[sql_type = "VarChar"]
+    pub metadata_address: String,
+
+    /// The verified collection of the NFT, if any
+    #[sql_type = "Nullable<VarChar>"]
+    pub collection_address: Option<String>,
+
+    /// The floor price of the collection
+    #[sql_type = "Nullable<Int8>"]
+    pub floor_price: Option<i64>,
+
+    /// The last sale price of the NFT in lamports of SOL
+    #[sql_type = "Nullable<Int8>"]
+    pub last_sale_price: Option<i64>,
+
+    /// The highest floor price within the collection of any of the NFT's
+    /// traits, in lamports of SOL
+    #[sql_type = "Nullable<Int8>"]
+    pub trait_floor: Option<i64>,
+}
+
+/// A row in a `portfolio::trades` query, representing a sale a wallet took
+/// part in
+#[derive(Debug, Clone, QueryableByName)]
+pub struct WalletTrade {
+    /// Address of the metadata of the traded NFT
+    #[sql_type = "VarChar"]
+    pub metadata_address: String,
+
+    /// The verified collection of the NFT, if any
+    #[sql_type = "Nullable<VarChar>"]
+    pub collection_address: Option<String>,
+
+    /// True if the wallet was the buyer, false if it was the seller
+    #[sql_type = "Bool"]
+    pub bought: bool,
+
+    /// The sale price in lamports of SOL, or `None` if it could not be
+    /// converted
+    #[sql_type = "Nullable<Int8>"]
+    pub price: Option<i64>,
+
+    /// The time of the sale
+    #[sql_type = "Timestamp"]
+    pub created_at: NaiveDateTime,
+}
+
+/// A row in a `portfolio::daily_closes` query
+#[derive(Debug, Clone, QueryableByName)]
+pub struct CollectionDailyClose {
+    /// Mint address of the collection NFT
+    #[sql_type = "VarChar"]
+    pub collection_address: String,
+
+    /// The start of the day
+    #[sql_type = "Timestamp"]
+    pub bucket_start: NaiveDateTime,
+
+    /// The price of the last sale of the day in lamports of SOL
+    #[sql_type = "Int8"]
+    pub close: i64,
+}
+
+/// A row in the `wash_trades` table
+#[derive(Debug, Clone, Queryable, Insertable, AsChangeset)]
+#[table_name = "wash_trades"]
+pub struct WashTrade {
+    /// The sale suspected of being a wash trade
+    pub purchase_id: Uuid,
+    /// The reasons the sale was flagged, as the string forms of
+    /// [`WashTradeReason`](crate::db::custom_types::WashTradeReason)
+    pub reasons: Vec<String>,
+    /// The time the sale was first flagged
+    pub detected_at: NaiveDateTime,
+}
+
+/// A row in the `collection_holders` table
+#[derive(Debug, Clone, Queryable, Insertable, AsChangeset)]
+#[table_name = "collection_holders"]
+pub struct CollectionHolder<'a> {
+    /// Mint address of the collection NFT
+    pub collection_address: Cow<'a, str>,
+    /// Address of the holding wallet
+    pub owner_address: Cow<'a, str>,
+    /// The number of NFTs of the collection held by the wallet
+    pub nft_count: i64,
+}
+
+/// A row in the `collection_holder_days` table
+#[derive(Debug, Clone, Queryable, Insertable, AsChangeset)]
+#[table_name = "collection_holder_days"]
+pub struct CollectionHolderDay<'a> {
+    /// Mint address of the collection NFT
+    pub collection_address: Cow<'a, str>,
+    /// The day
+    pub day: NaiveDate,
+    /// The number of wallets which started holding NFTs of the collection
+    pub new_holders: i64,
+    /// The number of wallets which stopped holding NFTs of the collection
+    pub churned_holders: i64,
+}
+
+/// An event in the provenance of an NFT, merged from the tables recording
+/// mints, listings, offers, sales, ownership history, rentals, and burns
+#[derive(Debug, Clone, QueryableByName)]
+pub struct NftEvent {
+    /// The string form of the event's
+    /// [`NftEventKind`](crate::db::custom_types::NftEventKind)
+    #[sql_type = "Text"]
+    pub kind: String,
+
+    /// A key identifying the event among events of the NFT at the same time
+    #[sql_type = "Text"]
+    pub key: String,
+
+    /// The time the event occurred
+    #[sql_type = "Timestamp"]
+    pub occurred_at: NaiveDateTime,
+
+    /// The slot of the event, if recorded by its source
+    #[sql_type = "Nullable<Int8>"]
+    pub slot: Option<i64>,
+
+    /// The wallet acting in the event, i.e. the minter, seller of a listing,
+    /// bidder of an offer, buyer of a sale, new owner of a transfer, issuer of
+    /// a Cardinal token manager, renter of a habitat, or owner of a burned NFT
+    #[sql_type = "Nullable<VarChar>"]
+    pub wallet: Option<String>,
+
+    /// The other side of the event, i.e. the seller of a sale, the previous
+    /// owner of a transfer, or the owner of a rented habitat
+    #[sql_type = "Nullable<VarChar>"]
+    pub counterparty: Option<String>,
+
+    /// The price of a listing, offer, or sale
+    #[sql_type = "Nullable<Int8>"]
+    pub price: Option<i64>,
+
+    /// The auction house of a listing, offer, or sale
+    #[sql_type = "Nullable<VarChar>"]
+    pub auction_house: Option<String>,
+
+    /// The marketplace program of a listing, offer, or sale
+    #[sql_type = "Nullable<VarChar>"]
+    pub marketplace_program: Option<String>,
+}
+
+/// A row in the `metadata_owner_history` table
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "metadata_owner_history"]
+pub struct MetadataOwnerHistory<'a> {
+    /// The mint address of the token
+    pub mint_address: Cow<'a, str>,
+    /// The slot from which the owner held the token
+    pub slot: i64,
+    /// The token owner address
+    pub owner_address: Cow<'a, str>,
+    /// The address of the token account
+    pub token_account_address: Cow<'a, str>,
+}
+
+/// A row in the `metadata_burns` table
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "metadata_burns"]
+pub struct MetadataBurn<'a> {
+    /// The mint address of the burned token
+    pub mint_address: Cow<'a, str>,
+    /// The slot the token was burned in
+    pub slot: i64,
+}
+
+/// The period an NFT was held by one owner, computed from the
+/// `metadata_owner_history` and `metadata_burns` tables
+#[derive(Debug, Clone, QueryableByName)]
+pub struct MetadataOwnerRange {
+    /// The mint address of the token
+    #[sql_type = "VarChar"]
+    pub mint_address: String,
+
+    /// The token owner address
+    #[sql_type = "VarChar"]
+    pub owner_address: String,
+
+    /// The address of the token account
+    #[sql_type = "VarChar"]
+    pub token_account_address: String,
+
+    /// The slot from which the owner held the token
+    #[sql_type = "Int8"]
+    pub start_slot: i64,
+
+    /// The slot of the next owner or of the burn of the token, or `None` if
+    /// the owner still holds it
+    #[sql_type = "Nullable<Int8>"]
+    pub end_slot: Option<i64>,
+
+    /// The block time of the start slot, or the time the owner was indexed
+    /// if the block time is unknown
+    #[sql_type = "Timestamp"]
+    pub started_at: NaiveDateTime,
+
+    /// The block time of the end slot, or the time it was indexed if the
+    /// block time is unknown, or `None` if the owner still holds the token
+    #[sql_type = "Nullable<Timestamp>"]
+    pub ended_at: Option<NaiveDateTime>,
+}
+
+/// The number of NFTs of a collection held by a wallet at a point in time,
+/// computed from the `metadata_owner_history` table
+#[derive(Debug, Clone, QueryableByName)]
+pub struct HolderSnapshot {
+    /// The address of the holding wallet
+    #[sql_type = "VarChar"]
+    pub owner_address: String,
+
+    /// The number of NFTs of the collection held by the wallet
+    #[sql_type = "Int8"]
+    pub nft_count: i64,
+}
+
+/// A page entry of a Postgres NFT search
+#[derive(Debug, Clone, QueryableByName)]
+pub struct NftSearchHit {
+    /// The address of the matching metadata account
+    #[sql_type = "Text"]
+    pub address: String,
+
+    /// The total number of NFTs matching the search
+    #[sql_type = "Int8"]
+    pub total: i64,
+}
+
+/// The number of NFTs matching a search with a trait value
+#[derive(Debug, Clone, QueryableByName)]
+pub struct TraitCount {
+    /// The trait type
+    #[sql_type = "Text"]
+    pub trait_type: String,
+
+    /// The trait value
+    #[sql_type = "Text"]
+    pub value: String,
+
+    /// The number of matching NFTs with this trait value
+    #[sql_type = "Int8"]
+    pub count: i64,
+}
@@ -0,0 +1,72 @@
+use diesel::sql_types::{Int8, Nullable};
+
+use crate::{
+    db::{
+        tables::{metadata_burns, metadata_owner_history},
+        update, Connection,
+    },
+    error::Result,
+    prelude::*,
+};
+
+#[derive(QueryableByName)]
+struct PendingSlot {
+    #[sql_type = "Int8"]
+    slot: i64,
+}
+
+const PENDING_QUERY: &str = r"
+select slot from (
+    select slot from metadata_owner_history
+    where block_time is null and ($1::bigint is null or slot < $1)
+    union
+    select slot from metadata_burns
+    where block_time is null and ($1::bigint is null or slot < $1)
+) s
+order by slot desc
+limit $2;
+ -- $1: before slot::bigint
+ -- $2: limit::bigint";
+
+/// Load up to `limit` slots below `before` with owner history or burns whose
+/// block time has not been recorded, highest first
+///
+/// # Errors
+/// This function fails if the query fails
+pub fn pending_slots(db: &Connection, before: Option<i64>, limit: i64) -> Result<Vec<i64>> {
+    diesel::sql_query(PENDING_QUERY)
+        .bind::<Nullable<Int8>, _>(before)
+        .bind::<Int8, _>(limit)
+        .load::<PendingSlot>(db)
+        .context("Failed to load slots missing a block time")
+        .map(|v| v.into_iter().map(|PendingSlot { slot }| slot).collect())
+}
+
+/// Record the block time of a slot on the owner history and burns recorded
+/// in it.  Returns the number of rows updated.
+///
+/// # Errors
+/// This function fails if the update fails
+pub fn record(db: &Connection, slot: i64, block_time: NaiveDateTime) -> Result<usize> {
+    db.build_transaction().read_write().run(|| {
+        let owners = update(
+            metadata_owner_history::table
+                .filter(metadata_owner_history::slot.eq(slot))
+                .filter(metadata_owner_history::block_time.is_null()),
+        )
+        .set(metadata_owner_history::block_time.eq(block_time))
+        .execute(db)
+        .context("Failed to record owner history block time")?;
+
+        let burns = update(
+            metadata_burns::table
+                .filter(metadata_burns::slot.eq(slot))
+                .filter(metadata_burns::block_time.is_null()),
+        )
+        .set(metadata_burns::block_time.eq(block_time))
+        .execute(db)
+        .context("Failed to record burn block time")?;
+
+        Ok(owners + burns)
+    })
+}
@@ -0,0 +1,32 @@
+use crate::{db::Connection, error::Result, prelude::*};
+
+const REBUILD_QUERY: &str = r"
+insert into collection_holders (collection_address, owner_address, nft_count)
+select mck.collection_address, cmo.owner_address, count(*)
+from metadata_collection_keys mck
+inner join metadatas md on md.address = mck.metadata_address
+inner join current_metadata_owners cmo on cmo.mint_address = md.mint_address
+where mck.verified and md.burned_at is null
+group by mck.collection_address, cmo.owner_address;";
+
+/// Discard the number of NFTs held by each wallet for every verified
+/// collection and recount them from the `current_metadata_owners` table.
+/// Returns the number of rows written.
+///
+/// Holdings are normally maintained by triggers as ownership, collections,
+/// and burns are indexed.  The daily new and churned holder counts are left
+/// untouched, as past ownership is not recorded.
+///
+/// # Errors
+/// This function fails if the holdings cannot be rebuilt
+pub fn rebuild(db: &Connection) -> Result<usize> {
+    db.build_transaction().read_write().run(|| {
+        diesel::sql_query("delete from collection_holders;")
+            .execute(db)
+            .context("Failed to clear collection holders")?;
+
+        diesel::sql_query(REBUILD_QUERY)
+            .execute(db)
+            .context("Failed to rebuild collection holders")
+    })
+}
@@ -0,0 +1,18 @@
+use crate::{db::Connection, error::Result, prelude::*};
+
+/// Recompute the normalized and flagged volumes of every collection in the
+/// `collection_trends` table from the hourly and daily price candles
+///
+/// The other columns of the table are maintained outside the indexer, so
+/// these are refreshed periodically by the trends worker rather than as sales
+/// are indexed.
+///
+/// # Errors
+/// This function fails if the volumes cannot be refreshed
+pub fn refresh_volumes(db: &Connection) -> Result<()> {
+    diesel::sql_query("select refresh_collection_trend_volumes();")
+        .execute(db)
+        .context("Failed to refresh collection trend volumes")?;
+
+    Ok(())
+}
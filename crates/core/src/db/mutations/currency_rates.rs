@@ -0,0 +1,169 @@
+use std::{io::BufRead, str::FromStr};
+
+use bigdecimal::BigDecimal;
+
+use crate::{
+    db::{
+        excluded, insert_into,
+        models::{Currency, CurrencyRate},
+        tables::{currencies, currency_rates},
+        Connection,
+    },
+    error::Result,
+    prelude::*,
+};
+
+fn parse_time(s: &str) -> Result<NaiveDateTime> {
+    if let Ok(secs) = s.parse::<i64>() {
+        return NaiveDateTime::from_timestamp_opt(secs, 0)
+            .ok_or_else(|| anyhow!("Timestamp {} is out of range", secs));
+    }
+
+    DateTime::parse_from_rfc3339(s)
+        .map(|t| t.naive_utc())
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S"))
+        .or_else(|_| NaiveDate::parse_from_str(s, "%Y-%m-%d").map(|d| d.and_hms(0, 0, 0)))
+        .with_context(|| format!("Invalid time {:?}", s))
+}
+
+/// Parse currency rates from lines of the form `mint,effective_at,sol_price`,
+/// where `effective_at` is a UNIX timestamp, an RFC 3339 time, or a UTC date
+/// and `sol_price` is the price of one whole token in SOL.  Blank lines,
+/// lines starting with `#`, and a leading header line are skipped.
+///
+/// # Errors
+/// This function fails if the input cannot be read or any line is malformed
+pub fn parse<R: BufRead>(input: R) -> Result<Vec<CurrencyRate<'static>>> {
+    let mut rates = vec![];
+
+    for (i, line) in input.lines().enumerate() {
+        let line = line.context("Failed to read currency rates")?;
+        let line = line.trim();
+
+        if line.is_empty()
+            || line.starts_with('#')
+            || (i == 0 && line == "mint,effective_at,sol_price")
+        {
+            continue;
+        }
+
+        let rate = (|| -> Result<_> {
+            let mut fields = line.split(',').map(str::trim);
+            let (mint, time, price) = match (fields.next(), fields.next(), fields.next()) {
+                (Some(m), Some(t), Some(p)) if fields.next().is_none() => (m, t, p),
+                _ => bail!("Expected mint,effective_at,sol_price"),
+            };
+
+            if mint.is_empty() {
+                bail!("Missing mint");
+            }
+
+            let sol_price = BigDecimal::from_str(price)
+                .with_context(|| format!("Invalid SOL price {:?}", price))?;
+
+            if sol_price < BigDecimal::from(0) {
+                bail!("SOL price {} is negative", sol_price);
+            }
+
+            Ok(CurrencyRate {
+                mint: Owned(mint.to_owned()),
+                effective_at: parse_time(time)?,
+                sol_price,
+            })
+        })()
+        .with_context(|| format!("Invalid currency rate on line {}", i + 1))?;
+
+        rates.push(rate);
+    }
+
+    Ok(rates)
+}
+
+/// Insert the given currency rates, replacing any existing rates for the
+/// same mint and time.  Returns the number of rates written.
+///
+/// Rates are applied to price candles as sales are indexed, so candles for
+/// sales made before this call should be rebuilt with
+/// [`price_candles::rebuild`](super::price_candles::rebuild).
+///
+/// # Errors
+/// This function fails if any rate is for an unregistered currency or the
+/// rates cannot be written
+pub fn insert(db: &Connection, rates: &[CurrencyRate]) -> Result<usize> {
+    db.build_transaction().read_write().run(|| {
+        let mut n = 0;
+
+        // Insert in chunks to stay below the bind parameter limit
+        for chunk in rates.chunks(4096) {
+            let mints: Vec<&str> = chunk.iter().map(|r| r.mint.as_ref()).collect();
+            let known: Vec<String> = currencies::table
+                .filter(currencies::mint.eq_any(&mints))
+                .select(currencies::mint)
+                .load(db)
+                .context("Failed to check currency mints")?;
+
+            if let Some(mint) = mints.iter().find(|m| !known.iter().any(|k| k == *m)) {
+                bail!("Currency {} is not registered", mint);
+            }
+
+            n += insert_into(currency_rates::table)
+                .values(chunk)
+                .on_conflict((currency_rates::mint, currency_rates::effective_at))
+                .do_update()
+                .set(currency_rates::sol_price.eq(excluded(currency_rates::sol_price)))
+                .execute(db)
+                .context("Failed to write currency rates")?;
+        }
+
+        Ok(n)
+    })
+}
+
+/// Register a currency or update its symbol and decimals
+///
+/// # Errors
+/// This function fails if the currency cannot be written
+pub fn upsert_currency(db: &Connection, currency: &Currency) -> Result<()> {
+    insert_into(currencies::table)
+        .values(currency)
+        .on_conflict(currencies::mint)
+        .do_update()
+        .set(currency)
+        .execute(db)
+        .context("Failed to write currency")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bigdecimal::BigDecimal;
+
+    use super::parse;
+
+    #[test]
+    fn test_parse_rates() {
+        let input = "mint,effective_at,sol_price\n\
+                     # USDC\n\
+                     EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v,1667952000,0.0625\n\
+                     \n\
+                     EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v, 2022-11-10 ,0.08\n";
+
+        let rates = parse(input.as_bytes()).unwrap();
+
+        assert_eq!(rates.len(), 2);
+        assert_eq!(rates[0].effective_at.to_string(), "2022-11-09 00:00:00");
+        assert_eq!(rates[1].effective_at.to_string(), "2022-11-10 00:00:00");
+        assert_eq!(rates[1].sol_price, "0.08".parse::<BigDecimal>().unwrap());
+
+        for bad in [
+            "So11111111111111111111111111111111111111112,1667952000",
+            "So11111111111111111111111111111111111111112,1667952000,1,2",
+            ",1667952000,1",
+            "So11111111111111111111111111111111111111112,yesterday,1",
+            "So11111111111111111111111111111111111111112,1667952000,-1",
+        ] {
+            assert!(parse(bad.as_bytes()).is_err(), "{:?}", bad);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{
+    db::{
+        delete, insert_into,
+        models::DenylistAuditEntry,
+        tables::{denylist_audit_log, listing_denylist, store_denylist},
+        Connection,
+    },
+    error::Result,
+    prelude::*,
+};
+
+/// A denylist which can be modified by an admin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum List {
+    /// The `store_denylist` table, keyed by storefront owner address
+    Store,
+    /// The `listing_denylist` table, keyed by listing address
+    Listing,
+}
+
+impl List {
+    /// The value recorded in the `list` column of the audit log
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Store => "store",
+            Self::Listing => "listing",
+        }
+    }
+}
+
+/// An admin-requested change to one of the denylists
+#[derive(Debug, Clone, Copy)]
+pub struct Change<'a> {
+    /// The denylist to modify
+    pub list: List,
+    /// The storefront owner or listing address to add or remove
+    pub address: &'a str,
+    /// Justification for the change, recorded in the audit log
+    pub reason: &'a str,
+    /// Name of the admin making the change, recorded in the audit log
+    pub actor: &'a str,
+}
+
+fn audit(
+    db: &Connection,
+    change: Change,
+    action: &str,
+    hard_ban: Option<bool>,
+) -> Result<DenylistAuditEntry<'static>> {
+    insert_into(denylist_audit_log::table)
+        .values(DenylistAuditEntry {
+            id: None,
+            list: Borrowed(change.list.as_str()),
+            address: Borrowed(change.address),
+            action: Borrowed(action),
+            hard_ban,
+            reason: Borrowed(change.reason),
+            actor: Borrowed(change.actor),
+            created_at: Utc::now().naive_utc(),
+        })
+        .returning(denylist_audit_log::all_columns)
+        .get_result(db)
+        .context("Failed to insert denylist audit entry")
+}
+
+/// Add an address to a denylist, or update its hard-ban flag if it is already
+/// present, and record the change in the audit log
+///
+/// # Errors
+/// This function fails if the denylist upsert or audit log insert fails
+pub fn add(db: &Connection, change: Change, hard_ban: bool) -> Result<DenylistAuditEntry<'static>> {
+    db.build_transaction().read_write().run(|| {
+        match change.list {
+            List::Store => insert_into(store_denylist::table)
+                .values((
+                    store_denylist::owner_address.eq(change.address),
+                    store_denylist::hard_ban.eq(hard_ban),
+                ))
+                .on_conflict(store_denylist::owner_address)
+                .do_update()
+                .set(store_denylist::hard_ban.eq(hard_ban))
+                .execute(db),
+            List::Listing => insert_into(listing_denylist::table)
+                .values((
+                    listing_denylist::listing_address.eq(change.address),
+                    listing_denylist::hard_ban.eq(hard_ban),
+                ))
+                .on_conflict(listing_denylist::listing_address)
+                .do_update()
+                .set(listing_denylist::hard_ban.eq(hard_ban))
+                .execute(db),
+        }
+        .context("Failed to upsert denylist entry")?;
+
+        audit(db, change, "add", Some(hard_ban))
+    })
+}
+
+/// Remove an address from a denylist and record the change in the audit log
+///
+/// Returns `None` without writing to the audit log if the address was not
+/// present in the denylist.
+///
+/// # Errors
+/// This function fails if the denylist delete or audit log insert fails
+pub fn remove(db: &Connection, change: Change) -> Result<Option<DenylistAuditEntry<'static>>> {
+    db.build_transaction().read_write().run(|| {
+        let removed = match change.list {
+            List::Store => delete(
+                store_denylist::table.filter(store_denylist::owner_address.eq(change.address)),
+            )
+            .execute(db),
+            List::Listing => delete(
+                listing_denylist::table
+                    .filter(listing_denylist::listing_address.eq(change.address)),
+            )
+            .execute(db),
+        }
+        .context("Failed to delete denylist entry")?;
+
+        if removed == 0 {
+            return Ok(None);
+        }
+
+        audit(db, change, "remove", None).map(Some)
+    })
+}
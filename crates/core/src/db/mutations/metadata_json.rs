@@ -0,0 +1,185 @@
+use crate::{
+    db::{
+        insert_into,
+        models::MetadataJsonRefetch,
+        sql_types::Int8,
+        tables::{metadata_json_refetches, metadata_jsons, metadatas},
+        update, Connection,
+    },
+    error::Result,
+    prelude::*,
+};
+
+/// Schedule an immediate re-fetch of the metadata JSON documents for the given
+/// mints, regardless of whether their URIs point to mutable storage
+///
+/// Mints with no existing re-fetch schedule are added with an interval of
+/// zero, which the HTTP indexer clamps to its configured minimum.  Returns the
+/// addresses of the metadata accounts scheduled.
+///
+/// # Errors
+/// This function fails if the metadata accounts cannot be loaded or the
+/// schedule cannot be updated
+pub fn request_refetch(db: &Connection, mint_addresses: &[String]) -> Result<Vec<String>> {
+    let now = Local::now().naive_utc();
+
+    db.build_transaction().read_write().run(|| {
+        let addresses: Vec<String> = metadatas::table
+            .filter(metadatas::mint_address.eq(any(mint_addresses)))
+            .select(metadatas::address)
+            .load(db)
+            .context("Failed to load metadata addresses")?;
+
+        let rows: Vec<_> = addresses
+            .iter()
+            .map(|a| MetadataJsonRefetch {
+                metadata_address: Borrowed(a),
+                interval_seconds: 0,
+                next_fetch_at: now,
+                pending: false,
+                last_fetched_at: None,
+                last_changed_at: None,
+            })
+            .collect();
+
+        insert_into(metadata_json_refetches::table)
+            .values(&rows)
+            .on_conflict(metadata_json_refetches::metadata_address)
+            .do_update()
+            .set(metadata_json_refetches::next_fetch_at.eq(now))
+            .execute(db)
+            .context("Failed to schedule metadata JSON re-fetch")?;
+
+        Ok(addresses)
+    })
+}
+
+/// Clear the recorded content change of the metadata JSON documents for the
+/// given mints, e.g. once the change has been reviewed.  Returns the addresses
+/// of the metadata accounts whose documents had a recorded change.
+///
+/// # Errors
+/// This function fails if the metadata JSON rows cannot be updated
+pub fn clear_content_change(db: &Connection, mint_addresses: &[String]) -> Result<Vec<String>> {
+    update(
+        metadata_jsons::table
+            .filter(
+                metadata_jsons::metadata_address.eq(any(metadatas::table
+                    .filter(metadatas::mint_address.eq(any(mint_addresses)))
+                    .select(metadatas::address))),
+            )
+            .filter(metadata_jsons::content_changed_at.is_not_null()),
+    )
+    .set(metadata_jsons::content_changed_at.eq(None::<NaiveDateTime>))
+    .returning(metadata_jsons::metadata_address)
+    .get_results(db)
+    .context("Failed to clear metadata JSON content changes")
+}
+
+/// Counts of rows removed or repaired by [`remove_stale_rows`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StaleRows {
+    /// Files left by a previous metadata JSON document
+    pub files: usize,
+    /// Attributes left by a previous metadata JSON document
+    pub attributes: usize,
+    /// Collections replaced with, or removed according to, the current
+    /// document
+    pub collections: usize,
+}
+
+impl StaleRows {
+    /// The total number of rows affected
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.files + self.attributes + self.collections
+    }
+}
+
+const STALE_FILES_QUERY: &str = r"
+delete from files
+where id in (
+    select f.id
+    from files f
+    inner join metadata_jsons mj on mj.metadata_address = f.metadata_address
+    where f.source_uri is null
+        and (f.slot, f.write_version) < (mj.slot, mj.write_version)
+    limit $1
+);
+ -- $1: limit::bigint";
+
+const STALE_ATTRIBUTES_QUERY: &str = r"
+delete from attributes
+where id in (
+    select a.id
+    from attributes a
+    inner join metadata_jsons mj on mj.metadata_address = a.metadata_address
+    where (a.slot, a.write_version) < (mj.slot, mj.write_version)
+    limit $1
+);
+ -- $1: limit::bigint";
+
+const STALE_COLLECTIONS_QUERY: &str = r"
+with stale as (
+    select mc.metadata_address, mj.raw_content -> 'collection' as collection,
+        mj.slot, mj.write_version
+    from metadata_collections mc
+    inner join metadata_jsons mj on mj.metadata_address = mc.metadata_address
+    where (mc.slot, mc.write_version) < (mj.slot, mj.write_version)
+    limit $1
+), deleted as (
+    delete from metadata_collections mc
+    using stale s
+    where mc.metadata_address = s.metadata_address
+        and jsonb_typeof(s.collection) is distinct from 'object'
+    returning mc.metadata_address
+), updated as (
+    update metadata_collections mc
+    set name = s.collection ->> 'name',
+        family = s.collection ->> 'family',
+        slot = s.slot,
+        write_version = s.write_version
+    from stale s
+    where mc.metadata_address = s.metadata_address
+        and jsonb_typeof(s.collection) = 'object'
+    returning mc.metadata_address
+)
+select (select count(*) from deleted) + (select count(*) from updated) as count;
+ -- $1: limit::bigint";
+
+#[derive(Debug, QueryableByName)]
+struct Count {
+    #[sql_type = "Int8"]
+    count: i64,
+}
+
+/// Remove or repair up to `limit` rows of each type left behind in the
+/// `files`, `attributes`, and `metadata_collections` tables by metadata JSON
+/// documents which have since been replaced
+///
+/// # Errors
+/// This function fails if any of the underlying queries fail
+pub fn remove_stale_rows(db: &Connection, limit: i64) -> Result<StaleRows> {
+    db.build_transaction().read_write().run(|| {
+        let files = diesel::sql_query(STALE_FILES_QUERY)
+            .bind::<Int8, _>(limit)
+            .execute(db)
+            .context("Failed to remove stale files")?;
+
+        let attributes = diesel::sql_query(STALE_ATTRIBUTES_QUERY)
+            .bind::<Int8, _>(limit)
+            .execute(db)
+            .context("Failed to remove stale attributes")?;
+
+        let Count { count } = diesel::sql_query(STALE_COLLECTIONS_QUERY)
+            .bind::<Int8, _>(limit)
+            .get_result(db)
+            .context("Failed to repair stale collections")?;
+
+        Ok(StaleRows {
+            files,
+            attributes,
+            collections: count.try_into().unwrap_or_default(),
+        })
+    })
+}
@@ -1,6 +1,30 @@
-//! listing and offer upsert functions
+//! listing and offer upsert functions, admin denylist and metadata JSON
+//! mutations, currency rate loading, block time recording, and rarity, price
+//! candle, collection holder, collection trend volume, sale royalty, and wash
+//! trade recomputation
 
+/// Recording of the block times of slots with owner history or burns
+pub mod block_times;
+/// Rebuilding of the number of NFTs of each collection held by each wallet
+pub mod collection_holders;
+/// Periodic refreshing of the normalized volumes of collection trends
+pub mod collection_trends;
+/// Loading of currencies and their historical SOL conversion rates
+pub mod currency_rates;
+/// Admin functions for adding and removing denylist entries with an audit log
+pub mod denylist;
 /// Generic listing upsert function which returns listing uuid if upsert is successful
 pub mod listing;
+/// On-demand scheduling of metadata JSON re-fetches
+pub mod metadata_json;
 /// Generic offer upsert function which returns offer uuid if upsert is successful
 pub mod offer;
+/// Rebuilding of the collection price candles and buyer rollups maintained
+/// from purchases
+pub mod price_candles;
+/// Recomputation of NFT rarity scores for verified collections
+pub mod rarity;
+/// Rebuilding of the royalties owed and paid to creators for each sale
+pub mod royalties;
+/// Re-running wash trade detection over all sales
+pub mod wash_trades;
@@ -0,0 +1,125 @@
+use crate::{
+    db::{tables::price_candles, Connection},
+    error::Result,
+    prelude::*,
+};
+
+const REBUILD_QUERY: &str = r"
+insert into price_candles (
+    collection_address, auction_house, resolution, bucket_start,
+    open, high, low, close, volume, sales, opened_at, closed_at,
+    currency_mint, normalized_open, normalized_high, normalized_low,
+    normalized_close, normalized_volume, flagged_volume, flagged_sales,
+    flagged_normalized_volume
+)
+select mck.collection_address, p.auction_house, r.resolution,
+       date_trunc(r.unit, p.created_at) as bucket_start,
+       (array_agg(p.price order by p.created_at asc, p.id asc))[1],
+       max(p.price),
+       min(p.price),
+       (array_agg(p.price order by p.created_at desc, p.id desc))[1],
+       sum(p.price),
+       count(*),
+       min(p.created_at),
+       max(p.created_at),
+       min(p.currency_mint),
+       (array_agg(p.normalized_price order by p.created_at asc, p.id asc))[1],
+       max(p.normalized_price),
+       min(p.normalized_price),
+       (array_agg(p.normalized_price order by p.created_at desc, p.id desc))[1],
+       case when bool_and(p.normalized_price is not null) then sum(p.normalized_price) end,
+       coalesce(sum(p.price) filter (where p.flagged), 0),
+       count(*) filter (where p.flagged),
+       case when coalesce(bool_and(p.normalized_price is not null) filter (where p.flagged), true)
+           then coalesce(sum(p.normalized_price) filter (where p.flagged), 0)
+       end
+from (
+    select p.*,
+           normalize_price(p.currency_mint, p.price, p.created_at) as normalized_price,
+           exists (select 1 from wash_trades wt where wt.purchase_id = p.id) as flagged
+    from purchases p
+) as p
+inner join metadata_collection_keys mck
+    on mck.metadata_address = p.metadata and mck.verified
+cross join (values ('1m', 'minute'), ('1h', 'hour'), ('1d', 'day')) as r (resolution, unit)
+group by mck.collection_address, p.auction_house, r.resolution, bucket_start;";
+
+const REBUILD_BUYERS_QUERY: &str = r"
+insert into collection_buyer_buckets (
+    collection_address, resolution, bucket_start, buyer, sales
+)
+select mck.collection_address, r.resolution,
+       date_trunc(r.unit, p.created_at) as bucket_start,
+       p.buyer,
+       count(*)
+from purchases p
+inner join metadata_collection_keys mck
+    on mck.metadata_address = p.metadata and mck.verified
+cross join (values ('1m', 'minute'), ('1h', 'hour'), ('1d', 'day')) as r (resolution, unit)
+group by mck.collection_address, r.resolution, bucket_start, p.buyer;";
+
+const REBUILD_LISTINGS_QUERY: &str = r"
+insert into price_candles (
+    collection_address, auction_house, resolution, bucket_start,
+    volume, sales, currency_mint, normalized_volume,
+    listings, listing_low, normalized_listing_low
+)
+select mck.collection_address, l.auction_house, r.resolution,
+       date_trunc(r.unit, l.created_at) as bucket_start,
+       0, 0, min(l.currency_mint), 0,
+       count(*),
+       min(l.price),
+       min(normalize_price(l.currency_mint, l.price, l.created_at))
+from listings l
+inner join metadata_collection_keys mck
+    on mck.metadata_address = l.metadata and mck.verified
+cross join (values ('1m', 'minute'), ('1h', 'hour'), ('1d', 'day')) as r (resolution, unit)
+group by mck.collection_address, l.auction_house, r.resolution, bucket_start
+on conflict (collection_address, resolution, bucket_start, auction_house) do update
+    set listings = excluded.listings,
+        listing_low = excluded.listing_low,
+        normalized_listing_low = excluded.normalized_listing_low,
+        currency_mint = coalesce(price_candles.currency_mint, excluded.currency_mint);";
+
+/// Discard all price candles and per-bucket buyer rollups and rebuild them
+/// from the `purchases` and `listings` tables.  Returns the number of candles
+/// written.
+///
+/// Both are normally maintained by triggers as purchases and listings are
+/// indexed; this repairs them for sales and listings indexed before the
+/// triggers existed or before their NFT was verified into a collection, and
+/// picks up currency rates loaded after the trades they apply to.  The volume
+/// of sales flagged as wash trades is taken from the existing flags.
+///
+/// # Errors
+/// This function fails if the candles cannot be rebuilt
+pub fn rebuild(db: &Connection) -> Result<usize> {
+    db.build_transaction().read_write().run(|| {
+        diesel::sql_query("delete from price_candles;")
+            .execute(db)
+            .context("Failed to clear price candles")?;
+
+        diesel::sql_query("delete from collection_buyer_buckets;")
+            .execute(db)
+            .context("Failed to clear collection buyers")?;
+
+        diesel::sql_query(REBUILD_BUYERS_QUERY)
+            .execute(db)
+            .context("Failed to rebuild collection buyers")?;
+
+        diesel::sql_query(REBUILD_QUERY)
+            .execute(db)
+            .context("Failed to rebuild price candles")?;
+
+        diesel::sql_query(REBUILD_LISTINGS_QUERY)
+            .execute(db)
+            .context("Failed to rebuild price candle listings")?;
+
+        let candles: i64 = price_candles::table
+            .count()
+            .get_result(db)
+            .context("Failed to count price candles")?;
+
+        Ok(candles.try_into().unwrap_or(usize::MAX))
+    })
+}
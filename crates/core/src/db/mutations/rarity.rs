@@ -0,0 +1,235 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use diesel::{
+    dsl::now,
+    sql_types::{Int4, Text},
+};
+
+use crate::{
+    db::{
+        delete, excluded, insert_into,
+        models::NftRarity,
+        tables::{
+            attributes, metadata_collection_keys, metadatas, nft_rarities, rarity_dirty_collections,
+        },
+        update, Connection,
+    },
+    error::Result,
+    prelude::*,
+    rarity::{self, RarityMethod},
+};
+
+/// Load up to `limit` collections whose rarity scores need to be recomputed,
+/// along with the time each was marked, oldest first.  Collections which
+/// failed to score are skipped until their retry time.
+///
+/// # Errors
+/// This function fails if the query fails
+pub fn dirty_collections(db: &Connection, limit: i64) -> Result<Vec<(String, NaiveDateTime)>> {
+    rarity_dirty_collections::table
+        .filter(
+            rarity_dirty_collections::retry_at
+                .is_null()
+                .or(rarity_dirty_collections::retry_at.le(now)),
+        )
+        .select((
+            rarity_dirty_collections::collection_address,
+            rarity_dirty_collections::marked_at,
+        ))
+        .order_by(rarity_dirty_collections::marked_at)
+        .limit(limit)
+        .load(db)
+        .context("Failed to load dirty rarity collections")
+}
+
+#[derive(Debug, QueryableByName)]
+struct Attempts {
+    #[sql_type = "Int4"]
+    failed_attempts: i32,
+}
+
+const MARK_FAILED_QUERY: &str = r"
+update rarity_dirty_collections
+set failed_attempts = failed_attempts + 1,
+    retry_at = now() + least(interval '1 minute' * power(2, failed_attempts), interval '1 day'),
+    last_error = $2
+where collection_address = $1
+returning failed_attempts;
+ -- $1: collection address::text
+ -- $2: error message::text";
+
+/// Record a failure to score a collection, postponing its next attempt with
+/// exponential backoff of up to a day.  Returns the number of consecutive
+/// failures, or `None` if the collection is no longer marked.
+///
+/// # Errors
+/// This function fails if the update fails
+pub fn mark_failed(db: &Connection, collection_address: &str, error: &str) -> Result<Option<i32>> {
+    diesel::sql_query(MARK_FAILED_QUERY)
+        .bind::<Text, _>(collection_address)
+        .bind::<Text, _>(error)
+        .get_result::<Attempts>(db)
+        .optional()
+        .context("Failed to record rarity scoring failure")
+        .map(|a| a.map(|a| a.failed_attempts))
+}
+
+/// The address of an NFT and its `(trait_type, value)` pairs
+type Item = (String, Vec<(String, String)>);
+
+/// Load the attributes of each unburned NFT in the given verified collection
+fn load_items(db: &Connection, collection_address: &str) -> Result<Vec<Item>> {
+    let rows: Vec<(String, Option<String>, Option<String>)> = metadata_collection_keys::table
+        .inner_join(
+            metadatas::table.on(metadatas::address.eq(metadata_collection_keys::metadata_address)),
+        )
+        .left_join(attributes::table.on(attributes::metadata_address.eq(metadatas::address)))
+        .filter(metadata_collection_keys::collection_address.eq(collection_address))
+        .filter(metadata_collection_keys::verified)
+        .filter(metadatas::burned_at.is_null())
+        .select((
+            metadatas::address,
+            attributes::trait_type.nullable(),
+            attributes::value.nullable(),
+        ))
+        .load(db)
+        .context("Failed to load collection attributes")?;
+
+    let mut items: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+
+    for (address, trait_type, value) in rows {
+        let traits = items.entry(address).or_default();
+
+        if let (Some(trait_type), Some(value)) = (trait_type, value) {
+            traits.push((trait_type, value));
+        }
+    }
+
+    Ok(items.into_iter().collect())
+}
+
+/// Recompute the rarity scores of every unburned NFT in the given verified
+/// collection using each of the supported methods.  Returns the number of NFTs
+/// scored.
+///
+/// Scores are updated incrementally: only scores or ranks which changed are
+/// rewritten, and scores of NFTs no longer in the collection are removed.
+///
+/// The collection is unmarked only if it has not been marked again since
+/// `marked_at`, so changes made while scoring are picked up by a later run.
+///
+/// # Errors
+/// This function fails if the collection cannot be loaded or the scores
+/// cannot be written
+pub fn recompute_collection(
+    db: &Connection,
+    collection_address: &str,
+    marked_at: NaiveDateTime,
+) -> Result<usize> {
+    let items = load_items(db, collection_address)?;
+    let updated_at = Local::now().naive_utc();
+
+    db.build_transaction().read_write().run(|| {
+        let previous: BTreeMap<(String, String), (f64, i32)> = nft_rarities::table
+            .filter(nft_rarities::collection_address.eq(collection_address))
+            .select((
+                nft_rarities::metadata_address,
+                nft_rarities::method,
+                nft_rarities::score,
+                nft_rarities::rank,
+            ))
+            .load::<(String, String, f64, i32)>(db)
+            .context("Failed to load previous rarity scores")?
+            .into_iter()
+            .map(|(address, method, score, rank)| ((address, method), (score, rank)))
+            .collect();
+
+        let rarities: Vec<_> = RarityMethod::ALL
+            .into_iter()
+            .flat_map(|method| {
+                rarity::score(&items, method)
+                    .into_iter()
+                    .map(move |r| NftRarity {
+                        metadata_address: Owned(r.key),
+                        method: Borrowed(method.into()),
+                        collection_address: Borrowed(collection_address),
+                        score: r.score,
+                        rank: r.rank.try_into().unwrap_or(i32::MAX),
+                        updated_at,
+                    })
+            })
+            .filter(|r| {
+                let key = (r.metadata_address.to_string(), r.method.to_string());
+
+                previous.get(&key).map_or(true, |(score, rank)| {
+                    *rank != r.rank || !rarity::tied(*score, r.score)
+                })
+            })
+            .collect();
+
+        let scored: BTreeSet<&str> = items.iter().map(|(a, _)| a.as_str()).collect();
+        let removed: BTreeSet<&str> = previous
+            .keys()
+            .map(|(a, _)| a.as_str())
+            .filter(|a| !scored.contains(a))
+            .collect();
+        let removed: Vec<_> = removed.into_iter().collect();
+
+        for chunk in removed.chunks(4096) {
+            delete(
+                nft_rarities::table
+                    .filter(nft_rarities::collection_address.eq(collection_address))
+                    .filter(nft_rarities::metadata_address.eq(any(chunk))),
+            )
+            .execute(db)
+            .context("Failed to remove stale rarity scores")?;
+        }
+
+        // Insert in chunks to stay below the bind parameter limit.  NFTs
+        // verified into more than one collection keep their latest scores.
+        for chunk in rarities.chunks(4096) {
+            insert_into(nft_rarities::table)
+                .values(chunk)
+                .on_conflict((nft_rarities::metadata_address, nft_rarities::method))
+                .do_update()
+                .set((
+                    nft_rarities::collection_address.eq(excluded(nft_rarities::collection_address)),
+                    nft_rarities::score.eq(excluded(nft_rarities::score)),
+                    nft_rarities::rank.eq(excluded(nft_rarities::rank)),
+                    nft_rarities::updated_at.eq(excluded(nft_rarities::updated_at)),
+                ))
+                .execute(db)
+                .context("Failed to write rarity scores")?;
+        }
+
+        trace!(
+            "Rewrote {} rarity score(s) and removed those of {} NFT(s) in collection {}",
+            rarities.len(),
+            removed.len(),
+            collection_address
+        );
+
+        delete(
+            rarity_dirty_collections::table
+                .filter(rarity_dirty_collections::collection_address.eq(collection_address))
+                .filter(rarity_dirty_collections::marked_at.le(marked_at)),
+        )
+        .execute(db)
+        .context("Failed to unmark collection")?;
+
+        // A collection marked again while scoring is no longer failing
+        update(
+            rarity_dirty_collections::table
+                .filter(rarity_dirty_collections::collection_address.eq(collection_address)),
+        )
+        .set((
+            rarity_dirty_collections::failed_attempts.eq(0),
+            rarity_dirty_collections::retry_at.eq(None::<NaiveDateTime>),
+            rarity_dirty_collections::last_error.eq(None::<String>),
+        ))
+        .execute(db)
+        .context("Failed to reset collection failures")?;
+
+        Result::<_>::Ok(items.len())
+    })
+}
@@ -0,0 +1,145 @@
+use diesel::sql_types::{Array, Int8, Nullable, Text, Timestamp, VarChar};
+
+use crate::{
+    db::{tables::sale_royalties, update, Connection},
+    error::Result,
+    prelude::*,
+    uuid::Uuid,
+};
+
+const REBUILD_QUERY: &str = r"
+insert into sale_royalties (
+    purchase_id, creator_address, metadata_address, auction_house,
+    marketplace_program, currency_mint, sold_at, share, expected, paid
+)
+select p.id, mc.creator_address, p.metadata, p.auction_house,
+       p.marketplace_program, p.currency_mint, p.created_at, mc.share,
+       div(div(p.price::numeric * md.seller_fee_basis_points, 10000) * mc.share, 100)::bigint,
+       null
+from purchases p
+inner join metadatas md
+    on md.address = p.metadata and md.seller_fee_basis_points > 0
+inner join metadata_creators mc
+    on mc.metadata_address = md.address and mc.share > 0;";
+
+/// Discard all recorded sale royalties and rebuild them from the `purchases`
+/// table.  Returns the number of rows written.  The payments of the rebuilt
+/// royalties are looked up again by the royalties worker.
+///
+/// Royalties are normally recorded by a trigger as sales are indexed; this
+/// repairs them for sales indexed before the trigger existed or before the
+/// metadata of the sold NFT.
+///
+/// # Errors
+/// This function fails if the royalties cannot be rebuilt
+pub fn rebuild(db: &Connection) -> Result<usize> {
+    db.build_transaction().read_write().run(|| {
+        diesel::sql_query("delete from sale_royalties;")
+            .execute(db)
+            .context("Failed to clear sale royalties")?;
+
+        diesel::sql_query(REBUILD_QUERY)
+            .execute(db)
+            .context("Failed to rebuild sale royalties")
+    })
+}
+
+/// A sale whose royalty payments have not been looked up
+#[derive(Debug, Clone, QueryableByName)]
+pub struct UncheckedSale {
+    /// The sale
+    #[sql_type = "diesel::sql_types::Uuid"]
+    pub purchase_id: Uuid,
+    /// Address of the metadata account of the sold NFT, which every sale
+    /// transaction references
+    #[sql_type = "VarChar"]
+    pub metadata: String,
+    /// The buyer's wallet
+    #[sql_type = "VarChar"]
+    pub buyer: String,
+    /// The seller's wallet
+    #[sql_type = "VarChar"]
+    pub seller: String,
+    /// The slot the sale was made in
+    #[sql_type = "Int8"]
+    pub slot: i64,
+    /// The mint of the currency the sale was made in, if known
+    #[sql_type = "Nullable<VarChar>"]
+    pub currency_mint: Option<String>,
+    /// The creators owed a royalty for the sale
+    #[sql_type = "Array<VarChar>"]
+    pub creators: Vec<String>,
+}
+
+const UNCHECKED_QUERY: &str = r"
+select p.id as purchase_id, p.metadata, p.buyer, p.seller, p.slot, p.currency_mint,
+       array_agg(sr.creator_address order by sr.creator_address) as creators
+from sale_royalties sr
+inner join purchases p on p.id = sr.purchase_id
+where sr.checked_at is null and sr.sold_at < $1
+group by p.id
+order by max(sr.sold_at) desc
+limit $2;
+ -- $1: sold before::timestamp
+ -- $2: limit::bigint";
+
+/// Load up to `limit` sales made before `before` whose royalty payments have
+/// not been looked up, most recent first
+///
+/// # Errors
+/// This function fails if the query fails
+pub fn unchecked_sales(
+    db: &Connection,
+    before: NaiveDateTime,
+    limit: i64,
+) -> Result<Vec<UncheckedSale>> {
+    diesel::sql_query(UNCHECKED_QUERY)
+        .bind::<Timestamp, _>(before)
+        .bind::<Int8, _>(limit)
+        .load(db)
+        .context("Failed to load sales with unchecked royalties")
+}
+
+const RECORD_QUERY: &str = r"
+update sale_royalties sr
+set paid = payments.paid,
+    currency_mint = coalesce(sr.currency_mint, p.currency_mint),
+    checked_at = now()
+from purchases p,
+    unnest($2::text[], $3::bigint[]) as payments (creator_address, paid)
+where sr.purchase_id = $1
+    and p.id = sr.purchase_id
+    and sr.creator_address = payments.creator_address;
+ -- $1: purchase id::uuid
+ -- $2: creator addresses::text[]
+ -- $3: amounts paid::bigint[]";
+
+/// Record the royalties paid to each creator for a sale, as read from its
+/// transaction.  Creators whose payment is `None` could not be determined.
+///
+/// # Errors
+/// This function fails if the update fails
+pub fn record_payments(
+    db: &Connection,
+    purchase_id: Uuid,
+    payments: &[(String, Option<i64>)],
+) -> Result<()> {
+    let (creators, paid): (Vec<_>, Vec<_>) = payments.iter().cloned().unzip();
+
+    db.build_transaction().read_write().run(|| {
+        // Mark creators missing from the payments as checked too
+        update(sale_royalties::table.filter(sale_royalties::purchase_id.eq(purchase_id)))
+            .set(sale_royalties::checked_at.eq(diesel::dsl::now))
+            .execute(db)
+            .context("Failed to mark sale royalties checked")?;
+
+        diesel::sql_query(RECORD_QUERY)
+            .bind::<diesel::sql_types::Uuid, _>(purchase_id)
+            .bind::<Array<Text>, _>(creators)
+            .bind::<Array<Nullable<Int8>>, _>(paid)
+            .execute(db)
+            .context("Failed to record royalty payments")?;
+
+        Ok(())
+    })
+}
@@ -0,0 +1,42 @@
+use crate::{
+    db::{tables::wash_trades, Connection},
+    error::Result,
+    prelude::*,
+};
+
+/// Discard all wash trade flags and re-run detection over every sale in the
+/// `purchases` table.  Returns the number of sales flagged.
+///
+/// Sales are normally checked by a trigger as they are indexed; this repairs
+/// the flags for sales indexed before the trigger existed, and picks up
+/// wallet graph connections made after the sales between the wallets.  The
+/// flagged
+/// volume of the price candles is recomputed along with the flags.
+///
+/// # Errors
+/// This function fails if the flags cannot be rebuilt
+pub fn rebuild(db: &Connection) -> Result<usize> {
+    db.build_transaction().read_write().run(|| {
+        diesel::sql_query("delete from wash_trades;")
+            .execute(db)
+            .context("Failed to clear wash trade flags")?;
+
+        diesel::sql_query(
+            "update price_candles
+             set flagged_volume = 0, flagged_sales = 0, flagged_normalized_volume = 0;",
+        )
+        .execute(db)
+        .context("Failed to clear flagged candle volume")?;
+
+        diesel::sql_query("select detect_wash_trades(id) from purchases;")
+            .execute(db)
+            .context("Failed to detect wash trades")?;
+
+        let flagged: i64 = wash_trades::table
+            .count()
+            .get_result(db)
+            .context("Failed to count wash trade flags")?;
+
+        Ok(flagged.try_into().unwrap_or(usize::MAX))
+    })
+}
@@ -1,19 +1,54 @@
 //! Query utilities for NFT activity.
 
 use anyhow::Context;
+use chrono::NaiveDateTime;
 use diesel::{
     pg::Pg,
-    sql_types::{Array, Nullable, Text},
+    sql_types::{Array, Int8, Nullable, Text, Timestamp},
     types::ToSql,
     RunQueryDsl,
 };
+use uuid::Uuid;
 
 use crate::{
     db::{models::NftActivity, Connection},
     error::Result,
 };
 
-const ACTIVITES_QUERY: &str = r"
+/// The sort key of an activity, after which the next page of a keyset-paged
+/// activity query starts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityKey {
+    /// The time of the activity
+    pub created_at: NaiveDateTime,
+    /// The id of the activity
+    pub id: Uuid,
+    /// The type of the activity, which tells apart the purchase and sale
+    /// activities a wallet has for the same sale
+    pub activity_type: String,
+}
+
+/// Wrap a union of activities taking two parameters in keyset pagination on
+/// `(created_at, id)`, newest first.  Pages start after the activity given by
+/// parameters `$3` to `$5`, or at the newest activity if they are null.
+pub(crate) fn keyset_page_query(activities: &str) -> String {
+    format!(
+        r"
+SELECT * FROM ({}
+) a
+WHERE $3::timestamp IS NULL
+    OR (a.created_at, a.id, a.activity_type) < ($3, $4::uuid, $5::text)
+ORDER BY a.created_at DESC, a.id DESC, a.activity_type DESC
+LIMIT $6;
+ -- $3: after created_at::timestamp
+ -- $4: after id::uuid
+ -- $5: after activity_type::text
+ -- $6: limit::bigint",
+        activities
+    )
+}
+
+const ACTIVITIES_UNION: &str = r"
 SELECT listings.id as id, metadata, auction_house, marketplace_program, price, created_at,
     array[seller] as wallets,
     array[twitter_handle_name_services.twitter_handle] as wallet_twitter_handles,
@@ -38,7 +73,6 @@ SELECT listings.id as id, metadata, auction_house, marketplace_program, price, c
         INNER JOIN metadata_creators mc
         on md.address = mc.metadata_address
         WHERE auction_house = ANY($1) and ($2 is null OR mc.creator_address = ANY($2))
-    ORDER BY created_at DESC;
  -- $1: auction_houses::text[]
  -- $2: creators::text[]";
 
@@ -51,9 +85,32 @@ pub fn list(
     auction_houses: impl ToSql<Array<Text>, Pg>,
     creators: impl ToSql<Nullable<Array<Text>>, Pg>,
 ) -> Result<Vec<NftActivity>> {
-    diesel::sql_query(ACTIVITES_QUERY)
+    diesel::sql_query(format!("{}\nORDER BY created_at DESC;", ACTIVITIES_UNION))
+        .bind(auction_houses)
+        .bind(creators)
+        .load(conn)
+        .context("Failed to load activities")
+}
+
+/// Load a page of activities for desired auction house address, starting
+/// after the activity with the given key
+///
+/// # Errors
+/// This function fails if the underlying SQL query returns an error
+pub fn list_page(
+    conn: &Connection,
+    auction_houses: impl ToSql<Array<Text>, Pg>,
+    creators: impl ToSql<Nullable<Array<Text>>, Pg>,
+    after: Option<&ActivityKey>,
+    limit: i64,
+) -> Result<Vec<NftActivity>> {
+    diesel::sql_query(keyset_page_query(ACTIVITIES_UNION))
         .bind(auction_houses)
         .bind(creators)
+        .bind::<Nullable<Timestamp>, _>(after.map(|k| k.created_at))
+        .bind::<Nullable<diesel::sql_types::Uuid>, _>(after.map(|k| k.id))
+        .bind::<Nullable<Text>, _>(after.map(|k| k.activity_type.as_str()))
+        .bind::<Int8, _>(limit)
         .load(conn)
         .context("Failed to load activities")
 }
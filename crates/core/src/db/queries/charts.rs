@@ -1,4 +1,9 @@
-//! Query utilities for nft price charts
+//! Query utilities for nft price charts.
+//!
+//! Prices are reported both in the native units of the currency each trade
+//! was made in, as stored alongside it, and normalized to lamports of SOL using the historical rates
+//! in the `currency_rates` table.  Normalized prices are `None` where a sale
+//! could not be converted.
 
 use anyhow::Context;
 use chrono::NaiveDateTime;
@@ -6,26 +11,32 @@ use diesel::{
     pg::Pg,
     prelude::*,
     serialize::ToSql,
-    sql_types::{Array, Nullable, Text, Timestamp},
+    sql_types::{Array, Bool, Nullable, Text, Timestamp},
 };
 
 use crate::{
-    db::{models::PricePoint, Connection},
+    db::{
+        models::{PriceCandle, PricePoint},
+        Connection,
+    },
     error::Result,
 };
 
 const FLOOR_PRICES_QUERY: &str = r"
 select series as date,
-       coalesce(min(price), 0)::bigint as price
+       coalesce(min(price), 0)::bigint as price,
+       case when bool_and(price is null or normalized_price is not null)
+           then coalesce(min(normalized_price), 0)::bigint end as normalized_price
 from generate_series($3::date, $4::date, '1 day'::interval) as series
 left join (
-    select date_trunc('day', created_at) as created_at_day, price
-    from listing_receipts lr
+    select date_trunc('day', l.created_at) as created_at_day, price,
+           normalize_price(l.currency_mint, price, l.created_at) as normalized_price
+    from listings l
     inner join metadatas md
-    on lr.metadata = md.address
+    on l.metadata = md.address
     inner join metadata_creators mc
     on md.address = mc.metadata_address
-    where lr.auction_house = ANY($1) and ($2 is null OR mc.creator_address = ANY($2)) and lr.created_at >= $3 and lr.created_at <= $4 and lr.canceled_at is null and lr.purchase_receipt is null
+    where l.auction_house = ANY($1) and ($2 is null OR mc.creator_address = ANY($2)) and l.created_at >= $3 and l.created_at <= $4 and l.canceled_at is null and l.purchase_id is null
 ) as i
 on i.created_at_day = series
 group by date
@@ -57,16 +68,19 @@ pub fn floor_prices(
 
 const AVERAGE_PRICES_QUERY: &str = r"
 select series as date,
-       coalesce(round(avg(price)), 0)::bigint as price
+       coalesce(round(avg(price)), 0)::bigint as price,
+       case when bool_and(price is null or normalized_price is not null)
+           then coalesce(round(avg(normalized_price)), 0)::bigint end as normalized_price
 from generate_series($3::date, $4::date, '1 day'::interval) as series
 left join (
-    select date_trunc('day', created_at) as created_at_day, price
-    from purchase_receipts pr
+    select date_trunc('day', p.created_at) as created_at_day, price,
+           normalize_price(p.currency_mint, price, p.created_at) as normalized_price
+    from purchases p
     inner join metadatas md
-    on pr.metadata = md.address
+    on p.metadata = md.address
     inner join metadata_creators mc
     on md.address = mc.metadata_address
-        where pr.auction_house = ANY($1) and ($2 is null OR mc.creator_address = ANY($2)) and pr.created_at >= $3 and pr.created_at <= $4
+        where p.auction_house = ANY($1) and ($2 is null OR mc.creator_address = ANY($2)) and p.created_at >= $3 and p.created_at <= $4
 ) as i
 on i.created_at_day = series
 group by date
@@ -98,16 +112,20 @@ pub fn average_prices(
 
 const TOTAL_VOLUME_QUERY: &str = r"
 select series as date,
-       coalesce(round(sum(price)), 0)::bigint as price
+       coalesce(round(sum(price)), 0)::bigint as price,
+       case when bool_and(price is null or normalized_price is not null)
+           then coalesce(round(sum(normalized_price)), 0)::bigint end as normalized_price
 from generate_series($3::date, $4::date, '1 day'::interval) as series
 left join (
-    select date_trunc('day', created_at) as created_at_day, price
+    select date_trunc('day', p.created_at) as created_at_day, price,
+           normalize_price(p.currency_mint, price, p.created_at) as normalized_price
     from purchases p
     inner join metadatas md
     on p.metadata = md.address
     inner join metadata_creators mc
     on md.address = mc.metadata_address
         where p.auction_house = ANY($1) and ($2 is null OR mc.creator_address = ANY($2)) and p.created_at >= $3 and p.created_at <= $4
+            and not ($5 and exists (select 1 from wash_trades wt where wt.purchase_id = p.id))
 ) as i
 on i.created_at_day = series
 group by date
@@ -115,9 +133,11 @@ order by date asc;
  -- $1: auction house addresses::text[]
  -- $2: creators addresses::text[]
  -- $3: start date::timestamp
- -- $4: end date::timestamp";
+ -- $4: end date::timestamp
+ -- $5: exclude flagged::boolean";
 
-/// Load total sales volum during a given date range for the desired auction house address per day
+/// Load total sales volum during a given date range for the desired auction house address per day,
+/// optionally excluding sales flagged as wash trades
 ///
 /// # Errors
 /// This function fails if the underlying SQL query returns an error
@@ -127,12 +147,132 @@ pub fn total_volume_prices(
     creators: impl ToSql<Nullable<Array<Text>>, Pg>,
     start_date: NaiveDateTime,
     end_date: NaiveDateTime,
+    exclude_flagged: bool,
 ) -> Result<Vec<PricePoint>> {
     diesel::sql_query(TOTAL_VOLUME_QUERY)
         .bind(auction_houses)
         .bind(creators)
         .bind::<Timestamp, _>(start_date)
         .bind::<Timestamp, _>(end_date)
+        .bind::<Bool, _>(exclude_flagged)
         .load(conn)
         .context("Failed to load average prices")
 }
+
+/// The width of the time buckets of a price candle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::Display, strum::AsRefStr)]
+pub enum CandleResolution {
+    /// One-minute candles
+    #[strum(serialize = "1m")]
+    Minute,
+    /// One-hour candles
+    #[strum(serialize = "1h")]
+    Hour,
+    /// One-day candles
+    #[strum(serialize = "1d")]
+    Day,
+}
+
+impl CandleResolution {
+    /// The duration of one bucket at this resolution
+    #[must_use]
+    pub fn duration(self) -> chrono::Duration {
+        match self {
+            Self::Minute => chrono::Duration::minutes(1),
+            Self::Hour => chrono::Duration::hours(1),
+            Self::Day => chrono::Duration::days(1),
+        }
+    }
+}
+
+const COLLECTION_CANDLES_QUERY: &str = r"
+select bucket_start,
+       case when single_currency
+           then (array_agg(open order by opened_at asc nulls last))[1] end as open,
+       case when single_currency then max(high) end as high,
+       case when single_currency then min(low) end as low,
+       case when single_currency
+           then (array_agg(close order by closed_at desc nulls last))[1] end as close,
+       case when single_currency
+           then sum(volume - case when $7 then flagged_volume else 0 end) end as volume,
+       case when single_currency then min(currency_mint) end as currency_mint,
+       case when normalized
+           then (array_agg(normalized_open order by opened_at asc nulls last))[1]::bigint
+           end as normalized_open,
+       case when normalized then max(normalized_high)::bigint end as normalized_high,
+       case when normalized then min(normalized_low)::bigint end as normalized_low,
+       case when normalized
+           then (array_agg(normalized_close order by closed_at desc nulls last))[1]::bigint
+           end as normalized_close,
+       case when normalized
+           then sum(normalized_volume - case when $7 then flagged_normalized_volume else 0 end)
+           end as normalized_volume,
+       sum(sales - case when $7 then flagged_sales else 0 end)::bigint as sales,
+       sum(listings)::bigint as listings,
+       case when single_currency then min(listing_low) end as listing_low,
+       min(normalized_listing_low)::bigint as normalized_listing_low
+from (
+    select *,
+           coalesce(bool_and(currency_mint is not null) over b
+               and min(currency_mint) over b = max(currency_mint) over b, false)
+               as single_currency,
+           -- Candles have no normalized volume if any of their sales could
+           -- not be converted
+           bool_and(normalized_volume is not null) over b as normalized
+    from price_candles
+    where collection_address = $1
+        and resolution = $2
+        and bucket_start >= date_trunc($3, $4::timestamp)
+        and bucket_start < $5
+        and ($6 is null or auction_house = any($6))
+    window b as (partition by bucket_start)
+) as pc
+group by bucket_start, single_currency, normalized
+order by bucket_start asc;
+ -- $1: collection address::text
+ -- $2: resolution::text
+ -- $3: date_trunc field::text
+ -- $4: start date::timestamp
+ -- $5: end date::timestamp
+ -- $6: auction house addresses::text[]
+ -- $7: exclude flagged::boolean";
+
+/// Load the price candles of a verified collection at the given resolution
+/// for buckets overlapping the given date range, combining sales across the
+/// given auction houses, or across all auction houses if none are given.
+///
+/// Buckets with neither sales nor new listings are omitted.  Native prices
+/// are reported only for buckets whose trades were all made in a single
+/// currency, and normalized sale prices only for buckets whose sales could
+/// all be converted to SOL.  If `exclude_flagged` is set, sales flagged as
+/// wash trades are left out of the volume and sale count, but not the
+/// prices, of each bucket.
+///
+/// # Errors
+/// This function fails if the underlying SQL query returns an error
+pub fn collection_candles(
+    conn: &Connection,
+    collection: impl ToSql<Text, Pg>,
+    resolution: CandleResolution,
+    auction_houses: impl ToSql<Nullable<Array<Text>>, Pg>,
+    start_date: NaiveDateTime,
+    end_date: NaiveDateTime,
+    exclude_flagged: bool,
+) -> Result<Vec<PriceCandle>> {
+    let field = match resolution {
+        CandleResolution::Minute => "minute",
+        CandleResolution::Hour => "hour",
+        CandleResolution::Day => "day",
+    };
+
+    diesel::sql_query(COLLECTION_CANDLES_QUERY)
+        .bind(collection)
+        .bind::<Text, _>(resolution.as_ref())
+        .bind::<Text, _>(field)
+        .bind::<Timestamp, _>(start_date)
+        .bind::<Timestamp, _>(end_date)
+        .bind(auction_houses)
+        .bind::<Bool, _>(exclude_flagged)
+        .load(conn)
+        .context("Failed to load price candles")
+}
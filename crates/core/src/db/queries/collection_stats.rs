@@ -0,0 +1,539 @@
+//! Query utilities for collection statistics over arbitrary trailing windows,
+//! computed from the incrementally-maintained `price_candles` and
+//! `collection_buyer_buckets` rollups.  Volumes are reported both in native
+//! units, where every sale was made in the same currency, and normalized to
+//! lamports of SOL.
+
+use std::{fmt, str::FromStr};
+
+use anyhow::{bail, Context};
+use chrono::{Duration, NaiveDateTime};
+use diesel::{
+    prelude::*,
+    sql_types::{Bool, Int8, Text, Timestamp},
+};
+use sea_query::Order;
+
+use crate::{
+    db::{
+        custom_types::CollectionSort,
+        models::{NormalizedCollectionTrend, WindowedCollectionTrend},
+        queries::charts::CandleResolution,
+        Connection,
+    },
+    error::{Error, Result},
+};
+
+/// The fixed periods the `collection_trends` table precomputes statistics
+/// over
+const TREND_PERIODS: [&str; 3] = ["1d", "7d", "30d"];
+
+/// The columns of the `collection_trends` table, aliased `ct`, along with the
+/// volumes of each period normalized to lamports of SOL.  Where
+/// `exclude_flagged` is set, the volumes and sales counts are read from the
+/// columns with sales flagged as wash trades left out.
+fn trend_columns(exclude_flagged: bool) -> String {
+    let mut columns = vec![
+        "ct.collection".to_owned(),
+        "ct.floor_price".to_owned(),
+        "ct.nft_count".to_owned(),
+    ];
+
+    let change = |current: &str, prev: &str| {
+        format!(
+            "(100 * ({c} - {p}) / nullif({p}, 0))::bigint",
+            c = current,
+            p = prev
+        )
+    };
+
+    for name in TREND_PERIODS {
+        let (normalized, prev_normalized) = if exclude_flagged {
+            let volume = format!("(ct._{n}_volume - ct._{n}_flagged_volume)", n = name);
+            let prev_volume = format!(
+                "(ct._prev_{n}_volume - ct._prev_{n}_flagged_volume)",
+                n = name
+            );
+            let sales = format!("ct._{}_unflagged_sales_count", name);
+            let prev_sales = format!(
+                "(ct.prev_{n}_sales_count - ct._prev_{n}_flagged_sales)",
+                n = name
+            );
+
+            columns.extend([
+                format!("{} as _{}_volume", volume, name),
+                format!("{} as _prev_{}_volume", prev_volume, name),
+                format!("{} as _{}_sales_count", sales, name),
+                format!("{} as prev_{}_sales_count", prev_sales, name),
+                format!("ct.prev_{}_floor_price", name),
+                format!(
+                    "coalesce({}, 0) as _{}_volume_change",
+                    change(&volume, &prev_volume),
+                    name
+                ),
+                format!("ct._{}_floor_price_change", name),
+                format!(
+                    "coalesce({}, 0) as _{}_sales_count_change",
+                    change(&sales, &prev_sales),
+                    name
+                ),
+            ]);
+
+            (
+                format!("ct._{}_unflagged_normalized_volume", name),
+                format!("ct._prev_{}_unflagged_normalized_volume", name),
+            )
+        } else {
+            columns.extend([
+                format!("ct._{}_volume", name),
+                format!("ct._prev_{}_volume", name),
+                format!("ct._{}_sales_count", name),
+                format!("ct.prev_{}_sales_count", name),
+                format!("ct.prev_{}_floor_price", name),
+                format!("ct._{}_volume_change", name),
+                format!("ct._{}_floor_price_change", name),
+                format!("ct._{}_sales_count_change", name),
+            ]);
+
+            (
+                format!("ct._{}_normalized_volume", name),
+                format!("ct._prev_{}_normalized_volume", name),
+            )
+        };
+
+        columns.extend([
+            format!("ct._{}_marketcap", name),
+            format!("ct._{}_marketcap_change", name),
+            format!("{} as _{}_normalized_volume", normalized, name),
+            format!("{} as _prev_{}_normalized_volume", prev_normalized, name),
+            format!(
+                "{} as _{}_normalized_volume_change",
+                change(&normalized, &prev_normalized),
+                name
+            ),
+        ]);
+    }
+
+    columns.join(",\n       ")
+}
+
+/// Input parameters for the [`trends`] query
+#[derive(Debug)]
+pub struct TrendsOptions {
+    /// The statistic to sort by
+    pub sort_by: CollectionSort,
+    /// Order the resulting rows by 'Asc' or 'Desc'
+    pub order: Option<Order>,
+    /// Limit the number of returned rows
+    pub limit: i64,
+    /// Skip the first `n` resulting rows
+    pub offset: i64,
+    /// Leave sales flagged as wash trades out of the volumes and sales counts
+    pub exclude_flagged: bool,
+}
+
+fn sort_column(sort: CollectionSort, exclude_flagged: bool) -> &'static str {
+    match (sort, exclude_flagged) {
+        (CollectionSort::FloorPrice, _) => "ct.floor_price",
+        (CollectionSort::OneDayVolume, false) => "ct._1d_normalized_volume",
+        (CollectionSort::OneDayVolume, true) => "ct._1d_unflagged_normalized_volume",
+        (CollectionSort::SevenDayVolume, false) => "ct._7d_normalized_volume",
+        (CollectionSort::SevenDayVolume, true) => "ct._7d_unflagged_normalized_volume",
+        (CollectionSort::ThirtyDayVolume, false) => "ct._30d_normalized_volume",
+        (CollectionSort::ThirtyDayVolume, true) => "ct._30d_unflagged_normalized_volume",
+        (CollectionSort::OneDaySalesCount, false) => "ct._1d_sales_count",
+        (CollectionSort::OneDaySalesCount, true) => "ct._1d_unflagged_sales_count",
+        (CollectionSort::SevenDaySalesCount, false) => "ct._7d_sales_count",
+        (CollectionSort::SevenDaySalesCount, true) => "ct._7d_unflagged_sales_count",
+        (CollectionSort::ThirtyDaySalesCount, false) => "ct._30d_sales_count",
+        (CollectionSort::ThirtyDaySalesCount, true) => "ct._30d_unflagged_sales_count",
+        (CollectionSort::OneDayMarketcap, _) => "ct._1d_marketcap",
+        (CollectionSort::SevenDayMarketcap, _) => "ct._7d_marketcap",
+        (CollectionSort::ThirtyDayMarketcap, _) => "ct._30d_marketcap",
+    }
+}
+
+fn order_keyword(order: Option<&Order>) -> &'static str {
+    match order {
+        Some(Order::Asc) => "asc",
+        _ => "desc",
+    }
+}
+
+fn trends_query(sort_by: CollectionSort, order: Option<&Order>, exclude_flagged: bool) -> String {
+    format!(
+        r"
+select {}
+from collection_trends ct
+order by {} {} nulls last
+limit $1
+offset $2;
+ -- $1: limit::bigint
+ -- $2: offset::bigint",
+        trend_columns(exclude_flagged),
+        sort_column(sort_by, exclude_flagged),
+        order_keyword(order),
+    )
+}
+
+/// Load collection trends over the periods precomputed in the
+/// `collection_trends` table, along with their volumes normalized to
+/// lamports of SOL.  Volume sorts use the normalized volumes.
+///
+/// Normalized and flagged volumes are refreshed periodically from the price
+/// candles covering each period, which are aligned to the start of an hour
+/// or day.
+///
+/// # Errors
+/// This function fails if the underlying SQL query returns an error
+pub fn trends(conn: &Connection, options: TrendsOptions) -> Result<Vec<NormalizedCollectionTrend>> {
+    let TrendsOptions {
+        sort_by,
+        order,
+        limit,
+        offset,
+        exclude_flagged,
+    } = options;
+
+    diesel::sql_query(trends_query(sort_by, order.as_ref(), exclude_flagged))
+        .bind::<Int8, _>(limit)
+        .bind::<Int8, _>(offset)
+        .load(conn)
+        .context("Failed to load collection trends")
+}
+
+/// A trailing time window over which collection statistics are computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsWindow {
+    /// The given duration, ending now
+    Trailing(Duration),
+    /// All recorded sales
+    AllTime,
+}
+
+impl FromStr for StatsWindow {
+    type Err = Error;
+
+    /// Parse a window of the form `<count><unit>`, where the unit is one of
+    /// `m`, `h`, `d`, or `w`, or `all` for all time
+    fn from_str(s: &str) -> Result<Self> {
+        if s.eq_ignore_ascii_case("all") {
+            return Ok(Self::AllTime);
+        }
+
+        let unit_at = s
+            .find(|c: char| !c.is_ascii_digit())
+            .filter(|i| *i > 0 && *i == s.len() - 1);
+
+        let (count, unit) = match unit_at {
+            Some(i) => s.split_at(i),
+            None => bail!(
+                "Invalid stats window {:?}, expected e.g. 24h, 90d, or all",
+                s
+            ),
+        };
+
+        let count: i64 = count
+            .parse()
+            .with_context(|| format!("Invalid stats window {:?}", s))?;
+
+        if count == 0 || count > 100_000 {
+            bail!("Stats window {:?} is out of range", s);
+        }
+
+        Ok(Self::Trailing(match unit {
+            "m" => Duration::minutes(count),
+            "h" => Duration::hours(count),
+            "d" => Duration::days(count),
+            "w" => Duration::weeks(count),
+            u => bail!("Invalid stats window unit {:?}, expected m, h, d, or w", u),
+        }))
+    }
+}
+
+impl fmt::Display for StatsWindow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::AllTime => f.write_str("all"),
+            Self::Trailing(d) if d.num_weeks() * 7 == d.num_days() && d.num_weeks() > 0 => {
+                write!(f, "{}w", d.num_weeks())
+            },
+            Self::Trailing(d) if d.num_days() * 24 == d.num_hours() && d.num_days() > 0 => {
+                write!(f, "{}d", d.num_days())
+            },
+            Self::Trailing(d) if d.num_hours() * 60 == d.num_minutes() && d.num_hours() > 0 => {
+                write!(f, "{}h", d.num_hours())
+            },
+            Self::Trailing(d) => write!(f, "{}m", d.num_minutes()),
+        }
+    }
+}
+
+impl StatsWindow {
+    /// The candle resolution used to compute statistics over this window.
+    ///
+    /// Windows are aligned to the start of a bucket at this resolution, so
+    /// the first bucket may include up to one bucket of extra sales.
+    #[must_use]
+    pub fn resolution(self) -> CandleResolution {
+        match self {
+            Self::Trailing(d) if d <= Duration::hours(6) => CandleResolution::Minute,
+            Self::Trailing(d) if d <= Duration::days(14) => CandleResolution::Hour,
+            Self::Trailing(_) | Self::AllTime => CandleResolution::Day,
+        }
+    }
+
+    /// The start of this window and of the equally-long window preceding it,
+    /// if this window ends at `now`
+    #[must_use]
+    pub fn bounds(self, now: NaiveDateTime) -> (NaiveDateTime, NaiveDateTime) {
+        match self {
+            Self::Trailing(d) => (now - d, now - d - d),
+            Self::AllTime => {
+                let epoch = NaiveDateTime::from_timestamp(0, 0);
+
+                (epoch, epoch)
+            },
+        }
+    }
+}
+
+/// Statistic to sort windowed collection trends by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowStatsSort {
+    /// Total sales volume within the window, normalized to SOL
+    Volume,
+    /// Number of sales within the window
+    SalesCount,
+    /// Number of distinct buyers within the window
+    UniqueBuyers,
+    /// Lowest listing or sale price within the window
+    FloorPrice,
+    /// Floor price within the window times the number of NFTs in the
+    /// collection
+    Marketcap,
+}
+
+impl WindowStatsSort {
+    fn column(self) -> &'static str {
+        match self {
+            Self::Volume => "window_normalized_volume",
+            Self::SalesCount => "window_sales_count",
+            Self::UniqueBuyers => "window_unique_buyers",
+            Self::FloorPrice => "window_floor_price",
+            Self::Marketcap => "window_marketcap",
+        }
+    }
+}
+
+/// Input parameters for the [`windowed_trends`] query
+#[derive(Debug)]
+pub struct WindowedTrendsOptions {
+    /// The window to compute statistics over
+    pub window: StatsWindow,
+    /// The statistic to sort by
+    pub sort_by: WindowStatsSort,
+    /// Order the resulting rows by 'Asc' or 'Desc'
+    pub order: Option<Order>,
+    /// Limit the number of returned rows
+    pub limit: i64,
+    /// Skip the first `n` resulting rows
+    pub offset: i64,
+    /// Leave sales flagged as wash trades out of the volumes and sales counts
+    pub exclude_flagged: bool,
+}
+
+fn windowed_trends_query(
+    sort_by: WindowStatsSort,
+    order: Option<&Order>,
+    exclude_flagged: bool,
+) -> String {
+    format!(
+        r"
+with candles as (
+    select collection_address as collection,
+           case when coalesce(bool_and(currency_mint is not null) filter (where sales > 0), true)
+                   and min(currency_mint) filter (where sales > 0)
+                       is not distinct from max(currency_mint) filter (where sales > 0)
+               then coalesce(sum(volume) filter (
+                   where bucket_start >= date_trunc($2, $3::timestamp)), 0)
+           end as window_volume,
+           case when bool_and(currency_mint is not null) filter (where sales > 0)
+                   and min(currency_mint) filter (where sales > 0)
+                       = max(currency_mint) filter (where sales > 0)
+               then min(currency_mint) filter (where sales > 0)
+           end as window_currency_mint,
+           case when coalesce(bool_and(normalized_volume is not null) filter (
+                   where bucket_start >= date_trunc($2, $3::timestamp)), true)
+               then coalesce(sum(normalized_volume) filter (
+                   where bucket_start >= date_trunc($2, $3::timestamp)), 0)
+           end as window_normalized_volume,
+           coalesce(sum(sales) filter (
+               where bucket_start >= date_trunc($2, $3::timestamp)), 0)::bigint as window_sales_count,
+           sum(volume) filter (
+               where bucket_start < date_trunc($2, $3::timestamp)) as window_prev_volume,
+           case when bool_and(normalized_volume is not null) filter (
+                   where bucket_start < date_trunc($2, $3::timestamp))
+               then sum(normalized_volume) filter (
+                   where bucket_start < date_trunc($2, $3::timestamp))
+           end as window_prev_normalized_volume,
+           (sum(sales) filter (
+               where bucket_start < date_trunc($2, $3::timestamp)))::bigint as window_prev_sales_count,
+           -- Lowest sale or new listing price within the window
+           min(least(low, listing_low)) filter (
+               where bucket_start >= date_trunc($2, $3::timestamp)) as window_candle_floor
+    from (
+        select collection_address, bucket_start, currency_mint, low, listing_low,
+               volume - case when $7 then flagged_volume else 0 end as volume,
+               normalized_volume - case when $7 then flagged_normalized_volume else 0 end
+                   as normalized_volume,
+               sales - case when $7 then flagged_sales else 0 end as sales
+        from price_candles
+        where resolution = $1
+            and bucket_start >= date_trunc($2, $4::timestamp)
+    ) as pc
+    group by collection_address
+), buyers as (
+    select collection_address as collection,
+           count(distinct buyer) as window_unique_buyers
+    from collection_buyer_buckets
+    where resolution = $1
+        and bucket_start >= date_trunc($2, $3::timestamp)
+    group by collection_address
+), listed as (
+    -- Lowest price among listings still open at some point within the window
+    select mck.collection_address as collection,
+           min(l.price) as window_listing_floor
+    from listings l
+    inner join metadata_collection_keys mck
+        on mck.metadata_address = l.metadata and mck.verified
+    left join purchases p on p.id = l.purchase_id
+    where (l.canceled_at is null or l.canceled_at >= $3)
+        and (l.expiry is null or l.expiry >= $3)
+        and (l.purchase_id is null or p.created_at >= $3)
+    group by mck.collection_address
+)
+select {},
+       case when c.collection is null then 0 else c.window_volume end as window_volume,
+       coalesce(c.window_normalized_volume, case when c.collection is null then 0 end)
+           as window_normalized_volume,
+       c.window_currency_mint,
+       coalesce(c.window_sales_count, 0) as window_sales_count,
+       case when c.window_volume is not null then c.window_prev_volume end as window_prev_volume,
+       c.window_prev_normalized_volume,
+       c.window_prev_sales_count,
+       (100 * (c.window_normalized_volume - c.window_prev_normalized_volume)
+           / nullif(c.window_prev_normalized_volume, 0))::bigint as window_volume_change,
+       (100 * (c.window_sales_count - c.window_prev_sales_count)
+           / nullif(c.window_prev_sales_count, 0))::bigint as window_sales_count_change,
+       coalesce(b.window_unique_buyers, 0) as window_unique_buyers,
+       least(l.window_listing_floor, c.window_candle_floor)::numeric as window_floor_price,
+       least(l.window_listing_floor, c.window_candle_floor)::numeric * ct.nft_count
+           as window_marketcap
+from collection_trends ct
+left join candles c on c.collection = ct.collection
+left join buyers b on b.collection = ct.collection
+left join listed l on l.collection = ct.collection
+order by {} {} nulls last
+limit $5
+offset $6;
+ -- $1: resolution::text
+ -- $2: date_trunc field::text
+ -- $3: window start::timestamp
+ -- $4: previous window start::timestamp
+ -- $5: limit::bigint
+ -- $6: offset::bigint
+ -- $7: exclude flagged::boolean",
+        trend_columns(exclude_flagged),
+        sort_by.column(),
+        order_keyword(order),
+    )
+}
+
+/// Load collection trends along with statistics over the given window,
+/// computed from the candle and buyer rollups.  The floor price over the
+/// window is the lowest price among listings open at any point within it and
+/// the sales and new listings in its candles.  Collections without sales in
+/// the window are returned with zero volume and sales.
+///
+/// # Errors
+/// This function fails if the underlying SQL query returns an error
+pub fn windowed_trends(
+    conn: &Connection,
+    options: WindowedTrendsOptions,
+) -> Result<Vec<WindowedCollectionTrend>> {
+    let WindowedTrendsOptions {
+        window,
+        sort_by,
+        order,
+        limit,
+        offset,
+        exclude_flagged,
+    } = options;
+
+    let resolution = window.resolution();
+    let field = match resolution {
+        CandleResolution::Minute => "minute",
+        CandleResolution::Hour => "hour",
+        CandleResolution::Day => "day",
+    };
+    let (start, prev_start) = window.bounds(chrono::Utc::now().naive_utc());
+
+    diesel::sql_query(windowed_trends_query(
+        sort_by,
+        order.as_ref(),
+        exclude_flagged,
+    ))
+    .bind::<Text, _>(resolution.as_ref())
+    .bind::<Text, _>(field)
+    .bind::<Timestamp, _>(start)
+    .bind::<Timestamp, _>(prev_start)
+    .bind::<Int8, _>(limit)
+    .bind::<Int8, _>(offset)
+    .bind::<Bool, _>(exclude_flagged)
+    .load(conn)
+    .context("Failed to load windowed collection trends")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::{CandleResolution, StatsWindow};
+
+    #[test]
+    fn test_parse_window() {
+        assert_eq!(
+            "1h".parse::<StatsWindow>().unwrap(),
+            StatsWindow::Trailing(Duration::hours(1))
+        );
+        assert_eq!(
+            "90d".parse::<StatsWindow>().unwrap(),
+            StatsWindow::Trailing(Duration::days(90))
+        );
+        assert_eq!("all".parse::<StatsWindow>().unwrap(), StatsWindow::AllTime);
+
+        for bad in ["", "h", "24", "0d", "1.5h", "-1d", "1y", "1dd"] {
+            assert!(bad.parse::<StatsWindow>().is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn test_display_window() {
+        for s in ["30m", "12h", "90d", "2w", "all"] {
+            assert_eq!(s.parse::<StatsWindow>().unwrap().to_string(), s);
+        }
+
+        assert_eq!(StatsWindow::Trailing(Duration::hours(48)).to_string(), "2d");
+    }
+
+    #[test]
+    fn test_window_resolution() {
+        let res = |s: &str| s.parse::<StatsWindow>().unwrap().resolution();
+
+        assert_eq!(res("1h"), CandleResolution::Minute);
+        assert_eq!(res("24h"), CandleResolution::Hour);
+        assert_eq!(res("90d"), CandleResolution::Day);
+        assert_eq!(res("all"), CandleResolution::Day);
+    }
+}
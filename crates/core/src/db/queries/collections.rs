@@ -9,89 +9,23 @@ use diesel::{
     query_builder::{QueryFragment, QueryId},
     query_source::joins::{Inner, Join, JoinOn},
     serialize::ToSql,
-    sql_types::{Array, Integer, Nullable, Text, Timestamp},
+    sql_types::{Array, Bool, Int8, Integer, Nullable, Text, Timestamp},
 };
-use sea_query::{Iden, Order, PostgresQueryBuilder, Query};
 
 use crate::{
     db::{
-        custom_types::{CollectionSort, OrderDirection},
-        models::{CollectionTrend, Nft, NftActivity},
-        queries::metadatas::NFT_COLUMNS,
+        custom_types::OrderDirection,
+        models::{Nft, NftActivity},
+        queries::{
+            activities::{keyset_page_query, ActivityKey},
+            metadatas::NFT_COLUMNS,
+        },
         tables::{current_metadata_owners, metadata_collection_keys, metadata_jsons, metadatas},
         Connection,
     },
     error::Result,
 };
 
-#[derive(Iden)]
-enum CollectionTrends {
-    Table,
-    Collection,
-    FloorPrice,
-    NftCount,
-    #[iden(rename = "_1d_volume")]
-    OneDayVolume,
-    #[iden(rename = "_7d_volume")]
-    SevenDayVolume,
-    #[iden(rename = "_30d_volume")]
-    ThirtyDayVolume,
-    #[iden(rename = "_1d_sales_count")]
-    OneDaySalesCount,
-    #[iden(rename = "_7d_sales_count")]
-    SevenDaySalesCount,
-    #[iden(rename = "_30d_sales_count")]
-    ThirtyDaySalesCount,
-    #[iden(rename = "_prev_1d_volume")]
-    PrevOneDayVolume,
-    #[iden(rename = "_prev_7d_volume")]
-    PrevSevenDayVolume,
-    #[iden(rename = "_prev_30d_volume")]
-    PrevThirtyDayVolume,
-    #[iden(rename = "prev_1d_sales_count")]
-    PrevOneDaySalesCount,
-    #[iden(rename = "prev_7d_sales_count")]
-    PrevSevenDaySalesCount,
-    #[iden(rename = "prev_30d_sales_count")]
-    PrevThirtyDaySalesCount,
-    #[iden(rename = "prev_1d_floor_price")]
-    PrevOneDayFloorPrice,
-    #[iden(rename = "prev_7d_floor_price")]
-    PrevSevenDayFloorPrice,
-    #[iden(rename = "prev_30d_floor_price")]
-    PrevThirtyDayFloorPrice,
-    #[iden(rename = "_1d_volume_change")]
-    OneDayVolumeChange,
-    #[iden(rename = "_7d_volume_change")]
-    SevenDayVolumeChange,
-    #[iden(rename = "_30d_volume_change")]
-    ThirtyDayVolumeChange,
-    #[iden(rename = "_1d_floor_price_change")]
-    OneDayFloorPriceChange,
-    #[iden(rename = "_7d_floor_price_change")]
-    SevenDayFloorPriceChange,
-    #[iden(rename = "_30d_floor_price_change")]
-    ThirtyDayFloorPriceChange,
-    #[iden(rename = "_1d_sales_count_change")]
-    OneDaySalesCountChange,
-    #[iden(rename = "_7d_sales_count_change")]
-    SevenDaySalesCountChange,
-    #[iden(rename = "_30d_sales_count_change")]
-    ThirtyDaySalesCountChange,
-    #[iden(rename = "_1d_marketcap")]
-    OneDayMarketcap,
-    #[iden(rename = "_7d_marketcap")]
-    SevenDayMarketcap,
-    #[iden(rename = "_30d_marketcap")]
-    ThirtyDayMarketcap,
-    #[iden(rename = "_1d_marketcap_change")]
-    OneDayMarketcapChange,
-    #[iden(rename = "_7d_marketcap_change")]
-    SevenDayMarketcapChange,
-    #[iden(rename = "_30d_marketcap_change")]
-    ThirtyDayMarketcapChange,
-}
-
 /// Query collection by address
 ///
 /// # Errors
@@ -150,16 +84,19 @@ where
         .context("Failed to load Collection NFT by collection address")
 }
 
-/// Query collections ordered by volume
+/// Query collections ordered by volume, optionally excluding sales flagged as
+/// wash trades
 ///
 /// # Errors
 /// returns an error when the underlying queries throw an error
+#[allow(clippy::too_many_arguments)]
 pub fn by_volume(
     conn: &Connection,
     addresses: impl ToSql<Nullable<Array<Text>>, Pg>,
     order_direction: OrderDirection,
     start_date: DateTime<Utc>,
     end_date: DateTime<Utc>,
+    exclude_flagged: bool,
     limit: impl ToSql<Integer, Pg>,
     offset: impl ToSql<Integer, Pg>,
 ) -> Result<Vec<Nft>> {
@@ -169,10 +106,12 @@ pub fn by_volume(
         .bind::<Timestamp, _>(end_date.naive_utc())
         .bind(limit)
         .bind(offset)
+        .bind::<Bool, _>(exclude_flagged)
         .load(conn)
         .context("Failed to load collections by volume")
 }
 
+#[allow(clippy::too_many_lines)]
 fn make_by_volume_query_string(order_direction: OrderDirection) -> String {
     format!(
         r"
@@ -187,6 +126,7 @@ fn make_by_volume_query_string(order_direction: OrderDirection) -> String {
             AND purchases.created_at >= $2
             AND purchases.created_at <= $3
             AND purchases.marketplace_program = 'M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K'
+            AND NOT ($6 AND EXISTS (SELECT 1 FROM wash_trades WHERE wash_trades.purchase_id = purchases.id))
             GROUP BY collection_address
             LIMIT $4)
             UNION ALL
@@ -200,6 +140,7 @@ fn make_by_volume_query_string(order_direction: OrderDirection) -> String {
             AND purchases.created_at >= $2
             AND purchases.created_at <= $3
             AND purchases.marketplace_program = 'M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K'
+            AND NOT ($6 AND EXISTS (SELECT 1 FROM wash_trades WHERE wash_trades.purchase_id = purchases.id))
             GROUP BY collection_id
             LIMIT $4)
             ORDER BY total_volume {order_direction}
@@ -269,7 +210,8 @@ fn make_by_volume_query_string(order_direction: OrderDirection) -> String {
     -- $2: start date::timestamp
     -- $3: end date::timestamp
     -- $4: limit::integer
-    -- $5: offset::integer",
+    -- $5: offset::integer
+    -- $6: exclude flagged::boolean",
         order_direction = order_direction
     )
 }
@@ -402,7 +344,7 @@ fn make_by_market_cap_query_string(order_direction: OrderDirection) -> String {
     )
 }
 
-const COLLECTION_ACTIVITES_QUERY: &str = r"
+const COLLECTION_ACTIVITIES_UNION: &str = r"
 SELECT listings.id as id, metadata, auction_house, price, created_at, marketplace_program,
     array[seller] as wallets,
     array[twitter_handle_name_services.twitter_handle] as wallet_twitter_handles,
@@ -468,14 +410,8 @@ SELECT listings.id as id, metadata, auction_house, price, created_at, marketplac
         WHERE me_metadata_collections.collection_id::text = $1
         AND offers.purchase_id IS NULL
         AND ('OFFERS' = ANY($2) OR $2 IS NULL)
-    ORDER BY created_at DESC
-    LIMIT $3
-    OFFSET $4;
-
  -- $1: address::text
- -- $2: event_types::text[]
- -- $3: limit::integer
- -- $4: offset::integer";
+ -- $2: event_types::text[]";
 
 /// Load listing, sales, offers activity for a collection
 ///
@@ -488,176 +424,42 @@ pub fn collection_activities(
     limit: impl ToSql<Integer, Pg>,
     offset: impl ToSql<Integer, Pg>,
 ) -> Result<Vec<NftActivity>> {
-    diesel::sql_query(COLLECTION_ACTIVITES_QUERY)
-        .bind(address)
-        .bind(event_types)
-        .bind(limit)
-        .bind(offset)
-        .load(conn)
-        .context("Failed to load collection activities")
-}
-
-/// Input parameters for the [`trending`] query.
-#[derive(Debug)]
-pub struct TrendingQueryOptions {
-    /// Sort by Price or Listed at
-    pub sort_by: CollectionSort,
-    /// Order the resulting rows by 'Asc' or 'Desc'
-    pub order: Option<Order>,
-    /// Limit the number of returned rows
-    pub limit: u64,
-    /// Skip the first `n` resulting rows
-    pub offset: u64,
-}
-
-impl From<CollectionSort> for CollectionTrends {
-    fn from(sort: CollectionSort) -> Self {
-        match sort {
-            CollectionSort::FloorPrice => CollectionTrends::FloorPrice,
-            CollectionSort::OneDayVolume => CollectionTrends::OneDayVolume,
-            CollectionSort::SevenDayVolume => CollectionTrends::SevenDayVolume,
-            CollectionSort::ThirtyDayVolume => CollectionTrends::ThirtyDayVolume,
-            CollectionSort::OneDaySalesCount => CollectionTrends::OneDaySalesCount,
-            CollectionSort::SevenDaySalesCount => CollectionTrends::SevenDaySalesCount,
-            CollectionSort::ThirtyDaySalesCount => CollectionTrends::ThirtyDaySalesCount,
-            CollectionSort::OneDayMarketcap => CollectionTrends::OneDayMarketcap,
-            CollectionSort::SevenDayMarketcap => CollectionTrends::SevenDayMarketcap,
-            CollectionSort::ThirtyDayMarketcap => CollectionTrends::ThirtyDayMarketcap,
-        }
-    }
+    diesel::sql_query(format!(
+        r"{}
+    ORDER BY created_at DESC
+    LIMIT $3
+    OFFSET $4;
+ -- $3: limit::integer
+ -- $4: offset::integer",
+        COLLECTION_ACTIVITIES_UNION
+    ))
+    .bind(address)
+    .bind(event_types)
+    .bind(limit)
+    .bind(offset)
+    .load(conn)
+    .context("Failed to load collection activities")
 }
 
-/// Handles queries for trending collections
+/// Load a page of listing, sales, offers activity for a collection, starting
+/// after the activity with the given key
 ///
 /// # Errors
-/// returns an error when the underlying queries throw an error
-#[allow(clippy::too_many_lines)]
-pub fn trends(conn: &Connection, options: TrendingQueryOptions) -> Result<Vec<CollectionTrend>> {
-    let TrendingQueryOptions {
-        sort_by,
-        order,
-        limit,
-        offset,
-    } = options;
-
-    let sort_by: CollectionTrends = sort_by.into();
-
-    let order = order.unwrap_or(Order::Desc);
-
-    let query = Query::select()
-        .columns(vec![
-            (CollectionTrends::Table, CollectionTrends::Collection),
-            (CollectionTrends::Table, CollectionTrends::FloorPrice),
-            (CollectionTrends::Table, CollectionTrends::NftCount),
-            (CollectionTrends::Table, CollectionTrends::OneDayVolume),
-            (CollectionTrends::Table, CollectionTrends::SevenDayVolume),
-            (CollectionTrends::Table, CollectionTrends::ThirtyDayVolume),
-            (CollectionTrends::Table, CollectionTrends::OneDaySalesCount),
-            (CollectionTrends::Table, CollectionTrends::OneDayMarketcap),
-            (CollectionTrends::Table, CollectionTrends::SevenDayMarketcap),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::ThirtyDayMarketcap,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::OneDayMarketcapChange,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::SevenDayMarketcapChange,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::ThirtyDayMarketcapChange,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::SevenDaySalesCount,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::ThirtyDaySalesCount,
-            ),
-            (CollectionTrends::Table, CollectionTrends::PrevOneDayVolume),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::PrevSevenDayVolume,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::PrevThirtyDayVolume,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::PrevOneDaySalesCount,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::PrevSevenDaySalesCount,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::PrevThirtyDaySalesCount,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::PrevOneDayFloorPrice,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::PrevSevenDayFloorPrice,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::PrevThirtyDayFloorPrice,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::OneDayVolumeChange,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::SevenDayVolumeChange,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::ThirtyDayVolumeChange,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::OneDayFloorPriceChange,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::SevenDayFloorPriceChange,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::ThirtyDayFloorPriceChange,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::OneDaySalesCountChange,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::SevenDaySalesCountChange,
-            ),
-            (
-                CollectionTrends::Table,
-                CollectionTrends::ThirtyDaySalesCountChange,
-            ),
-        ])
-        .from(CollectionTrends::Table)
-        .limit(limit)
-        .offset(offset)
-        .order_by((CollectionTrends::Table, sort_by), order)
-        .take();
-
-    let query = query.to_string(PostgresQueryBuilder);
-
-    diesel::sql_query(query)
+/// This function fails if the underlying SQL query returns an error
+pub fn collection_activities_page(
+    conn: &Connection,
+    address: impl ToSql<Text, Pg>,
+    event_types: impl ToSql<Nullable<Array<Text>>, Pg>,
+    after: Option<&ActivityKey>,
+    limit: i64,
+) -> Result<Vec<NftActivity>> {
+    diesel::sql_query(keyset_page_query(COLLECTION_ACTIVITIES_UNION))
+        .bind(address)
+        .bind(event_types)
+        .bind::<Nullable<Timestamp>, _>(after.map(|k| k.created_at))
+        .bind::<Nullable<diesel::sql_types::Uuid>, _>(after.map(|k| k.id))
+        .bind::<Nullable<Text>, _>(after.map(|k| k.activity_type.as_str()))
+        .bind::<Int8, _>(limit)
         .load(conn)
-        .context("Failed to load trending collection(s)")
+        .context("Failed to load collection activities")
 }
@@ -29,14 +29,27 @@ SELECT
     a.canceled_at,
     a.slot,
     a.write_version,
-    a.expiry
+    a.expiry,
+    a.currency_mint,
+    a.currency_decimals
 
 FROM (
 
     SELECT
         listings.*,
+        -- Compare prices across treasury mints at the current rate, falling
+        -- back to the native price if it cannot be converted
+        coalesce(normalize_price(
+            listings.currency_mint,
+            listings.price,
+            (now() AT TIME ZONE 'utc')::timestamp
+        ), listings.price) as normalized_price,
         row_number() OVER (
-            PARTITION BY listings.seller ORDER BY listings.price DESC
+            PARTITION BY listings.seller ORDER BY coalesce(normalize_price(
+                listings.currency_mint,
+                listings.price,
+                (now() AT TIME ZONE 'utc')::timestamp
+            ), listings.price) DESC
         ) as row
 
     FROM listings, metadata_creators, wallet_totals
@@ -54,7 +67,7 @@ FROM (
         AND (($2 IS NULL) OR NOT(listings.seller = ANY($2)))
 
     GROUP BY listings.metadata, listings.id
-    ORDER BY sum(wallet_totals.followers) DESC, listings.price DESC
+    ORDER BY sum(wallet_totals.followers) DESC, normalized_price DESC
 
 ) as a
 
@@ -0,0 +1,76 @@
+//! Query utilities for the holders of verified collections, maintained
+//! incrementally in the `collection_holders` and `collection_holder_days`
+//! tables as ownership changes.
+
+use anyhow::Context;
+use chrono::NaiveDate;
+use diesel::{pg::Pg, prelude::*, serialize::ToSql, sql_types::Text};
+
+use crate::{
+    db::{
+        models::{CollectionCount, CollectionHolder, CollectionHolderDay},
+        tables::{collection_holder_days, collection_holders},
+        Connection,
+    },
+    error::Result,
+};
+
+/// Load the number of NFTs held by every wallet holding any NFT of a verified
+/// collection, largest holdings first
+///
+/// # Errors
+/// This function fails if the underlying SQL query returns an error
+pub fn holders<'a>(conn: &Connection, collection: &str) -> Result<Vec<CollectionHolder<'a>>> {
+    collection_holders::table
+        .filter(collection_holders::collection_address.eq(collection))
+        .order((
+            collection_holders::nft_count.desc(),
+            collection_holders::owner_address.asc(),
+        ))
+        .load(conn)
+        .context("Failed to load collection holders")
+}
+
+/// Load the number of new and churned holders of a verified collection for
+/// each day since `start` with any, oldest first
+///
+/// # Errors
+/// This function fails if the underlying SQL query returns an error
+pub fn days<'a>(
+    conn: &Connection,
+    collection: &str,
+    start: NaiveDate,
+) -> Result<Vec<CollectionHolderDay<'a>>> {
+    collection_holder_days::table
+        .filter(collection_holder_days::collection_address.eq(collection))
+        .filter(collection_holder_days::day.ge(start))
+        .order(collection_holder_days::day.asc())
+        .load(conn)
+        .context("Failed to load collection holder history")
+}
+
+const LISTED_QUERY: &str = r"
+select mck.collection_address as collection, count(distinct l.metadata) as count
+from listings l
+inner join metadata_collection_keys mck
+    on mck.metadata_address = l.metadata and mck.verified
+inner join metadatas md on md.address = l.metadata
+where mck.collection_address = $1
+    and l.purchase_id is null
+    and l.canceled_at is null
+    and md.burned_at is null
+group by mck.collection_address;
+ -- $1: collection address::text";
+
+/// Count the unburned NFTs of a verified collection with at least one
+/// active listing
+///
+/// # Errors
+/// This function fails if the underlying SQL query returns an error
+pub fn listed_count(conn: &Connection, collection: impl ToSql<Text, Pg>) -> Result<i64> {
+    diesel::sql_query(LISTED_QUERY)
+        .bind(collection)
+        .load::<CollectionCount>(conn)
+        .context("Failed to count listed collection NFTs")
+        .map(|rows| rows.first().map_or(0, |r| r.count))
+}
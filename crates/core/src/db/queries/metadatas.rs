@@ -6,8 +6,8 @@ use diesel::{
     sql_types::{Array, Text},
 };
 use sea_query::{
-    Alias, Condition, DynIden, Expr, Iden, JoinType, Order, PostgresQueryBuilder, Query, SeaRc,
-    Value,
+    Alias, Condition, DynIden, Expr, Iden, JoinType, NullOrdering, Order, OrderedStatement,
+    PostgresQueryBuilder, Query, SeaRc, Value,
 };
 use uuid::Uuid;
 
@@ -20,6 +20,7 @@ use crate::{
     },
     error::prelude::*,
     prelude::Utc,
+    rarity::RarityMethod,
 };
 
 /// Format for incoming filters on attributes
@@ -31,6 +32,17 @@ pub struct AttributeFilter {
     pub values: Vec<String>,
 }
 
+/// Format for incoming range filters on numeric attributes
+#[derive(Debug)]
+pub struct AttributeRangeFilter {
+    /// name of trait
+    pub trait_type: String,
+    /// inclusive lower bound of the trait value
+    pub min: Option<f64>,
+    /// inclusive upper bound of the trait value
+    pub max: Option<f64>,
+}
+
 #[derive(Iden)]
 enum Metadatas {
     Table,
@@ -112,6 +124,7 @@ enum Attributes {
     MetadataAddress,
     TraitType,
     Value,
+    ValueNumeric,
 }
 
 #[derive(Iden)]
@@ -121,6 +134,14 @@ enum MetadataCollectionKeys {
     CollectionAddress,
 }
 
+#[derive(Iden)]
+enum NftRarities {
+    Table,
+    MetadataAddress,
+    Method,
+    Rank,
+}
+
 /// List query options
 #[derive(Debug)]
 pub struct ListQueryOptions {
@@ -138,6 +159,8 @@ pub struct ListQueryOptions {
     pub offerers: Option<Vec<String>>,
     /// nft attributes
     pub attributes: Option<Vec<AttributeFilter>>,
+    /// nft numeric attribute ranges
+    pub attribute_ranges: Option<Vec<AttributeRangeFilter>>,
     /// nfts listed for sale
     pub listed: Option<bool>,
     /// return nfts from unverified creators
@@ -205,6 +228,7 @@ pub fn list<O: Clone + Into<Value>>(
         auction_houses,
         offerers,
         attributes,
+        attribute_ranges,
         listed,
         allow_unverified,
         with_offers,
@@ -418,6 +442,42 @@ pub fn list<O: Clone + Into<Value>>(
         }
     }
 
+    if let Some(attribute_ranges) = attribute_ranges {
+        for AttributeRangeFilter {
+            trait_type,
+            min,
+            max,
+        } in attribute_ranges
+        {
+            let alias = format!("attribute_ranges_{}", trait_type);
+            let alias: DynIden = SeaRc::new(Alias::new(&alias));
+
+            let mut conditions = Condition::all()
+                .add(Expr::col(Attributes::TraitType).eq(trait_type))
+                .add(Expr::col(Attributes::ValueNumeric).is_not_null());
+
+            if let Some(min) = min {
+                conditions = conditions.add(Expr::col(Attributes::ValueNumeric).gte(min));
+            }
+
+            if let Some(max) = max {
+                conditions = conditions.add(Expr::col(Attributes::ValueNumeric).lte(max));
+            }
+
+            query.join_lateral(
+                JoinType::InnerJoin,
+                Query::select()
+                    .from(Attributes::Table)
+                    .column((Attributes::Table, Attributes::MetadataAddress))
+                    .cond_where(conditions)
+                    .take(),
+                alias.clone(),
+                Expr::tbl(alias, Attributes::MetadataAddress)
+                    .equals(Metadatas::Table, Metadatas::Address),
+            );
+        }
+    }
+
     if let Some(collections) = collections {
         query.inner_join(
             MetadataCollectionKeys::Table,
@@ -455,9 +515,12 @@ pub struct CollectionNftOptions {
     pub attributes: Option<Vec<AttributeFilter>>,
     /// Marketplace program in which the collection is listed
     pub marketplace_program: Option<String>,
-    /// Sort by Price or Listed at
+    /// Sort by Price, Listed at, or Rarity
     pub sort_by: Option<NftSort>,
-    /// Order the resulting rows by 'Asc' or 'Desc'
+    /// Scoring method used when sorting by rarity
+    pub rarity_method: Option<RarityMethod>,
+    /// Order the resulting rows by 'Asc' or 'Desc'.  Rarity is ordered from
+    /// rarest to most common for 'Desc'.
     pub order: Option<Order>,
     /// Limit the number of returned rows
     pub limit: u64,
@@ -486,12 +549,12 @@ pub struct WalletNftOptions {
     pub offset: u64,
 }
 
-impl From<NftSort> for Listings {
-    fn from(sort: NftSort) -> Self {
-        match sort {
-            NftSort::Price => Listings::Price,
-            NftSort::ListedAt => Listings::CreatedAt,
-        }
+/// The listing column to order by for the given sort, if any
+fn listing_sort_column(sort: NftSort) -> Option<Listings> {
+    match sort {
+        NftSort::Price => Some(Listings::Price),
+        NftSort::ListedAt => Some(Listings::CreatedAt),
+        NftSort::Rarity => None,
     }
 }
 
@@ -511,12 +574,13 @@ pub fn collection_nfts<O: Into<Value>>(
         attributes,
         marketplace_program,
         sort_by,
+        rarity_method,
         order,
         limit,
         offset,
     } = options;
 
-    let sort_by = sort_by.map_or(Listings::Price, Into::into);
+    let sort_by = sort_by.unwrap_or(NftSort::Price);
 
     let current_time = Utc::now().naive_utc();
 
@@ -636,9 +700,37 @@ pub fn collection_nfts<O: Into<Value>>(
         )
         .limit(limit)
         .offset(offset)
-        .order_by((Listings::Table, sort_by), order)
         .take();
 
+    if let Some(column) = listing_sort_column(sort_by) {
+        query.order_by((Listings::Table, column), order);
+    } else {
+        let method = rarity_method.unwrap_or(RarityMethod::InformationContent);
+
+        query.left_join(
+            NftRarities::Table,
+            Condition::all()
+                .add(
+                    Expr::tbl(NftRarities::Table, NftRarities::MetadataAddress)
+                        .equals(Metadatas::Table, Metadatas::Address),
+                )
+                .add(Expr::col((NftRarities::Table, NftRarities::Method)).eq(method.to_string())),
+        );
+
+        // Rank 1 is the rarest, so descending rarity is ascending rank.  NFTs
+        // which have not been ranked yet are listed last either way.
+        let rank_order = match order {
+            Order::Desc => Order::Asc,
+            _ => Order::Desc,
+        };
+
+        query.order_by_with_nulls(
+            (NftRarities::Table, NftRarities::Rank),
+            rank_order,
+            NullOrdering::Last,
+        );
+    }
+
     if let Some(attributes) = attributes {
         for AttributeFilter { trait_type, values } in attributes {
             let alias = format!("attributes_{}", trait_type);
@@ -690,7 +782,8 @@ pub fn wallet_nfts<O: Into<Value>>(
         offset,
     } = options;
 
-    let sort_unwrap = sort_by.map_or(Listings::Price, Into::into);
+    let sort_unwrap = listing_sort_column(sort_by.unwrap_or(NftSort::Price))
+        .ok_or_else(|| anyhow!("Sorting by rarity is only supported within a collection"))?;
 
     let order_unwrap = order.unwrap_or(Order::Desc);
 
@@ -3,16 +3,23 @@
 pub mod activities;
 pub mod bonding_changes;
 pub mod charts;
+pub mod collection_stats;
 pub mod collections;
 pub mod featured_listings;
 pub mod feed_event;
 pub mod genopets;
 pub mod graph_connection;
+pub mod holders;
 pub mod listing_denylist;
 pub mod metadata_edition;
 pub mod metadatas;
 pub mod nft_count;
+pub mod nft_history;
+pub mod nft_search;
+pub mod owner_history;
+pub mod portfolio;
 pub mod reward_centers;
+pub mod royalties;
 pub mod spl_governance;
 pub mod stats;
 pub mod store_denylist;
@@ -0,0 +1,215 @@
+//! Query utilities for the provenance of an NFT, merging its mints, listings,
+//! offers, sales, ownership history, Cardinal claims, Genopets rentals, and
+//! burns into a single timeline.
+
+use std::{fmt, str::FromStr};
+
+use anyhow::{bail, Context};
+use chrono::NaiveDateTime;
+use diesel::{
+    prelude::*,
+    sql_types::{Int8, Nullable, Text, Timestamp},
+};
+
+use crate::{
+    db::{models::NftEvent, Connection},
+    error::{Error, Result},
+};
+
+/// A position in the provenance timeline of an NFT, identifying the last
+/// event of a page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryCursor {
+    /// The time of the event
+    pub occurred_at: NaiveDateTime,
+    /// The key of the event
+    pub key: String,
+}
+
+impl From<&NftEvent> for HistoryCursor {
+    fn from(event: &NftEvent) -> Self {
+        Self {
+            occurred_at: event.occurred_at,
+            key: event.key.clone(),
+        }
+    }
+}
+
+impl FromStr for HistoryCursor {
+    type Err = Error;
+
+    /// Parse a cursor of the form `<microseconds since the epoch>:<key>`
+    fn from_str(s: &str) -> Result<Self> {
+        let (micros, key) = match s.split_once(':') {
+            Some((m, k)) if !k.is_empty() => (m, k),
+            _ => bail!("Invalid history cursor {:?}", s),
+        };
+
+        let micros: i64 = micros
+            .parse()
+            .with_context(|| format!("Invalid history cursor {:?}", s))?;
+
+        let occurred_at = NaiveDateTime::from_timestamp_opt(
+            micros.div_euclid(1_000_000),
+            u32::try_from(micros.rem_euclid(1_000_000) * 1_000)?,
+        )
+        .with_context(|| format!("History cursor {:?} is out of range", s))?;
+
+        Ok(Self {
+            occurred_at,
+            key: key.to_owned(),
+        })
+    }
+}
+
+impl fmt::Display for HistoryCursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let micros = self.occurred_at.timestamp() * 1_000_000
+            + i64::from(self.occurred_at.timestamp_subsec_micros());
+
+        write!(f, "{}:{}", micros, self.key)
+    }
+}
+
+const HISTORY_QUERY: &str = r"
+with md as (
+    select address, mint_address, burned_at
+    from metadatas
+    where address = $1
+)
+select * from (
+    select 'mint'::text as kind,
+        'mint:' || me.feed_event_id::text as key,
+        fe.created_at at time zone 'UTC' as occurred_at,
+        null::bigint as slot,
+        (select few.wallet_address
+            from feed_event_wallets few
+            where few.feed_event_id = me.feed_event_id
+            limit 1) as wallet,
+        null::varchar as counterparty,
+        null::bigint as price,
+        null::varchar as auction_house,
+        null::varchar as marketplace_program
+    from mint_events me
+    inner join feed_events fe on fe.id = me.feed_event_id
+    where me.metadata_address = $1
+
+    union all
+
+    select 'listing', 'listing:' || l.id::text, l.created_at, l.slot,
+        l.seller, null, l.price, l.auction_house, l.marketplace_program
+    from listings l
+    where l.metadata = $1
+
+    union all
+
+    select 'offer', 'offer:' || o.id::text, o.created_at, o.slot,
+        o.buyer, null, o.price, o.auction_house, o.marketplace_program
+    from offers o
+    where o.metadata = $1 and o.purchase_id is null
+
+    union all
+
+    select 'sale', 'sale:' || p.id::text, p.created_at, p.slot,
+        p.buyer, p.seller, p.price, p.auction_house, p.marketplace_program
+    from purchases p
+    where p.metadata = $1
+
+    union all
+
+    select 'transfer', 'transfer:' || h.slot::text, h.recorded_at, h.slot,
+        h.owner_address, h.previous_owner, null, null, null
+    from (
+        select moh.slot, moh.recorded_at, moh.owner_address,
+            lag(moh.owner_address) over (order by moh.slot) as previous_owner
+        from md
+        inner join metadata_owner_history moh on moh.mint_address = md.mint_address
+    ) h
+
+    union all
+
+    select 'cardinal_claim',
+        'cardinal_claim:' || c.token_manager_address || ':' || c.state_changed_at::text,
+        c.state_changed_at, null, c.issuer, null, null, null, null
+    from md
+    inner join cardinal_claim_events c on c.mint = md.mint_address
+
+    union all
+
+    select 'genopets_rent', 'genopets_rent:' || r.habitat_address, r.last_rent_payment, r.slot,
+        r.alchemist, cmo.owner_address, null, null, null
+    from md
+    inner join geno_habitat_datas h on h.habitat_mint = md.mint_address
+    inner join geno_rental_agreements r on r.habitat_address = h.address
+    left join current_metadata_owners cmo on cmo.mint_address = md.mint_address
+    where r.alchemist is not null
+
+    union all
+
+    select 'burn', 'burn:' || md.address, md.burned_at, null,
+        cmo.owner_address, null, null, null, null
+    from md
+    left join current_metadata_owners cmo on cmo.mint_address = md.mint_address
+    where md.burned_at is not null
+) e
+where $2::timestamp is null or (e.occurred_at, e.key) < ($2::timestamp, $3::text)
+order by e.occurred_at desc, e.key desc
+limit $4;
+ -- $1: metadata address::text
+ -- $2: cursor time::timestamp
+ -- $3: cursor key::text
+ -- $4: limit::bigint";
+
+/// Load the provenance of an NFT, newest first, starting after the given
+/// cursor if any
+///
+/// # Errors
+/// This function fails if the underlying SQL query returns an error
+pub fn list(
+    conn: &Connection,
+    address: &str,
+    cursor: Option<HistoryCursor>,
+    limit: i64,
+) -> Result<Vec<NftEvent>> {
+    let time = cursor.as_ref().map(|c| c.occurred_at);
+    let key = cursor.map(|c| c.key);
+
+    diesel::sql_query(HISTORY_QUERY)
+        .bind::<Text, _>(address)
+        .bind::<Nullable<Timestamp>, _>(time)
+        .bind::<Nullable<Text>, _>(key)
+        .bind::<Int8, _>(limit)
+        .load(conn)
+        .context("Failed to load NFT history")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::HistoryCursor;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = HistoryCursor {
+            occurred_at: NaiveDate::from_ymd(2022, 11, 13).and_hms_micro(11, 27, 38, 123_456),
+            key: "cardinal_claim:abc:2022-11-13 11:27:38".into(),
+        };
+
+        let s = cursor.to_string();
+
+        assert_eq!(s.parse::<HistoryCursor>().unwrap(), cursor);
+
+        let early = HistoryCursor {
+            occurred_at: NaiveDate::from_ymd(1969, 12, 31).and_hms_micro(23, 59, 59, 500_000),
+            key: "mint:x".into(),
+        };
+
+        assert_eq!(early.to_string(), "-500000:mint:x");
+        assert_eq!(early.to_string().parse::<HistoryCursor>().unwrap(), early);
+
+        for bad in ["", "123", "123:", "abc:mint:x", "1.5:mint:x"] {
+            assert!(bad.parse::<HistoryCursor>().is_err(), "{:?}", bad);
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! Query utilities for full-text NFT search in Postgres, used when Meilisearch
+//! is unavailable or disabled.
+
+use anyhow::Context;
+use diesel::{
+    prelude::*,
+    sql_types::{Array, Bool, Int8, Nullable, Text},
+};
+use sea_query::Order;
+
+use crate::{
+    db::{
+        models::{NftSearchHit, TraitCount},
+        Connection,
+    },
+    error::Result,
+};
+
+/// Filters and pagination for an NFT search
+#[derive(Debug, Default)]
+pub struct SearchOptions {
+    /// Full-text query against the NFT name
+    pub term: Option<String>,
+    /// Return only NFTs in one of these verified collections
+    pub collections: Option<Vec<String>>,
+    /// Return only NFTs with one of these verified creators
+    pub creators: Option<Vec<String>>,
+    /// Return only NFTs held by one of these wallets
+    pub owners: Option<Vec<String>>,
+    /// Return only NFTs with, for every listed trait type, one of the listed
+    /// values
+    pub attributes: Vec<(String, Vec<String>)>,
+    /// Return only NFTs with (or without) an active listing
+    pub listed: Option<bool>,
+    /// Sort by the lowest active listing price instead of by name
+    pub order_by_price: Option<Order>,
+    /// Number of results to skip
+    pub offset: i64,
+    /// Maximum number of results to return
+    pub limit: i64,
+}
+
+// $5 and $6 are the trait types and values of the attribute filter pairs.
+// An NFT matches if no filtered trait type lacks a matching attribute.
+const MATCHES: &str = r"
+with matches as (
+    select m.address, m.name, (
+        select min(l.price) from listings l
+        where l.metadata = m.address
+            and l.purchase_id is null
+            and l.canceled_at is null
+            and (l.expiry is null or l.expiry > now())
+    ) as listing_price
+    from metadatas m
+    where m.burned_at is null
+        and ($1::text is null
+            or to_tsvector('english', m.name) @@ websearch_to_tsquery('english', $1))
+        and ($2::text[] is null or exists (
+            select from metadata_collection_keys k
+            where k.metadata_address = m.address
+                and k.verified
+                and k.collection_address = any($2)))
+        and ($3::text[] is null or exists (
+            select from metadata_creators c
+            where c.metadata_address = m.address
+                and c.verified
+                and c.creator_address = any($3)))
+        and ($4::text[] is null or exists (
+            select from current_metadata_owners o
+            where o.mint_address = m.mint_address
+                and o.owner_address = any($4)))
+        and not exists (
+            select from unnest($5::text[]) f(trait_type)
+            where not exists (
+                select from attributes a
+                inner join unnest($5::text[], $6::text[]) p(trait_type, value)
+                    on p.trait_type = a.trait_type and p.value = a.value
+                where a.metadata_address = m.address
+                    and a.trait_type = f.trait_type))
+), filtered as (
+    select * from matches
+    where $7::bool is null or (listing_price is not null) = $7
+)";
+
+/// Split attribute filters into parallel arrays of trait types and values
+fn attribute_pairs(attributes: &[(String, Vec<String>)]) -> (Vec<String>, Vec<String>) {
+    attributes
+        .iter()
+        .flat_map(|(t, vs)| vs.iter().map(move |v| (t.clone(), v.clone())))
+        .unzip()
+}
+
+macro_rules! bind_filters {
+    ($query:expr, $opts:expr, $types:expr, $values:expr) => {
+        $query
+            .bind::<Nullable<Text>, _>(&$opts.term)
+            .bind::<Nullable<Array<Text>>, _>(&$opts.collections)
+            .bind::<Nullable<Array<Text>>, _>(&$opts.creators)
+            .bind::<Nullable<Array<Text>>, _>(&$opts.owners)
+            .bind::<Array<Text>, _>(&$types)
+            .bind::<Array<Text>, _>(&$values)
+            .bind::<Nullable<Bool>, _>($opts.listed)
+    };
+}
+
+/// Load a page of NFTs matching a search, along with the total number of
+/// matching NFTs
+///
+/// # Errors
+/// This function fails if the underlying SQL query returns an error
+pub fn search(conn: &Connection, opts: &SearchOptions) -> Result<(Vec<String>, i64)> {
+    let (types, values) = attribute_pairs(&opts.attributes);
+    let order = match opts.order_by_price {
+        Some(Order::Desc) => "listing_price desc nulls last, name",
+        Some(_) => "listing_price asc nulls last, name",
+        None => "name",
+    };
+
+    let hits: Vec<NftSearchHit> = bind_filters!(
+        diesel::sql_query(format!(
+            "{} select address, count(*) over () as total from filtered order by {}, address \
+             offset $8 limit $9;",
+            MATCHES, order
+        )),
+        opts,
+        types,
+        values
+    )
+    .bind::<Int8, _>(opts.offset)
+    .bind::<Int8, _>(opts.limit)
+    .load(conn)
+    .context("Failed to search NFTs")?;
+
+    let total = match hits.first() {
+        Some(h) => h.total,
+        // The page is past the end of the results, so count them separately
+        None if opts.offset > 0 => {
+            bind_filters!(
+                diesel::sql_query(format!(
+                    "{} select '' as address, count(*) as total from filtered;",
+                    MATCHES
+                )),
+                opts,
+                types,
+                values
+            )
+            .get_result::<NftSearchHit>(conn)
+            .context("Failed to count NFT search results")?
+            .total
+        },
+        None => 0,
+    };
+
+    Ok((hits.into_iter().map(|h| h.address).collect(), total))
+}
+
+/// Count the NFTs matching a search with each trait type and value
+///
+/// # Errors
+/// This function fails if the underlying SQL query returns an error
+pub fn trait_counts(conn: &Connection, opts: &SearchOptions) -> Result<Vec<TraitCount>> {
+    let (types, values) = attribute_pairs(&opts.attributes);
+
+    bind_filters!(
+        diesel::sql_query(format!(
+            "{} select a.trait_type, a.value, count(distinct a.metadata_address) as count
+            from attributes a
+            inner join filtered f on f.address = a.metadata_address
+            where a.trait_type is not null and a.value is not null
+            group by a.trait_type, a.value;",
+            MATCHES
+        )),
+        opts,
+        types,
+        values
+    )
+    .load(conn)
+    .context("Failed to count NFT search traits")
+}
@@ -0,0 +1,131 @@
+//! Query utilities for the owners of NFTs at a point in time, looked up from
+//! the append-only `metadata_owner_history` and `metadata_burns` tables.
+
+use anyhow::Context;
+use chrono::NaiveDateTime;
+use diesel::{
+    prelude::*,
+    sql_types::{Int8, Nullable, Text, Timestamp},
+};
+
+use crate::{
+    db::{
+        models::{HolderSnapshot, MetadataOwnerRange},
+        Connection,
+    },
+    error::Result,
+};
+
+/// A point in time at which to look up ownership
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointInTime {
+    /// The end of the given slot
+    Slot(i64),
+    /// The given time, compared against the block time of each owner's slot,
+    /// or the time it was indexed where the block time is unknown
+    Time(NaiveDateTime),
+}
+
+impl PointInTime {
+    fn split(self) -> (Option<i64>, Option<NaiveDateTime>) {
+        match self {
+            Self::Slot(s) => (Some(s), None),
+            Self::Time(t) => (None, Some(t)),
+        }
+    }
+}
+
+const OWNER_QUERY: &str = r"
+select r.mint_address, r.owner_address, r.token_account_address, r.start_slot,
+    coalesce(r.end_slot, b.slot) as end_slot,
+    r.started_at,
+    case when r.end_slot is not null then r.ended_at
+        else coalesce(b.block_time, b.recorded_at)
+    end as ended_at
+from (
+    select mint_address, owner_address, token_account_address,
+        slot as start_slot,
+        lead(slot) over w as end_slot,
+        coalesce(block_time, recorded_at) as started_at,
+        lead(coalesce(block_time, recorded_at)) over w as ended_at
+    from metadata_owner_history
+    where mint_address = $1
+    window w as (order by slot)
+) r
+left join metadata_burns b on b.mint_address = r.mint_address
+where ($2::bigint is null or r.start_slot <= $2)
+    and ($3::timestamp is null or r.started_at <= $3)
+    and ($2::bigint is null or b.slot is null or b.slot > $2)
+    and ($3::timestamp is null or b.slot is null or coalesce(b.block_time, b.recorded_at) > $3)
+order by r.start_slot desc
+limit 1;
+ -- $1: mint address::text
+ -- $2: slot::bigint
+ -- $3: time::timestamp";
+
+/// Load the owner of an NFT mint at a point in time, along with the period
+/// during which it held the NFT.  Returns `None` if the NFT was burned by
+/// then.
+///
+/// # Errors
+/// This function fails if the underlying SQL query returns an error
+pub fn owner_at(
+    conn: &Connection,
+    mint: &str,
+    at: PointInTime,
+) -> Result<Option<MetadataOwnerRange>> {
+    let (slot, time) = at.split();
+
+    diesel::sql_query(OWNER_QUERY)
+        .bind::<Text, _>(mint)
+        .bind::<Nullable<Int8>, _>(slot)
+        .bind::<Nullable<Timestamp>, _>(time)
+        .get_result(conn)
+        .optional()
+        .context("Failed to load NFT owner")
+}
+
+const HOLDERS_QUERY: &str = r"
+select o.owner_address, count(*) as nft_count
+from (
+    select distinct on (h.mint_address) h.mint_address, h.owner_address
+    from metadata_collection_keys mck
+    inner join metadatas md on md.address = mck.metadata_address
+    inner join metadata_owner_history h on h.mint_address = md.mint_address
+    left join metadata_burns b on b.mint_address = md.mint_address
+    where mck.collection_address = $1
+        and mck.verified
+        and ($2::bigint is null or h.slot <= $2)
+        and ($3::timestamp is null or coalesce(h.block_time, h.recorded_at) <= $3)
+        and ($2::bigint is null or b.slot is null or b.slot > $2)
+        and ($3::timestamp is null or b.slot is null or coalesce(b.block_time, b.recorded_at) > $3)
+    order by h.mint_address, h.slot desc
+) o
+group by o.owner_address
+order by nft_count desc, o.owner_address asc;
+ -- $1: collection address::text
+ -- $2: slot::bigint
+ -- $3: time::timestamp";
+
+/// Count the NFTs of a verified collection held by each wallet at a point in
+/// time, largest holdings first
+///
+/// Collection membership is taken from the current verified collection of
+/// each NFT.  NFTs burned by the point in time are excluded.
+///
+/// # Errors
+/// This function fails if the underlying SQL query returns an error
+pub fn collection_holders_at(
+    conn: &Connection,
+    collection: &str,
+    at: PointInTime,
+) -> Result<Vec<HolderSnapshot>> {
+    let (slot, time) = at.split();
+
+    diesel::sql_query(HOLDERS_QUERY)
+        .bind::<Text, _>(collection)
+        .bind::<Nullable<Int8>, _>(slot)
+        .bind::<Nullable<Timestamp>, _>(time)
+        .load(conn)
+        .context("Failed to load collection holders")
+}
@@ -0,0 +1,146 @@
+//! Query utilities for valuing the holdings of a wallet and loading its
+//! trades.  Prices are normalized to lamports of SOL.
+
+use anyhow::Context;
+use chrono::NaiveDateTime;
+use diesel::{
+    pg::Pg,
+    prelude::*,
+    serialize::ToSql,
+    sql_types::{Array, Text, Timestamp},
+};
+
+use crate::{
+    db::{
+        models::{CollectionDailyClose, PortfolioHolding, WalletTrade},
+        Connection,
+    },
+    error::Result,
+};
+
+const HOLDINGS_QUERY: &str = r"
+with held as (
+    select md.address,
+           (select mck.collection_address
+            from metadata_collection_keys mck
+            where mck.metadata_address = md.address and mck.verified
+            limit 1) as collection_address
+    from current_metadata_owners cmo
+    inner join metadatas md on md.mint_address = cmo.mint_address
+    where cmo.owner_address = $1 and md.burned_at is null
+)
+select h.address as metadata_address,
+       h.collection_address,
+       cs.floor_price,
+       (select normalize_price(p.currency_mint, p.price, p.created_at)::bigint
+        from purchases p
+        where p.metadata = h.address
+        order by p.created_at desc
+        limit 1) as last_sale_price,
+       (select max(trait_floors.floor)
+        from attributes a
+        cross join lateral (
+            select min(normalize_price(l.currency_mint, l.price, $2))::bigint as floor
+            from attributes ta
+            inner join metadata_collection_keys tmck
+                on tmck.metadata_address = ta.metadata_address
+                and tmck.collection_address = h.collection_address
+                and tmck.verified
+            inner join listings l
+                on l.metadata = ta.metadata_address
+                and l.purchase_id is null
+                and l.canceled_at is null
+            where ta.trait_type = a.trait_type and ta.value = a.value
+        ) as trait_floors
+        where a.metadata_address = h.address
+            and a.trait_type is not null
+            and a.value is not null
+            and h.collection_address is not null) as trait_floor
+from held h
+left join collection_stats cs on cs.collection_address = h.collection_address;
+ -- $1: wallet address::text
+ -- $2: valuation time::timestamp";
+
+/// Load the NFTs currently held by a wallet, along with the floor price of
+/// their verified collection, their last sale price, and the highest floor
+/// price of any of their traits within their collection, valued at `now`
+///
+/// # Errors
+/// This function fails if the underlying SQL query returns an error
+pub fn holdings(
+    conn: &Connection,
+    wallet: impl ToSql<Text, Pg>,
+    now: NaiveDateTime,
+) -> Result<Vec<PortfolioHolding>> {
+    diesel::sql_query(HOLDINGS_QUERY)
+        .bind(wallet)
+        .bind::<Timestamp, _>(now)
+        .load(conn)
+        .context("Failed to load wallet holdings")
+}
+
+const TRADES_QUERY: &str = r"
+select p.metadata as metadata_address,
+       (select mck.collection_address
+        from metadata_collection_keys mck
+        where mck.metadata_address = p.metadata and mck.verified
+        limit 1) as collection_address,
+       p.buyer = $1 as bought,
+       normalize_price(p.currency_mint, p.price, p.created_at)::bigint as price,
+       p.created_at
+from purchases p
+where (p.buyer = $1 or p.seller = $1) and p.buyer <> p.seller
+order by p.created_at asc, p.id asc;
+ -- $1: wallet address::text";
+
+/// Load every sale a wallet bought or sold an NFT in, oldest first
+///
+/// # Errors
+/// This function fails if the underlying SQL query returns an error
+pub fn trades(conn: &Connection, wallet: impl ToSql<Text, Pg>) -> Result<Vec<WalletTrade>> {
+    diesel::sql_query(TRADES_QUERY)
+        .bind(wallet)
+        .load(conn)
+        .context("Failed to load wallet trades")
+}
+
+const DAILY_CLOSES_QUERY: &str = r"
+with closes as (
+    select collection_address, bucket_start,
+           (array_agg(normalized_close order by closed_at desc))[1]::bigint as close
+    from price_candles
+    where resolution = '1d'
+        and collection_address = any($1)
+        and normalized_close is not null
+    group by collection_address, bucket_start
+)
+select collection_address, bucket_start, close
+from closes
+where bucket_start >= $2
+union all
+(
+    select distinct on (collection_address) collection_address, bucket_start, close
+    from closes
+    where bucket_start < $2
+    order by collection_address, bucket_start desc
+)
+order by collection_address, bucket_start;
+ -- $1: collection addresses::text[]
+ -- $2: start date::timestamp";
+
+/// Load the daily closing sale prices of the given verified collections since
+/// `start`, along with the last close before `start` for each collection
+///
+/// # Errors
+/// This function fails if the underlying SQL query returns an error
+pub fn daily_closes(
+    conn: &Connection,
+    collections: impl ToSql<Array<Text>, Pg>,
+    start: NaiveDateTime,
+) -> Result<Vec<CollectionDailyClose>> {
+    diesel::sql_query(DAILY_CLOSES_QUERY)
+        .bind(collections)
+        .bind::<Timestamp, _>(start)
+        .load(conn)
+        .context("Failed to load collection daily closes")
+}
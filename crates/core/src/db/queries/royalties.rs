@@ -0,0 +1,125 @@
+//! Query utilities for the royalties owed to and paid to creators, computed
+//! from the `sale_royalties` table
+
+use anyhow::Context;
+use chrono::NaiveDateTime;
+use diesel::{
+    prelude::*,
+    sql_types::{Nullable, Text, Timestamp},
+};
+
+use crate::{
+    db::{models::RoyaltyStats, Connection},
+    error::Result,
+};
+
+/// Input parameters for the [`stats`] and [`by_marketplace`] queries
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RoyaltyOptions<'a> {
+    /// Only include royalties owed to this creator
+    pub creator: Option<&'a str>,
+    /// Only include sales of NFTs verified into this collection
+    pub collection: Option<&'a str>,
+    /// Only include sales made at or after this time
+    pub start_date: Option<NaiveDateTime>,
+    /// Only include sales made before this time
+    pub end_date: Option<NaiveDateTime>,
+}
+
+fn royalties_query(group_by_marketplace: bool) -> String {
+    let (select, group) = if group_by_marketplace {
+        ("marketplace_program", "group by marketplace_program")
+    } else {
+        ("null::varchar as marketplace_program", "")
+    };
+
+    format!(
+        r"
+select {},
+       count(distinct purchase_id) as sales,
+       count(distinct purchase_id) filter (where paid is not null) as verified_sales,
+       case when bool_and(single_currency) then min(currency_mint) end as currency_mint,
+       case when bool_and(single_currency) then sum(expected) end as expected,
+       case when bool_and(single_currency) then coalesce(sum(expected) filter (
+           where paid is not null), 0) end as verified_expected,
+       case when bool_and(single_currency) then coalesce(sum(paid), 0) end as paid,
+       case when bool_and(normalized_expected is not null)
+           then sum(normalized_expected) end as normalized_expected,
+       case when bool_and(normalized_expected is not null)
+           then coalesce(sum(normalized_paid), 0) end as normalized_paid,
+       (sum(normalized_paid) / nullif(sum(normalized_expected) filter (
+           where paid is not null), 0))::double precision as paid_ratio
+from (
+    select sr.*,
+           coalesce(bool_and(currency_mint is not null) over g
+               and min(currency_mint) over g = max(currency_mint) over g, false)
+               as single_currency,
+           normalize_price(currency_mint, expected, sold_at) as normalized_expected,
+           normalize_price(currency_mint, paid, sold_at) as normalized_paid
+    from sale_royalties sr
+    where ($1::text is null or creator_address = $1)
+        and ($2::text is null or metadata_address in (
+            select metadata_address
+            from metadata_collection_keys
+            where collection_address = $2 and verified))
+        and ($3::timestamp is null or sold_at >= $3)
+        and ($4::timestamp is null or sold_at < $4)
+    window g as ({})
+) as sr
+{}
+having count(*) > 0;
+ -- $1: creator address::text
+ -- $2: collection address::text
+ -- $3: start date::timestamp
+ -- $4: end date::timestamp",
+        select,
+        if group_by_marketplace {
+            "partition by marketplace_program"
+        } else {
+            ""
+        },
+        group,
+    )
+}
+
+fn load(
+    conn: &Connection,
+    options: RoyaltyOptions,
+    group_by_marketplace: bool,
+) -> Result<Vec<RoyaltyStats>> {
+    let RoyaltyOptions {
+        creator,
+        collection,
+        start_date,
+        end_date,
+    } = options;
+
+    diesel::sql_query(royalties_query(group_by_marketplace))
+        .bind::<Nullable<Text>, _>(creator)
+        .bind::<Nullable<Text>, _>(collection)
+        .bind::<Nullable<Timestamp>, _>(start_date)
+        .bind::<Nullable<Timestamp>, _>(end_date)
+        .load(conn)
+        .context("Failed to load royalty stats")
+}
+
+/// Summarize the royalties owed and paid for sales matching the given
+/// options, or return `None` if no matching sale owed royalties.
+///
+/// Payments are known only for sales whose execute-sale instruction was
+/// indexed; `paid` and `paid_ratio` cover only those sales.
+///
+/// # Errors
+/// This function fails if the underlying SQL query returns an error
+pub fn stats(conn: &Connection, options: RoyaltyOptions) -> Result<Option<RoyaltyStats>> {
+    load(conn, options, false).map(|r| r.into_iter().next())
+}
+
+/// Summarize the royalties owed and paid for sales matching the given
+/// options, grouped by the marketplace program they were made through
+///
+/// # Errors
+/// This function fails if the underlying SQL query returns an error
+pub fn by_marketplace(conn: &Connection, options: RoyaltyOptions) -> Result<Vec<RoyaltyStats>> {
+    load(conn, options, true)
+}
@@ -6,7 +6,7 @@ use diesel::{
     pg::Pg,
     prelude::*,
     serialize::ToSql,
-    sql_types::{Array, Text, Timestamp},
+    sql_types::{Array, Bool, Text, Timestamp},
 };
 
 use crate::{
@@ -115,6 +115,7 @@ from listings l
         on (md.address = mc.metadata_address)
     left join purchases p
         on (l.purchase_id = p.id)
+        and not ($4 and exists (select 1 from wash_trades wt where wt.purchase_id = p.id))
 
 where l.auction_house = ANY($1)
     and mc.creator_address = $2
@@ -123,9 +124,12 @@ where l.auction_house = ANY($1)
 group by auction_house, mint;
  -- $1: auction_house_addresses::text[]
  -- $2: creator::text
- -- $3: now::timestamp";
+ -- $3: now::timestamp
+ -- $4: exclude flagged::boolean";
 
-/// Load per-mint statistics for the given creator for provided auction houses
+/// Load per-mint statistics for the given creator for provided auction houses,
+/// optionally leaving sales flagged as wash trades out of the average price
+/// and volume
 ///
 /// # Errors
 /// This function fails if the underlying SQL query returns an error
@@ -133,11 +137,13 @@ pub fn collection(
     conn: &Connection,
     auction_houses: impl ToSql<Array<Text>, Pg>,
     creator: impl ToSql<Text, Pg>,
+    exclude_flagged: bool,
 ) -> Result<Vec<MintStats>> {
     diesel::sql_query(COLLECTION_QUERY)
         .bind(auction_houses)
         .bind(creator)
         .bind::<Timestamp, _>(Local::now().naive_utc())
+        .bind::<Bool, _>(exclude_flagged)
         .load(conn)
         .context("Failed to load collection mint stats")
 }
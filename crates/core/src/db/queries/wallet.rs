@@ -3,18 +3,19 @@ use diesel::{
     pg::Pg,
     prelude::*,
     serialize::ToSql,
-    sql_types::{Array, Integer, Nullable, Text},
+    sql_types::{Array, Int8, Integer, Nullable, Text, Timestamp},
 };
 
 use crate::{
     db::{
         models::{CollectedCollection, CreatedCollection, Offer, WalletActivity},
+        queries::activities::{keyset_page_query, ActivityKey},
         Connection,
     },
     error::prelude::*,
 };
 
-const ACTIVITES_QUERY: &str = r"
+const ACTIVITIES_UNION: &str = r"
 SELECT listings.id as id, metadata, price, auction_house, created_at, marketplace_program,
 array[seller] as wallets,
 array[twitter_handle_name_services.twitter_handle] as wallet_twitter_handles,
@@ -56,14 +57,8 @@ array[bth.twitter_handle] as wallet_twitter_handles,
     AND offers.purchase_id IS NULL
     AND offers.auction_house != '3o9d13qUvEuuauhFrVom1vuCzgNsJifeaBYDPquaT73Y'
     AND ('OFFERS' = ANY($2) OR $2 IS NULL)
-ORDER BY created_at DESC
-LIMIT $3
-OFFSET $4;
-
 -- $1: address::text
--- $2: event_types::text[]
--- $3: limit::integer
--- $4: offset::integer";
+-- $2: event_types::text[]";
 
 /// Load listing, purchase, sales and offer activity for wallets.
 ///
@@ -76,11 +71,42 @@ pub fn activities(
     limit: impl ToSql<Integer, Pg>,
     offset: impl ToSql<Integer, Pg>,
 ) -> Result<Vec<WalletActivity>> {
-    diesel::sql_query(ACTIVITES_QUERY)
+    diesel::sql_query(format!(
+        r"{}
+ORDER BY created_at DESC
+LIMIT $3
+OFFSET $4;
+-- $3: limit::integer
+-- $4: offset::integer",
+        ACTIVITIES_UNION
+    ))
+    .bind(address)
+    .bind(event_types)
+    .bind(limit)
+    .bind(offset)
+    .load(conn)
+    .context("Failed to load wallet(s) activities")
+}
+
+/// Load a page of listing, purchase, sales and offer activity for wallets,
+/// starting after the activity with the given key
+///
+/// # Errors
+/// This function fails if the underlying SQL query returns an error
+pub fn activities_page(
+    conn: &Connection,
+    address: impl ToSql<Text, Pg>,
+    event_types: impl ToSql<Nullable<Array<Text>>, Pg>,
+    after: Option<&ActivityKey>,
+    limit: i64,
+) -> Result<Vec<WalletActivity>> {
+    diesel::sql_query(keyset_page_query(ACTIVITIES_UNION))
         .bind(address)
         .bind(event_types)
-        .bind(limit)
-        .bind(offset)
+        .bind::<Nullable<Timestamp>, _>(after.map(|k| k.created_at))
+        .bind::<Nullable<diesel::sql_types::Uuid>, _>(after.map(|k| k.id))
+        .bind::<Nullable<Text>, _>(after.map(|k| k.activity_type.as_str()))
+        .bind::<Int8, _>(limit)
         .load(conn)
         .context("Failed to load wallet(s) activities")
 }
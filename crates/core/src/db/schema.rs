@@ -11,6 +11,10 @@ table! {
         first_verified_creator -> Nullable<Varchar>,
         slot -> Int8,
         write_version -> Int8,
+        value_type -> Nullable<Text>,
+        display_type -> Nullable<Text>,
+        value_numeric -> Nullable<Float8>,
+        max_value -> Nullable<Float8>,
     }
 }
 
@@ -493,6 +497,45 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
+    use crate::db::custom_types::{ListingEventLifecycle as Listingeventlifecycle, Mode, ProposalState as Proposalstate, InstructionExecutionFlags as Instructionexecutionflags, ProposalVoteType as Proposalvotetype, OptionVoteResult as Optionvoteresult, MintMaxVoteType as Mintmaxvotetype, VoteTipping as Votetipping, VoteWeightV1 as Voteweightv1, VoteRecordV2Vote as Vote_record_v2_vote, VoteThresholdType as Votethresholdtype, GovernanceAccountType as Governanceaccounttype, TransactionExecutionStatus as Transactionexecutionstatus, OfferEventLifecycle as Offereventlifecycle, SettingType as Settingtype, TokenStandard as Token_standard, PayoutOperation as Payout_operation, };
+
+    collection_buyer_buckets (collection_address, resolution, bucket_start, buyer) {
+        collection_address -> Varchar,
+        resolution -> Text,
+        bucket_start -> Timestamp,
+        buyer -> Varchar,
+        sales -> Int8,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
+    use crate::db::custom_types::{ListingEventLifecycle as Listingeventlifecycle, Mode, ProposalState as Proposalstate, InstructionExecutionFlags as Instructionexecutionflags, ProposalVoteType as Proposalvotetype, OptionVoteResult as Optionvoteresult, MintMaxVoteType as Mintmaxvotetype, VoteTipping as Votetipping, VoteWeightV1 as Voteweightv1, VoteRecordV2Vote as Vote_record_v2_vote, VoteThresholdType as Votethresholdtype, GovernanceAccountType as Governanceaccounttype, TransactionExecutionStatus as Transactionexecutionstatus, OfferEventLifecycle as Offereventlifecycle, SettingType as Settingtype, TokenStandard as Token_standard, PayoutOperation as Payout_operation, };
+
+    collection_holder_days (collection_address, day) {
+        collection_address -> Varchar,
+        day -> Date,
+        new_holders -> Int8,
+        churned_holders -> Int8,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
+    use crate::db::custom_types::{ListingEventLifecycle as Listingeventlifecycle, Mode, ProposalState as Proposalstate, InstructionExecutionFlags as Instructionexecutionflags, ProposalVoteType as Proposalvotetype, OptionVoteResult as Optionvoteresult, MintMaxVoteType as Mintmaxvotetype, VoteTipping as Votetipping, VoteWeightV1 as Voteweightv1, VoteRecordV2Vote as Vote_record_v2_vote, VoteThresholdType as Votethresholdtype, GovernanceAccountType as Governanceaccounttype, TransactionExecutionStatus as Transactionexecutionstatus, OfferEventLifecycle as Offereventlifecycle, SettingType as Settingtype, TokenStandard as Token_standard, PayoutOperation as Payout_operation, };
+
+    collection_holders (collection_address, owner_address) {
+        collection_address -> Varchar,
+        owner_address -> Varchar,
+        nft_count -> Int8,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
@@ -576,6 +619,33 @@ table! {
         _1d_marketcap_change -> Nullable<Int8>,
         _7d_marketcap_change -> Nullable<Int8>,
         _30d_marketcap_change -> Nullable<Int8>,
+        _1d_normalized_volume -> Nullable<Numeric>,
+        _prev_1d_normalized_volume -> Nullable<Numeric>,
+        _1d_unflagged_normalized_volume -> Nullable<Numeric>,
+        _prev_1d_unflagged_normalized_volume -> Nullable<Numeric>,
+        _1d_flagged_volume -> Numeric,
+        _prev_1d_flagged_volume -> Numeric,
+        _1d_flagged_sales -> Numeric,
+        _prev_1d_flagged_sales -> Numeric,
+        _7d_normalized_volume -> Nullable<Numeric>,
+        _prev_7d_normalized_volume -> Nullable<Numeric>,
+        _7d_unflagged_normalized_volume -> Nullable<Numeric>,
+        _prev_7d_unflagged_normalized_volume -> Nullable<Numeric>,
+        _7d_flagged_volume -> Numeric,
+        _prev_7d_flagged_volume -> Numeric,
+        _7d_flagged_sales -> Numeric,
+        _prev_7d_flagged_sales -> Numeric,
+        _30d_normalized_volume -> Nullable<Numeric>,
+        _prev_30d_normalized_volume -> Nullable<Numeric>,
+        _30d_unflagged_normalized_volume -> Nullable<Numeric>,
+        _prev_30d_unflagged_normalized_volume -> Nullable<Numeric>,
+        _30d_flagged_volume -> Numeric,
+        _prev_30d_flagged_volume -> Numeric,
+        _30d_flagged_sales -> Numeric,
+        _prev_30d_flagged_sales -> Numeric,
+        _1d_unflagged_sales_count -> Nullable<Numeric>,
+        _7d_unflagged_sales_count -> Nullable<Numeric>,
+        _30d_unflagged_sales_count -> Nullable<Numeric>,
     }
 }
 
@@ -602,6 +672,30 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
+    use crate::db::custom_types::{ListingEventLifecycle as Listingeventlifecycle, Mode, ProposalState as Proposalstate, InstructionExecutionFlags as Instructionexecutionflags, ProposalVoteType as Proposalvotetype, OptionVoteResult as Optionvoteresult, MintMaxVoteType as Mintmaxvotetype, VoteTipping as Votetipping, VoteWeightV1 as Voteweightv1, VoteRecordV2Vote as Vote_record_v2_vote, VoteThresholdType as Votethresholdtype, GovernanceAccountType as Governanceaccounttype, TransactionExecutionStatus as Transactionexecutionstatus, OfferEventLifecycle as Offereventlifecycle, SettingType as Settingtype, TokenStandard as Token_standard, PayoutOperation as Payout_operation, };
+
+    currencies (mint) {
+        mint -> Varchar,
+        symbol -> Text,
+        decimals -> Int2,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
+    use crate::db::custom_types::{ListingEventLifecycle as Listingeventlifecycle, Mode, ProposalState as Proposalstate, InstructionExecutionFlags as Instructionexecutionflags, ProposalVoteType as Proposalvotetype, OptionVoteResult as Optionvoteresult, MintMaxVoteType as Mintmaxvotetype, VoteTipping as Votetipping, VoteWeightV1 as Voteweightv1, VoteRecordV2Vote as Vote_record_v2_vote, VoteThresholdType as Votethresholdtype, GovernanceAccountType as Governanceaccounttype, TransactionExecutionStatus as Transactionexecutionstatus, OfferEventLifecycle as Offereventlifecycle, SettingType as Settingtype, TokenStandard as Token_standard, PayoutOperation as Payout_operation, };
+
+    currency_rates (mint, effective_at) {
+        mint -> Varchar,
+        effective_at -> Timestamp,
+        sol_price -> Numeric,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
@@ -616,6 +710,23 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
+    use crate::db::custom_types::{ListingEventLifecycle as Listingeventlifecycle, Mode, ProposalState as Proposalstate, InstructionExecutionFlags as Instructionexecutionflags, ProposalVoteType as Proposalvotetype, OptionVoteResult as Optionvoteresult, MintMaxVoteType as Mintmaxvotetype, VoteTipping as Votetipping, VoteWeightV1 as Voteweightv1, VoteRecordV2Vote as Vote_record_v2_vote, VoteThresholdType as Votethresholdtype, GovernanceAccountType as Governanceaccounttype, TransactionExecutionStatus as Transactionexecutionstatus, OfferEventLifecycle as Offereventlifecycle, SettingType as Settingtype, TokenStandard as Token_standard, PayoutOperation as Payout_operation, };
+
+    denylist_audit_log (id) {
+        id -> Uuid,
+        list -> Text,
+        address -> Varchar,
+        action -> Text,
+        hard_ban -> Nullable<Bool>,
+        reason -> Text,
+        actor -> Text,
+        created_at -> Timestamp,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
@@ -728,6 +839,7 @@ table! {
         token_size -> Int8,
         created_at -> Timestamp,
         slot -> Int8,
+        creator_accounts -> Array<Varchar>,
     }
 }
 
@@ -765,6 +877,8 @@ table! {
         id -> Uuid,
         slot -> Int8,
         write_version -> Int8,
+        source_uri -> Nullable<Text>,
+        width -> Nullable<Int4>,
     }
 }
 
@@ -927,6 +1041,20 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
+    use crate::db::custom_types::{ListingEventLifecycle as Listingeventlifecycle, Mode, ProposalState as Proposalstate, InstructionExecutionFlags as Instructionexecutionflags, ProposalVoteType as Proposalvotetype, OptionVoteResult as Optionvoteresult, MintMaxVoteType as Mintmaxvotetype, VoteTipping as Votetipping, VoteWeightV1 as Voteweightv1, VoteRecordV2Vote as Vote_record_v2_vote, VoteThresholdType as Votethresholdtype, GovernanceAccountType as Governanceaccounttype, TransactionExecutionStatus as Transactionexecutionstatus, OfferEventLifecycle as Offereventlifecycle, SettingType as Settingtype, TokenStandard as Token_standard, PayoutOperation as Payout_operation, };
+
+    image_derivative_failures (metadata_address) {
+        metadata_address -> Varchar,
+        source_uri -> Text,
+        attempts -> Int4,
+        error -> Text,
+        updated_at -> Timestamp,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
@@ -1072,6 +1200,8 @@ table! {
         write_version -> Nullable<Int8>,
         marketplace_program -> Varchar,
         expiry -> Nullable<Timestamp>,
+        currency_mint -> Nullable<Varchar>,
+        currency_decimals -> Nullable<Int2>,
     }
 }
 
@@ -1169,6 +1299,19 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
+    use crate::db::custom_types::{ListingEventLifecycle as Listingeventlifecycle, Mode, ProposalState as Proposalstate, InstructionExecutionFlags as Instructionexecutionflags, ProposalVoteType as Proposalvotetype, OptionVoteResult as Optionvoteresult, MintMaxVoteType as Mintmaxvotetype, VoteTipping as Votetipping, VoteWeightV1 as Voteweightv1, VoteRecordV2Vote as Vote_record_v2_vote, VoteThresholdType as Votethresholdtype, GovernanceAccountType as Governanceaccounttype, TransactionExecutionStatus as Transactionexecutionstatus, OfferEventLifecycle as Offereventlifecycle, SettingType as Settingtype, TokenStandard as Token_standard, PayoutOperation as Payout_operation, };
+
+    metadata_burns (mint_address) {
+        mint_address -> Varchar,
+        slot -> Int8,
+        block_time -> Nullable<Timestamp>,
+        recorded_at -> Timestamp,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
@@ -1209,6 +1352,37 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
+    use crate::db::custom_types::{ListingEventLifecycle as Listingeventlifecycle, Mode, ProposalState as Proposalstate, InstructionExecutionFlags as Instructionexecutionflags, ProposalVoteType as Proposalvotetype, OptionVoteResult as Optionvoteresult, MintMaxVoteType as Mintmaxvotetype, VoteTipping as Votetipping, VoteWeightV1 as Voteweightv1, VoteRecordV2Vote as Vote_record_v2_vote, VoteThresholdType as Votethresholdtype, GovernanceAccountType as Governanceaccounttype, TransactionExecutionStatus as Transactionexecutionstatus, OfferEventLifecycle as Offereventlifecycle, SettingType as Settingtype, TokenStandard as Token_standard, PayoutOperation as Payout_operation, };
+
+    metadata_json_issues (id) {
+        id -> Uuid,
+        metadata_address -> Varchar,
+        code -> Text,
+        path -> Text,
+        message -> Text,
+        slot -> Int8,
+        write_version -> Int8,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
+    use crate::db::custom_types::{ListingEventLifecycle as Listingeventlifecycle, Mode, ProposalState as Proposalstate, InstructionExecutionFlags as Instructionexecutionflags, ProposalVoteType as Proposalvotetype, OptionVoteResult as Optionvoteresult, MintMaxVoteType as Mintmaxvotetype, VoteTipping as Votetipping, VoteWeightV1 as Voteweightv1, VoteRecordV2Vote as Vote_record_v2_vote, VoteThresholdType as Votethresholdtype, GovernanceAccountType as Governanceaccounttype, TransactionExecutionStatus as Transactionexecutionstatus, OfferEventLifecycle as Offereventlifecycle, SettingType as Settingtype, TokenStandard as Token_standard, PayoutOperation as Payout_operation, };
+
+    metadata_json_refetches (metadata_address) {
+        metadata_address -> Varchar,
+        interval_seconds -> Int4,
+        next_fetch_at -> Timestamp,
+        pending -> Bool,
+        last_fetched_at -> Nullable<Timestamp>,
+        last_changed_at -> Nullable<Timestamp>,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
@@ -1229,6 +1403,26 @@ table! {
         slot -> Int8,
         write_version -> Int8,
         name -> Nullable<Text>,
+        content_hash -> Nullable<Bytea>,
+        content_source -> Nullable<Text>,
+        cid_verified -> Nullable<Bool>,
+        content_changed_at -> Nullable<Timestamp>,
+        fetch_strategy -> Nullable<Text>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
+    use crate::db::custom_types::{ListingEventLifecycle as Listingeventlifecycle, Mode, ProposalState as Proposalstate, InstructionExecutionFlags as Instructionexecutionflags, ProposalVoteType as Proposalvotetype, OptionVoteResult as Optionvoteresult, MintMaxVoteType as Mintmaxvotetype, VoteTipping as Votetipping, VoteWeightV1 as Voteweightv1, VoteRecordV2Vote as Vote_record_v2_vote, VoteThresholdType as Votethresholdtype, GovernanceAccountType as Governanceaccounttype, TransactionExecutionStatus as Transactionexecutionstatus, OfferEventLifecycle as Offereventlifecycle, SettingType as Settingtype, TokenStandard as Token_standard, PayoutOperation as Payout_operation, };
+
+    metadata_owner_history (mint_address, slot) {
+        mint_address -> Varchar,
+        slot -> Int8,
+        owner_address -> Varchar,
+        token_account_address -> Varchar,
+        recorded_at -> Timestamp,
+        block_time -> Nullable<Timestamp>,
     }
 }
 
@@ -1266,6 +1460,21 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
+    use crate::db::custom_types::{ListingEventLifecycle as Listingeventlifecycle, Mode, ProposalState as Proposalstate, InstructionExecutionFlags as Instructionexecutionflags, ProposalVoteType as Proposalvotetype, OptionVoteResult as Optionvoteresult, MintMaxVoteType as Mintmaxvotetype, VoteTipping as Votetipping, VoteWeightV1 as Voteweightv1, VoteRecordV2Vote as Vote_record_v2_vote, VoteThresholdType as Votethresholdtype, GovernanceAccountType as Governanceaccounttype, TransactionExecutionStatus as Transactionexecutionstatus, OfferEventLifecycle as Offereventlifecycle, SettingType as Settingtype, TokenStandard as Token_standard, PayoutOperation as Payout_operation, };
+
+    nft_rarities (metadata_address, method) {
+        metadata_address -> Varchar,
+        method -> Text,
+        collection_address -> Varchar,
+        score -> Float8,
+        rank -> Int4,
+        updated_at -> Timestamp,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
@@ -1303,6 +1512,39 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
+    use crate::db::custom_types::{ListingEventLifecycle as Listingeventlifecycle, Mode, ProposalState as Proposalstate, InstructionExecutionFlags as Instructionexecutionflags, ProposalVoteType as Proposalvotetype, OptionVoteResult as Optionvoteresult, MintMaxVoteType as Mintmaxvotetype, VoteTipping as Votetipping, VoteWeightV1 as Voteweightv1, VoteRecordV2Vote as Vote_record_v2_vote, VoteThresholdType as Votethresholdtype, GovernanceAccountType as Governanceaccounttype, TransactionExecutionStatus as Transactionexecutionstatus, OfferEventLifecycle as Offereventlifecycle, SettingType as Settingtype, TokenStandard as Token_standard, PayoutOperation as Payout_operation, };
+
+    price_candles (collection_address, resolution, bucket_start, auction_house) {
+        collection_address -> Varchar,
+        auction_house -> Varchar,
+        resolution -> Text,
+        bucket_start -> Timestamp,
+        open -> Nullable<Int8>,
+        high -> Nullable<Int8>,
+        low -> Nullable<Int8>,
+        close -> Nullable<Int8>,
+        volume -> Numeric,
+        sales -> Int8,
+        opened_at -> Nullable<Timestamp>,
+        closed_at -> Nullable<Timestamp>,
+        currency_mint -> Nullable<Varchar>,
+        normalized_open -> Nullable<Numeric>,
+        normalized_high -> Nullable<Numeric>,
+        normalized_low -> Nullable<Numeric>,
+        normalized_close -> Nullable<Numeric>,
+        normalized_volume -> Nullable<Numeric>,
+        flagged_volume -> Numeric,
+        flagged_sales -> Int8,
+        flagged_normalized_volume -> Nullable<Numeric>,
+        listings -> Int8,
+        listing_low -> Nullable<Int8>,
+        normalized_listing_low -> Nullable<Numeric>,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
@@ -1600,6 +1842,22 @@ table! {
         slot -> Int8,
         write_version -> Nullable<Int8>,
         marketplace_program -> Varchar,
+        currency_mint -> Nullable<Varchar>,
+        currency_decimals -> Nullable<Int2>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
+    use crate::db::custom_types::{ListingEventLifecycle as Listingeventlifecycle, Mode, ProposalState as Proposalstate, InstructionExecutionFlags as Instructionexecutionflags, ProposalVoteType as Proposalvotetype, OptionVoteResult as Optionvoteresult, MintMaxVoteType as Mintmaxvotetype, VoteTipping as Votetipping, VoteWeightV1 as Voteweightv1, VoteRecordV2Vote as Vote_record_v2_vote, VoteThresholdType as Votethresholdtype, GovernanceAccountType as Governanceaccounttype, TransactionExecutionStatus as Transactionexecutionstatus, OfferEventLifecycle as Offereventlifecycle, SettingType as Settingtype, TokenStandard as Token_standard, PayoutOperation as Payout_operation, };
+
+    rarity_dirty_collections (collection_address) {
+        collection_address -> Varchar,
+        marked_at -> Timestamp,
+        failed_attempts -> Int4,
+        retry_at -> Nullable<Timestamp>,
+        last_error -> Nullable<Text>,
     }
 }
 
@@ -1760,6 +2018,26 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
+    use crate::db::custom_types::{ListingEventLifecycle as Listingeventlifecycle, Mode, ProposalState as Proposalstate, InstructionExecutionFlags as Instructionexecutionflags, ProposalVoteType as Proposalvotetype, OptionVoteResult as Optionvoteresult, MintMaxVoteType as Mintmaxvotetype, VoteTipping as Votetipping, VoteWeightV1 as Voteweightv1, VoteRecordV2Vote as Vote_record_v2_vote, VoteThresholdType as Votethresholdtype, GovernanceAccountType as Governanceaccounttype, TransactionExecutionStatus as Transactionexecutionstatus, OfferEventLifecycle as Offereventlifecycle, SettingType as Settingtype, TokenStandard as Token_standard, PayoutOperation as Payout_operation, };
+
+    sale_royalties (purchase_id, creator_address) {
+        purchase_id -> Uuid,
+        creator_address -> Varchar,
+        metadata_address -> Varchar,
+        auction_house -> Varchar,
+        marketplace_program -> Varchar,
+        currency_mint -> Nullable<Varchar>,
+        sold_at -> Timestamp,
+        share -> Int4,
+        expected -> Int8,
+        paid -> Nullable<Int8>,
+        checked_at -> Nullable<Timestamp>,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
@@ -1843,6 +2121,19 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
+    use crate::db::custom_types::{ListingEventLifecycle as Listingeventlifecycle, Mode, ProposalState as Proposalstate, InstructionExecutionFlags as Instructionexecutionflags, ProposalVoteType as Proposalvotetype, OptionVoteResult as Optionvoteresult, MintMaxVoteType as Mintmaxvotetype, VoteTipping as Votetipping, VoteWeightV1 as Voteweightv1, VoteRecordV2Vote as Vote_record_v2_vote, VoteThresholdType as Votethresholdtype, GovernanceAccountType as Governanceaccounttype, TransactionExecutionStatus as Transactionexecutionstatus, OfferEventLifecycle as Offereventlifecycle, SettingType as Settingtype, TokenStandard as Token_standard, PayoutOperation as Payout_operation, };
+
+    store_config_json_issues (id) {
+        id -> Uuid,
+        config_address -> Varchar,
+        path -> Text,
+        message -> Text,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
@@ -1854,9 +2145,16 @@ table! {
         description -> Text,
         logo_url -> Text,
         banner_url -> Text,
-        subdomain -> Text,
-        owner_address -> Varchar,
+        subdomain -> Nullable<Text>,
+        owner_address -> Nullable<Varchar>,
         store_address -> Nullable<Varchar>,
+        schema_version -> Int4,
+        primary_color -> Nullable<Text>,
+        background_color -> Nullable<Text>,
+        text_color -> Nullable<Text>,
+        title_font -> Nullable<Text>,
+        text_font -> Nullable<Text>,
+        custom_domain -> Nullable<Text>,
     }
 }
 
@@ -2112,6 +2410,18 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
+    use crate::db::custom_types::{ListingEventLifecycle as Listingeventlifecycle, Mode, ProposalState as Proposalstate, InstructionExecutionFlags as Instructionexecutionflags, ProposalVoteType as Proposalvotetype, OptionVoteResult as Optionvoteresult, MintMaxVoteType as Mintmaxvotetype, VoteTipping as Votetipping, VoteWeightV1 as Voteweightv1, VoteRecordV2Vote as Vote_record_v2_vote, VoteThresholdType as Votethresholdtype, GovernanceAccountType as Governanceaccounttype, TransactionExecutionStatus as Transactionexecutionstatus, OfferEventLifecycle as Offereventlifecycle, SettingType as Settingtype, TokenStandard as Token_standard, PayoutOperation as Payout_operation, };
+
+    wash_trades (purchase_id) {
+        purchase_id -> Uuid,
+        reasons -> Array<Text>,
+        detected_at -> Timestamp,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::{TsVector as Tsvector, TsQuery as Tsquery};
@@ -2219,12 +2529,18 @@ allow_tables_to_appear_in_same_query!(
     cardinal_token_manager_invalidators,
     cardinal_token_managers,
     cardinal_use_invalidators,
+    collection_buyer_buckets,
+    collection_holder_days,
+    collection_holders,
     collection_mint_attributes,
     collection_mints,
     collection_stats,
     collection_trends,
     collections,
+    currencies,
+    currency_rates,
     current_metadata_owners,
+    denylist_audit_log,
     deposit_instructions,
     dolphin_stats,
     editions,
@@ -2241,6 +2557,7 @@ allow_tables_to_appear_in_same_query!(
     governances,
     governors,
     graph_connections,
+    image_derivative_failures,
     ins_buffer_bundle_ins_keys,
     ins_buffer_bundle_instructions,
     ins_buffer_bundles,
@@ -2258,14 +2575,20 @@ allow_tables_to_appear_in_same_query!(
     me_collection_stats,
     me_collections,
     me_metadata_collections,
+    metadata_burns,
     metadata_collection_keys,
     metadata_collections,
     metadata_creators,
+    metadata_json_issues,
+    metadata_json_refetches,
     metadata_jsons,
+    metadata_owner_history,
     metadatas,
     mint_events,
+    nft_rarities,
     offer_events,
     offers,
+    price_candles,
     proposal_account_metas,
     proposal_instructions,
     proposal_metas,
@@ -2281,6 +2604,7 @@ allow_tables_to_appear_in_same_query!(
     purchase_events,
     purchase_receipts,
     purchases,
+    rarity_dirty_collections,
     realm_config_accounts,
     realm_configs,
     realms,
@@ -2289,11 +2613,13 @@ allow_tables_to_appear_in_same_query!(
     rewards_listings,
     rewards_offers,
     rewards_purchase_tickets,
+    sale_royalties,
     sell_instructions,
     signatory_records,
     smart_wallet_owners,
     smart_wallets,
     store_auction_houses,
+    store_config_json_issues,
     store_config_jsons,
     store_configs,
     store_creators,
@@ -2312,6 +2638,7 @@ allow_tables_to_appear_in_same_query!(
     votes,
     wallet_total_rewards,
     wallet_totals,
+    wash_trades,
     whitelisted_creators,
     withdraw_from_fee_instructions,
     withdraw_from_treasury_instructions,
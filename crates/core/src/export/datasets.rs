@@ -0,0 +1,525 @@
+use std::io::Write;
+
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+
+use super::{col, from_str, from_str_opt, Column, ColumnType, Record, Sink, Value, BATCH_SIZE};
+use crate::{
+    db::{
+        models::{
+            CollectionHolder, HolderSnapshot, NftActivity, PriceCandle, PricePoint, WalletActivity,
+        },
+        queries::{
+            self, activities::ActivityKey, charts::CandleResolution, owner_history::PointInTime,
+        },
+        Connection,
+    },
+    error::prelude::*,
+};
+
+/// Parameters shared by the auction house price charts
+#[derive(Debug, Clone, clap::Args, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartArgs {
+    /// Auction houses to include sales or listings from
+    #[clap(long = "auction-house", required = true)]
+    pub auction_houses: Vec<String>,
+
+    /// Only include NFTs by these creators
+    #[clap(long = "creator")]
+    #[serde(default)]
+    pub creators: Vec<String>,
+
+    /// Start of the time range, e.g. 2022-11-01T00:00:00
+    #[clap(long)]
+    #[serde(deserialize_with = "from_str")]
+    pub start_date: NaiveDateTime,
+
+    /// End of the time range, e.g. 2022-12-01T00:00:00
+    #[clap(long)]
+    #[serde(deserialize_with = "from_str")]
+    pub end_date: NaiveDateTime,
+}
+
+/// A query whose results can be exported
+#[derive(Debug, Clone, clap::Subcommand, Deserialize)]
+#[serde(tag = "dataset", rename_all = "camelCase")]
+pub enum Dataset {
+    /// Listings, sales, and offers on the given auction houses
+    #[serde(rename_all = "camelCase")]
+    Activities {
+        /// Auction houses to include activity from
+        #[clap(long = "auction-house", required = true)]
+        auction_houses: Vec<String>,
+
+        /// Only include NFTs by these creators
+        #[clap(long = "creator")]
+        #[serde(default)]
+        creators: Vec<String>,
+    },
+    /// Listings, sales, and offers for the NFTs of a verified collection
+    #[serde(rename_all = "camelCase")]
+    CollectionActivities {
+        /// Mint address of the collection NFT
+        collection: String,
+
+        /// Only include these activity types, e.g. listing or purchase
+        #[clap(long = "event-type")]
+        #[serde(default)]
+        event_types: Vec<String>,
+    },
+    /// Listings, sales, and offers involving a wallet
+    #[serde(rename_all = "camelCase")]
+    WalletActivities {
+        /// Address of the wallet
+        wallet: String,
+
+        /// Only include these activity types, e.g. listing or purchase
+        #[clap(long = "event-type")]
+        #[serde(default)]
+        event_types: Vec<String>,
+    },
+    /// Daily floor prices of listings
+    FloorPrices(ChartArgs),
+    /// Daily average sale prices
+    AveragePrices(ChartArgs),
+    /// Daily total sale volume
+    #[serde(rename_all = "camelCase")]
+    TotalVolume {
+        /// Auction houses, creators, and time range of the chart
+        #[clap(flatten)]
+        #[serde(flatten)]
+        chart: ChartArgs,
+
+        /// Leave out sales flagged as suspected wash trades
+        #[clap(long)]
+        #[serde(default)]
+        exclude_flagged: bool,
+    },
+    /// Open, high, low, and close sale prices of a verified collection
+    #[serde(rename_all = "camelCase")]
+    CollectionCandles {
+        /// Mint address of the collection NFT
+        collection: String,
+
+        /// Width of each candle, one of 1m, 1h, or 1d
+        #[clap(long)]
+        #[serde(deserialize_with = "from_str")]
+        resolution: CandleResolution,
+
+        /// Start of the time range, e.g. 2022-11-01T00:00:00
+        #[clap(long)]
+        #[serde(deserialize_with = "from_str")]
+        from: NaiveDateTime,
+
+        /// End of the time range, exclusive
+        #[clap(long)]
+        #[serde(deserialize_with = "from_str")]
+        to: NaiveDateTime,
+
+        /// Only include sales from these auction houses
+        #[clap(long = "auction-house")]
+        #[serde(default)]
+        auction_houses: Vec<String>,
+
+        /// Leave out the volume of sales flagged as suspected wash trades
+        #[clap(long)]
+        #[serde(default)]
+        exclude_flagged: bool,
+    },
+    /// The number of NFTs of a verified collection held by each wallet, now
+    /// or at a point in time
+    #[serde(rename_all = "camelCase")]
+    CollectionHolders {
+        /// Mint address of the collection NFT
+        collection: String,
+
+        /// Look up holders as of the end of this slot
+        #[clap(long, conflicts_with = "timestamp")]
+        #[serde(default)]
+        slot: Option<i64>,
+
+        /// Look up holders as of this time, e.g. 2022-11-01T00:00:00
+        #[clap(long)]
+        #[serde(default, deserialize_with = "from_str_opt")]
+        timestamp: Option<NaiveDateTime>,
+    },
+}
+
+fn non_empty(values: &[String]) -> Option<&[String]> {
+    (!values.is_empty()).then(|| values)
+}
+
+impl Dataset {
+    /// The columns of the dataset's rows
+    #[must_use]
+    pub fn columns(&self) -> &'static [Column] {
+        match self {
+            Self::Activities { .. } | Self::CollectionActivities { .. } => NftActivity::COLUMNS,
+            Self::WalletActivities { .. } => WalletActivity::COLUMNS,
+            Self::FloorPrices(_) | Self::AveragePrices(_) | Self::TotalVolume { .. } => {
+                PricePoint::COLUMNS
+            },
+            Self::CollectionCandles { .. } => PriceCandle::COLUMNS,
+            Self::CollectionHolders { .. } => HolderSnapshot::COLUMNS,
+        }
+    }
+
+    /// Run the dataset's query, writing its rows to `sink`, and return the
+    /// number of rows written
+    ///
+    /// # Errors
+    /// This function fails if the query fails or the rows cannot be written
+    #[allow(clippy::too_many_lines)]
+    pub fn run<W: Write>(&self, conn: &Connection, sink: &mut Sink<W>) -> Result<u64> {
+        match self {
+            Self::Activities {
+                auction_houses,
+                creators,
+            } => write_paged(sink, |after, limit| {
+                queries::activities::list_page(
+                    conn,
+                    auction_houses,
+                    non_empty(creators),
+                    after,
+                    limit,
+                )
+            }),
+            Self::CollectionActivities {
+                collection,
+                event_types,
+            } => write_paged(sink, |after, limit| {
+                queries::collections::collection_activities_page(
+                    conn,
+                    collection,
+                    non_empty(event_types),
+                    after,
+                    limit,
+                )
+            }),
+            Self::WalletActivities {
+                wallet,
+                event_types,
+            } => write_paged(sink, |after, limit| {
+                queries::wallet::activities_page(conn, wallet, non_empty(event_types), after, limit)
+            }),
+            Self::FloorPrices(c) => write_all(
+                sink,
+                queries::charts::floor_prices(
+                    conn,
+                    &c.auction_houses,
+                    non_empty(&c.creators),
+                    c.start_date,
+                    c.end_date,
+                )?,
+            ),
+            Self::AveragePrices(c) => write_all(
+                sink,
+                queries::charts::average_prices(
+                    conn,
+                    non_empty(&c.creators),
+                    &c.auction_houses,
+                    c.start_date,
+                    c.end_date,
+                )?,
+            ),
+            Self::TotalVolume {
+                chart: c,
+                exclude_flagged,
+            } => write_all(
+                sink,
+                queries::charts::total_volume_prices(
+                    conn,
+                    &c.auction_houses,
+                    non_empty(&c.creators),
+                    c.start_date,
+                    c.end_date,
+                    *exclude_flagged,
+                )?,
+            ),
+            Self::CollectionCandles {
+                collection,
+                resolution,
+                from,
+                to,
+                auction_houses,
+                exclude_flagged,
+            } => write_all(
+                sink,
+                queries::charts::collection_candles(
+                    conn,
+                    collection,
+                    *resolution,
+                    non_empty(auction_houses),
+                    *from,
+                    *to,
+                    *exclude_flagged,
+                )?,
+            ),
+            Self::CollectionHolders {
+                collection,
+                slot,
+                timestamp,
+            } => {
+                let at = match (slot, timestamp) {
+                    (Some(_), Some(_)) => bail!("Only one of slot or timestamp may be given"),
+                    (Some(s), None) => Some(PointInTime::Slot(*s)),
+                    (None, Some(t)) => Some(PointInTime::Time(*t)),
+                    (None, None) => None,
+                };
+
+                match at {
+                    Some(at) => write_all(
+                        sink,
+                        queries::owner_history::collection_holders_at(conn, collection, at)?,
+                    ),
+                    None => write_all(sink, queries::holders::holders(conn, collection)?),
+                }
+            },
+        }
+    }
+}
+
+/// Write rows to `sink` in batches of [`BATCH_SIZE`]
+fn write_all<R: Record, W: Write>(sink: &mut Sink<W>, rows: Vec<R>) -> Result<u64> {
+    let mut batch = Vec::with_capacity(rows.len().min(BATCH_SIZE));
+    let mut total = 0;
+
+    for row in rows {
+        batch.push(row.into_values());
+
+        if batch.len() == BATCH_SIZE {
+            sink.write(&batch)?;
+            total += batch.len();
+            batch.clear();
+        }
+    }
+
+    if !batch.is_empty() {
+        sink.write(&batch)?;
+        total += batch.len();
+    }
+
+    Ok(total.try_into()?)
+}
+
+/// A row of a keyset-paginated query
+trait Keyed {
+    /// The sort key of the row, after which the next page starts
+    fn key(&self) -> ActivityKey;
+}
+
+/// Write every page of a keyset-paginated query to `sink`, passing the key of
+/// the last row written and the page size to `page` until it returns a
+/// partial page
+fn write_paged<R: Record + Keyed, W: Write>(
+    sink: &mut Sink<W>,
+    mut page: impl FnMut(Option<&ActivityKey>, i64) -> Result<Vec<R>>,
+) -> Result<u64> {
+    let limit = i64::try_from(BATCH_SIZE)?;
+    let mut after = None;
+    let mut total = 0;
+
+    loop {
+        let rows = page(after.as_ref(), limit)?;
+        let done = rows.len() < BATCH_SIZE;
+
+        after = rows.last().map(Keyed::key);
+        total += write_all(sink, rows)?;
+
+        if done {
+            break Ok(total);
+        }
+    }
+}
+
+const ACTIVITY_COLUMNS: &[Column] = &[
+    col("id", ColumnType::Text),
+    col("activity_type", ColumnType::Text),
+    col("metadata", ColumnType::Text),
+    col("auction_house", ColumnType::Text),
+    col("marketplace_program", ColumnType::Text),
+    col("price", ColumnType::Int),
+    col("created_at", ColumnType::Timestamp),
+    col("wallets", ColumnType::Text),
+];
+
+impl Keyed for NftActivity {
+    fn key(&self) -> ActivityKey {
+        ActivityKey {
+            created_at: self.created_at,
+            id: self.id,
+            activity_type: self.activity_type.clone(),
+        }
+    }
+}
+
+impl Record for NftActivity {
+    const COLUMNS: &'static [Column] = ACTIVITY_COLUMNS;
+
+    fn into_values(self) -> Vec<Value> {
+        vec![
+            self.id.to_string().into(),
+            self.activity_type.into(),
+            self.metadata.into(),
+            self.auction_house.into(),
+            self.marketplace_program.into(),
+            self.price.into(),
+            self.created_at.into(),
+            self.wallets.join(",").into(),
+        ]
+    }
+}
+
+impl Keyed for WalletActivity {
+    fn key(&self) -> ActivityKey {
+        ActivityKey {
+            created_at: self.created_at,
+            id: self.id,
+            activity_type: self.activity_type.clone(),
+        }
+    }
+}
+
+impl Record for WalletActivity {
+    const COLUMNS: &'static [Column] = ACTIVITY_COLUMNS;
+
+    fn into_values(self) -> Vec<Value> {
+        vec![
+            self.id.to_string().into(),
+            self.activity_type.into(),
+            self.metadata.into(),
+            self.auction_house.into(),
+            self.marketplace_program.into(),
+            self.price.into(),
+            self.created_at.into(),
+            self.wallets.join(",").into(),
+        ]
+    }
+}
+
+impl Record for PricePoint {
+    const COLUMNS: &'static [Column] = &[
+        col("date", ColumnType::Timestamp),
+        col("price", ColumnType::Int),
+        col("normalized_price", ColumnType::Int),
+    ];
+
+    fn into_values(self) -> Vec<Value> {
+        vec![
+            self.date.into(),
+            self.price.into(),
+            self.normalized_price.into(),
+        ]
+    }
+}
+
+impl Record for PriceCandle {
+    const COLUMNS: &'static [Column] = &[
+        col("bucket_start", ColumnType::Timestamp),
+        col("open", ColumnType::Int),
+        col("high", ColumnType::Int),
+        col("low", ColumnType::Int),
+        col("close", ColumnType::Int),
+        col("volume", ColumnType::Text),
+        col("currency_mint", ColumnType::Text),
+        col("normalized_open", ColumnType::Int),
+        col("normalized_high", ColumnType::Int),
+        col("normalized_low", ColumnType::Int),
+        col("normalized_close", ColumnType::Int),
+        col("normalized_volume", ColumnType::Text),
+        col("sales", ColumnType::Int),
+        col("listings", ColumnType::Int),
+        col("listing_low", ColumnType::Int),
+        col("normalized_listing_low", ColumnType::Int),
+    ];
+
+    fn into_values(self) -> Vec<Value> {
+        vec![
+            self.bucket_start.into(),
+            self.open.into(),
+            self.high.into(),
+            self.low.into(),
+            self.close.into(),
+            self.volume.map(|v| v.to_string()).into(),
+            self.currency_mint.into(),
+            self.normalized_open.into(),
+            self.normalized_high.into(),
+            self.normalized_low.into(),
+            self.normalized_close.into(),
+            self.normalized_volume.map(|v| v.to_string()).into(),
+            self.sales.into(),
+            self.listings.into(),
+            self.listing_low.into(),
+            self.normalized_listing_low.into(),
+        ]
+    }
+}
+
+const HOLDER_COLUMNS: &[Column] = &[
+    col("owner_address", ColumnType::Text),
+    col("nft_count", ColumnType::Int),
+];
+
+impl Record for HolderSnapshot {
+    const COLUMNS: &'static [Column] = HOLDER_COLUMNS;
+
+    fn into_values(self) -> Vec<Value> {
+        vec![self.owner_address.into(), self.nft_count.into()]
+    }
+}
+
+impl<'a> Record for CollectionHolder<'a> {
+    const COLUMNS: &'static [Column] = HOLDER_COLUMNS;
+
+    fn into_values(self) -> Vec<Value> {
+        vec![
+            self.owner_address.into_owned().into(),
+            self.nft_count.into(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Dataset;
+
+    #[test]
+    fn test_deserialize_dataset() {
+        let dataset: Dataset = serde_json::from_str(
+            r#"{
+                "dataset": "collectionCandles",
+                "collection": "abc",
+                "resolution": "1h",
+                "from": "2022-11-01T00:00:00",
+                "to": "2022-11-02T00:00:00"
+            }"#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            dataset,
+            Dataset::CollectionCandles {
+                exclude_flagged: false,
+                ref auction_houses,
+                ..
+            } if auction_houses.is_empty()
+        ));
+
+        let dataset: Dataset = serde_json::from_str(
+            r#"{
+                "dataset": "totalVolume",
+                "auctionHouses": ["ah"],
+                "startDate": "2022-11-01T00:00:00",
+                "endDate": "2022-11-02T00:00:00",
+                "excludeFlagged": true
+            }"#,
+        )
+        .unwrap();
+
+        assert!(matches!(dataset, Dataset::TotalVolume {
+            exclude_flagged: true,
+            ..
+        }));
+    }
+}
@@ -0,0 +1,185 @@
+//! Streaming CSV and Parquet export of analytics query results.
+//!
+//! A [`Dataset`] names a query and its parameters.  [`export`] runs it and
+//! writes the rows to any [`Write`] in batches of [`BATCH_SIZE`], paging
+//! through the database where the query supports it, so large exports are
+//! never held in memory all at once.
+
+mod datasets;
+mod sink;
+
+use std::{io::Write, str::FromStr};
+
+use chrono::NaiveDateTime;
+pub use datasets::Dataset;
+use serde::{Deserialize, Deserializer};
+pub use sink::Sink;
+
+use crate::{db::Connection, error::prelude::*};
+
+/// The number of rows written per CSV flush or Parquet row group, and the
+/// page size of paginated queries
+pub const BATCH_SIZE: usize = 1000;
+
+/// A file format to export to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::Display, Deserialize)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// Comma-separated values with a header row
+    Csv,
+    /// Apache Parquet with Snappy compression, one row group per batch
+    Parquet,
+}
+
+impl Format {
+    /// The MIME type of the format
+    #[must_use]
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv",
+            Self::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+
+    /// The file extension of the format
+    #[must_use]
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Parquet => "parquet",
+        }
+    }
+}
+
+/// The type of an exported column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    /// A boolean
+    Bool,
+    /// A 64-bit signed integer
+    Int,
+    /// A 64-bit float
+    Float,
+    /// A UTF-8 string.  Decimal amounts are exported as strings to keep them
+    /// exact.
+    Text,
+    /// A UTC timestamp with microsecond precision
+    Timestamp,
+}
+
+/// A named, nullable column of an export
+#[derive(Debug, Clone, Copy)]
+pub struct Column {
+    /// The column name
+    pub name: &'static str,
+    /// The column type
+    pub ty: ColumnType,
+}
+
+/// Shorthand for declaring a [`Column`]
+const fn col(name: &'static str, ty: ColumnType) -> Column {
+    Column { name, ty }
+}
+
+/// A single exported value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// A missing value
+    Null,
+    /// A [`ColumnType::Bool`] value
+    Bool(bool),
+    /// A [`ColumnType::Int`] value
+    Int(i64),
+    /// A [`ColumnType::Float`] value
+    Float(f64),
+    /// A [`ColumnType::Text`] value
+    Text(String),
+    /// A [`ColumnType::Timestamp`] value
+    Timestamp(NaiveDateTime),
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<NaiveDateTime> for Value {
+    fn from(value: NaiveDateTime) -> Self {
+        Self::Timestamp(value)
+    }
+}
+
+/// A query result row which can be exported
+pub trait Record {
+    /// The columns of the row, in order
+    const COLUMNS: &'static [Column];
+
+    /// Convert the row into one value per column
+    fn into_values(self) -> Vec<Value>;
+}
+
+/// Run a dataset's query and write its rows to `out` in the given format,
+/// returning the number of rows written
+///
+/// # Errors
+/// This function fails if the query fails or the output cannot be written
+pub fn export<W: Write>(
+    conn: &Connection,
+    dataset: &Dataset,
+    format: Format,
+    out: W,
+) -> Result<u64> {
+    let mut sink = Sink::new(format, dataset.columns(), out)?;
+    let rows = dataset.run(conn, &mut sink)?;
+
+    sink.finish()?;
+
+    Ok(rows)
+}
+
+/// Deserialize a value from a string using its [`FromStr`] implementation
+fn from_str<'de, D: Deserializer<'de>, T: FromStr>(de: D) -> Result<T, D::Error>
+where
+    T::Err: std::fmt::Display,
+{
+    String::deserialize(de)?
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
+
+/// Deserialize an optional value from a string using its [`FromStr`]
+/// implementation
+fn from_str_opt<'de, D: Deserializer<'de>, T: FromStr>(de: D) -> Result<Option<T>, D::Error>
+where
+    T::Err: std::fmt::Display,
+{
+    Option::<String>::deserialize(de)?
+        .map(|s| s.parse().map_err(serde::de::Error::custom))
+        .transpose()
+}
@@ -0,0 +1,259 @@
+use std::{io::Write, sync::Arc};
+
+use chrono::NaiveDateTime;
+use parquet::{
+    basic::{Compression, ConvertedType, Repetition, Type as PhysicalType},
+    column::writer::ColumnWriter,
+    data_type::ByteArray,
+    file::{properties::WriterProperties, writer::SerializedFileWriter},
+    schema::types::Type,
+};
+
+use super::{Column, ColumnType, Format, Value};
+use crate::error::prelude::*;
+
+/// A destination for exported rows in a particular format
+#[allow(missing_debug_implementations)]
+pub enum Sink<W: Write> {
+    /// CSV output
+    Csv(csv::Writer<W>),
+    /// Parquet output
+    Parquet(SerializedFileWriter<W>, &'static [Column]),
+}
+
+impl<W: Write> Sink<W> {
+    /// Start writing rows with the given columns to `out`.  CSV output begins
+    /// with a header row.
+    ///
+    /// # Errors
+    /// This function fails if the header or schema cannot be written
+    pub fn new(format: Format, columns: &'static [Column], out: W) -> Result<Self> {
+        match format {
+            Format::Csv => {
+                let mut writer = csv::Writer::from_writer(out);
+
+                writer
+                    .write_record(columns.iter().map(|c| c.name))
+                    .context("Failed to write CSV header")?;
+
+                Ok(Self::Csv(writer))
+            },
+            Format::Parquet => {
+                let props = WriterProperties::builder()
+                    .set_compression(Compression::SNAPPY)
+                    .build();
+                let writer =
+                    SerializedFileWriter::new(out, Arc::new(schema(columns)?), Arc::new(props))
+                        .context("Failed to start Parquet file")?;
+
+                Ok(Self::Parquet(writer, columns))
+            },
+        }
+    }
+
+    /// Write a batch of rows, each with one value per column.  CSV output is
+    /// flushed after each batch, and each batch becomes one Parquet row group.
+    ///
+    /// # Errors
+    /// This function fails if the rows cannot be written or a value does not
+    /// match the type of its column
+    pub fn write(&mut self, rows: &[Vec<Value>]) -> Result<()> {
+        match self {
+            Self::Csv(writer) => {
+                for row in rows {
+                    writer
+                        .write_record(row.iter().map(csv_field))
+                        .context("Failed to write CSV row")?;
+                }
+
+                writer.flush().context("Failed to flush CSV rows")
+            },
+            Self::Parquet(writer, columns) => {
+                let mut group = writer
+                    .next_row_group()
+                    .context("Failed to start Parquet row group")?;
+
+                for (i, column) in columns.iter().enumerate() {
+                    let mut col = group
+                        .next_column()
+                        .context("Failed to start Parquet column")?
+                        .ok_or_else(|| anyhow!("Parquet schema is missing {:?}", column.name))?;
+
+                    write_column(col.untyped(), column, rows.iter().map(|r| &r[i]))
+                        .with_context(|| format!("Failed to write column {:?}", column.name))?;
+
+                    col.close().context("Failed to finish Parquet column")?;
+                }
+
+                group
+                    .close()
+                    .context("Failed to finish Parquet row group")
+                    .map(|_| ())
+            },
+        }
+    }
+
+    /// Finish the output, writing the Parquet footer if any
+    ///
+    /// # Errors
+    /// This function fails if the output cannot be flushed or finished
+    pub fn finish(self) -> Result<()> {
+        match self {
+            Self::Csv(mut writer) => writer.flush().context("Failed to flush CSV rows"),
+            Self::Parquet(writer, _) => writer
+                .close()
+                .context("Failed to finish Parquet file")
+                .map(|_| ()),
+        }
+    }
+}
+
+fn csv_field(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Bool(b) => b.to_string(),
+        Value::Int(i) => i.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::Text(s) => s.clone(),
+        Value::Timestamp(t) => t.format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string(),
+    }
+}
+
+fn micros(time: NaiveDateTime) -> i64 {
+    time.timestamp() * 1_000_000 + i64::from(time.timestamp_subsec_micros())
+}
+
+fn schema(columns: &[Column]) -> Result<Type> {
+    let mut fields = columns
+        .iter()
+        .map(|c| {
+            let (physical, converted) = match c.ty {
+                ColumnType::Bool => (PhysicalType::BOOLEAN, ConvertedType::NONE),
+                ColumnType::Int => (PhysicalType::INT64, ConvertedType::NONE),
+                ColumnType::Float => (PhysicalType::DOUBLE, ConvertedType::NONE),
+                ColumnType::Text => (PhysicalType::BYTE_ARRAY, ConvertedType::UTF8),
+                ColumnType::Timestamp => (PhysicalType::INT64, ConvertedType::TIMESTAMP_MICROS),
+            };
+
+            Type::primitive_type_builder(c.name, physical)
+                .with_repetition(Repetition::OPTIONAL)
+                .with_converted_type(converted)
+                .build()
+                .map(Arc::new)
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to build Parquet column schema")?;
+
+    Type::group_type_builder("export")
+        .with_fields(&mut fields)
+        .build()
+        .context("Failed to build Parquet schema")
+}
+
+/// Split a column of values into its non-null values and definition levels,
+/// failing on values of the wrong type
+fn levels<'a, T>(
+    values: impl Iterator<Item = &'a Value>,
+    f: impl Fn(&'a Value) -> Option<T>,
+) -> Result<(Vec<T>, Vec<i16>)> {
+    let mut present = Vec::new();
+    let mut defs = Vec::new();
+
+    for value in values {
+        if *value == Value::Null {
+            defs.push(0);
+        } else {
+            present.push(f(value).ok_or_else(|| anyhow!("Unexpected value {:?}", value))?);
+            defs.push(1);
+        }
+    }
+
+    Ok((present, defs))
+}
+
+fn write_column<'a>(
+    writer: &mut ColumnWriter,
+    column: &Column,
+    values: impl Iterator<Item = &'a Value>,
+) -> Result<()> {
+    match (writer, column.ty) {
+        (ColumnWriter::BoolColumnWriter(w), ColumnType::Bool) => {
+            let (vals, defs) = levels(values, |v| match v {
+                Value::Bool(b) => Some(*b),
+                _ => None,
+            })?;
+
+            w.write_batch(&vals, Some(&defs), None)?;
+        },
+        (ColumnWriter::Int64ColumnWriter(w), ColumnType::Int) => {
+            let (vals, defs) = levels(values, |v| match v {
+                Value::Int(i) => Some(*i),
+                _ => None,
+            })?;
+
+            w.write_batch(&vals, Some(&defs), None)?;
+        },
+        (ColumnWriter::Int64ColumnWriter(w), ColumnType::Timestamp) => {
+            let (vals, defs) = levels(values, |v| match v {
+                Value::Timestamp(t) => Some(micros(*t)),
+                _ => None,
+            })?;
+
+            w.write_batch(&vals, Some(&defs), None)?;
+        },
+        (ColumnWriter::DoubleColumnWriter(w), ColumnType::Float) => {
+            let (vals, defs) = levels(values, |v| match v {
+                Value::Float(f) => Some(*f),
+                _ => None,
+            })?;
+
+            w.write_batch(&vals, Some(&defs), None)?;
+        },
+        (ColumnWriter::ByteArrayColumnWriter(w), ColumnType::Text) => {
+            let (vals, defs) = levels(values, |v| match v {
+                Value::Text(s) => Some(ByteArray::from(s.as_str())),
+                _ => None,
+            })?;
+
+            w.write_batch(&vals, Some(&defs), None)?;
+        },
+        (_, ty) => bail!("Parquet column writer does not match column type {:?}", ty),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{csv_field, micros, Sink};
+    use crate::export::{col, ColumnType, Format, Value};
+
+    #[test]
+    fn test_csv() {
+        const COLUMNS: &[crate::export::Column] = &[
+            col("name", ColumnType::Text),
+            col("count", ColumnType::Int),
+            col("at", ColumnType::Timestamp),
+        ];
+
+        let at = NaiveDate::from_ymd(2022, 11, 14).and_hms_micro(8, 31, 52, 250_000);
+        let mut out = Vec::new();
+        let mut sink = Sink::new(Format::Csv, COLUMNS, &mut out).unwrap();
+
+        sink.write(&[
+            vec!["a, b".to_owned().into(), 3_i64.into(), at.into()],
+            vec![Value::Null, Value::Null, Value::Null],
+        ])
+        .unwrap();
+        sink.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "name,count,at\n\"a, b\",3,2022-11-14T08:31:52.250000Z\n,,\n"
+        );
+        assert_eq!(csv_field(&Value::Bool(true)), "true");
+        assert_eq!(micros(at), 1_668_414_712_250_000);
+    }
+}
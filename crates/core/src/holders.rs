@@ -0,0 +1,128 @@
+//! Distribution and concentration statistics for the holders of a collection
+
+/// Ranges of holding sizes used by [`distribution`], as inclusive lower
+/// bounds and optional inclusive upper bounds
+pub const BUCKETS: [(u64, Option<u64>); 6] = [
+    (1, Some(1)),
+    (2, Some(4)),
+    (5, Some(9)),
+    (10, Some(24)),
+    (25, Some(49)),
+    (50, None),
+];
+
+/// The wallets holding a range of NFT counts from a collection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DistributionBucket {
+    /// The smallest holding in the range
+    pub min: u64,
+    /// The largest holding in the range, or `None` if unbounded
+    pub max: Option<u64>,
+    /// The number of wallets with a holding in the range
+    pub holders: u64,
+    /// The total number of NFTs held by these wallets
+    pub nfts: u64,
+}
+
+/// Count the wallets and NFTs in each of the [`BUCKETS`], given the number of
+/// NFTs held by each wallet
+#[must_use]
+pub fn distribution(counts: &[u64]) -> Vec<DistributionBucket> {
+    BUCKETS
+        .iter()
+        .map(|&(min, max)| {
+            let (holders, nfts) = counts
+                .iter()
+                .filter(|&&c| c >= min && max.map_or(true, |m| c <= m))
+                .fold((0, 0), |(h, n), c| (h + 1, n + c));
+
+            DistributionBucket {
+                min,
+                max,
+                holders,
+                nfts,
+            }
+        })
+        .collect()
+}
+
+/// The Gini coefficient of the NFTs held by each wallet, from 0 when every
+/// wallet holds the same number to approaching 1 when one wallet holds
+/// nearly all of them.  Returns 0 if there are no holders.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn gini(counts: &[u64]) -> f64 {
+    let mut sorted = counts.to_vec();
+    sorted.sort_unstable();
+
+    let n = sorted.len() as f64;
+    let total: u64 = sorted.iter().sum();
+
+    if total == 0 {
+        return 0.0;
+    }
+
+    let weighted: f64 = sorted
+        .iter()
+        .zip(1_u64..)
+        .map(|(&c, i)| (i as f64) * (c as f64))
+        .sum();
+
+    2.0 * weighted / (n * total as f64) - (n + 1.0) / n
+}
+
+/// The Herfindahl-Hirschman index of the NFTs held by each wallet: the sum
+/// of the squared share of the supply held by each wallet, from `1 / n` for
+/// `n` equal holders to 1 for a single holder.  Returns 0 if there are no
+/// holders.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn hhi(counts: &[u64]) -> f64 {
+    let total: u64 = counts.iter().sum();
+
+    if total == 0 {
+        return 0.0;
+    }
+
+    counts
+        .iter()
+        .map(|&c| {
+            let share = c as f64 / total as f64;
+
+            share * share
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{distribution, gini, hhi};
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_concentration() {
+        assert!(close(gini(&[]), 0.0));
+        assert!(close(hhi(&[]), 0.0));
+
+        assert!(close(gini(&[3, 3, 3, 3]), 0.0));
+        assert!(close(hhi(&[3, 3, 3, 3]), 0.25));
+
+        assert!(close(gini(&[5]), 0.0));
+        assert!(close(hhi(&[5]), 1.0));
+
+        assert!(close(gini(&[3, 1]), 0.25));
+        assert!(close(hhi(&[3, 1]), 0.625));
+    }
+
+    #[test]
+    fn test_distribution() {
+        let buckets = distribution(&[1, 1, 3, 9, 10, 120]);
+        let counts: Vec<_> = buckets.iter().map(|b| (b.holders, b.nfts)).collect();
+
+        assert_eq!(counts, [(2, 2), (1, 3), (1, 9), (1, 10), (0, 0), (1, 120)]);
+        assert_eq!(buckets.last().unwrap().max, None);
+    }
+}
@@ -9,6 +9,8 @@
 )]
 #![warn(clippy::pedantic, clippy::cargo, missing_docs)]
 #![feature(bound_map, iter_intersperse)]
+// Required by diesel to allow all the tables in the schema to be joined
+#![recursion_limit = "256"]
 
 // TODO: #[macro_use] is somewhat deprecated, but diesel still relies on it
 #[cfg(feature = "db")]
@@ -31,11 +33,16 @@ pub mod assets;
 #[cfg(feature = "db")]
 pub mod db;
 pub mod error;
+#[cfg(feature = "export")]
+pub mod export;
 pub mod hash;
+pub mod holders;
 #[cfg(feature = "meilisearch")]
 pub mod meilisearch;
+pub mod portfolio;
 #[cfg(feature = "solana")]
 pub mod pubkeys;
+pub mod rarity;
 pub mod util;
 
 /// Commonly used utilities
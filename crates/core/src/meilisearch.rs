@@ -25,6 +25,18 @@ impl Args {
 
         client::Client::new(meili_url, meili_key)
     }
+
+    /// Get the Meilisearch database endpoint
+    #[must_use]
+    pub fn url(&self) -> &str {
+        &self.meili_url
+    }
+
+    /// Get the Meilisearch database API key
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.meili_key
+    }
 }
 
 /// Document added to an index by an `IndirectMetadata` message
@@ -45,3 +57,113 @@ pub struct IndirectMetadataDocument {
     /// The certified collection address of the metadata account
     pub collection_address: Option<String>,
 }
+
+/// Name of the index containing [`NftDocument`]s
+pub const NFTS_INDEX: &str = "nfts";
+
+/// Document added to the `nfts` index by an `IndirectMetadata` message
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct NftDocument {
+    /// The address of the Metaplex metadata account
+    pub metadata_address: String,
+    /// The address of the NFT mint
+    pub mint_address: String,
+    /// The name of the metadata account
+    pub name: String,
+    /// The description from the metadata JSON
+    pub description: Option<String>,
+    /// The image associated with the metadata account
+    pub image: Option<String>,
+    /// Attributes from the metadata JSON, formatted by
+    /// [`format_trait`](Self::format_trait)
+    pub traits: Vec<String>,
+    /// The verified collection address of the metadata account
+    pub collection_address: Option<String>,
+    /// The verified creators of the metadata account, in order
+    pub creator_addresses: Vec<String>,
+    /// The wallet currently holding the NFT
+    pub owner_address: Option<String>,
+    /// The lowest price of any active listing, in lamports
+    pub listing_price: Option<i64>,
+    /// True if the NFT has any active listing
+    pub listed: bool,
+}
+
+impl NftDocument {
+    /// Attributes which can be used to filter and facet the `nfts` index
+    pub const FILTERABLE_ATTRIBUTES: &'static [&'static str] = &[
+        "traits",
+        "collection_address",
+        "creator_addresses",
+        "owner_address",
+        "listing_price",
+        "listed",
+    ];
+    /// Attributes which can be used to sort the `nfts` index
+    pub const SORTABLE_ATTRIBUTES: &'static [&'static str] = &["name", "listing_price"];
+    /// Attributes searched by full-text queries on the `nfts` index, in order
+    /// of importance
+    pub const SEARCHABLE_ATTRIBUTES: &'static [&'static str] = &["name", "description", "traits"];
+
+    /// Format an attribute for the `traits` field.
+    ///
+    /// Backslashes and colons in the trait type are escaped with a backslash,
+    /// so the first unescaped colon always separates the type from the value.
+    #[must_use]
+    pub fn format_trait(trait_type: &str, value: &str) -> String {
+        let mut s = String::with_capacity(trait_type.len() + value.len() + 1);
+
+        for c in trait_type.chars() {
+            if matches!(c, '\\' | ':') {
+                s.push('\\');
+            }
+
+            s.push(c);
+        }
+
+        s.push(':');
+        s.push_str(value);
+        s
+    }
+
+    /// Split a value of the `traits` field into its trait type and value
+    #[must_use]
+    pub fn parse_trait(s: &str) -> Option<(String, &str)> {
+        let mut trait_type = String::new();
+        let mut chars = s.char_indices();
+
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => trait_type.push(chars.next()?.1),
+                ':' => return Some((trait_type, &s[i + 1..])),
+                c => trait_type.push(c),
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NftDocument;
+
+    #[test]
+    fn test_trait_round_trip() {
+        for (trait_type, value) in [
+            ("Background", "Blue"),
+            ("Time: Day", "12:00"),
+            ("a\\:b", ""),
+            ("", ":"),
+        ] {
+            let s = NftDocument::format_trait(trait_type, value);
+
+            assert_eq!(
+                NftDocument::parse_trait(&s),
+                Some((trait_type.to_owned(), value))
+            );
+        }
+
+        assert_eq!(NftDocument::parse_trait("no separator\\:"), None);
+    }
+}
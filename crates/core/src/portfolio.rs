@@ -0,0 +1,309 @@
+//! Valuation and profit and loss accounting for the NFTs held and traded by
+//! a wallet.  All prices are in lamports of SOL.
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    hash::BuildHasher,
+};
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+/// Which side of a sale a wallet was on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// The wallet bought the NFT
+    Buy,
+    /// The wallet sold the NFT
+    Sell,
+}
+
+/// A sale a wallet took part in
+#[derive(Debug, Clone)]
+pub struct Trade {
+    /// Address of the metadata of the traded NFT
+    pub nft: String,
+    /// The verified collection of the NFT, if any
+    pub collection: Option<String>,
+    /// Which side of the sale the wallet was on
+    pub side: Side,
+    /// The sale price, or `None` if it could not be converted to SOL
+    pub price: Option<i64>,
+    /// The time of the sale
+    pub at: NaiveDateTime,
+}
+
+/// An NFT currently held by a wallet
+#[derive(Debug, Clone)]
+pub struct Holding {
+    /// Address of the metadata of the held NFT
+    pub nft: String,
+    /// The verified collection of the NFT, if any
+    pub collection: Option<String>,
+    /// The estimated value of the NFT, if it could be estimated
+    pub value: Option<i64>,
+}
+
+/// Estimate the value of an NFT from the floor price of its collection, the
+/// highest floor price of any of its traits, and its last sale price.
+///
+/// Trait floors only ever raise the estimate above the collection floor, and
+/// the last sale is used only for NFTs with no floor.
+#[must_use]
+pub fn estimate_value(
+    floor: Option<i64>,
+    trait_floor: Option<i64>,
+    last_sale: Option<i64>,
+) -> Option<i64> {
+    match (floor, trait_floor) {
+        (Some(f), Some(t)) => Some(f.max(t)),
+        (Some(p), None) | (None, Some(p)) => Some(p),
+        (None, None) => last_sale,
+    }
+}
+
+/// Valuation and profit and loss of the NFTs of a single collection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CollectionSummary {
+    /// The number of NFTs currently held
+    pub nft_count: u64,
+    /// The estimated value of the NFTs currently held
+    pub value: i64,
+    /// The purchase price of the held NFTs with a known purchase price
+    pub cost_basis: i64,
+    /// Profit from NFTs bought and later sold
+    pub realized_pnl: i64,
+    /// Estimated value less cost basis of the held NFTs with both known
+    pub unrealized_pnl: i64,
+}
+
+/// The state of a portfolio at the end of a day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Snapshot {
+    /// The day
+    pub date: NaiveDate,
+    /// The value of the NFTs held at the end of the day, at the closing
+    /// price of their collections
+    pub value: i64,
+    /// Total realized profit as of the end of the day
+    pub realized_pnl: i64,
+}
+
+/// Valuation and profit and loss of a wallet, as computed by [`compute`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Portfolio {
+    /// The estimated value of the NFTs currently held
+    pub total_value: i64,
+    /// The purchase price of the held NFTs with a known purchase price
+    pub cost_basis: i64,
+    /// Profit from NFTs bought and later sold
+    pub realized_pnl: i64,
+    /// Estimated value less cost basis of the held NFTs with both known
+    pub unrealized_pnl: i64,
+    /// The number of held NFTs whose value could not be estimated
+    pub unvalued_count: u64,
+    /// Summaries per verified collection, or for NFTs with no collection
+    /// under `None`
+    pub by_collection: BTreeMap<Option<String>, CollectionSummary>,
+    /// End-of-day snapshots, oldest first
+    pub history: Vec<Snapshot>,
+}
+
+/// The latest price on or before `date` in a list of daily prices sorted by
+/// date
+fn price_at(prices: &[(NaiveDate, i64)], date: NaiveDate) -> Option<i64> {
+    match prices.binary_search_by_key(&date, |(d, _)| *d) {
+        Ok(i) => Some(prices[i].1),
+        Err(0) => None,
+        Err(i) => Some(prices[i - 1].1),
+    }
+}
+
+/// Value a wallet's holdings and account for its trades.
+///
+/// Realized profit matches each sale against the earliest unsold purchase of
+/// the same NFT; sales of NFTs with no recorded purchase, e.g. mints, and
+/// trades with unknown prices are not counted.  The history covers the
+/// `history_days` days ending with `today`, valuing the NFTs held at the end
+/// of each day at the latest of the `daily_closes` of their collection.
+#[must_use]
+pub fn compute<S: BuildHasher>(
+    holdings: &[Holding],
+    trades: &[Trade],
+    daily_closes: &HashMap<String, Vec<(NaiveDate, i64)>, S>,
+    today: NaiveDate,
+    history_days: u32,
+) -> Portfolio {
+    let mut trades: Vec<_> = trades.iter().collect();
+    trades.sort_by_key(|t| t.at);
+
+    let mut portfolio = Portfolio::default();
+    let mut lots: HashMap<&str, VecDeque<Option<i64>>> = HashMap::new();
+    let mut realized = vec![];
+
+    for trade in &trades {
+        let open = lots.entry(&trade.nft).or_default();
+
+        match trade.side {
+            Side::Buy => open.push_back(trade.price),
+            Side::Sell => {
+                if let (Some(Some(cost)), Some(price)) = (open.pop_front(), trade.price) {
+                    let pnl = price - cost;
+
+                    portfolio.realized_pnl += pnl;
+                    portfolio
+                        .by_collection
+                        .entry(trade.collection.clone())
+                        .or_default()
+                        .realized_pnl += pnl;
+                    realized.push((trade.at, pnl));
+                }
+            },
+        }
+    }
+
+    for holding in holdings {
+        let summary = portfolio
+            .by_collection
+            .entry(holding.collection.clone())
+            .or_default();
+        let cost = lots
+            .get(holding.nft.as_str())
+            .and_then(|l| l.front().copied().flatten());
+
+        summary.nft_count += 1;
+
+        if let Some(cost) = cost {
+            portfolio.cost_basis += cost;
+            summary.cost_basis += cost;
+        }
+
+        match holding.value {
+            Some(value) => {
+                portfolio.total_value += value;
+                summary.value += value;
+
+                if let Some(cost) = cost {
+                    portfolio.unrealized_pnl += value - cost;
+                    summary.unrealized_pnl += value - cost;
+                }
+            },
+            None => portfolio.unvalued_count += 1,
+        }
+    }
+
+    // Walk backwards from the current holdings, undoing each trade made
+    // after the end of each day
+    let mut held: HashMap<&str, Option<&str>> = holdings
+        .iter()
+        .map(|h| (h.nft.as_str(), h.collection.as_deref()))
+        .collect();
+    let mut undone = trades.iter().rev().peekable();
+    let mut realized_total = portfolio.realized_pnl;
+    let mut undone_realized = realized.iter().rev().peekable();
+
+    for days_ago in 0..history_days {
+        let date = today - Duration::days(days_ago.into());
+        let end = (date + Duration::days(1)).and_hms(0, 0, 0);
+
+        while let Some(trade) = undone.next_if(|t| t.at >= end) {
+            match trade.side {
+                Side::Buy => {
+                    held.remove(trade.nft.as_str());
+                },
+                Side::Sell => {
+                    held.insert(&trade.nft, trade.collection.as_deref());
+                },
+            }
+        }
+
+        while let Some((_, pnl)) = undone_realized.next_if(|(at, _)| *at >= end) {
+            realized_total -= pnl;
+        }
+
+        let value = held
+            .values()
+            .filter_map(|c| c.and_then(|c| daily_closes.get(c)))
+            .filter_map(|p| price_at(p, date))
+            .sum();
+
+        portfolio.history.push(Snapshot {
+            date,
+            value,
+            realized_pnl: realized_total,
+        });
+    }
+
+    portfolio.history.reverse();
+
+    portfolio
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::NaiveDate;
+
+    use super::{compute, estimate_value, Holding, Side, Trade};
+
+    fn trade(nft: &str, side: Side, price: i64, day: u32) -> Trade {
+        Trade {
+            nft: nft.into(),
+            collection: Some("c".into()),
+            side,
+            price: Some(price),
+            at: NaiveDate::from_ymd(2022, 11, day).and_hms(12, 0, 0),
+        }
+    }
+
+    #[test]
+    fn test_estimate_value() {
+        assert_eq!(estimate_value(Some(10), Some(15), Some(50)), Some(15));
+        assert_eq!(estimate_value(Some(10), None, Some(50)), Some(10));
+        assert_eq!(estimate_value(None, None, Some(50)), Some(50));
+        assert_eq!(estimate_value(None, None, None), None);
+    }
+
+    #[test]
+    fn test_pnl() {
+        let trades = [
+            trade("a", Side::Buy, 100, 1),
+            trade("a", Side::Sell, 150, 3),
+            trade("b", Side::Buy, 80, 2),
+            // Minted, so no cost basis
+            trade("m", Side::Sell, 40, 2),
+        ];
+        let holdings = [Holding {
+            nft: "b".into(),
+            collection: Some("c".into()),
+            value: Some(60),
+        }];
+        let closes = HashMap::from([("c".to_owned(), vec![
+            (NaiveDate::from_ymd(2022, 11, 1), 90),
+            (NaiveDate::from_ymd(2022, 11, 3), 70),
+        ])]);
+
+        let p = compute(
+            &holdings,
+            &trades,
+            &closes,
+            NaiveDate::from_ymd(2022, 11, 4),
+            4,
+        );
+
+        assert_eq!(p.total_value, 60);
+        assert_eq!(p.cost_basis, 80);
+        assert_eq!(p.realized_pnl, 50);
+        assert_eq!(p.unrealized_pnl, -20);
+        assert_eq!(p.by_collection[&Some("c".to_owned())].nft_count, 1);
+
+        let history: Vec<_> = p
+            .history
+            .iter()
+            .map(|s| (s.value, s.realized_pnl))
+            .collect();
+
+        // Nov 1: a and m held at 90; Nov 2: m sold, b bought; Nov 3: a sold
+        assert_eq!(history, [(180, 0), (180, 0), (70, 50), (70, 50)]);
+    }
+}
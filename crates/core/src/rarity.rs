@@ -0,0 +1,287 @@
+//! Rarity scoring of NFTs within a collection based on their attributes
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap},
+};
+
+/// A method for scoring the rarity of the NFTs in a collection
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::EnumString,
+    strum::Display,
+    strum::AsRefStr,
+    strum::IntoStaticStr,
+)]
+#[strum(serialize_all = "snake_case")]
+#[allow(clippy::module_name_repetitions)]
+pub enum RarityMethod {
+    /// The probability of an NFT's combination of traits occurring, computed
+    /// as the product of the frequency of each of its traits.  Lower scores
+    /// are rarer.
+    Statistical,
+    /// The information content (surprisal) of an NFT's traits, normalized by
+    /// the entropy of the collection.  Higher scores are rarer.
+    InformationContent,
+    /// The sum of the inverse frequency of each of an NFT's traits, divided by
+    /// the number of values of that trait type.  Higher scores are rarer.
+    TraitNormalized,
+}
+
+impl RarityMethod {
+    /// All supported rarity methods
+    pub const ALL: [Self; 3] = [
+        Self::Statistical,
+        Self::InformationContent,
+        Self::TraitNormalized,
+    ];
+
+    /// Returns true if lower scores are rarer for this method
+    #[must_use]
+    pub fn lower_is_rarer(self) -> bool {
+        matches!(self, Self::Statistical)
+    }
+}
+
+/// The rarity of a single NFT, as computed by [`score`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rarity<K> {
+    /// The key of the scored item
+    pub key: K,
+    /// The rarity score of the item, interpreted according to the method
+    pub score: f64,
+    /// The rank of the item within its collection, starting from 1 for the
+    /// rarest.  Items with equal scores share a rank.
+    pub rank: u32,
+}
+
+/// Relative tolerance used to decide whether two scores are tied
+const TIE_EPSILON: f64 = 1e-9;
+
+/// Returns true if two scores are equal up to floating-point error
+pub(crate) fn tied(a: f64, b: f64) -> bool {
+    (a - b).abs() <= TIE_EPSILON * a.abs().max(b.abs())
+}
+
+/// Score and rank every item of a collection, given the `(trait_type, value)`
+/// pairs of each item.
+///
+/// Items missing a trait type present elsewhere in the collection are treated
+/// as having a distinct "none" value for it.  The returned rarities are in
+/// rank order.
+#[must_use]
+pub fn score<K: Clone>(
+    items: &[(K, Vec<(String, String)>)],
+    method: RarityMethod,
+) -> Vec<Rarity<K>> {
+    if items.is_empty() {
+        return vec![];
+    }
+
+    let trait_types: BTreeSet<&str> = items
+        .iter()
+        .flat_map(|(_, t)| t.iter().map(|(k, _)| k.as_str()))
+        .collect();
+
+    // The value of each trait type for each item, or None if it is missing
+    let values: Vec<Vec<Option<&str>>> = items
+        .iter()
+        .map(|(_, traits)| {
+            trait_types
+                .iter()
+                .map(|t| traits.iter().find(|(k, _)| k == t).map(|(_, v)| v.as_str()))
+                .collect()
+        })
+        .collect();
+
+    let mut counts: Vec<HashMap<Option<&str>, usize>> = vec![HashMap::new(); trait_types.len()];
+
+    for item in &values {
+        for (count, value) in counts.iter_mut().zip(item) {
+            *count.entry(*value).or_default() += 1;
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let total = items.len() as f64;
+
+    let entropy: f64 = counts
+        .iter()
+        .flat_map(HashMap::values)
+        .map(|c| {
+            #[allow(clippy::cast_precision_loss)]
+            let p = *c as f64 / total;
+
+            -p * p.log2()
+        })
+        .sum();
+
+    let scores = values.iter().map(|item| {
+        let probabilities = item.iter().zip(&counts).map(|(value, count)| {
+            #[allow(clippy::cast_precision_loss)]
+            let p = count[value] as f64 / total;
+
+            (p, count.len())
+        });
+
+        match method {
+            RarityMethod::Statistical => probabilities.map(|(p, _)| p).product(),
+            RarityMethod::InformationContent => {
+                let information: f64 = probabilities.map(|(p, _)| -p.log2()).sum();
+
+                if entropy > 0.0 {
+                    information / entropy
+                } else {
+                    0.0
+                }
+            },
+            RarityMethod::TraitNormalized => probabilities
+                .map(|(p, n)| {
+                    #[allow(clippy::cast_precision_loss)]
+                    let n = n as f64;
+
+                    1.0 / (p * n)
+                })
+                .sum(),
+        }
+    });
+
+    let mut ranked: Vec<_> = items
+        .iter()
+        .zip(scores)
+        .map(|((key, _), score)| (key, score))
+        .collect();
+
+    ranked.sort_by(|(_, a), (_, b)| {
+        let ord = a.partial_cmp(b).unwrap_or(Ordering::Equal);
+
+        if method.lower_is_rarer() {
+            ord
+        } else {
+            ord.reverse()
+        }
+    });
+
+    let mut rank = 0;
+    let mut prev: Option<f64> = None;
+
+    ranked
+        .into_iter()
+        .enumerate()
+        .map(|(i, (key, score))| {
+            if !prev.map_or(false, |p| tied(p, score)) {
+                rank = u32::try_from(i + 1).unwrap_or(u32::MAX);
+            }
+            prev = Some(score);
+
+            Rarity {
+                key: key.clone(),
+                score,
+                rank,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{score, RarityMethod};
+
+    fn collection() -> Vec<(u8, Vec<(String, String)>)> {
+        let item = |k, traits: &[(&str, &str)]| {
+            (
+                k,
+                traits
+                    .iter()
+                    .map(|(t, v)| ((*t).to_owned(), (*v).to_owned()))
+                    .collect(),
+            )
+        };
+
+        vec![
+            item(0, &[("hat", "cap"), ("eyes", "blue")]),
+            item(1, &[("hat", "cap"), ("eyes", "blue")]),
+            item(2, &[("hat", "cap"), ("eyes", "green")]),
+            item(3, &[("hat", "crown"), ("eyes", "blue")]),
+            // Missing hat, which is rarer than any hat
+            item(4, &[("eyes", "red")]),
+        ]
+    }
+
+    fn ranks(method: RarityMethod) -> Vec<(u8, u32)> {
+        score(&collection(), method)
+            .into_iter()
+            .map(|r| (r.key, r.rank))
+            .collect()
+    }
+
+    #[test]
+    fn test_empty() {
+        assert!(score::<u8>(&[], RarityMethod::Statistical).is_empty());
+    }
+
+    #[test]
+    fn test_statistical() {
+        let scores = score(&collection(), RarityMethod::Statistical);
+
+        assert_eq!(scores[0].key, 4);
+        assert!((scores[0].score - 0.2 * 0.2).abs() < 1e-12);
+        assert_eq!(ranks(RarityMethod::Statistical), vec![
+            (4, 1),
+            (2, 2),
+            (3, 2),
+            (0, 4),
+            (1, 4),
+        ]);
+    }
+
+    #[test]
+    fn test_information_content() {
+        assert_eq!(ranks(RarityMethod::InformationContent), vec![
+            (4, 1),
+            (2, 2),
+            (3, 2),
+            (0, 4),
+            (1, 4),
+        ]);
+    }
+
+    #[test]
+    fn test_trait_normalized() {
+        // Three hat values (including none) and three eye colors, so the
+        // normalization does not change the relative order here
+        assert_eq!(ranks(RarityMethod::TraitNormalized), vec![
+            (4, 1),
+            (2, 2),
+            (3, 2),
+            (0, 4),
+            (1, 4),
+        ]);
+    }
+
+    #[test]
+    fn test_uniform_collection() {
+        let items = vec![(0, vec![]), (1, vec![])];
+
+        for method in RarityMethod::ALL {
+            assert!(score(&items, method).iter().all(|r| r.rank == 1));
+        }
+    }
+
+    #[test]
+    fn test_method_names() {
+        assert_eq!(
+            RarityMethod::InformationContent.to_string(),
+            "information_content"
+        );
+        assert_eq!(
+            "trait_normalized".parse::<RarityMethod>().unwrap(),
+            RarityMethod::TraitNormalized
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! Bearer token authentication for privileged requests
+
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use actix_web::{http, HttpRequest};
+use indexer_core::prelude::*;
+
+/// A `name:token` pair from the command line.  The token is redacted when
+/// debug-formatted so it is never logged.
+#[derive(Clone)]
+pub struct NamedToken {
+    name: String,
+    token: String,
+}
+
+impl fmt::Debug for NamedToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NamedToken")
+            .field("name", &self.name)
+            .field("token", &"<redacted>")
+            .finish()
+    }
+}
+
+impl FromStr for NamedToken {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, token) = s
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Invalid token pair, expected name:token"))?;
+
+        if name.is_empty() {
+            bail!("Token pair is missing a name");
+        }
+
+        // An empty token would match any request sending an empty bearer
+        if token.trim().is_empty() {
+            bail!("Token pair for {:?} is missing a token", name);
+        }
+
+        Ok(Self {
+            name: name.to_owned(),
+            token: token.to_owned(),
+        })
+    }
+}
+
+/// Collect a list of `name:token` pairs into a map of tokens to names
+pub fn token_map(pairs: Vec<NamedToken>) -> HashMap<String, String> {
+    pairs
+        .into_iter()
+        .map(|NamedToken { name, token }| (token, name))
+        .collect()
+}
+
+/// Compare two byte strings in time independent of their contents
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b).fold(0_u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Look up the name associated with the `Authorization: Bearer <token>`
+/// header of a request in a map of tokens to names.  Every configured token is
+/// compared in constant time, so response timing does not reveal how much of
+/// a token was guessed correctly.
+pub fn bearer_name(tokens: &HashMap<String, String>, req: &HttpRequest) -> Option<String> {
+    let token = req
+        .headers()
+        .get(http::header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))?
+        .trim();
+
+    tokens
+        .iter()
+        .fold(None, |found, (candidate, name)| {
+            let matched = constant_time_eq(candidate.as_bytes(), token.as_bytes());
+
+            found.or(if matched { Some(name) } else { None })
+        })
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{constant_time_eq, NamedToken};
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"", b""));
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secrets"));
+    }
+
+    #[test]
+    fn test_named_token() {
+        let pair: NamedToken = "ops:hunter2".parse().unwrap();
+        assert_eq!(pair.name, "ops");
+        assert_eq!(pair.token, "hunter2");
+        assert!(!format!("{:?}", pair).contains("hunter2"));
+
+        assert!("ops".parse::<NamedToken>().is_err());
+        assert!("ops:".parse::<NamedToken>().is_err());
+        assert!("ops: ".parse::<NamedToken>().is_err());
+        assert!(":hunter2".parse::<NamedToken>().is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//! Authenticated HTTP endpoint streaming analytics exports as CSV or Parquet
+
+use std::io;
+
+use actix_web::{http, web, web::Bytes, HttpRequest, HttpResponse};
+use indexer_core::{
+    export::{self, Dataset, Format},
+    prelude::*,
+};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+
+use crate::{auth, SharedData};
+
+/// The size of each chunk of the response body
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The number of chunks buffered before the export waits for the client
+const CHANNEL_CAPACITY: usize = 4;
+
+/// The body of an export request, e.g.
+/// `{"format": "csv", "dataset": "floorPrices", "auctionHouses": [...], ...}`
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Deserialize)]
+pub struct ExportRequest {
+    #[serde(default = "default_format")]
+    format: Format,
+    #[serde(flatten)]
+    dataset: Dataset,
+}
+
+fn default_format() -> Format {
+    Format::Csv
+}
+
+/// Writer sending its output to the response body in chunks of
+/// [`CHUNK_SIZE`]
+struct ChannelWriter {
+    tx: mpsc::Sender<Result<Bytes, io::Error>>,
+    buf: Vec<u8>,
+}
+
+impl ChannelWriter {
+    fn new(tx: mpsc::Sender<Result<Bytes, io::Error>>) -> Self {
+        Self {
+            tx,
+            buf: Vec::with_capacity(CHUNK_SIZE),
+        }
+    }
+
+    fn send(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+
+        let chunk = std::mem::replace(&mut self.buf, Vec::with_capacity(CHUNK_SIZE));
+
+        self.tx
+            .blocking_send(Ok(chunk.into()))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "Export client disconnected"))
+    }
+}
+
+impl io::Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+
+        if self.buf.len() >= CHUNK_SIZE {
+            self.send()?;
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buf.len() >= CHUNK_SIZE {
+            self.send()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for ChannelWriter {
+    fn drop(&mut self) {
+        self.send().ok();
+    }
+}
+
+/// Stream the rows of the requested dataset.  Requests authenticate with an
+/// `Authorization: Bearer <token>` header using one of the configured export
+/// tokens.
+#[allow(clippy::unused_async)]
+pub(crate) async fn export(
+    data: web::Data<SharedData>,
+    req: web::Json<ExportRequest>,
+    http_req: HttpRequest,
+) -> HttpResponse {
+    let actor = match auth::bearer_name(&data.export_tokens, &http_req) {
+        Some(a) => a,
+        None => return HttpResponse::Unauthorized().body("Invalid or missing export token"),
+    };
+
+    let ExportRequest { format, dataset } = req.into_inner();
+    let (tx, mut rx) = mpsc::channel(CHANNEL_CAPACITY);
+    let db = data.db.clone();
+
+    info!(
+        "Export of {:?} as {} requested by {:?}",
+        dataset, format, actor
+    );
+
+    actix_web::rt::task::spawn_blocking(move || {
+        let err_tx = tx.clone();
+        let result = db
+            .get()
+            .context("Failed to connect to the database")
+            .and_then(|conn| export::export(&conn, &dataset, format, ChannelWriter::new(tx)));
+
+        match result {
+            Ok(rows) => info!("Exported {} row(s) for {:?}", rows, actor),
+            Err(e) => {
+                error!("Export for {:?} failed: {:?}", actor, e);
+
+                // Abort the response so the client does not mistake a partial
+                // export for a complete one
+                err_tx
+                    .blocking_send(Err(io::Error::new(io::ErrorKind::Other, "Export failed")))
+                    .ok();
+            },
+        }
+    });
+
+    HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header((
+            http::header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"export.{}\"", format.extension()),
+        ))
+        .streaming(futures_util::stream::poll_fn(move |cx| rx.poll_recv(cx)))
+}
@@ -9,29 +9,30 @@
 )]
 #![warn(clippy::pedantic, clippy::cargo, missing_docs)]
 
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use actix_cors::Cors;
-use actix_web::{dev::ConnectionInfo, http, web, App, Error, HttpResponse, HttpServer};
+use actix_web::{
+    dev::ConnectionInfo, http, web, App, Error, HttpRequest, HttpResponse, HttpServer,
+};
 use indexer_core::{
-    assets::AssetProxyArgs,
-    chrono::{Duration, Local},
-    clap,
-    clap::Parser,
-    db,
-    db::Pool,
-    meilisearch,
-    prelude::*,
-    util::duration_hhmmssfff,
-    ServerOpts,
+    assets::AssetProxyArgs, clap, clap::Parser, db, db::Pool, prelude::*, ServerOpts,
 };
 use juniper::http::{graphiql::graphiql_source, GraphQLRequest};
 // TODO: use nonblocking once we upgrade past 1.9
 use solana_client::rpc_client::RpcClient;
 
-use crate::schema::{AppContext, Schema};
+use crate::{
+    schema::{AppContext, Schema},
+    search::SearchBackend,
+    telemetry::Telemetry,
+};
 
+mod auth;
+mod export;
 mod schema;
+mod search;
+mod telemetry;
 
 #[derive(Debug, Parser)]
 struct Opts {
@@ -48,7 +49,7 @@ struct Opts {
     asset_proxy: AssetProxyArgs,
 
     #[clap(flatten)]
-    search: meilisearch::Args,
+    search: search::Args,
 
     #[clap(long, env)]
     solana_endpoint: String,
@@ -67,6 +68,21 @@ struct Opts {
 
     #[clap(long, env)]
     pre_query_search_limit: usize,
+
+    #[clap(flatten)]
+    telemetry: telemetry::Args,
+
+    /// Comma-separated list of `name:token` pairs granting access to admin
+    /// mutations.  Requests authenticate with an `Authorization: Bearer
+    /// <token>` header, and `name` is recorded as the actor in audit logs.
+    /// Setting this requires a writable database connection.
+    #[clap(long, env, use_value_delimiter(true))]
+    admin_tokens: Vec<auth::NamedToken>,
+
+    /// Comma-separated list of `name:token` pairs granting access to the
+    /// `/v1/export` endpoint, in the same format as `--admin-tokens`
+    #[clap(long, env, use_value_delimiter(true))]
+    export_tokens: Vec<auth::NamedToken>,
 }
 
 struct GraphiqlData {
@@ -81,15 +97,22 @@ struct RedirectData {
 pub(crate) struct SharedData {
     schema: Schema,
     pub db: Arc<Pool>,
+    /// Writable database pool, only available if admin tokens are configured
+    pub db_write: Option<Arc<Pool>>,
     pub asset_proxy: AssetProxyArgs,
     pub twitter_bearer_token: String,
-    pub search: meilisearch::client::Client,
+    pub search: Arc<dyn SearchBackend>,
     pub rpc: RpcClient,
     pub follow_wallets_exclusions: Vec<String>,
     pub featured_listings_auction_houses: Vec<String>,
     pub featured_listings_seller_exclusions: Vec<String>,
     pub marketplaces_store_address_exclusions: Vec<String>,
     pub pre_query_search_limit: usize,
+    pub telemetry: Telemetry,
+    /// Map of admin bearer tokens to admin names
+    pub admin_tokens: HashMap<String, String>,
+    /// Map of export bearer tokens to exporter names
+    pub export_tokens: HashMap<String, String>,
 }
 
 #[allow(clippy::unused_async)]
@@ -115,49 +138,28 @@ async fn graphql(
     data: web::Data<SharedData>,
     req: web::Json<GraphQLRequest>,
     conn: ConnectionInfo,
+    http_req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
-    let ctx = AppContext::new(data.clone().into_inner());
-    let start = Local::now();
+    let admin = auth::bearer_name(&data.admin_tokens, &http_req);
+    let ctx = AppContext::new(data.clone().into_inner(), admin);
+    let start = std::time::Instant::now();
 
     let resp = req.execute(&data.schema, &ctx).await;
-    let end = Local::now();
-    let duration = end - start;
+    let duration = start.elapsed();
     info!(
         "host={:?}, remote_addr={:?}, peer_addr={:?}",
         conn.host(),
         conn.realip_remote_addr().unwrap_or(&String::new()),
         conn.peer_addr().unwrap_or(&String::new())
     );
-    if duration > Duration::milliseconds(5000) {
-        #[derive(serde::Deserialize)]
-        struct Data {
-            #[serde(default)]
-            query: serde_json::Value,
-            #[serde(default)]
-            operation_name: serde_json::Value,
-            #[serde(default)]
-            variables: serde_json::Value,
-        }
-
-        match serde_json::to_value(&req).and_then(serde_json::from_value) {
-            Ok(Data {
-                query,
-                operation_name,
-                variables,
-            }) => warn!(
-                "Long graphql request query={}, operation={:?}, variables={}, duration={}",
-                query,
-                operation_name,
-                variables,
-                duration_hhmmssfff(duration),
-            ),
-            Err(e) => error!("Failed to format long query for printing: {}", e),
-        }
-    }
+
+    data.telemetry
+        .finish(&req, conn.realip_remote_addr(), &ctx.trace, duration);
 
     Ok(HttpResponse::Ok().json(&resp))
 }
 
+#[allow(clippy::too_many_lines)]
 fn main() {
     indexer_core::run(|| {
         let opts = Opts::parse();
@@ -174,6 +176,9 @@ fn main() {
             featured_listings_seller_exclusions,
             marketplaces_store_address_exclusions,
             pre_query_search_limit,
+            telemetry,
+            admin_tokens,
+            export_tokens,
         } = opts;
 
         let (addr,) = server.into_parts();
@@ -181,19 +186,35 @@ fn main() {
 
         let twitter_bearer_token = twitter_bearer_token.unwrap_or_else(String::new);
 
-        // TODO: db_ty indicates if any actions that mutate the database can be run
+        let admin_tokens = auth::token_map(admin_tokens);
+        let export_tokens = auth::token_map(export_tokens);
+
+        let db_write = if admin_tokens.is_empty() {
+            None
+        } else {
+            let db::ConnectResult { pool, .. } =
+                db::connect(db.clone(), db::ConnectMode::Write { migrate: false })
+                    .context("Failed to connect to Postgres for admin mutations")?;
+
+            Some(Arc::new(pool))
+        };
+
         let db::ConnectResult {
             pool,
             ty: _,
             migrated: _,
         } = db::connect(db, db::ConnectMode::Read).context("Failed to connect to Postgres")?;
         let db = Arc::new(pool);
-        let search = search.into_client();
+        let search = search
+            .build(Arc::clone(&db))
+            .context("Failed to configure search backend")?;
         let rpc = RpcClient::new(solana_endpoint);
+        let telemetry = Telemetry::new(telemetry).context("Failed to initialize telemetry")?;
 
         let shared = web::Data::new(SharedData {
             schema: schema::create(),
             db,
+            db_write,
             asset_proxy,
             twitter_bearer_token,
             search,
@@ -203,6 +224,9 @@ fn main() {
             featured_listings_seller_exclusions,
             marketplaces_store_address_exclusions,
             pre_query_search_limit,
+            telemetry,
+            admin_tokens,
+            export_tokens,
         });
 
         let version_extension = "/v1";
@@ -238,6 +262,11 @@ fn main() {
                                 .app_data(shared.clone())
                                 .route(web::post().to(graphql)),
                         )
+                        .service(
+                            web::resource("/v1/export")
+                                .app_data(shared.clone())
+                                .route(web::post().to(export::export)),
+                        )
                         .service(
                             web::resource(redirect_data.route)
                                 .app_data(redirect_data.clone())
@@ -18,7 +18,11 @@ use objects::{
     graph_connection::GraphConnection,
     listing::{Bid, Listing},
     listing_receipt::ListingReceipt,
-    nft::{Collection, Nft, NftActivity, NftAttribute, NftCreator, NftFile, NftOwner},
+    marketplace::StoreConfigIssue,
+    nft::{
+        Collection, MetadataIssue, Nft, NftActivity, NftAttribute, NftCreator, NftFile,
+        NftImageDerivative, NftMetadataIntegrity, NftOwner, NftRarity,
+    },
     profile::TwitterProfile,
     purchase_receipt::PurchaseReceipt,
     reward_center::RewardCenter,
@@ -37,10 +41,14 @@ use scalars::{
 };
 
 use super::prelude::*;
+use crate::{schema::enums::WashTradeReason, telemetry::RequestTrace};
 
 #[derive(Clone)]
 pub struct AppContext {
     pub(crate) shared: Arc<SharedData>,
+    pub(crate) trace: RequestTrace,
+    /// Name of the authenticated admin making this request, if any
+    pub(crate) admin: Option<String>,
 
     // Postgres dataloaders
     pub ah_listing_loader: Loader<Uuid, Option<AhListing>>,
@@ -86,8 +94,12 @@ pub struct AppContext {
     pub generic_collection_loader: Loader<String, Option<Collection>>,
     pub nft_creators_loader: Loader<PublicKey<Nft>, Vec<NftCreator>>,
     pub nft_files_loader: Loader<PublicKey<Nft>, Vec<NftFile>>,
+    pub nft_image_derivatives_loader: Loader<PublicKey<Nft>, Vec<NftImageDerivative>>,
+    pub nft_metadata_integrity_loader: Loader<PublicKey<Nft>, Option<NftMetadataIntegrity>>,
+    pub nft_metadata_issues_loader: Loader<PublicKey<Nft>, Vec<MetadataIssue>>,
     pub nft_loader: Loader<PublicKey<Nft>, Option<Nft>>,
     pub nft_owner_loader: Loader<PublicKey<Nft>, Option<NftOwner>>,
+    pub nft_rarities_loader: Loader<PublicKey<Nft>, Vec<NftRarity>>,
     pub offer_loader: Loader<Uuid, Option<AhOffer>>,
     pub offers_loader: Loader<PublicKey<Nft>, Vec<AhOffer>>,
     pub purchase_loader: Loader<Uuid, Option<AhPurchase>>,
@@ -108,9 +120,12 @@ pub struct AppContext {
         Loader<PublicKey<TokenOwnerRecord>, Option<TokenOwnerRecord>>,
     pub spl_vote_record_token_owner_loader: Loader<PublicKey<Wallet>, Vec<TokenOwnerRecord>>,
     pub store_auction_houses_loader: Loader<PublicKey<StoreConfig>, Vec<AuctionHouse>>,
+    pub store_config_issues_loader: Loader<PublicKey<StoreConfig>, Vec<StoreConfigIssue>>,
     pub store_creator_loader: Loader<PublicKey<StoreConfig>, Vec<StoreCreator>>,
+    pub storefront_config_issues_loader: Loader<PublicKey<Storefront>, Vec<StoreConfigIssue>>,
     pub storefront_loader: Loader<PublicKey<Storefront>, Option<Storefront>>,
     pub twitter_handle_loader: Loader<PublicKey<Wallet>, Option<String>>,
+    pub wash_trade_reasons_loader: Loader<Uuid, Vec<WashTradeReason>>,
 
     // Twitter dataloaders
     pub twitter_profile_loader: Loader<String, Option<TwitterProfile>, TwitterBatcher>,
@@ -119,12 +134,15 @@ pub struct AppContext {
 impl juniper::Context for AppContext {}
 
 impl AppContext {
-    pub(crate) fn new(shared: Arc<SharedData>) -> AppContext {
-        let batcher = Batcher::new(shared.db.clone());
-        let twitter_batcher = TwitterBatcher::new(shared.clone());
+    pub(crate) fn new(shared: Arc<SharedData>, admin: Option<String>) -> AppContext {
+        let trace = RequestTrace::new();
+        let batcher = Batcher::new(shared.db.clone(), trace.clone());
+        let twitter_batcher = TwitterBatcher::new(shared.clone(), trace.clone());
 
         Self {
             shared,
+            trace,
+            admin,
 
             // Postgres dataloaders
             ah_listing_loader: Loader::new(batcher.clone()),
@@ -163,8 +181,12 @@ impl AppContext {
             generic_collection_loader: Loader::new(batcher.clone()),
             nft_creators_loader: Loader::new(batcher.clone()),
             nft_files_loader: Loader::new(batcher.clone()),
+            nft_image_derivatives_loader: Loader::new(batcher.clone()),
+            nft_metadata_integrity_loader: Loader::new(batcher.clone()),
+            nft_metadata_issues_loader: Loader::new(batcher.clone()),
             nft_loader: Loader::new(batcher.clone()),
             nft_owner_loader: Loader::new(batcher.clone()),
+            nft_rarities_loader: Loader::new(batcher.clone()),
             offer_loader: Loader::new(batcher.clone()),
             offers_loader: Loader::new(batcher.clone()),
             purchase_loader: Loader::new(batcher.clone()),
@@ -184,9 +206,12 @@ impl AppContext {
             spl_token_owner_record_loader: Loader::new(batcher.clone()),
             spl_vote_record_token_owner_loader: Loader::new(batcher.clone()),
             store_auction_houses_loader: Loader::new(batcher.clone()),
+            store_config_issues_loader: Loader::new(batcher.clone()),
             store_creator_loader: Loader::new(batcher.clone()),
+            storefront_config_issues_loader: Loader::new(batcher.clone()),
             storefront_loader: Loader::new(batcher.clone()),
-            twitter_handle_loader: Loader::new(batcher),
+            twitter_handle_loader: Loader::new(batcher.clone()),
+            wash_trade_reasons_loader: Loader::new(batcher),
 
             // Twitter dataloaders
             twitter_profile_loader: Loader::new(twitter_batcher),
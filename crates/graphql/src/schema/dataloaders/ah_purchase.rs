@@ -1,9 +1,10 @@
-use indexer_core::uuid::Uuid;
+use indexer_core::{db::custom_types, uuid::Uuid};
 use objects::{ah_purchase::Purchase, nft::Nft};
 use scalars::PublicKey;
-use tables::{metadatas, purchases};
+use tables::{metadatas, purchases, wash_trades};
 
 use super::prelude::*;
+use crate::schema::enums::WashTradeReason;
 
 #[async_trait]
 impl TryBatchFn<Uuid, Option<Purchase>> for Batcher {
@@ -45,3 +46,32 @@ impl TryBatchFn<PublicKey<Nft>, Vec<Purchase>> for Batcher {
             .batch(addresses))
     }
 }
+
+#[async_trait]
+impl TryBatchFn<Uuid, Vec<WashTradeReason>> for Batcher {
+    async fn load(&mut self, ids: &[Uuid]) -> TryBatchMap<Uuid, Vec<WashTradeReason>> {
+        let conn = self.db()?;
+
+        let rows: Vec<models::WashTrade> = wash_trades::table
+            .select(wash_trades::all_columns)
+            .filter(wash_trades::purchase_id.eq(any(ids)))
+            .load(&conn)
+            .context("Failed to load wash trade flags")?;
+
+        Ok(rows
+            .into_iter()
+            .flat_map(|trade| {
+                let id = trade.purchase_id;
+
+                trade.reasons.into_iter().map(move |r| {
+                    let reason = r
+                        .parse::<custom_types::WashTradeReason>()
+                        .map(WashTradeReason::from)
+                        .context("Failed to parse wash trade reason");
+
+                    (id, reason)
+                })
+            })
+            .batch(ids))
+    }
+}
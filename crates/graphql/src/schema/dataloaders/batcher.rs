@@ -3,7 +3,7 @@ use std::{collections::HashMap, hash::Hash, sync::Arc};
 use indexer_core::{assets::proxy_twitter_handle_url, url::Url};
 
 use super::prelude::*;
-use crate::SharedData;
+use crate::{telemetry::RequestTrace, SharedData};
 
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum Error {
@@ -118,28 +118,32 @@ pub trait TryBatchFn<K, V> {
 }
 
 #[derive(Clone)]
-pub struct Batcher(Arc<Pool>);
+pub struct Batcher {
+    db: Arc<Pool>,
+    trace: RequestTrace,
+}
 
 #[derive(Clone)]
 pub struct TwitterBatcher {
     shared: Arc<SharedData>,
+    trace: RequestTrace,
 }
 
 impl Batcher {
     #[must_use]
-    pub(crate) fn new(pool: Arc<Pool>) -> Self {
-        Self(pool)
+    pub(crate) fn new(db: Arc<Pool>, trace: RequestTrace) -> Self {
+        Self { db, trace }
     }
 
     pub fn db(&self) -> Result<indexer_core::db::PooledConnection, Error> {
-        self.0.get().map_err(|_| Error::ConnectionFailed)
+        self.db.get().map_err(|_| Error::ConnectionFailed)
     }
 }
 
 impl TwitterBatcher {
     #[must_use]
-    pub(crate) fn new(shared: Arc<SharedData>) -> Self {
-        Self { shared }
+    pub(crate) fn new(shared: Arc<SharedData>, trace: RequestTrace) -> Self {
+        Self { shared, trace }
     }
 
     #[inline]
@@ -159,6 +163,8 @@ where
     Batcher: TryBatchFn<K, V>,
 {
     async fn load(&mut self, keys: &[K]) -> BatchMap<K, V> {
+        let _span = self.trace.loader::<K, V>(keys.len());
+
         match TryBatchFn::load(self, keys).await {
             Ok(m) => m,
             Err(e) => keys.iter().cloned().map(|k| (k, Err(e.clone()))).collect(),
@@ -172,6 +178,8 @@ where
     TwitterBatcher: TryBatchFn<K, V>,
 {
     async fn load(&mut self, keys: &[K]) -> BatchMap<K, V> {
+        let _span = self.trace.loader::<K, V>(keys.len());
+
         match TryBatchFn::load(self, keys).await {
             Ok(m) => m,
             Err(e) => keys.iter().cloned().map(|k| (k, Err(e.clone()))).collect(),
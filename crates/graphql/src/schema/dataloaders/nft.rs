@@ -1,13 +1,17 @@
 use indexer_core::db::{queries, tables::metadata_collection_keys};
 use objects::{
     listing_receipt::ListingReceipt,
-    nft::{Collection, Nft, NftActivity, NftAttribute, NftCreator, NftFile, NftOwner},
+    nft::{
+        Collection, MetadataIssue, Nft, NftActivity, NftAttribute, NftCreator, NftFile,
+        NftImageDerivative, NftMetadataIntegrity, NftOwner, NftRarity,
+    },
     purchase_receipt::PurchaseReceipt,
 };
 use scalars::{markers::TokenMint, PublicKey};
 use tables::{
     attributes, current_metadata_owners, files, listing_receipts, metadata_creators,
-    metadata_jsons, metadatas, purchase_receipts, twitter_handle_name_services,
+    metadata_json_issues, metadata_jsons, metadatas, nft_rarities, purchase_receipts,
+    twitter_handle_name_services,
 };
 
 use super::prelude::*;
@@ -214,6 +218,7 @@ impl TryBatchFn<PublicKey<Nft>, Vec<NftFile>> for Batcher {
 
         let rows: Vec<models::MetadataFile> = files::table
             .filter(files::metadata_address.eq(any(addresses)))
+            .filter(files::source_uri.is_null())
             .load(&conn)
             .context("Failed to load NFT files")?;
 
@@ -224,6 +229,132 @@ impl TryBatchFn<PublicKey<Nft>, Vec<NftFile>> for Batcher {
     }
 }
 
+#[async_trait]
+impl TryBatchFn<PublicKey<Nft>, Vec<NftImageDerivative>> for Batcher {
+    async fn load(
+        &mut self,
+        addresses: &[PublicKey<Nft>],
+    ) -> TryBatchMap<PublicKey<Nft>, Vec<NftImageDerivative>> {
+        let conn = self.db()?;
+
+        let rows: Vec<models::MetadataFile> = files::table
+            .filter(files::metadata_address.eq(any(addresses)))
+            .filter(files::source_uri.is_not_null())
+            .load(&conn)
+            .context("Failed to load NFT image derivatives")?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(
+                |models::MetadataFile {
+                     metadata_address,
+                     uri,
+                     file_type,
+                     source_uri,
+                     width,
+                     ..
+                 }| {
+                    source_uri.zip(width).map(|(source_uri, width)| {
+                        (metadata_address, NftImageDerivative {
+                            source_uri: source_uri.into_owned(),
+                            uri: uri.into_owned(),
+                            file_type: file_type.into_owned(),
+                            width,
+                        })
+                    })
+                },
+            )
+            .batch(addresses))
+    }
+}
+
+#[async_trait]
+impl TryBatchFn<PublicKey<Nft>, Vec<MetadataIssue>> for Batcher {
+    async fn load(
+        &mut self,
+        addresses: &[PublicKey<Nft>],
+    ) -> TryBatchMap<PublicKey<Nft>, Vec<MetadataIssue>> {
+        let conn = self.db()?;
+
+        let rows: Vec<models::MetadataJsonIssue> = metadata_json_issues::table
+            .filter(metadata_json_issues::metadata_address.eq(any(addresses)))
+            .order_by(metadata_json_issues::path)
+            .load(&conn)
+            .context("Failed to load NFT metadata issues")?;
+
+        Ok(rows
+            .into_iter()
+            .map(|i| (i.metadata_address.clone(), i.try_into()))
+            .batch(addresses))
+    }
+}
+
+#[async_trait]
+impl TryBatchFn<PublicKey<Nft>, Vec<NftRarity>> for Batcher {
+    async fn load(
+        &mut self,
+        addresses: &[PublicKey<Nft>],
+    ) -> TryBatchMap<PublicKey<Nft>, Vec<NftRarity>> {
+        let conn = self.db()?;
+
+        let rows: Vec<models::NftRarity> = nft_rarities::table
+            .filter(nft_rarities::metadata_address.eq(any(addresses)))
+            .load(&conn)
+            .context("Failed to load NFT rarities")?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| (r.metadata_address.clone(), r.try_into()))
+            .batch(addresses))
+    }
+}
+
+#[async_trait]
+impl TryBatchFn<PublicKey<Nft>, Option<NftMetadataIntegrity>> for Batcher {
+    async fn load(
+        &mut self,
+        addresses: &[PublicKey<Nft>],
+    ) -> TryBatchMap<PublicKey<Nft>, Option<NftMetadataIntegrity>> {
+        let conn = self.db()?;
+
+        let rows: Vec<(
+            String,
+            Option<Vec<u8>>,
+            Option<String>,
+            Option<bool>,
+            Option<NaiveDateTime>,
+        )> = metadata_jsons::table
+            .filter(metadata_jsons::metadata_address.eq(any(addresses)))
+            .select((
+                metadata_jsons::metadata_address,
+                metadata_jsons::content_hash,
+                metadata_jsons::content_source,
+                metadata_jsons::cid_verified,
+                metadata_jsons::content_changed_at,
+            ))
+            .load(&conn)
+            .context("Failed to load NFT metadata integrity")?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(
+                |(address, content_hash, content_source, cid_verified, content_changed_at)| {
+                    content_hash
+                        .zip(content_source)
+                        .map(|(content_hash, content_source)| {
+                            (address, NftMetadataIntegrity {
+                                content_hash,
+                                content_source,
+                                cid_verified,
+                                content_changed_at,
+                            })
+                        })
+                },
+            )
+            .batch(addresses))
+    }
+}
+
 #[async_trait]
 impl TryBatchFn<PublicKey<Nft>, Option<Nft>> for Batcher {
     async fn load(
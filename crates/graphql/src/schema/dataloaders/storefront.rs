@@ -1,6 +1,9 @@
-use objects::storefront::{Storefront, StorefrontColumns};
-use scalars::PublicKey;
-use tables::storefronts;
+use objects::{
+    marketplace::StoreConfigIssue,
+    storefront::{Storefront, StorefrontColumns},
+};
+use scalars::{markers::StoreConfig, PublicKey};
+use tables::{store_config_json_issues, store_config_jsons, storefronts};
 
 use super::prelude::*;
 
@@ -24,3 +27,51 @@ impl TryBatchFn<PublicKey<Storefront>, Option<Storefront>> for Batcher {
             .batch(keys))
     }
 }
+
+#[async_trait]
+impl TryBatchFn<PublicKey<StoreConfig>, Vec<StoreConfigIssue>> for Batcher {
+    async fn load(
+        &mut self,
+        addresses: &[PublicKey<StoreConfig>],
+    ) -> TryBatchMap<PublicKey<StoreConfig>, Vec<StoreConfigIssue>> {
+        let conn = self.db()?;
+
+        let rows: Vec<models::StoreConfigJsonIssue> = store_config_json_issues::table
+            .filter(store_config_json_issues::config_address.eq(any(addresses)))
+            .load(&conn)
+            .context("Failed to load store config issues")?;
+
+        Ok(rows
+            .into_iter()
+            .map(|i| (i.config_address.clone(), StoreConfigIssue::from(i)))
+            .batch(addresses))
+    }
+}
+
+#[async_trait]
+impl TryBatchFn<PublicKey<Storefront>, Vec<StoreConfigIssue>> for Batcher {
+    async fn load(
+        &mut self,
+        addresses: &[PublicKey<Storefront>],
+    ) -> TryBatchMap<PublicKey<Storefront>, Vec<StoreConfigIssue>> {
+        let conn = self.db()?;
+
+        let rows: Vec<(Option<String>, models::StoreConfigJsonIssue)> =
+            store_config_json_issues::table
+                .inner_join(store_config_jsons::table.on(
+                    store_config_jsons::config_address.eq(store_config_json_issues::config_address),
+                ))
+                .filter(store_config_jsons::store_address.eq(any(addresses)))
+                .select((
+                    store_config_jsons::store_address,
+                    store_config_json_issues::all_columns,
+                ))
+                .load(&conn)
+                .context("Failed to load storefront config issues")?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(store, i)| store.map(|s| (s, StoreConfigIssue::from(i))))
+            .batch(addresses))
+    }
+}
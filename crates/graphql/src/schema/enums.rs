@@ -1,4 +1,4 @@
-use indexer_core::db;
+use indexer_core::{db, prelude::*};
 
 #[derive(Debug, Clone, Copy, juniper::GraphQLEnum)]
 #[graphql(description = "Sorts results ascending or descending")]
@@ -28,12 +28,17 @@ impl From<OrderDirection> for db::Order {
 }
 
 #[derive(Debug, Clone, Copy, juniper::GraphQLEnum)]
-#[graphql(description = "Sorts results by price or listed at")]
+#[graphql(description = "Sorts results by price, listed at, or rarity")]
 pub enum NftSort {
     #[graphql(name = "PRICE")]
     Price,
     #[graphql(name = "LISTED_AT")]
     ListedAt,
+    #[graphql(
+        name = "RARITY",
+        description = "Rarity rank within the collection, rarest first when descending.  Only supported for collection NFTs."
+    )]
+    Rarity,
 }
 
 impl From<NftSort> for db::custom_types::NftSort {
@@ -41,6 +46,49 @@ impl From<NftSort> for db::custom_types::NftSort {
         match other {
             NftSort::Price => Self::Price,
             NftSort::ListedAt => Self::ListedAt,
+            NftSort::Rarity => Self::Rarity,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, juniper::GraphQLEnum)]
+#[graphql(description = "Methods of scoring the rarity of an NFT within its collection")]
+pub enum RarityMethod {
+    #[graphql(
+        name = "STATISTICAL",
+        description = "The probability of the NFT's combination of traits.  Lower scores are rarer."
+    )]
+    Statistical,
+    #[graphql(
+        name = "INFORMATION_CONTENT",
+        description = "The information content of the NFT's traits, normalized by the collection's entropy.  Higher scores are rarer."
+    )]
+    InformationContent,
+    #[graphql(
+        name = "TRAIT_NORMALIZED",
+        description = "The sum of the inverse frequency of each trait, normalized by the number of values of that trait.  Higher scores are rarer."
+    )]
+    TraitNormalized,
+}
+
+impl From<RarityMethod> for indexer_core::rarity::RarityMethod {
+    fn from(other: RarityMethod) -> Self {
+        match other {
+            RarityMethod::Statistical => Self::Statistical,
+            RarityMethod::InformationContent => Self::InformationContent,
+            RarityMethod::TraitNormalized => Self::TraitNormalized,
+        }
+    }
+}
+
+impl From<indexer_core::rarity::RarityMethod> for RarityMethod {
+    fn from(other: indexer_core::rarity::RarityMethod) -> Self {
+        use indexer_core::rarity::RarityMethod as Method;
+
+        match other {
+            Method::Statistical => Self::Statistical,
+            Method::InformationContent => Self::InformationContent,
+            Method::TraitNormalized => Self::TraitNormalized,
         }
     }
 }
@@ -48,7 +96,10 @@ impl From<NftSort> for db::custom_types::NftSort {
 #[derive(Debug, Clone, Copy, juniper::GraphQLEnum)]
 #[graphql(description = "Sorts collection results")]
 pub enum CollectionSort {
-    #[graphql(name = "VOLUME")]
+    #[graphql(
+        name = "VOLUME",
+        description = "Sales volume normalized to lamports of SOL"
+    )]
     Volume,
     #[graphql(name = "FLOOR")]
     Floor,
@@ -56,6 +107,94 @@ pub enum CollectionSort {
     NumberSales,
     #[graphql(name = "MARKETCAP")]
     Marketcap,
+    #[graphql(
+        name = "UNIQUE_BUYERS",
+        description = "Number of distinct buyers.  Only supported with a window."
+    )]
+    UniqueBuyers,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, juniper::GraphQLEnum)]
+#[graphql(description = "Encodings of resized NFT images")]
+pub enum ImageFormat {
+    #[graphql(name = "WEBP")]
+    Webp,
+    #[graphql(name = "AVIF")]
+    Avif,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, juniper::GraphQLEnum)]
+#[graphql(description = "Integrity classification of an NFT's metadata JSON")]
+pub enum MetadataIntegrity {
+    #[graphql(
+        name = "IMMUTABLE",
+        description = "The document is stored on content-addressed or permanent storage"
+    )]
+    Immutable,
+    #[graphql(
+        name = "MUTABLE_STABLE",
+        description = "The document is mutable but has not changed since it was indexed"
+    )]
+    MutableStable,
+    #[graphql(
+        name = "MUTABLE_CHANGED",
+        description = "The document is mutable and has changed since it was indexed"
+    )]
+    MutableChanged,
+    #[graphql(
+        name = "TAMPERED",
+        description = "The document failed CID verification, or changed after it was indexed \
+                       despite being stored on permanent storage"
+    )]
+    Tampered,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, juniper::GraphQLEnum)]
+#[graphql(description = "Types of problem found in an NFT's metadata JSON")]
+pub enum MetadataIssueCode {
+    #[graphql(
+        name = "PARSE_ERROR",
+        description = "The document does not match the Metaplex metadata JSON model"
+    )]
+    ParseError,
+    #[graphql(name = "MISSING_IMAGE", description = "The document has no image URI")]
+    MissingImage,
+    #[graphql(
+        name = "INVALID_SELLER_FEE_BASIS_POINTS",
+        description = "seller_fee_basis_points is not an integer between 0 and 10000"
+    )]
+    InvalidSellerFeeBasisPoints,
+    #[graphql(
+        name = "CREATOR_SHARE_MISMATCH",
+        description = "properties.creators does not match the on-chain creators"
+    )]
+    CreatorShareMismatch,
+    #[graphql(
+        name = "INVALID_FILE",
+        description = "A file or image URI is missing or invalid"
+    )]
+    InvalidFile,
+    #[graphql(
+        name = "UNSUPPORTED_CATEGORY",
+        description = "properties.category is not a category defined by the standard"
+    )]
+    UnsupportedCategory,
+}
+
+impl std::str::FromStr for MetadataIssueCode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "parse_error" => Self::ParseError,
+            "missing_image" => Self::MissingImage,
+            "invalid_seller_fee_basis_points" => Self::InvalidSellerFeeBasisPoints,
+            "creator_share_mismatch" => Self::CreatorShareMismatch,
+            "invalid_file" => Self::InvalidFile,
+            "unsupported_category" => Self::UnsupportedCategory,
+            s => bail!("Unknown metadata issue code {:?}", s),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Copy, juniper::GraphQLEnum)]
@@ -69,6 +208,28 @@ pub enum CollectionInterval {
     Thirty,
 }
 
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Copy, juniper::GraphQLEnum)]
+#[graphql(description = "Time bucket widths of price candles")]
+pub enum CandleResolution {
+    #[graphql(name = "ONE_MINUTE")]
+    OneMinute,
+    #[graphql(name = "ONE_HOUR")]
+    OneHour,
+    #[graphql(name = "ONE_DAY")]
+    OneDay,
+}
+
+impl From<CandleResolution> for db::queries::charts::CandleResolution {
+    fn from(other: CandleResolution) -> Self {
+        match other {
+            CandleResolution::OneMinute => Self::Minute,
+            CandleResolution::OneHour => Self::Hour,
+            CandleResolution::OneDay => Self::Day,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, juniper::GraphQLEnum)]
 #[graphql(description = "Reward center mathematical operands")]
 pub enum PayoutOperation {
@@ -104,3 +265,91 @@ impl From<OfferType> for String {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, juniper::GraphQLEnum)]
+#[graphql(description = "Reasons a sale was flagged as a suspected wash trade")]
+pub enum WashTradeReason {
+    #[graphql(
+        name = "SELF_TRADE",
+        description = "The buyer and seller are the same wallet"
+    )]
+    SelfTrade,
+    #[graphql(
+        name = "ROUND_TRIP",
+        description = "The NFT was bought back by a wallet which sold it within the previous seven days"
+    )]
+    RoundTrip,
+    #[graphql(
+        name = "GRAPH_CONNECTIONS",
+        description = "The buyer and seller are connected in the wallet graph"
+    )]
+    GraphConnections,
+    #[graphql(
+        name = "ZERO_FEE",
+        description = "The sale was made through an auction house charging no fee"
+    )]
+    ZeroFee,
+}
+
+impl From<db::custom_types::WashTradeReason> for WashTradeReason {
+    fn from(reason: db::custom_types::WashTradeReason) -> Self {
+        use db::custom_types::WashTradeReason as Reason;
+
+        match reason {
+            Reason::SelfTrade => Self::SelfTrade,
+            Reason::RoundTrip => Self::RoundTrip,
+            Reason::GraphConnections => Self::GraphConnections,
+            Reason::ZeroFee => Self::ZeroFee,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, juniper::GraphQLEnum)]
+#[graphql(description = "Kinds of events in the provenance of an NFT")]
+pub enum NftEventKind {
+    #[graphql(name = "MINT", description = "The NFT was minted")]
+    Mint,
+    #[graphql(
+        name = "LISTING",
+        description = "The NFT was listed for sale on an auction house"
+    )]
+    Listing,
+    #[graphql(
+        name = "OFFER",
+        description = "An offer was made for the NFT on an auction house"
+    )]
+    Offer,
+    #[graphql(name = "SALE", description = "The NFT was sold on an auction house")]
+    Sale,
+    #[graphql(name = "TRANSFER", description = "The NFT moved to a new owner")]
+    Transfer,
+    #[graphql(
+        name = "CARDINAL_CLAIM",
+        description = "The NFT was claimed from a Cardinal token manager"
+    )]
+    CardinalClaim,
+    #[graphql(
+        name = "GENOPETS_RENT",
+        description = "Rent was paid for a Genopets habitat"
+    )]
+    GenopetsRent,
+    #[graphql(name = "BURN", description = "The NFT was burned")]
+    Burn,
+}
+
+impl From<db::custom_types::NftEventKind> for NftEventKind {
+    fn from(kind: db::custom_types::NftEventKind) -> Self {
+        use db::custom_types::NftEventKind as Kind;
+
+        match kind {
+            Kind::Mint => Self::Mint,
+            Kind::Listing => Self::Listing,
+            Kind::Offer => Self::Offer,
+            Kind::Sale => Self::Sale,
+            Kind::Transfer => Self::Transfer,
+            Kind::CardinalClaim => Self::CardinalClaim,
+            Kind::GenopetsRent => Self::GenopetsRent,
+            Kind::Burn => Self::Burn,
+        }
+    }
+}
@@ -1,14 +1,16 @@
 #![allow(clippy::module_name_repetitions)]
 
-use juniper::{EmptyMutation, EmptySubscription, RootNode};
+use juniper::{EmptySubscription, RootNode};
 
 mod context;
 pub(self) mod dataloaders;
 pub mod enums;
+mod mutation_root;
 pub(self) mod objects;
 mod query_root;
 pub(self) mod scalars;
 pub(self) mod services;
+mod traced;
 
 pub(self) mod prelude {
     pub use std::{collections::HashMap, sync::Arc};
@@ -22,7 +24,11 @@ pub(self) mod prelude {
         GraphQLObject,
     };
 
-    pub(super) use super::{context::AppContext, dataloaders, objects, scalars, services};
+    pub(super) use super::{
+        context::AppContext,
+        dataloaders, objects, scalars, services,
+        traced::{traced_object, Fields},
+    };
     pub(crate) use crate::SharedData;
 }
 
@@ -31,14 +37,14 @@ pub use context::AppContext;
 pub type Schema = RootNode<
     'static,
     query_root::QueryRoot,
-    EmptyMutation<AppContext>,
+    mutation_root::MutationRoot,
     EmptySubscription<AppContext>,
 >;
 
 pub fn create() -> Schema {
     Schema::new(
         query_root::QueryRoot,
-        EmptyMutation::new(),
+        mutation_root::MutationRoot,
         EmptySubscription::new(),
     )
 }
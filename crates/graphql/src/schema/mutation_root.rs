@@ -0,0 +1,228 @@
+use indexer_core::db::{
+    mutations::{
+        denylist::{self, Change, List},
+        metadata_json,
+    },
+    PooledConnection,
+};
+use objects::{denylist::DenylistAuditEntry, listing::Listing, nft::Nft, wallet::Wallet};
+use scalars::{markers::TokenMint, PublicKey};
+
+use super::prelude::*;
+
+pub struct MutationRoot;
+
+impl MutationRoot {
+    /// Return the authenticated admin's name and a writable database
+    /// connection, or an error if the request is not authorized
+    fn admin(ctx: &AppContext) -> FieldResult<(&str, PooledConnection)> {
+        let actor = ctx.admin.as_deref().ok_or_else(|| {
+            FieldError::new(
+                "Unauthorized",
+                graphql_value!({ "Authorization": "Bearer token required" }),
+            )
+        })?;
+
+        let db = ctx
+            .shared
+            .db_write
+            .as_ref()
+            .ok_or_else(|| anyhow!("Admin mutations are not enabled on this server"))?
+            .get()
+            .context("Failed to connect to writable DB")?;
+
+        Ok((actor, db))
+    }
+
+    fn add(
+        ctx: &AppContext,
+        list: List,
+        address: &str,
+        hard_ban: bool,
+        reason: &str,
+    ) -> FieldResult<DenylistAuditEntry> {
+        let (actor, db) = Self::admin(ctx)?;
+
+        let entry = denylist::add(
+            &db,
+            Change {
+                list,
+                address,
+                reason,
+                actor,
+            },
+            hard_ban,
+        )?;
+
+        info!(
+            "{} added {} to the {} denylist",
+            actor,
+            address,
+            list.as_str()
+        );
+
+        Ok(entry.into())
+    }
+
+    fn remove(
+        ctx: &AppContext,
+        list: List,
+        address: &str,
+        reason: &str,
+    ) -> FieldResult<Option<DenylistAuditEntry>> {
+        let (actor, db) = Self::admin(ctx)?;
+
+        let entry = denylist::remove(&db, Change {
+            list,
+            address,
+            reason,
+            actor,
+        })?;
+
+        if entry.is_some() {
+            info!(
+                "{} removed {} from the {} denylist",
+                actor,
+                address,
+                list.as_str()
+            );
+        }
+
+        Ok(entry.map(Into::into))
+    }
+}
+
+traced_object!(MutationRoot);
+
+#[graphql_object(Context = AppContext, name = "MutationRoot")]
+impl Fields<MutationRoot> {
+    #[graphql(
+        description = "Add a storefront owner to the store denylist, or update the hard-ban \
+                             flag of an existing entry.  Requires admin authorization."
+    )]
+    fn add_storefront_to_denylist(
+        &self,
+        context: &AppContext,
+        #[graphql(description = "Address of the storefront owner")] owner_address: PublicKey<
+            Wallet,
+        >,
+        #[graphql(
+            description = "Mark the entry as a hard ban, reported by the `denylist` query",
+            default = true
+        )]
+        hard_ban: bool,
+        #[graphql(description = "Justification recorded in the audit log")] reason: String,
+    ) -> FieldResult<DenylistAuditEntry> {
+        MutationRoot::add(
+            context,
+            List::Store,
+            owner_address.as_ref(),
+            hard_ban,
+            &reason,
+        )
+    }
+
+    #[graphql(
+        description = "Remove a storefront owner from the store denylist.  Returns null \
+                             if the owner was not denylisted.  Requires admin authorization."
+    )]
+    fn remove_storefront_from_denylist(
+        &self,
+        context: &AppContext,
+        #[graphql(description = "Address of the storefront owner")] owner_address: PublicKey<
+            Wallet,
+        >,
+        #[graphql(description = "Justification recorded in the audit log")] reason: String,
+    ) -> FieldResult<Option<DenylistAuditEntry>> {
+        MutationRoot::remove(context, List::Store, owner_address.as_ref(), &reason)
+    }
+
+    #[graphql(
+        description = "Add a listing to the listing denylist, or update the hard-ban flag \
+                             of an existing entry.  Requires admin authorization."
+    )]
+    fn add_listing_to_denylist(
+        &self,
+        context: &AppContext,
+        #[graphql(description = "Address of the listing")] listing_address: PublicKey<Listing>,
+        #[graphql(
+            description = "Mark the entry as a hard ban, reported by the `denylist` query",
+            default = true
+        )]
+        hard_ban: bool,
+        #[graphql(description = "Justification recorded in the audit log")] reason: String,
+    ) -> FieldResult<DenylistAuditEntry> {
+        MutationRoot::add(
+            context,
+            List::Listing,
+            listing_address.as_ref(),
+            hard_ban,
+            &reason,
+        )
+    }
+
+    #[graphql(
+        description = "Remove a listing from the listing denylist.  Returns null if the \
+                             listing was not denylisted.  Requires admin authorization."
+    )]
+    fn remove_listing_from_denylist(
+        &self,
+        context: &AppContext,
+        #[graphql(description = "Address of the listing")] listing_address: PublicKey<Listing>,
+        #[graphql(description = "Justification recorded in the audit log")] reason: String,
+    ) -> FieldResult<Option<DenylistAuditEntry>> {
+        MutationRoot::remove(context, List::Listing, listing_address.as_ref(), &reason)
+    }
+
+    #[graphql(
+        description = "Schedule an immediate re-fetch of the off-chain metadata JSON for the \
+                             given mints.  Returns the addresses of the NFTs scheduled.  Requires \
+                             admin authorization."
+    )]
+    fn refresh_nft_metadata(
+        &self,
+        context: &AppContext,
+        #[graphql(description = "Mint addresses of the NFTs to refresh")] mint_addresses: Vec<
+            PublicKey<TokenMint>,
+        >,
+    ) -> FieldResult<Vec<PublicKey<Nft>>> {
+        let (actor, db) = MutationRoot::admin(context)?;
+
+        let mints: Vec<String> = mint_addresses.into_iter().map(Into::into).collect();
+        let addresses = metadata_json::request_refetch(&db, &mints)?;
+
+        info!(
+            "{} requested a metadata JSON re-fetch for {} NFT(s)",
+            actor,
+            addresses.len()
+        );
+
+        Ok(addresses.into_iter().map(Into::into).collect())
+    }
+
+    #[graphql(
+        description = "Clear the recorded content change of the off-chain metadata JSON for the \
+                             given mints, e.g. once the change has been reviewed.  Returns the \
+                             addresses of the NFTs cleared.  Requires admin authorization."
+    )]
+    fn clear_nft_metadata_change(
+        &self,
+        context: &AppContext,
+        #[graphql(description = "Mint addresses of the NFTs to clear")] mint_addresses: Vec<
+            PublicKey<TokenMint>,
+        >,
+    ) -> FieldResult<Vec<PublicKey<Nft>>> {
+        let (actor, db) = MutationRoot::admin(context)?;
+
+        let mints: Vec<String> = mint_addresses.into_iter().map(Into::into).collect();
+        let addresses = metadata_json::clear_content_change(&db, &mints)?;
+
+        info!(
+            "{} cleared the metadata JSON content change of {} NFT(s)",
+            actor,
+            addresses.len()
+        );
+
+        Ok(addresses.into_iter().map(Into::into).collect())
+    }
+}
@@ -18,11 +18,15 @@ pub struct AhListing {
     pub trade_state_bump: i32,
     pub created_at: DateTime<Utc>,
     pub canceled_at: Option<DateTime<Utc>>,
+    pub currency_mint: Option<String>,
+    pub currency_decimals: Option<i32>,
 }
 
-#[graphql_object(Context = AppContext)]
+traced_object!(AhListing);
+
+#[graphql_object(Context = AppContext, name = "AhListing")]
 #[graphql(description = "Auction house listing")]
-impl AhListing {
+impl Fields<AhListing> {
     fn id(&self) -> &Uuid {
         &self.id
     }
@@ -47,6 +51,18 @@ impl AhListing {
         self.price
     }
 
+    #[graphql(description = "The mint of the currency the price is denominated in, if known")]
+    fn currency_mint(&self) -> Option<&str> {
+        self.currency_mint.as_deref()
+    }
+
+    #[graphql(
+        description = "The number of decimals of the currency the price is denominated in, if known"
+    )]
+    fn currency_decimals(&self) -> Option<i32> {
+        self.currency_decimals
+    }
+
     fn token_size(&self) -> i32 {
         self.token_size
     }
@@ -99,6 +115,8 @@ impl<'a> TryFrom<models::Listing<'a>> for AhListing {
             trade_state_bump,
             created_at,
             canceled_at,
+            currency_mint,
+            currency_decimals,
             ..
         }: models::Listing,
     ) -> Result<Self, Self::Error> {
@@ -115,6 +133,8 @@ impl<'a> TryFrom<models::Listing<'a>> for AhListing {
             trade_state_bump: trade_state_bump.into(),
             created_at: DateTime::from_utc(created_at, Utc),
             canceled_at: canceled_at.map(|c| DateTime::from_utc(c, Utc)),
+            currency_mint: currency_mint.map(Cow::into_owned),
+            currency_decimals: currency_decimals.map(Into::into),
         })
     }
 }
@@ -21,9 +21,11 @@ pub struct Offer {
     pub token_size: i32,
 }
 
-#[graphql_object(Context = AppContext)]
+traced_object!(Offer);
+
+#[graphql_object(Context = AppContext, name = "Offer")]
 #[graphql(description = "Auction house offer")]
-impl Offer {
+impl Fields<Offer> {
     fn id(&self) -> &Uuid {
         &self.id
     }
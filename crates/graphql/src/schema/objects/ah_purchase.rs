@@ -3,6 +3,7 @@ use objects::{auction_house::AuctionHouse, nft::Nft, wallet::Wallet};
 use scalars::{PublicKey, U64};
 
 use super::prelude::*;
+use crate::schema::enums::WashTradeReason;
 
 #[derive(Debug, Clone)]
 pub struct Purchase {
@@ -15,11 +16,15 @@ pub struct Purchase {
     pub token_size: i32,
     pub price: U64,
     pub created_at: DateTime<Utc>,
+    pub currency_mint: Option<String>,
+    pub currency_decimals: Option<i32>,
 }
 
-#[graphql_object(Context = AppContext)]
+traced_object!(Purchase);
+
+#[graphql_object(Context = AppContext, name = "Purchase")]
 #[graphql(description = "Auction house purchase")]
-impl Purchase {
+impl Fields<Purchase> {
     fn id(&self) -> &Uuid {
         &self.id
     }
@@ -40,6 +45,18 @@ impl Purchase {
         self.price
     }
 
+    #[graphql(description = "The mint of the currency the price is denominated in, if known")]
+    fn currency_mint(&self) -> Option<&str> {
+        self.currency_mint.as_deref()
+    }
+
+    #[graphql(
+        description = "The number of decimals of the currency the price is denominated in, if known"
+    )]
+    fn currency_decimals(&self) -> Option<i32> {
+        self.currency_decimals
+    }
+
     fn created_at(&self) -> DateTime<Utc> {
         self.created_at
     }
@@ -66,6 +83,16 @@ impl Purchase {
             .await
             .map_err(Into::into)
     }
+
+    #[graphql(
+        description = "The reasons this sale was flagged as a suspected wash trade, or an empty list if it was not flagged"
+    )]
+    pub async fn wash_trade_reasons(&self, ctx: &AppContext) -> FieldResult<Vec<WashTradeReason>> {
+        ctx.wash_trade_reasons_loader
+            .load(self.id)
+            .await
+            .map_err(Into::into)
+    }
 }
 
 impl<'a> TryFrom<models::Purchase<'a>> for Purchase {
@@ -81,6 +108,8 @@ impl<'a> TryFrom<models::Purchase<'a>> for Purchase {
             token_size,
             price,
             created_at,
+            currency_mint,
+            currency_decimals,
             ..
         }: models::Purchase,
     ) -> Result<Self, Self::Error> {
@@ -94,6 +123,8 @@ impl<'a> TryFrom<models::Purchase<'a>> for Purchase {
             marketplace_program_address: marketplace_program.into_owned(),
             created_at: DateTime::from_utc(created_at, Utc),
             token_size: token_size.try_into()?,
+            currency_mint: currency_mint.map(Cow::into_owned),
+            currency_decimals: currency_decimals.map(Into::into),
         })
     }
 }
@@ -6,8 +6,12 @@ pub struct AttributeVariant {
     pub count: i32,
 }
 
-#[derive(Debug, GraphQLObject, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, GraphQLObject, PartialEq)]
 pub struct AttributeGroup {
     pub name: String,
     pub variants: Vec<AttributeVariant>,
+    #[graphql(description = "The smallest numeric value of this attribute, if any are numeric")]
+    pub min_value: Option<f64>,
+    #[graphql(description = "The largest numeric value of this attribute, if any are numeric")]
+    pub max_value: Option<f64>,
 }
@@ -62,8 +62,10 @@ impl<'a> From<models::AuctionHouse<'a>> for AuctionHouse {
     }
 }
 
-#[graphql_object(Context = AppContext)]
-impl AuctionHouse {
+traced_object!(AuctionHouse);
+
+#[graphql_object(Context = AppContext, name = "AuctionHouse")]
+impl Fields<AuctionHouse> {
     pub async fn stats(&self, context: &AppContext) -> FieldResult<Option<MintStats>> {
         context
             .mint_stats_loader
@@ -72,7 +74,7 @@ impl AuctionHouse {
             .map_err(Into::into)
     }
 
-    pub fn address(&self) -> &PublicKey<Self> {
+    pub fn address(&self) -> &PublicKey<AuctionHouse> {
         &self.address
     }
 
@@ -23,9 +23,11 @@ pub struct BidReceipt {
     pub bump: i32,
 }
 
-#[graphql_object(Context = AppContext)]
+traced_object!(BidReceipt);
+
+#[graphql_object(Context = AppContext, name = "BidReceipt")]
 #[graphql(description = "Auction house bid receipt")]
-impl BidReceipt {
+impl Fields<BidReceipt> {
     fn address(&self) -> &str {
         &self.address
     }
@@ -27,8 +27,10 @@ pub struct CandyMachine {
     pub items_available: U64,
 }
 
-#[graphql_object(Context = AppContext)]
-impl CandyMachine {
+traced_object!(CandyMachine);
+
+#[graphql_object(Context = AppContext, name = "CandyMachine")]
+impl Fields<CandyMachine> {
     pub fn address(&self) -> &PublicKey<CandyMachine> {
         &self.address
     }
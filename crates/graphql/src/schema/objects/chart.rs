@@ -16,23 +16,143 @@ pub struct PriceChart {
 pub struct PricePoint {
     pub price: U64,
     pub date: DateTime<Utc>,
+    #[graphql(
+        description = "The price in lamports of SOL, or null if any of the prices it was computed from could not be converted"
+    )]
+    pub normalized_price: Option<U64>,
+}
+
+#[derive(Debug, Clone, GraphQLObject)]
+#[graphql(
+    description = "Open, high, low, and close sale prices of a collection within a time bucket"
+)]
+pub struct PriceCandle {
+    #[graphql(description = "The start of the time bucket")]
+    pub bucket_start: DateTime<Utc>,
+    #[graphql(
+        description = "The price of the first sale in the bucket, or null if the bucket contains sales in more than one currency"
+    )]
+    pub open: Option<U64>,
+    #[graphql(
+        description = "The highest sale price in the bucket, or null if the bucket contains sales in more than one currency"
+    )]
+    pub high: Option<U64>,
+    #[graphql(
+        description = "The lowest sale price in the bucket, or null if the bucket contains sales in more than one currency"
+    )]
+    pub low: Option<U64>,
+    #[graphql(
+        description = "The price of the last sale in the bucket, or null if the bucket contains sales in more than one currency"
+    )]
+    pub close: Option<U64>,
+    #[graphql(
+        description = "The total price of all sales in the bucket, or null if the bucket contains sales in more than one currency"
+    )]
+    pub volume: Option<U64>,
+    #[graphql(
+        description = "The mint of the currency the native prices are denominated in, or null if the bucket contains sales in more than one currency"
+    )]
+    pub currency_mint: Option<String>,
+    #[graphql(
+        description = "The price of the first sale in the bucket in lamports of SOL, or null if any sale could not be converted"
+    )]
+    pub normalized_open: Option<U64>,
+    #[graphql(
+        description = "The highest sale price in the bucket in lamports of SOL, or null if any sale could not be converted"
+    )]
+    pub normalized_high: Option<U64>,
+    #[graphql(
+        description = "The lowest sale price in the bucket in lamports of SOL, or null if any sale could not be converted"
+    )]
+    pub normalized_low: Option<U64>,
+    #[graphql(
+        description = "The price of the last sale in the bucket in lamports of SOL, or null if any sale could not be converted"
+    )]
+    pub normalized_close: Option<U64>,
+    #[graphql(
+        description = "The total price of all sales in the bucket in lamports of SOL, or null if any sale could not be converted"
+    )]
+    pub normalized_volume: Option<U64>,
+    #[graphql(description = "The number of sales in the bucket")]
+    pub sales: i32,
+    #[graphql(description = "The number of listings created in the bucket")]
+    pub listings: i32,
+    #[graphql(
+        description = "The lowest price of the listings created in the bucket, or null if the bucket contains trades in more than one currency"
+    )]
+    pub listing_low: Option<U64>,
+    #[graphql(
+        description = "The lowest price of the listings created in the bucket in lamports of SOL, or null if none could be converted"
+    )]
+    pub normalized_listing_low: Option<U64>,
+}
+
+impl TryFrom<models::PriceCandle> for PriceCandle {
+    type Error = Error;
+
+    fn try_from(
+        models::PriceCandle {
+            bucket_start,
+            open,
+            high,
+            low,
+            close,
+            volume,
+            currency_mint,
+            normalized_open,
+            normalized_high,
+            normalized_low,
+            normalized_close,
+            normalized_volume,
+            sales,
+            listings,
+            listing_low,
+            normalized_listing_low,
+        }: models::PriceCandle,
+    ) -> Result<Self> {
+        Ok(Self {
+            bucket_start: DateTime::from_utc(bucket_start, Utc),
+            open: open.map(TryInto::try_into).transpose()?,
+            high: high.map(TryInto::try_into).transpose()?,
+            low: low.map(TryInto::try_into).transpose()?,
+            close: close.map(TryInto::try_into).transpose()?,
+            volume: volume.map(TryInto::try_into).transpose()?,
+            currency_mint,
+            normalized_open: normalized_open.map(TryInto::try_into).transpose()?,
+            normalized_high: normalized_high.map(TryInto::try_into).transpose()?,
+            normalized_low: normalized_low.map(TryInto::try_into).transpose()?,
+            normalized_close: normalized_close.map(TryInto::try_into).transpose()?,
+            normalized_volume: normalized_volume.map(TryInto::try_into).transpose()?,
+            sales: sales.try_into()?,
+            listings: listings.try_into()?,
+            listing_low: listing_low.map(TryInto::try_into).transpose()?,
+            normalized_listing_low: normalized_listing_low.map(TryInto::try_into).transpose()?,
+        })
+    }
 }
 
 impl<'a> TryFrom<models::PricePoint> for PricePoint {
     type Error = std::num::TryFromIntError;
 
     fn try_from(
-        models::PricePoint { price, date }: models::PricePoint,
+        models::PricePoint {
+            price,
+            date,
+            normalized_price,
+        }: models::PricePoint,
     ) -> Result<Self, Self::Error> {
         Ok(Self {
             price: price.try_into()?,
             date: DateTime::from_utc(date, Utc),
+            normalized_price: normalized_price.map(TryInto::try_into).transpose()?,
         })
     }
 }
 
-#[graphql_object(Context = AppContext)]
-impl PriceChart {
+traced_object!(PriceChart);
+
+#[graphql_object(Context = AppContext, name = "PriceChart")]
+impl Fields<PriceChart> {
     pub fn listing_floor(&self, ctx: &AppContext) -> FieldResult<Vec<PricePoint>> {
         let conn = ctx.shared.db.get()?;
         let rows = charts::floor_prices(
@@ -65,7 +185,14 @@ impl PriceChart {
             .map_err(Into::into)
     }
 
-    pub fn total_volume(&self, ctx: &AppContext) -> FieldResult<Vec<PricePoint>> {
+    #[graphql(arguments(exclude_flagged(
+        description = "Leave sales flagged as suspected wash trades out of the volume, defaults to false"
+    )))]
+    pub fn total_volume(
+        &self,
+        ctx: &AppContext,
+        exclude_flagged: Option<bool>,
+    ) -> FieldResult<Vec<PricePoint>> {
         let conn = ctx.shared.db.get()?;
         let rows = charts::total_volume_prices(
             &conn,
@@ -73,6 +200,7 @@ impl PriceChart {
             &self.creators,
             self.start_date.naive_utc(),
             self.end_date.naive_utc(),
+            exclude_flagged.unwrap_or(false),
         )?;
 
         rows.into_iter()
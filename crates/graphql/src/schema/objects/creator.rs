@@ -28,8 +28,10 @@ impl CreatorCounts {
     }
 }
 
-#[graphql_object(Context = AppContext)]
-impl CreatorCounts {
+traced_object!(CreatorCounts);
+
+#[graphql_object(Context = AppContext, name = "CreatorCounts")]
+impl Fields<CreatorCounts> {
     fn creations(&self, context: &AppContext) -> FieldResult<i32> {
         let conn = context.shared.db.get()?;
 
@@ -43,24 +45,37 @@ impl CreatorCounts {
     }
 }
 
-#[graphql_object(Context = AppContext)]
-impl Creator {
+traced_object!(Creator);
+
+#[graphql_object(Context = AppContext, name = "Creator")]
+impl Fields<Creator> {
     fn address(&self) -> &str {
         &self.address
     }
 
     fn counts(&self) -> CreatorCounts {
-        CreatorCounts::new(self.clone())
+        CreatorCounts::new(Creator::clone(self))
     }
 
-    #[graphql(arguments(auction_houses(description = "Auction house public keys")))]
+    #[graphql(arguments(
+        auction_houses(description = "Auction house public keys"),
+        exclude_flagged(
+            description = "Leave sales flagged as suspected wash trades out of the average price and volume, defaults to false"
+        ),
+    ))]
     pub async fn stats(
         &self,
         auction_houses: Vec<PublicKey<AuctionHouse>>,
+        exclude_flagged: Option<bool>,
         ctx: &AppContext,
     ) -> FieldResult<Vec<MintStats>> {
         let conn = ctx.shared.db.get()?;
-        let rows = stats::collection(&conn, auction_houses, &self.address)?;
+        let rows = stats::collection(
+            &conn,
+            auction_houses,
+            &self.address,
+            exclude_flagged.unwrap_or(false),
+        )?;
 
         rows.into_iter()
             .map(TryInto::try_into)
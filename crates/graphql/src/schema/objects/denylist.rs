@@ -1,4 +1,7 @@
-use indexer_core::db::queries::{listing_denylist, store_denylist};
+use indexer_core::{
+    db::queries::{listing_denylist, store_denylist},
+    uuid::Uuid,
+};
 use objects::{listing::Listing, storefront::Storefront};
 use scalars::PublicKey;
 
@@ -8,8 +11,10 @@ use super::prelude::*;
 /// Deny-list for Holaplex storefronts and listings
 pub struct Denylist;
 
-#[graphql_object(Context = AppContext)]
-impl Denylist {
+traced_object!(Denylist);
+
+#[graphql_object(Context = AppContext, name = "Denylist")]
+impl Fields<Denylist> {
     fn storefronts(&self, ctx: &AppContext) -> FieldResult<Vec<PublicKey<Storefront>>> {
         let db = ctx.shared.db.get().context("Failed to connect to DB")?;
 
@@ -26,3 +31,47 @@ impl Denylist {
             .map_err(Into::into)
     }
 }
+
+#[derive(Debug, Clone, GraphQLObject)]
+#[graphql(description = "A change made to the storefront or listing denylist by an admin")]
+pub struct DenylistAuditEntry {
+    pub id: Uuid,
+    #[graphql(description = "The denylist modified, either `store` or `listing`")]
+    pub list: String,
+    #[graphql(description = "The storefront owner or listing address added or removed")]
+    pub address: String,
+    #[graphql(description = "The change made, either `add` or `remove`")]
+    pub action: String,
+    #[graphql(description = "The hard-ban flag set when adding an entry")]
+    pub hard_ban: Option<bool>,
+    pub reason: String,
+    #[graphql(description = "The admin who made the change")]
+    pub actor: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'a> From<models::DenylistAuditEntry<'a>> for DenylistAuditEntry {
+    fn from(
+        models::DenylistAuditEntry {
+            id,
+            list,
+            address,
+            action,
+            hard_ban,
+            reason,
+            actor,
+            created_at,
+        }: models::DenylistAuditEntry,
+    ) -> Self {
+        Self {
+            id: id.unwrap_or_default(),
+            list: list.into_owned(),
+            address: address.into_owned(),
+            action: action.into_owned(),
+            hard_ban,
+            reason: reason.into_owned(),
+            actor: actor.into_owned(),
+            created_at: DateTime::from_utc(created_at, Utc),
+        }
+    }
+}
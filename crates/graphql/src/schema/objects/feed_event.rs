@@ -26,8 +26,10 @@ pub struct FollowEvent {
     graph_connection_address: PublicKey<GraphConnection>,
 }
 
-#[graphql_object(Context = AppContext)]
-impl FollowEvent {
+traced_object!(FollowEvent);
+
+#[graphql_object(Context = AppContext, name = "FollowEvent")]
+impl Fields<FollowEvent> {
     fn created_at(&self) -> DateTime<Utc> {
         self.created_at
     }
@@ -79,8 +81,10 @@ pub struct PurchaseEvent {
     purchase_id: Uuid,
 }
 
-#[graphql_object(Context = AppContext)]
-impl PurchaseEvent {
+traced_object!(PurchaseEvent);
+
+#[graphql_object(Context = AppContext, name = "PurchaseEvent")]
+impl Fields<PurchaseEvent> {
     fn created_at(&self) -> DateTime<Utc> {
         self.created_at
     }
@@ -133,8 +137,10 @@ pub struct OfferEvent {
     lifecycle: String,
 }
 
-#[graphql_object(Context = AppContext)]
-impl OfferEvent {
+traced_object!(OfferEvent);
+
+#[graphql_object(Context = AppContext, name = "OfferEvent")]
+impl Fields<OfferEvent> {
     fn created_at(&self) -> DateTime<Utc> {
         self.created_at
     }
@@ -191,8 +197,10 @@ pub struct ListingEvent {
     lifecycle: String,
 }
 
-#[graphql_object(Context = AppContext)]
-impl ListingEvent {
+traced_object!(ListingEvent);
+
+#[graphql_object(Context = AppContext, name = "ListingEvent")]
+impl Fields<ListingEvent> {
     fn created_at(&self) -> DateTime<Utc> {
         self.created_at
     }
@@ -239,8 +247,10 @@ impl ListingEvent {
     }
 }
 
-#[graphql_object(Context = AppContext)]
-impl MintEvent {
+traced_object!(MintEvent);
+
+#[graphql_object(Context = AppContext, name = "MintEvent")]
+impl Fields<MintEvent> {
     fn created_at(&self) -> DateTime<Utc> {
         self.created_at
     }
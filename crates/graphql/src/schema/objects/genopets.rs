@@ -1,11 +1,9 @@
-use indexer_core::{
-    db::{models, queries::genopets},
-    meilisearch::IndirectMetadataDocument,
-};
+use indexer_core::db::{models, queries::genopets};
 use objects::{nft::Nft, wallet::Wallet};
 use scalars::{markers::TokenMint, PublicKey, I64, U64};
 
 use super::prelude::*;
+use crate::search::SearchIndex;
 
 #[derive(Debug, GraphQLInputObject)]
 /// Input parameters for the `genoHabitatsCounted` query
@@ -111,16 +109,16 @@ impl GenoHabitatsParams {
             (None, Some(ref t)) => Some({
                 ctx.shared
                     .search
-                    .index("geno_habitats")
-                    .search()
-                    .with_query(t)
-                    .with_limit(ctx.shared.pre_query_search_limit)
-                    .execute::<IndirectMetadataDocument>()
+                    .search(
+                        SearchIndex::GenoHabitats,
+                        t,
+                        0,
+                        ctx.shared.pre_query_search_limit,
+                    )
                     .await
                     .context("Failed to load search results for Genopets habitats")?
-                    .hits
                     .into_iter()
-                    .map(|r| r.result.mint_address.into())
+                    .filter_map(|r| r.get("mint_address")?.as_str().map(|m| m.to_owned().into()))
                     .collect()
             }),
             (Some(_), Some(_)) => {
@@ -260,8 +258,10 @@ pub struct GenoHabitat {
     pub has_max_ki: Option<bool>,
 }
 
-#[graphql_object(Context = AppContext)]
-impl GenoHabitat {
+traced_object!(GenoHabitat);
+
+#[graphql_object(Context = AppContext, name = "GenoHabitat")]
+impl Fields<GenoHabitat> {
     pub fn address(&self) -> &PublicKey<GenoHabitat> {
         &self.address
     }
@@ -11,8 +11,10 @@ pub struct GraphConnection {
     pub connected_at: DateTime<Utc>,
 }
 
-#[graphql_object(Context = AppContext)]
-impl GraphConnection {
+traced_object!(GraphConnection);
+
+#[graphql_object(Context = AppContext, name = "GraphConnection")]
+impl Fields<GraphConnection> {
     pub fn address(&self) -> &str {
         &self.address
     }
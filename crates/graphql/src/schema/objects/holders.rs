@@ -0,0 +1,156 @@
+use indexer_core::holders;
+use objects::wallet::Wallet;
+use scalars::{PublicKey, U64};
+
+use super::prelude::*;
+
+#[derive(Debug, Clone, GraphQLObject)]
+#[graphql(
+    description = "Distribution and concentration of the wallets holding a collection",
+    Context = AppContext
+)]
+pub struct HolderStats {
+    #[graphql(description = "The number of wallets holding at least one NFT")]
+    pub holder_count: U64,
+    #[graphql(description = "The number of unburned NFTs held")]
+    pub supply: U64,
+    #[graphql(description = "The number of unburned NFTs with an active listing")]
+    pub listed_count: U64,
+    #[graphql(description = "The fraction of the supply with an active listing")]
+    pub listed_share: f64,
+    #[graphql(
+        description = "The Gini coefficient of the NFTs held per wallet, from 0 when every holder holds the same number towards 1 when one holder holds nearly all of them"
+    )]
+    pub gini: f64,
+    #[graphql(
+        description = "The Herfindahl-Hirschman index of the NFTs held per wallet: the sum of the squared fraction of the supply held by each holder, from 1/n for n equal holders to 1 for a single holder"
+    )]
+    pub hhi: f64,
+    #[graphql(description = "The number of holders and NFTs held per range of holding sizes")]
+    pub distribution: Vec<HolderBucket>,
+    #[graphql(description = "The largest holders, largest first")]
+    pub top_holders: Vec<TopHolder>,
+    #[graphql(
+        description = "New and churned holders per day with any, oldest first.  Days before holders were first tracked are not included."
+    )]
+    pub history: Vec<HolderDay>,
+}
+
+#[derive(Debug, Clone, GraphQLObject)]
+#[graphql(description = "The wallets holding a range of NFT counts from a collection")]
+pub struct HolderBucket {
+    #[graphql(description = "The smallest holding in the range")]
+    pub min: U64,
+    #[graphql(description = "The largest holding in the range, or null if unbounded")]
+    pub max: Option<U64>,
+    #[graphql(description = "The number of wallets with a holding in the range")]
+    pub holders: U64,
+    #[graphql(description = "The total number of NFTs held by these wallets")]
+    pub nfts: U64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TopHolder {
+    address: PublicKey<Wallet>,
+    nft_count: U64,
+    share: f64,
+}
+
+traced_object!(TopHolder);
+
+#[graphql_object(Context = AppContext, name = "TopHolder")]
+#[graphql(description = "A wallet holding NFTs of a collection")]
+impl Fields<TopHolder> {
+    fn address(&self) -> &PublicKey<Wallet> {
+        &self.address
+    }
+
+    pub async fn wallet(&self, ctx: &AppContext) -> FieldResult<Wallet> {
+        ctx.wallet(self.address.clone()).await.map_err(Into::into)
+    }
+
+    #[graphql(description = "The number of NFTs of the collection held")]
+    fn nft_count(&self) -> U64 {
+        self.nft_count
+    }
+
+    #[graphql(description = "The fraction of the supply held")]
+    fn share(&self) -> f64 {
+        self.share
+    }
+}
+
+#[derive(Debug, Clone, GraphQLObject)]
+#[graphql(description = "Changes in the holders of a collection during a day")]
+pub struct HolderDay {
+    #[graphql(description = "The start of the day")]
+    pub date: DateTime<Utc>,
+    #[graphql(description = "The number of wallets which started holding the collection")]
+    pub new_holders: U64,
+    #[graphql(description = "The number of wallets which stopped holding the collection")]
+    pub churned_holders: U64,
+}
+
+impl HolderStats {
+    /// Compute holder statistics from the holdings of every holder, largest
+    /// first, keeping the first `top` as the top holders
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(
+        holdings: Vec<models::CollectionHolder>,
+        days: Vec<models::CollectionHolderDay>,
+        listed_count: i64,
+        top: usize,
+    ) -> Result<Self> {
+        let counts = holdings
+            .iter()
+            .map(|h| h.nft_count.try_into())
+            .collect::<Result<Vec<u64>, _>>()?;
+        let supply: u64 = counts.iter().sum();
+        let share = |n: u64| {
+            if supply == 0 {
+                0.0
+            } else {
+                n as f64 / supply as f64
+            }
+        };
+        let listed: u64 = listed_count.try_into()?;
+
+        Ok(Self {
+            holder_count: u64::try_from(counts.len())?.into(),
+            supply: supply.into(),
+            listed_count: listed.into(),
+            listed_share: share(listed),
+            gini: holders::gini(&counts),
+            hhi: holders::hhi(&counts),
+            distribution: holders::distribution(&counts)
+                .into_iter()
+                .map(|b| HolderBucket {
+                    min: b.min.into(),
+                    max: b.max.map(Into::into),
+                    holders: b.holders.into(),
+                    nfts: b.nfts.into(),
+                })
+                .collect(),
+            top_holders: holdings
+                .into_iter()
+                .zip(counts)
+                .take(top)
+                .map(|(h, n)| TopHolder {
+                    address: h.owner_address.into_owned().into(),
+                    nft_count: n.into(),
+                    share: share(n),
+                })
+                .collect(),
+            history: days
+                .into_iter()
+                .map(|d| {
+                    Ok(HolderDay {
+                        date: DateTime::from_utc(d.day.and_hms(0, 0, 0), Utc),
+                        new_holders: d.new_holders.try_into()?,
+                        churned_holders: d.churned_holders.try_into()?,
+                    })
+                })
+                .collect::<Result<_>>()?,
+        })
+    }
+}
@@ -37,8 +37,10 @@ impl<'a> TryFrom<models::Bid<'a>> for Bid {
     }
 }
 
-#[graphql_object(Context = AppContext)]
-impl Bid {
+traced_object!(Bid);
+
+#[graphql_object(Context = AppContext, name = "Bid")]
+impl Fields<Bid> {
     pub fn listing_address(&self) -> &str {
         &self.listing_address
     }
@@ -138,8 +140,10 @@ impl Listing {
     }
 }
 
-#[graphql_object(Context = AppContext)]
-impl Listing {
+traced_object!(Listing);
+
+#[graphql_object(Context = AppContext, name = "Listing")]
+impl Fields<Listing> {
     pub fn address(&self) -> &str {
         &self.address
     }
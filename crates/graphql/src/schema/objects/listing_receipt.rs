@@ -22,9 +22,11 @@ pub struct ListingReceipt {
     pub bump: i32,
 }
 
-#[graphql_object(Context = AppContext)]
+traced_object!(ListingReceipt);
+
+#[graphql_object(Context = AppContext, name = "ListingReceipt")]
 #[graphql(description = "An NFT listing receipt")]
-impl ListingReceipt {
+impl Fields<ListingReceipt> {
     fn address(&self) -> &str {
         &self.address
     }
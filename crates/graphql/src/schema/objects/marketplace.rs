@@ -6,17 +6,58 @@ use objects::{
 use super::prelude::*;
 use crate::schema::scalars::{markers::StoreConfig, PublicKey};
 
+#[derive(Debug, Clone)]
+/// A problem found when parsing or validating a marketplace config JSON
+pub struct StoreConfigIssue {
+    pub path: String,
+    pub message: String,
+}
+
+traced_object!(StoreConfigIssue);
+
+#[graphql_object(Context = AppContext, name = "StoreConfigIssue")]
+impl Fields<StoreConfigIssue> {
+    #[graphql(
+        description = "Location of the issue within the document, e.g. `theme.logo.url`, or an empty string for the whole document"
+    )]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl<'a> From<models::StoreConfigJsonIssue<'a>> for StoreConfigIssue {
+    fn from(
+        models::StoreConfigJsonIssue { path, message, .. }: models::StoreConfigJsonIssue,
+    ) -> Self {
+        Self {
+            path: path.into_owned(),
+            message: message.into_owned(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 /// An Holaplex marketplace
 pub struct Marketplace {
     pub config_address: PublicKey<StoreConfig>,
-    pub subdomain: String,
+    pub subdomain: Option<String>,
     pub name: String,
     pub description: String,
     pub logo_url: String,
     pub banner_url: String,
-    pub owner_address: String,
+    pub owner_address: Option<String>,
     pub store_address: Option<PublicKey<Storefront>>,
+    pub schema_version: i32,
+    pub primary_color: Option<String>,
+    pub background_color: Option<String>,
+    pub text_color: Option<String>,
+    pub title_font: Option<String>,
+    pub text_font: Option<String>,
+    pub custom_domain: Option<String>,
 }
 
 impl<'a> From<models::StoreConfigJson<'a>> for Marketplace {
@@ -30,29 +71,48 @@ impl<'a> From<models::StoreConfigJson<'a>> for Marketplace {
             subdomain,
             owner_address,
             store_address,
+            schema_version,
+            primary_color,
+            background_color,
+            text_color,
+            title_font,
+            text_font,
+            custom_domain,
         }: models::StoreConfigJson,
     ) -> Self {
         Self {
             config_address: config_address.into(),
-            subdomain: subdomain.into_owned(),
+            subdomain: subdomain.map(Cow::into_owned),
             name: name.into_owned(),
             description: description.into_owned(),
             logo_url: logo_url.into_owned(),
             banner_url: banner_url.into_owned(),
-            owner_address: owner_address.into_owned(),
+            owner_address: owner_address.map(Cow::into_owned),
             store_address: store_address.map(Into::into),
+            schema_version,
+            primary_color: primary_color.map(Cow::into_owned),
+            background_color: background_color.map(Cow::into_owned),
+            text_color: text_color.map(Cow::into_owned),
+            title_font: title_font.map(Cow::into_owned),
+            text_font: text_font.map(Cow::into_owned),
+            custom_domain: custom_domain.map(Cow::into_owned),
         }
     }
 }
 
-#[graphql_object(Context = AppContext)]
-impl Marketplace {
+traced_object!(Marketplace);
+
+#[graphql_object(Context = AppContext, name = "Marketplace")]
+impl Fields<Marketplace> {
     pub fn config_address(&self) -> &PublicKey<StoreConfig> {
         &self.config_address
     }
 
-    pub fn subdomain(&self) -> &str {
-        &self.subdomain
+    #[graphql(
+        description = "The subdomain of the marketplace, or null if its config JSON is missing one"
+    )]
+    pub fn subdomain(&self) -> Option<&str> {
+        self.subdomain.as_deref()
     }
 
     pub fn name(&self) -> &str {
@@ -71,14 +131,54 @@ impl Marketplace {
         &self.banner_url
     }
 
-    pub fn owner_address(&self) -> &str {
-        &self.owner_address
+    #[graphql(
+        description = "The owner of the marketplace, or null if its config JSON is missing one"
+    )]
+    pub fn owner_address(&self) -> Option<&str> {
+        self.owner_address.as_deref()
     }
 
     pub fn store_address(&self) -> &Option<PublicKey<Storefront>> {
         &self.store_address
     }
 
+    #[graphql(description = "Version of the config JSON schema the marketplace was parsed with")]
+    pub fn schema_version(&self) -> i32 {
+        self.schema_version
+    }
+
+    pub fn primary_color(&self) -> Option<&str> {
+        self.primary_color.as_deref()
+    }
+
+    pub fn background_color(&self) -> Option<&str> {
+        self.background_color.as_deref()
+    }
+
+    pub fn text_color(&self) -> Option<&str> {
+        self.text_color.as_deref()
+    }
+
+    pub fn title_font(&self) -> Option<&str> {
+        self.title_font.as_deref()
+    }
+
+    pub fn text_font(&self) -> Option<&str> {
+        self.text_font.as_deref()
+    }
+
+    pub fn custom_domain(&self) -> Option<&str> {
+        self.custom_domain.as_deref()
+    }
+
+    #[graphql(description = "Problems found in the most recently fetched config JSON")]
+    pub async fn config_issues(&self, ctx: &AppContext) -> FieldResult<Vec<StoreConfigIssue>> {
+        ctx.store_config_issues_loader
+            .load(self.config_address.clone())
+            .await
+            .map_err(Into::into)
+    }
+
     pub async fn auction_houses(&self, context: &AppContext) -> FieldResult<Vec<AuctionHouse>> {
         context
             .store_auction_houses_loader
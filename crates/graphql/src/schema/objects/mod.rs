@@ -13,13 +13,18 @@ pub mod denylist;
 pub mod feed_event;
 pub mod genopets;
 pub mod graph_connection;
+pub mod holders;
 pub mod listing;
 pub mod listing_receipt;
 pub mod marketplace;
 pub mod nft;
+pub mod nft_history;
+pub mod owner_history;
+pub mod portfolio;
 pub mod profile;
 pub mod purchase_receipt;
 pub mod reward_center;
+pub mod royalty;
 pub mod spl_governance;
 pub mod stats;
 pub mod store_creator;
@@ -1,10 +1,13 @@
 use indexer_core::{
     assets::{proxy_url, AssetIdentifier, ImageSize},
-    bigdecimal::ToPrimitive,
+    bigdecimal::{BigDecimal, ToPrimitive},
     db::{
-        queries::{self, metadatas::CollectionNftOptions},
+        queries::{
+            self, collection_stats::StatsWindow, metadatas::CollectionNftOptions,
+            nft_history::HistoryCursor,
+        },
         sql_query,
-        sql_types::Text,
+        sql_types::{Bool, Text},
         tables::{
             attributes, auction_houses, bid_receipts, listing_receipts, listings,
             metadata_collection_keys, metadata_jsons, metadatas,
@@ -16,8 +19,16 @@ use indexer_core::{
     uuid::Uuid,
 };
 use objects::{
-    ah_listing::AhListing, ah_offer::Offer, ah_purchase::Purchase, attributes::AttributeGroup,
-    auction_house::AuctionHouse, profile::TwitterProfile, wallet::Wallet,
+    ah_listing::AhListing,
+    ah_offer::Offer,
+    ah_purchase::Purchase,
+    attributes::AttributeGroup,
+    auction_house::AuctionHouse,
+    holders::HolderStats,
+    nft_history::{NftEvent, NftHistory},
+    profile::TwitterProfile,
+    royalty::RoyaltyStats,
+    wallet::Wallet,
 };
 use scalars::{PublicKey, U64};
 use serde_json::Value;
@@ -25,7 +36,9 @@ use services;
 
 use super::prelude::*;
 use crate::schema::{
-    enums::{NftSort, OrderDirection},
+    enums::{
+        ImageFormat, MetadataIntegrity, MetadataIssueCode, NftSort, OrderDirection, RarityMethod,
+    },
     query_root::AttributeFilter,
 };
 #[derive(Debug, Clone)]
@@ -33,10 +46,16 @@ pub struct NftAttribute {
     pub metadata_address: String,
     pub value: Option<String>,
     pub trait_type: Option<String>,
+    pub value_type: Option<String>,
+    pub display_type: Option<String>,
+    pub value_numeric: Option<f64>,
+    pub max_value: Option<f64>,
 }
 
-#[graphql_object(Context = AppContext)]
-impl NftAttribute {
+traced_object!(NftAttribute);
+
+#[graphql_object(Context = AppContext, name = "NftAttribute")]
+impl Fields<NftAttribute> {
     pub fn metadata_address(&self) -> &str {
         &self.metadata_address
     }
@@ -48,6 +67,29 @@ impl NftAttribute {
     pub fn trait_type(&self) -> Option<&str> {
         self.trait_type.as_deref()
     }
+
+    #[graphql(description = "The type of the value, one of `string`, `number`, or `boolean`")]
+    pub fn value_type(&self) -> Option<&str> {
+        self.value_type.as_deref()
+    }
+
+    #[graphql(
+        description = "The display type hint, e.g. `date`, `boost_number`, or \
+                             `boost_percentage`"
+    )]
+    pub fn display_type(&self) -> Option<&str> {
+        self.display_type.as_deref()
+    }
+
+    #[graphql(description = "The value as a number, if it is numeric")]
+    pub fn value_numeric(&self) -> Option<f64> {
+        self.value_numeric
+    }
+
+    #[graphql(description = "The maximum value of a numeric attribute, if specified")]
+    pub fn max_value(&self) -> Option<f64> {
+        self.max_value
+    }
 }
 
 impl<'a> TryFrom<models::MetadataAttribute<'a>> for NftAttribute {
@@ -58,6 +100,10 @@ impl<'a> TryFrom<models::MetadataAttribute<'a>> for NftAttribute {
             metadata_address,
             value,
             trait_type,
+            value_type,
+            display_type,
+            value_numeric,
+            max_value,
             ..
         }: models::MetadataAttribute,
     ) -> Result<Self> {
@@ -65,6 +111,10 @@ impl<'a> TryFrom<models::MetadataAttribute<'a>> for NftAttribute {
             metadata_address: metadata_address.into_owned(),
             value: value.map(Cow::into_owned),
             trait_type: trait_type.map(Cow::into_owned),
+            value_type: value_type.map(Cow::into_owned),
+            display_type: display_type.map(Cow::into_owned),
+            value_numeric,
+            max_value,
         })
     }
 }
@@ -77,8 +127,10 @@ pub struct NftFile {
     pub file_type: String,
 }
 
-#[graphql_object(Context = AppContext)]
-impl NftFile {
+traced_object!(NftFile);
+
+#[graphql_object(Context = AppContext, name = "NftFile")]
+impl Fields<NftFile> {
     pub fn metadata_address(&self) -> &str {
         &self.metadata_address
     }
@@ -92,6 +144,170 @@ impl NftFile {
     }
 }
 
+#[derive(Debug, Clone)]
+/// Content-integrity information for the metadata JSON of an NFT
+pub struct NftMetadataIntegrity {
+    pub content_hash: Vec<u8>,
+    pub content_source: String,
+    pub cid_verified: Option<bool>,
+    pub content_changed_at: Option<NaiveDateTime>,
+}
+
+traced_object!(NftMetadataIntegrity);
+
+#[graphql_object(Context = AppContext, name = "NftMetadataIntegrity")]
+impl Fields<NftMetadataIntegrity> {
+    pub fn status(&self) -> MetadataIntegrity {
+        let changed = self.content_changed_at.is_some();
+
+        match (self.content_source.as_str(), changed) {
+            _ if self.cid_verified == Some(false) => MetadataIntegrity::Tampered,
+            ("http", false) => MetadataIntegrity::MutableStable,
+            ("http", true) => MetadataIntegrity::MutableChanged,
+            (_, true) => MetadataIntegrity::Tampered,
+            (_, false) => MetadataIntegrity::Immutable,
+        }
+    }
+
+    #[graphql(description = "Hex-encoded SHA-256 digest of the fetched document")]
+    pub fn content_hash(&self) -> String {
+        self.content_hash
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    #[graphql(description = "Storage the document was fetched from: ipfs, arweave, or http")]
+    pub fn content_source(&self) -> &str {
+        &self.content_source
+    }
+
+    #[graphql(
+        description = "Whether the document matched its IPFS CID, or null if it could not be checked"
+    )]
+    pub fn cid_verified(&self) -> Option<bool> {
+        self.cid_verified
+    }
+
+    #[graphql(
+        description = "When the document was last observed to differ from its previous fetch, or \
+                       null if it has not changed or the change was cleared"
+    )]
+    pub fn content_changed_at(&self) -> Option<DateTime<Utc>> {
+        self.content_changed_at.map(|t| DateTime::from_utc(t, Utc))
+    }
+}
+
+#[derive(Debug)]
+/// NFTs matching a search query, along with the number of matching NFTs with
+/// each trait
+pub struct NftSearchResults {
+    pub nfts: Vec<Nft>,
+    pub traits: Vec<AttributeGroup>,
+    pub total: i32,
+}
+
+traced_object!(NftSearchResults);
+
+#[graphql_object(Context = AppContext, name = "NftSearchResults")]
+impl Fields<NftSearchResults> {
+    pub fn nfts(&self) -> &[Nft] {
+        &self.nfts
+    }
+
+    #[graphql(description = "Number of matching NFTs with each trait value")]
+    pub fn traits(&self) -> &[AttributeGroup] {
+        &self.traits
+    }
+
+    #[graphql(description = "Estimated number of matching NFTs")]
+    pub fn total(&self) -> i32 {
+        self.total
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A problem found when validating an NFT's metadata JSON against the Metaplex
+/// standard
+pub struct MetadataIssue {
+    pub code: MetadataIssueCode,
+    pub path: String,
+    pub message: String,
+}
+
+traced_object!(MetadataIssue);
+
+#[graphql_object(Context = AppContext, name = "MetadataIssue")]
+impl Fields<MetadataIssue> {
+    pub fn code(&self) -> MetadataIssueCode {
+        self.code
+    }
+
+    #[graphql(
+        description = "Location of the issue within the document, e.g. `properties.files[0].uri`, or an empty string for the whole document"
+    )]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+#[derive(Debug, Clone, GraphQLObject)]
+#[graphql(description = "The rarity of an NFT within its verified collection")]
+pub struct NftRarity {
+    #[graphql(
+        description = "Position of the NFT in its collection, starting from 1 for the rarest"
+    )]
+    pub rank: i32,
+    #[graphql(description = "The rarity score, interpreted according to the method")]
+    pub score: f64,
+    pub method: RarityMethod,
+}
+
+impl<'a> TryFrom<models::NftRarity<'a>> for NftRarity {
+    type Error = Error;
+
+    fn try_from(
+        models::NftRarity {
+            method,
+            score,
+            rank,
+            ..
+        }: models::NftRarity,
+    ) -> Result<Self> {
+        Ok(Self {
+            rank,
+            score,
+            method: method
+                .parse::<indexer_core::rarity::RarityMethod>()
+                .with_context(|| format!("Unknown rarity method {:?}", method))?
+                .into(),
+        })
+    }
+}
+
+impl<'a> TryFrom<models::MetadataJsonIssue<'a>> for MetadataIssue {
+    type Error = Error;
+
+    fn try_from(
+        models::MetadataJsonIssue {
+            code,
+            path,
+            message,
+            ..
+        }: models::MetadataJsonIssue,
+    ) -> Result<Self> {
+        Ok(Self {
+            code: code.parse()?,
+            path: path.into_owned(),
+            message: message.into_owned(),
+        })
+    }
+}
+
 impl<'a> From<models::MetadataFile<'a>> for NftFile {
     fn from(
         models::MetadataFile {
@@ -109,6 +325,65 @@ impl<'a> From<models::MetadataFile<'a>> for NftFile {
     }
 }
 
+#[derive(Debug, Clone)]
+/// A resized copy of an NFT image generated by the asset worker
+pub struct NftImageDerivative {
+    pub source_uri: String,
+    pub uri: String,
+    pub file_type: String,
+    pub width: i32,
+}
+
+impl NftImageDerivative {
+    fn format(&self) -> Option<ImageFormat> {
+        match self.file_type.as_str() {
+            "image/webp" => Some(ImageFormat::Webp),
+            "image/avif" => Some(ImageFormat::Avif),
+            _ => None,
+        }
+    }
+}
+
+/// Resolve the URL of an NFT image at the requested width, preferring a
+/// locally generated derivative and falling back to the asset proxy
+async fn image_url(
+    ctx: &AppContext,
+    address: &str,
+    image: &str,
+    width: Option<i32>,
+    format: Option<ImageFormat>,
+) -> FieldResult<String> {
+    let url = Url::parse(image);
+    let id = if let Ok(ref url) = url {
+        AssetIdentifier::new(url)
+    } else {
+        return Ok(image.to_owned());
+    };
+
+    let width = ImageSize::from(width.unwrap_or(ImageSize::XSmall as i32));
+
+    if !matches!(width, ImageSize::Original) {
+        let format = format.unwrap_or(ImageFormat::Webp);
+        let derivatives = ctx
+            .nft_image_derivatives_loader
+            .load(address.to_owned().into())
+            .await?;
+
+        if let Some(d) = derivatives.into_iter().find(|d| {
+            d.source_uri == image && d.width == width as i32 && d.format() == Some(format)
+        }) {
+            return Ok(d.uri);
+        }
+    }
+
+    let width_str = (width as i32).to_string();
+
+    Ok(
+        proxy_url(&ctx.shared.asset_proxy, &id, Some(("width", &*width_str)))?
+            .map_or_else(|| image.to_owned(), |u| u.to_string()),
+    )
+}
+
 #[derive(Debug, Clone)]
 /// An NFT creator
 pub struct NftCreator {
@@ -120,8 +395,10 @@ pub struct NftCreator {
     pub twitter_handle: Option<String>,
 }
 
-#[graphql_object(Context = AppContext)]
-impl NftCreator {
+traced_object!(NftCreator);
+
+#[graphql_object(Context = AppContext, name = "NftCreator")]
+impl Fields<NftCreator> {
     pub fn address(&self) -> &str {
         &self.address
     }
@@ -190,8 +467,10 @@ pub struct NftOwner {
     pub twitter_handle: Option<String>,
 }
 
-#[graphql_object(Context = AppContext)]
-impl NftOwner {
+traced_object!(NftOwner);
+
+#[graphql_object(Context = AppContext, name = "NftOwner")]
+impl Fields<NftOwner> {
     pub fn address(&self) -> &str {
         &self.address
     }
@@ -262,8 +541,10 @@ impl TryFrom<models::NftActivity> for NftActivity {
     }
 }
 
-#[graphql_object(Context = AppContext)]
-impl NftActivity {
+traced_object!(NftActivity);
+
+#[graphql_object(Context = AppContext, name = "NftActivity")]
+impl Fields<NftActivity> {
     fn id(&self) -> &Uuid {
         &self.id
     }
@@ -370,8 +651,10 @@ impl TryFrom<models::Nft> for Nft {
     }
 }
 
-#[graphql_object(Context = AppContext)]
-impl Nft {
+traced_object!(Nft);
+
+#[graphql_object(Context = AppContext, name = "Nft")]
+impl Fields<Nft> {
     pub fn address(&self) -> &str {
         &self.address
     }
@@ -419,7 +702,8 @@ impl Nft {
         self.model.as_deref()
     }
 
-    #[graphql(arguments(width(description = r"Image width possible values are:
+    #[graphql(arguments(
+        width(description = r"Image width possible values are:
 - 0 (Original size)
 - 100 (Tiny)
 - 400 (XSmall)
@@ -429,22 +713,21 @@ impl Nft {
 
 Any other value will return the original image size.
 
-If no value is provided, it will return XSmall")))]
-    pub fn image(&self, width: Option<i32>, ctx: &AppContext) -> FieldResult<String> {
-        let url = Url::parse(&self.image);
-        let id = if let Ok(ref url) = url {
-            AssetIdentifier::new(url)
-        } else {
-            return Ok(self.image.clone());
-        };
-
-        let width = ImageSize::from(width.unwrap_or(ImageSize::XSmall as i32));
-        let width_str = (width as i32).to_string();
+If no value is provided, it will return XSmall"),
+        format(
+            description = r"Preferred encoding for resized images, defaulting to WEBP.
 
-        Ok(
-            proxy_url(&ctx.shared.asset_proxy, &id, Some(("width", &*width_str)))?
-                .map_or_else(|| self.image.clone(), |u| u.to_string()),
+This only applies when a locally generated derivative is available; otherwise
+the asset proxy URL is returned."
         )
+    ))]
+    pub async fn image(
+        &self,
+        width: Option<i32>,
+        format: Option<ImageFormat>,
+        ctx: &AppContext,
+    ) -> FieldResult<String> {
+        image_url(ctx, &self.address, &self.image, width, format).await
     }
 
     #[graphql(description = r"Get the original URL of the image as stored in the NFT's metadata")]
@@ -488,6 +771,50 @@ If no value is provided, it will return XSmall")))]
             .map_err(Into::into)
     }
 
+    #[graphql(
+        description = "The provenance of the NFT, newest first, merging its mint, listings, offers, sales, transfers, rentals, and burn",
+        arguments(
+            limit(description = "The number of events to return (default 25, at most 100)"),
+            cursor(description = "Return the events after this cursor from a previous page"),
+        )
+    )]
+    pub fn history(
+        &self,
+        ctx: &AppContext,
+        limit: Option<i32>,
+        cursor: Option<String>,
+    ) -> FieldResult<NftHistory> {
+        let limit = limit.unwrap_or(25);
+
+        if !(1..=100).contains(&limit) {
+            return Err(FieldError::new(
+                "limit must be between 1 and 100",
+                graphql_value!(None),
+            ));
+        }
+
+        let cursor = cursor.map(|c| c.parse::<HistoryCursor>()).transpose()?;
+
+        let conn = ctx.shared.db.get()?;
+        let mut rows =
+            queries::nft_history::list(&conn, &self.address, cursor, i64::from(limit) + 1)?;
+
+        let limit = limit.try_into()?;
+        let has_next_page = rows.len() > limit;
+        rows.truncate(limit);
+
+        let events = rows
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<NftEvent>>>()?;
+
+        Ok(NftHistory {
+            end_cursor: events.last().map(|e| e.cursor.clone()),
+            events,
+            has_next_page,
+        })
+    }
+
     pub async fn listings(&self, ctx: &AppContext) -> FieldResult<Vec<AhListing>> {
         ctx.ah_listings_loader
             .load(self.address.clone().into())
@@ -516,6 +843,48 @@ If no value is provided, it will return XSmall")))]
             .map_err(Into::into)
     }
 
+    #[graphql(
+        description = "Problems found when validating the NFT's metadata JSON against the Metaplex standard"
+    )]
+    pub async fn metadata_issues(&self, ctx: &AppContext) -> FieldResult<Vec<MetadataIssue>> {
+        ctx.nft_metadata_issues_loader
+            .load(self.address.clone().into())
+            .await
+            .map_err(Into::into)
+    }
+
+    #[graphql(
+        description = "The rarity of the NFT within its verified collection using the given method, defaulting to INFORMATION_CONTENT, or null if it has not been ranked",
+        arguments(method(description = "The scoring method to use"))
+    )]
+    pub async fn rarity(
+        &self,
+        ctx: &AppContext,
+        method: Option<RarityMethod>,
+    ) -> FieldResult<Option<NftRarity>> {
+        let method = method.unwrap_or(RarityMethod::InformationContent);
+
+        Ok(ctx
+            .nft_rarities_loader
+            .load(self.address.clone().into())
+            .await?
+            .into_iter()
+            .find(|r| r.method == method))
+    }
+
+    #[graphql(
+        description = "Content hash and integrity status of the NFT's metadata JSON, or null if it was indexed before hashing was introduced"
+    )]
+    pub async fn metadata_integrity(
+        &self,
+        ctx: &AppContext,
+    ) -> FieldResult<Option<NftMetadataIntegrity>> {
+        ctx.nft_metadata_integrity_loader
+            .load(self.address.clone().into())
+            .await
+            .map_err(Into::into)
+    }
+
     pub async fn collection(&self, ctx: &AppContext) -> FieldResult<Option<Collection>> {
         ctx.nft_collection_loader
             .load(self.address.clone().into())
@@ -553,8 +922,10 @@ impl From<Nft> for Collection {
     }
 }
 
-#[graphql_object(Context = AppContext)]
-impl Collection {
+traced_object!(Collection);
+
+#[graphql_object(Context = AppContext, name = "Collection")]
+impl Fields<Collection> {
     fn nft(&self) -> &Nft {
         &self.0
     }
@@ -586,6 +957,7 @@ impl Collection {
         marketplace_program: Option<String>,
         auction_house: Option<String>,
         attributes: Option<Vec<AttributeFilter>>,
+        rarity_method: Option<RarityMethod>,
     ) -> FieldResult<Vec<Nft>> {
         let conn = ctx.shared.db.get()?;
 
@@ -597,6 +969,7 @@ impl Collection {
                 attributes: attributes.map(|a| a.into_iter().map(Into::into).collect()),
                 marketplace_program,
                 sort_by: sort_by.map(Into::into),
+                rarity_method: rarity_method.map(Into::into),
                 order: order.map(Into::into),
                 limit: limit.try_into()?,
                 offset: offset.try_into()?,
@@ -632,6 +1005,77 @@ impl Collection {
             .map_err(Into::into)
     }
 
+    #[graphql(
+        description = "Royalties owed and paid for sales of NFTs in the collection, or null if none have sold with royalties",
+        arguments(
+            start_date(description = "Only include sales made at or after this time"),
+            end_date(description = "Only include sales made before this time"),
+        )
+    )]
+    pub fn royalty_compliance(
+        &self,
+        ctx: &AppContext,
+        start_date: Option<DateTime<Utc>>,
+        end_date: Option<DateTime<Utc>>,
+    ) -> FieldResult<Option<RoyaltyStats>> {
+        let conn = ctx.shared.db.get()?;
+
+        queries::royalties::stats(&conn, queries::royalties::RoyaltyOptions {
+            creator: None,
+            collection: Some(&self.0.mint_address),
+            start_date: start_date.map(|d| d.naive_utc()),
+            end_date: end_date.map(|d| d.naive_utc()),
+        })?
+        .map(TryInto::try_into)
+        .transpose()
+        .map_err(Into::into)
+    }
+
+    #[graphql(
+        description = "Distribution and concentration of the wallets holding NFTs of the collection",
+        arguments(
+            top_holders(
+                description = "Number of largest holders to return (default 10, at most 100)"
+            ),
+            history_days(
+                description = "Number of days of new and churned holders to return, ending today (default 30, at most 365)"
+            ),
+        )
+    )]
+    pub fn holder_stats(
+        &self,
+        ctx: &AppContext,
+        top_holders: Option<i32>,
+        history_days: Option<i32>,
+    ) -> FieldResult<HolderStats> {
+        let top_holders = top_holders.unwrap_or(10);
+        let history_days = history_days.unwrap_or(30);
+
+        if !(0..=100).contains(&top_holders) {
+            return Err(FieldError::new(
+                "topHolders must be between 0 and 100",
+                graphql_value!(None),
+            ));
+        }
+
+        if !(1..=365).contains(&history_days) {
+            return Err(FieldError::new(
+                "historyDays must be between 1 and 365",
+                graphql_value!(None),
+            ));
+        }
+
+        let conn = ctx.shared.db.get()?;
+        let start =
+            Utc::now().naive_utc().date() - chrono::Duration::days((history_days - 1).into());
+
+        let holdings = queries::holders::holders(&conn, &self.0.mint_address)?;
+        let days = queries::holders::days(&conn, &self.0.mint_address, start)?;
+        let listed = queries::holders::listed_count(&conn, &self.0.mint_address)?;
+
+        HolderStats::new(holdings, days, listed, top_holders.try_into()?).map_err(Into::into)
+    }
+
     #[graphql(description = "Lowest price of currently listed NFTs in the collection.")]
     async fn floor_price(&self, context: &AppContext) -> FieldResult<Option<scalars::I64>> {
         Ok(context
@@ -688,9 +1132,16 @@ impl Collection {
     }
 
     #[graphql(
-        description = "Total of all sales of all NFTs in the collection over all time, in lamports."
+        description = "Total of all sales of all NFTs in the collection over all time, in lamports.",
+        arguments(exclude_flagged(
+            description = "Leave sales flagged as suspected wash trades out of the total, defaults to false"
+        ))
     )]
-    pub async fn volume_total(&self, ctx: &AppContext) -> FieldResult<Option<scalars::U64>> {
+    pub async fn volume_total(
+        &self,
+        ctx: &AppContext,
+        exclude_flagged: Option<bool>,
+    ) -> FieldResult<Option<scalars::U64>> {
         let conn = ctx.shared.db.get()?;
 
         let total_volume: Option<models::CollectionVolume> = sql_query(
@@ -700,15 +1151,18 @@ impl Collection {
                             FROM PURCHASES
                         INNER JOIN METADATA_COLLECTION_KEYS ON METADATA_COLLECTION_KEYS.METADATA_ADDRESS = PURCHASES.METADATA
                             WHERE METADATA_COLLECTION_KEYS.COLLECTION_ADDRESS = $1
-                            AND METADATA_COLLECTION_KEYS.VERIFIED = TRUE)
+                            AND METADATA_COLLECTION_KEYS.VERIFIED = TRUE
+                            AND NOT ($2 AND EXISTS (SELECT 1 FROM WASH_TRADES WHERE WASH_TRADES.PURCHASE_ID = PURCHASES.ID)))
                         UNION
                             ( SELECT SUM(PURCHASES.PRICE) AS VOLUME
                             FROM PURCHASES
                             INNER JOIN ME_METADATA_COLLECTIONS ON ME_METADATA_COLLECTIONS.METADATA_ADDRESS = PURCHASES.METADATA
-                            WHERE ME_METADATA_COLLECTIONS.COLLECTION_ID::text = $1)) AS VOLUME_TABLE
+                            WHERE ME_METADATA_COLLECTIONS.COLLECTION_ID::text = $1
+                            AND NOT ($2 AND EXISTS (SELECT 1 FROM WASH_TRADES WHERE WASH_TRADES.PURCHASE_ID = PURCHASES.ID)))) AS VOLUME_TABLE
             LIMIT 1;",
         )
         .bind::<Text, _>(self.0.mint_address.clone())
+        .bind::<Bool, _>(exclude_flagged.unwrap_or(false))
         .load(&conn)
         .context("Failed to load collection volume total")?
         .first()
@@ -769,21 +1223,8 @@ impl Collection {
     }
 
     #[graphql(deprecated = "use `nft { image }`")]
-    pub fn image(&self, width: Option<i32>, ctx: &AppContext) -> FieldResult<String> {
-        let url = Url::parse(&self.0.image);
-        let id = if let Ok(ref url) = url {
-            AssetIdentifier::new(url)
-        } else {
-            return Ok(self.0.image.clone());
-        };
-
-        let width = ImageSize::from(width.unwrap_or(ImageSize::XSmall as i32));
-        let width_str = (width as i32).to_string();
-
-        Ok(
-            proxy_url(&ctx.shared.asset_proxy, &id, Some(("width", &*width_str)))?
-                .map_or_else(|| self.0.image.clone(), |u| u.to_string()),
-        )
+    pub async fn image(&self, width: Option<i32>, ctx: &AppContext) -> FieldResult<String> {
+        image_url(ctx, &self.0.address, &self.0.image, width, None).await
     }
 
     #[graphql(deprecated = "use `nft { animation_url }`")]
@@ -896,6 +1337,7 @@ pub struct CollectionIdentifier(pub String);
 
 #[derive(Debug, Clone)]
 pub struct CollectionTrend {
+    pub window: Option<CollectionWindowStats>,
     pub collection: String,
     pub floor_price: U64,
     pub nft_count: i32,
@@ -929,9 +1371,58 @@ pub struct CollectionTrend {
     pub one_day_marketcap_change: i32,
     pub seven_day_marketcap_change: i32,
     pub thirty_day_marketcap_change: i32,
+    pub one_day_normalized_volume: Option<U64>,
+    pub seven_day_normalized_volume: Option<U64>,
+    pub thirty_day_normalized_volume: Option<U64>,
+    pub prev_one_day_normalized_volume: Option<U64>,
+    pub prev_seven_day_normalized_volume: Option<U64>,
+    pub prev_thirty_day_normalized_volume: Option<U64>,
+    pub one_day_normalized_volume_change: Option<i32>,
+    pub seven_day_normalized_volume_change: Option<i32>,
+    pub thirty_day_normalized_volume_change: Option<i32>,
 }
 
-impl<'a> TryFrom<models::CollectionTrend> for CollectionTrend {
+impl TryFrom<models::NormalizedCollectionTrend> for CollectionTrend {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(
+        models::NormalizedCollectionTrend { trend, normalized }: models::NormalizedCollectionTrend,
+    ) -> Result<Self, Self::Error> {
+        let models::CollectionNormalizedVolumes {
+            one_day_volume,
+            seven_day_volume,
+            thirty_day_volume,
+            prev_one_day_volume,
+            prev_seven_day_volume,
+            prev_thirty_day_volume,
+            one_day_volume_change,
+            seven_day_volume_change,
+            thirty_day_volume_change,
+        } = normalized;
+        let volume = |v: Option<BigDecimal>| v.and_then(|v| v.try_into().ok());
+
+        Ok(Self {
+            one_day_normalized_volume: volume(one_day_volume),
+            seven_day_normalized_volume: volume(seven_day_volume),
+            thirty_day_normalized_volume: volume(thirty_day_volume),
+            prev_one_day_normalized_volume: volume(prev_one_day_volume),
+            prev_seven_day_normalized_volume: volume(prev_seven_day_volume),
+            prev_thirty_day_normalized_volume: volume(prev_thirty_day_volume),
+            one_day_normalized_volume_change: one_day_volume_change
+                .map(TryInto::try_into)
+                .transpose()?,
+            seven_day_normalized_volume_change: seven_day_volume_change
+                .map(TryInto::try_into)
+                .transpose()?,
+            thirty_day_normalized_volume_change: thirty_day_volume_change
+                .map(TryInto::try_into)
+                .transpose()?,
+            ..trend.try_into()?
+        })
+    }
+}
+
+impl TryFrom<models::CollectionTrend> for CollectionTrend {
     type Error = std::num::TryFromIntError;
 
     fn try_from(
@@ -972,6 +1463,7 @@ impl<'a> TryFrom<models::CollectionTrend> for CollectionTrend {
         }: models::CollectionTrend,
     ) -> Result<Self, Self::Error> {
         Ok(Self {
+            window: None,
             collection,
             floor_price: floor_price.try_into().unwrap_or_default(),
             nft_count: nft_count.try_into()?,
@@ -1017,12 +1509,130 @@ impl<'a> TryFrom<models::CollectionTrend> for CollectionTrend {
             one_day_marketcap_change: one_day_marketcap_change.try_into()?,
             seven_day_marketcap_change: seven_day_marketcap_change.try_into()?,
             thirty_day_marketcap_change: thirty_day_marketcap_change.try_into()?,
+            one_day_normalized_volume: None,
+            seven_day_normalized_volume: None,
+            thirty_day_normalized_volume: None,
+            prev_one_day_normalized_volume: None,
+            prev_seven_day_normalized_volume: None,
+            prev_thirty_day_normalized_volume: None,
+            one_day_normalized_volume_change: None,
+            seven_day_normalized_volume_change: None,
+            thirty_day_normalized_volume_change: None,
         })
     }
 }
 
-#[graphql_object(Context = AppContext)]
-impl CollectionTrend {
+#[derive(Debug, Clone, GraphQLObject)]
+#[graphql(description = "Statistics of a collection over a trailing window")]
+pub struct CollectionWindowStats {
+    #[graphql(description = "The window, e.g. `24h` or `all`")]
+    pub window: String,
+    #[graphql(
+        description = "Total sales volume within the window, or null if the sales were made in more than one currency"
+    )]
+    pub volume: Option<U64>,
+    #[graphql(
+        description = "Total sales volume within the window in lamports of SOL, or null if any sale could not be converted"
+    )]
+    pub normalized_volume: Option<U64>,
+    #[graphql(
+        description = "The mint of the currency the native volumes are denominated in, or null if the sales were made in more than one currency"
+    )]
+    pub currency_mint: Option<String>,
+    #[graphql(description = "Number of sales within the window")]
+    pub sales_count: U64,
+    #[graphql(description = "Number of distinct buyers within the window")]
+    pub unique_buyers: U64,
+    #[graphql(
+        description = "Total sales volume within the preceding window of the same length, or null for all-time windows"
+    )]
+    pub prev_volume: Option<U64>,
+    #[graphql(
+        description = "Total sales volume within the preceding window of the same length in lamports of SOL, or null for all-time windows or if any sale could not be converted"
+    )]
+    pub prev_normalized_volume: Option<U64>,
+    #[graphql(
+        description = "Number of sales within the preceding window of the same length, or null for all-time windows"
+    )]
+    pub prev_sales_count: Option<U64>,
+    #[graphql(
+        description = "Percent change in normalized volume from the preceding window, or null if it had no sales"
+    )]
+    pub volume_change: Option<i32>,
+    #[graphql(
+        description = "Percent change in sales count from the preceding window, or null if it had no sales"
+    )]
+    pub sales_count_change: Option<i32>,
+    #[graphql(
+        description = "Lowest price among listings open at any point within the window and sales within it, or null if there were neither"
+    )]
+    pub floor_price: Option<U64>,
+    #[graphql(
+        description = "Floor price within the window times the number of NFTs in the collection"
+    )]
+    pub marketcap: Option<U64>,
+}
+
+impl TryFrom<(StatsWindow, models::WindowedCollectionTrend)> for CollectionTrend {
+    type Error = Error;
+
+    fn try_from(
+        (
+            window,
+            models::WindowedCollectionTrend {
+                trend,
+                normalized,
+                window: stats,
+            },
+        ): (StatsWindow, models::WindowedCollectionTrend),
+    ) -> Result<Self> {
+        let models::CollectionWindowStats {
+            volume,
+            normalized_volume,
+            currency_mint,
+            sales_count,
+            unique_buyers,
+            prev_volume,
+            prev_normalized_volume,
+            prev_sales_count,
+            volume_change,
+            sales_count_change,
+            floor_price,
+            marketcap,
+        } = stats;
+
+        let stats = CollectionWindowStats {
+            window: window.to_string(),
+            volume: volume.map(TryInto::try_into).transpose()?,
+            normalized_volume: normalized_volume.map(TryInto::try_into).transpose()?,
+            currency_mint,
+            sales_count: sales_count.try_into()?,
+            unique_buyers: unique_buyers.try_into()?,
+            prev_volume: prev_volume.map(TryInto::try_into).transpose()?,
+            prev_normalized_volume: prev_normalized_volume.map(TryInto::try_into).transpose()?,
+            prev_sales_count: prev_sales_count.map(TryInto::try_into).transpose()?,
+            volume_change: volume_change.map(TryInto::try_into).transpose()?,
+            sales_count_change: sales_count_change.map(TryInto::try_into).transpose()?,
+            floor_price: floor_price.map(TryInto::try_into).transpose()?,
+            marketcap: marketcap.map(TryInto::try_into).transpose()?,
+        };
+
+        Ok(Self {
+            window: Some(stats),
+            ..models::NormalizedCollectionTrend { trend, normalized }.try_into()?
+        })
+    }
+}
+
+traced_object!(CollectionTrend);
+
+#[graphql_object(Context = AppContext, name = "CollectionTrend")]
+impl Fields<CollectionTrend> {
+    #[graphql(description = "Statistics over the window requested from `collectionTrends`, if any")]
+    pub fn window(&self) -> Option<&CollectionWindowStats> {
+        self.window.as_ref()
+    }
+
     pub fn floor_price(&self) -> U64 {
         self.floor_price
     }
@@ -1151,6 +1761,69 @@ impl CollectionTrend {
         self.one_day_marketcap_change
     }
 
+    #[graphql(
+        description = "1 day volume in lamports of SOL, or null if any sale could not be converted"
+    )]
+    pub fn one_day_normalized_volume(&self) -> Option<U64> {
+        self.one_day_normalized_volume
+    }
+
+    #[graphql(
+        description = "7 day volume in lamports of SOL, or null if any sale could not be converted"
+    )]
+    pub fn seven_day_normalized_volume(&self) -> Option<U64> {
+        self.seven_day_normalized_volume
+    }
+
+    #[graphql(
+        description = "30 day volume in lamports of SOL, or null if any sale could not be converted"
+    )]
+    pub fn thirty_day_normalized_volume(&self) -> Option<U64> {
+        self.thirty_day_normalized_volume
+    }
+
+    #[graphql(
+        description = "Previous 1 day volume in lamports of SOL, or null if any sale could not be converted"
+    )]
+    pub fn prev_one_day_normalized_volume(&self) -> Option<U64> {
+        self.prev_one_day_normalized_volume
+    }
+
+    #[graphql(
+        description = "Previous 7 day volume in lamports of SOL, or null if any sale could not be converted"
+    )]
+    pub fn prev_seven_day_normalized_volume(&self) -> Option<U64> {
+        self.prev_seven_day_normalized_volume
+    }
+
+    #[graphql(
+        description = "Previous 30 day volume in lamports of SOL, or null if any sale could not be converted"
+    )]
+    pub fn prev_thirty_day_normalized_volume(&self) -> Option<U64> {
+        self.prev_thirty_day_normalized_volume
+    }
+
+    #[graphql(
+        description = "Percent change in 1 day normalized volume, or null if the previous period had no sales"
+    )]
+    pub fn one_day_normalized_volume_change(&self) -> Option<i32> {
+        self.one_day_normalized_volume_change
+    }
+
+    #[graphql(
+        description = "Percent change in 7 day normalized volume, or null if the previous period had no sales"
+    )]
+    pub fn seven_day_normalized_volume_change(&self) -> Option<i32> {
+        self.seven_day_normalized_volume_change
+    }
+
+    #[graphql(
+        description = "Percent change in 30 day normalized volume, or null if the previous period had no sales"
+    )]
+    pub fn thirty_day_normalized_volume_change(&self) -> Option<i32> {
+        self.thirty_day_normalized_volume_change
+    }
+
     pub async fn collection(&self, ctx: &AppContext) -> FieldResult<Option<Collection>> {
         ctx.generic_collection_loader
             .load(self.collection.clone())
@@ -1171,8 +1844,10 @@ impl NftCount {
     }
 }
 
-#[graphql_object(Context = AppContext)]
-impl NftCount {
+traced_object!(NftCount);
+
+#[graphql_object(Context = AppContext, name = "NftCount")]
+impl Fields<NftCount> {
     fn total(&self, context: &AppContext) -> FieldResult<i32> {
         let conn = context.shared.db.get()?;
 
@@ -1244,8 +1919,10 @@ impl From<serde_json::Value> for MetadataJson {
 #[derive(Debug, Clone, Copy)]
 pub struct NftsStats;
 
-#[graphql_object(Context = AppContext)]
-impl NftsStats {
+traced_object!(NftsStats);
+
+#[graphql_object(Context = AppContext, name = "NftsStats")]
+impl Fields<NftsStats> {
     #[graphql(description = "The total number of indexed NFTs")]
     fn total_nfts(&self, context: &AppContext) -> FieldResult<i32> {
         let conn = context.shared.db.get()?;
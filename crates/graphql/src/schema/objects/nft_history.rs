@@ -0,0 +1,77 @@
+use indexer_core::db::{custom_types, queries::nft_history::HistoryCursor};
+use objects::{auction_house::AuctionHouse, wallet::Wallet};
+use scalars::{PublicKey, I64, U64};
+
+use super::prelude::*;
+use crate::schema::enums::NftEventKind;
+
+#[derive(Debug, Clone, GraphQLObject)]
+#[graphql(description = "A page of the provenance of an NFT, newest first")]
+pub struct NftHistory {
+    #[graphql(description = "The events of the page")]
+    pub events: Vec<NftEvent>,
+    #[graphql(
+        description = "The cursor of the last event of the page, to pass as the cursor of the next page"
+    )]
+    pub end_cursor: Option<String>,
+    #[graphql(description = "Whether there are older events after this page")]
+    pub has_next_page: bool,
+}
+
+#[derive(Debug, Clone, GraphQLObject)]
+#[graphql(description = "An event in the provenance of an NFT")]
+pub struct NftEvent {
+    #[graphql(description = "The kind of event")]
+    pub kind: NftEventKind,
+    #[graphql(description = "The time the event occurred")]
+    pub occurred_at: DateTime<Utc>,
+    #[graphql(description = "The slot of the event, if recorded by its source")]
+    pub slot: Option<I64>,
+    #[graphql(
+        description = "The wallet acting in the event, i.e. the minter, seller of a listing, bidder of an offer, buyer of a sale, new owner of a transfer, issuer of a Cardinal token manager, renter of a habitat, or owner of a burned NFT"
+    )]
+    pub wallet: Option<PublicKey<Wallet>>,
+    #[graphql(
+        description = "The other side of the event, i.e. the seller of a sale, the previous owner of a transfer, or the owner of a rented habitat"
+    )]
+    pub counterparty: Option<PublicKey<Wallet>>,
+    #[graphql(description = "The price of a listing, offer, or sale")]
+    pub price: Option<U64>,
+    #[graphql(description = "The auction house of a listing, offer, or sale")]
+    pub auction_house: Option<PublicKey<AuctionHouse>>,
+    #[graphql(description = "The marketplace program of a listing, offer, or sale")]
+    pub marketplace_program_address: Option<String>,
+    #[graphql(description = "The cursor of the event, to resume the history after it")]
+    pub cursor: String,
+}
+
+impl TryFrom<models::NftEvent> for NftEvent {
+    type Error = Error;
+
+    fn try_from(event: models::NftEvent) -> Result<Self> {
+        let cursor = HistoryCursor::from(&event).to_string();
+        let models::NftEvent {
+            kind,
+            key: _,
+            occurred_at,
+            slot,
+            wallet,
+            counterparty,
+            price,
+            auction_house,
+            marketplace_program,
+        } = event;
+
+        Ok(Self {
+            kind: kind.parse::<custom_types::NftEventKind>()?.into(),
+            occurred_at: DateTime::from_utc(occurred_at, Utc),
+            slot: slot.map(Into::into),
+            wallet: wallet.map(Into::into),
+            counterparty: counterparty.map(Into::into),
+            price: price.map(TryInto::try_into).transpose()?,
+            auction_house: auction_house.map(Into::into),
+            marketplace_program_address: marketplace_program,
+            cursor,
+        })
+    }
+}
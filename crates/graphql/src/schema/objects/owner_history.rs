@@ -0,0 +1,100 @@
+use indexer_core::db::queries::owner_history::PointInTime;
+use objects::wallet::Wallet;
+use scalars::{markers::TokenMint, PublicKey, U64};
+
+use super::prelude::*;
+
+#[derive(Debug, Clone, GraphQLObject)]
+#[graphql(description = "The period during which a wallet owned an NFT")]
+pub struct NftOwnerPeriod {
+    #[graphql(description = "The mint address of the NFT")]
+    pub mint_address: PublicKey<TokenMint>,
+    #[graphql(description = "The owning wallet")]
+    pub owner: PublicKey<Wallet>,
+    #[graphql(description = "The token account holding the NFT")]
+    pub token_account_address: String,
+    #[graphql(description = "The slot from which the wallet owned the NFT")]
+    pub start_slot: U64,
+    #[graphql(
+        description = "The slot from which the next owner owned the NFT or the slot it was burned in, or null if the wallet still owns it"
+    )]
+    pub end_slot: Option<U64>,
+    #[graphql(
+        description = "The block time of the start slot, or the time the ownership was indexed if the block time is unknown"
+    )]
+    pub started_at: DateTime<Utc>,
+    #[graphql(
+        description = "The block time of the end slot, or the time it was indexed if the block time is unknown, or null if the wallet still owns the NFT"
+    )]
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<models::MetadataOwnerRange> for NftOwnerPeriod {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(
+        models::MetadataOwnerRange {
+            mint_address,
+            owner_address,
+            token_account_address,
+            start_slot,
+            end_slot,
+            started_at,
+            ended_at,
+        }: models::MetadataOwnerRange,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            mint_address: mint_address.into(),
+            owner: owner_address.into(),
+            token_account_address,
+            start_slot: start_slot.try_into()?,
+            end_slot: end_slot.map(TryInto::try_into).transpose()?,
+            started_at: DateTime::from_utc(started_at, Utc),
+            ended_at: ended_at.map(|t| DateTime::from_utc(t, Utc)),
+        })
+    }
+}
+
+#[derive(Debug, Clone, GraphQLObject)]
+#[graphql(description = "The number of NFTs of a collection held by a wallet at a point in time")]
+pub struct SnapshotHolder {
+    #[graphql(description = "The holding wallet")]
+    pub owner: PublicKey<Wallet>,
+    #[graphql(description = "The number of NFTs of the collection held")]
+    pub nft_count: U64,
+}
+
+impl TryFrom<models::HolderSnapshot> for SnapshotHolder {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(
+        models::HolderSnapshot {
+            owner_address,
+            nft_count,
+        }: models::HolderSnapshot,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            owner: owner_address.into(),
+            nft_count: nft_count.try_into()?,
+        })
+    }
+}
+
+/// Build a point in time from exactly one of a slot or a timestamp
+///
+/// # Errors
+/// This function fails if neither or both are given, or the slot is out of
+/// range
+pub fn point_in_time(
+    slot: Option<U64>,
+    timestamp: Option<DateTime<Utc>>,
+) -> FieldResult<PointInTime> {
+    match (slot, timestamp) {
+        (Some(slot), None) => Ok(PointInTime::Slot(u64::from(slot).try_into()?)),
+        (None, Some(timestamp)) => Ok(PointInTime::Time(timestamp.naive_utc())),
+        _ => Err(FieldError::new(
+            "Exactly one of slot or timestamp must be given",
+            graphql_value!(None),
+        )),
+    }
+}
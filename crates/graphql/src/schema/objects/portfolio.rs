@@ -0,0 +1,150 @@
+use indexer_core::portfolio;
+use objects::nft::Collection;
+use scalars::{markers::TokenMint, PublicKey, I64, U64};
+
+use super::prelude::*;
+
+#[derive(Debug, Clone, GraphQLObject)]
+#[graphql(
+    description = "Valuation and profit and loss of the NFTs held and traded by a wallet, in lamports of SOL",
+    Context = AppContext
+)]
+pub struct Portfolio {
+    #[graphql(
+        description = "The estimated value of the NFTs currently held, at the higher of their collection floor and trait floors, or their last sale price if neither is known"
+    )]
+    pub total_value: U64,
+    #[graphql(description = "The purchase price of the held NFTs with a known purchase price")]
+    pub cost_basis: U64,
+    #[graphql(
+        description = "Profit from NFTs bought and later sold, matching each sale to the earliest unsold purchase of the same NFT"
+    )]
+    pub realized_pnl: I64,
+    #[graphql(
+        description = "Estimated value less purchase price of the held NFTs with both known"
+    )]
+    pub unrealized_pnl: I64,
+    #[graphql(description = "The number of held NFTs whose value could not be estimated")]
+    pub unvalued_count: i32,
+    #[graphql(description = "Valuation and profit and loss per verified collection")]
+    pub by_collection: Vec<PortfolioCollection>,
+    #[graphql(description = "End-of-day value and realized profit, oldest first")]
+    pub history: Vec<PortfolioSnapshot>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PortfolioCollection {
+    collection_mint: Option<PublicKey<TokenMint>>,
+    nft_count: i32,
+    value: U64,
+    cost_basis: U64,
+    realized_pnl: I64,
+    unrealized_pnl: I64,
+}
+
+traced_object!(PortfolioCollection);
+
+#[graphql_object(Context = AppContext, name = "PortfolioCollection")]
+#[graphql(
+    description = "Valuation and profit and loss of the NFTs of a single verified collection, or of NFTs with no collection"
+)]
+impl Fields<PortfolioCollection> {
+    #[graphql(description = "The collection, or null for NFTs with no verified collection")]
+    async fn collection(&self, ctx: &AppContext) -> FieldResult<Option<Collection>> {
+        let mint = match self.collection_mint {
+            Some(ref m) => m.clone(),
+            None => return Ok(None),
+        };
+
+        ctx.nft_by_mint_loader
+            .load(mint)
+            .await
+            .map(|op| op.map(Into::into))
+            .map_err(Into::into)
+    }
+
+    #[graphql(description = "The number of NFTs currently held")]
+    fn nft_count(&self) -> i32 {
+        self.nft_count
+    }
+
+    #[graphql(description = "The estimated value of the NFTs currently held")]
+    fn value(&self) -> U64 {
+        self.value
+    }
+
+    #[graphql(description = "The purchase price of the held NFTs with a known purchase price")]
+    fn cost_basis(&self) -> U64 {
+        self.cost_basis
+    }
+
+    #[graphql(description = "Profit from NFTs bought and later sold")]
+    fn realized_pnl(&self) -> I64 {
+        self.realized_pnl
+    }
+
+    #[graphql(description = "Estimated value less purchase price of the held NFTs")]
+    fn unrealized_pnl(&self) -> I64 {
+        self.unrealized_pnl
+    }
+}
+
+#[derive(Debug, Clone, GraphQLObject)]
+#[graphql(description = "The state of a portfolio at the end of a day")]
+pub struct PortfolioSnapshot {
+    #[graphql(description = "The start of the day")]
+    pub date: DateTime<Utc>,
+    #[graphql(
+        description = "The value of the NFTs held at the end of the day, at the last sale price of their collection on or before that day"
+    )]
+    pub value: U64,
+    #[graphql(description = "Total realized profit as of the end of the day")]
+    pub realized_pnl: I64,
+}
+
+impl TryFrom<portfolio::Portfolio> for Portfolio {
+    type Error = Error;
+
+    fn try_from(
+        portfolio::Portfolio {
+            total_value,
+            cost_basis,
+            realized_pnl,
+            unrealized_pnl,
+            unvalued_count,
+            by_collection,
+            history,
+        }: portfolio::Portfolio,
+    ) -> Result<Self> {
+        Ok(Self {
+            total_value: total_value.try_into()?,
+            cost_basis: cost_basis.try_into()?,
+            realized_pnl: realized_pnl.into(),
+            unrealized_pnl: unrealized_pnl.into(),
+            unvalued_count: unvalued_count.try_into()?,
+            by_collection: by_collection
+                .into_iter()
+                .map(|(collection, s)| {
+                    Ok(PortfolioCollection {
+                        collection_mint: collection.map(Into::into),
+                        nft_count: s.nft_count.try_into()?,
+                        value: s.value.try_into()?,
+                        cost_basis: s.cost_basis.try_into()?,
+                        realized_pnl: s.realized_pnl.into(),
+                        unrealized_pnl: s.unrealized_pnl.into(),
+                    })
+                })
+                .collect::<Result<_>>()?,
+            history: history
+                .into_iter()
+                .map(|s| {
+                    Ok(PortfolioSnapshot {
+                        date: DateTime::from_utc(s.date.and_hms(0, 0, 0), Utc),
+                        value: s.value.try_into()?,
+                        realized_pnl: s.realized_pnl.into(),
+                    })
+                })
+                .collect::<Result<_>>()?,
+        })
+    }
+}
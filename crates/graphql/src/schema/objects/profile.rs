@@ -12,8 +12,10 @@ pub struct TwitterProfile {
     pub description: String,
 }
 
-#[graphql_object(Context = AppContext)]
-impl TwitterProfile {
+traced_object!(TwitterProfile);
+
+#[graphql_object(Context = AppContext, name = "TwitterProfile")]
+impl Fields<TwitterProfile> {
     fn wallet_address(&self, ctx: &AppContext) -> FieldResult<Option<String>> {
         let db_conn = ctx.shared.db.get()?;
 
@@ -56,8 +58,10 @@ impl TwitterProfile {
 #[derive(Debug, Clone, Copy)]
 pub struct ProfilesStats;
 
-#[graphql_object(Context = AppContext)]
-impl ProfilesStats {
+traced_object!(ProfilesStats);
+
+#[graphql_object(Context = AppContext, name = "ProfilesStats")]
+impl Fields<ProfilesStats> {
     #[graphql(description = "The total number of indexed profiles")]
     fn total_profiles(&self, context: &AppContext) -> FieldResult<i32> {
         let conn = context.shared.db.get()?;
@@ -14,9 +14,11 @@ pub struct PurchaseReceipt {
     pub created_at: DateTime<Utc>,
 }
 
-#[graphql_object(Context = AppContext)]
+traced_object!(PurchaseReceipt);
+
+#[graphql_object(Context = AppContext, name = "PurchaseReceipt")]
 #[graphql(description = "A NFT bill of purchase")]
-impl PurchaseReceipt {
+impl Fields<PurchaseReceipt> {
     fn address(&self) -> &str {
         &self.address
     }
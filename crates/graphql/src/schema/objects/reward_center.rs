@@ -51,9 +51,11 @@ impl<'a> TryFrom<models::RewardCenter<'a>> for RewardCenter {
     }
 }
 
-#[graphql_object(Context = AppContext)]
-impl RewardCenter {
-    pub fn address(&self) -> &PublicKey<Self> {
+traced_object!(RewardCenter);
+
+#[graphql_object(Context = AppContext, name = "RewardCenter")]
+impl Fields<RewardCenter> {
+    pub fn address(&self) -> &PublicKey<RewardCenter> {
         &self.address
     }
 
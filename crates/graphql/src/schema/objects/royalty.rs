@@ -0,0 +1,78 @@
+use scalars::U64;
+
+use super::prelude::*;
+
+#[derive(Debug, Clone, GraphQLObject)]
+#[graphql(description = "Royalties owed to and paid to creators for a set of sales")]
+pub struct RoyaltyStats {
+    #[graphql(
+        description = "The marketplace program the sales were made through, if grouped by marketplace"
+    )]
+    pub marketplace_program: Option<String>,
+    #[graphql(description = "The number of sales owing royalties")]
+    pub sales: U64,
+    #[graphql(
+        description = "The number of sales whose royalty payments could be determined from their execute-sale instructions"
+    )]
+    pub verified_sales: U64,
+    #[graphql(
+        description = "The mint of the currency the native amounts are denominated in, or null if the sales were made in more than one currency"
+    )]
+    pub currency_mint: Option<String>,
+    #[graphql(
+        description = "Total royalties owed, or null if the sales were made in more than one currency"
+    )]
+    pub expected: Option<U64>,
+    #[graphql(
+        description = "Total royalties owed for verified sales, or null if the sales were made in more than one currency"
+    )]
+    pub verified_expected: Option<U64>,
+    #[graphql(
+        description = "Total royalties paid for verified sales, or null if the sales were made in more than one currency"
+    )]
+    pub paid: Option<U64>,
+    #[graphql(
+        description = "Total royalties owed in lamports of SOL, or null if any could not be converted"
+    )]
+    pub normalized_expected: Option<U64>,
+    #[graphql(
+        description = "Total royalties paid for verified sales in lamports of SOL, or null if any could not be converted"
+    )]
+    pub normalized_paid: Option<U64>,
+    #[graphql(
+        description = "The fraction of royalties owed for verified sales which were paid, or null if there were no verified sales"
+    )]
+    pub paid_ratio: Option<f64>,
+}
+
+impl TryFrom<models::RoyaltyStats> for RoyaltyStats {
+    type Error = Error;
+
+    fn try_from(
+        models::RoyaltyStats {
+            marketplace_program,
+            sales,
+            verified_sales,
+            currency_mint,
+            expected,
+            verified_expected,
+            paid,
+            normalized_expected,
+            normalized_paid,
+            paid_ratio,
+        }: models::RoyaltyStats,
+    ) -> Result<Self> {
+        Ok(Self {
+            marketplace_program,
+            sales: sales.try_into()?,
+            verified_sales: verified_sales.try_into()?,
+            currency_mint,
+            expected: expected.map(TryInto::try_into).transpose()?,
+            verified_expected: verified_expected.map(TryInto::try_into).transpose()?,
+            paid: paid.map(TryInto::try_into).transpose()?,
+            normalized_expected: normalized_expected.map(TryInto::try_into).transpose()?,
+            normalized_paid: normalized_paid.map(TryInto::try_into).transpose()?,
+            paid_ratio,
+        })
+    }
+}
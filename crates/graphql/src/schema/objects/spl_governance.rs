@@ -24,9 +24,11 @@ pub struct Governance {
     pub voting_proposal_count: i32,
 }
 
-#[graphql_object(Context = AppContext)]
+traced_object!(Governance);
+
+#[graphql_object(Context = AppContext, name = "Governance")]
 #[graphql(description = "SPL Governance account")]
-impl Governance {
+impl Fields<Governance> {
     fn address(&self) -> &PublicKey<Governance> {
         &self.address
     }
@@ -253,9 +255,11 @@ pub struct Realm {
     pub name: String,
 }
 
-#[graphql_object(Context = AppContext)]
+traced_object!(Realm);
+
+#[graphql_object(Context = AppContext, name = "Realm")]
 #[graphql(description = "SPLGovernance Realm account")]
-impl Realm {
+impl Fields<Realm> {
     fn address(&self) -> &PublicKey<Realm> {
         &self.address
     }
@@ -376,9 +380,11 @@ pub struct VoteRecordV1 {
     pub vote_weight: I64,
 }
 
-#[graphql_object(Context = AppContext)]
+traced_object!(VoteRecordV1);
+
+#[graphql_object(Context = AppContext, name = "VoteRecordV1")]
 #[graphql(description = "SPLGovernance VoteRecordV2 account")]
-impl VoteRecordV1 {
+impl Fields<VoteRecordV1> {
     fn address(&self) -> &PublicKey<VoteRecordV1> {
         &self.address
     }
@@ -473,9 +479,11 @@ pub enum Vote {
     Veto,
 }
 
-#[graphql_object(Context = AppContext)]
+traced_object!(VoteRecordV2);
+
+#[graphql_object(Context = AppContext, name = "VoteRecordV2")]
 #[graphql(description = "SPLGovernance VoteRecordV2 account")]
-impl VoteRecordV2 {
+impl Fields<VoteRecordV2> {
     fn address(&self) -> &PublicKey<VoteRecordV2> {
         &self.address
     }
@@ -644,9 +652,11 @@ pub struct TokenOwnerRecord {
     pub governance_delegate: Option<PublicKey<GovernanceDelegate>>,
 }
 
-#[graphql_object(Context = AppContext)]
+traced_object!(TokenOwnerRecord);
+
+#[graphql_object(Context = AppContext, name = "TokenOwnerRecord")]
 #[graphql(description = "SPLGovernance TokenOwnerRecord account")]
-impl TokenOwnerRecord {
+impl Fields<TokenOwnerRecord> {
     fn address(&self) -> &PublicKey<TokenOwnerRecord> {
         &self.address
     }
@@ -731,9 +741,11 @@ pub struct SignatoryRecord {
     pub signed_off: bool,
 }
 
-#[graphql_object(Context = AppContext)]
+traced_object!(SignatoryRecord);
+
+#[graphql_object(Context = AppContext, name = "SignatoryRecord")]
 #[graphql(description = "SPLGovernance SignatoryRecord account")]
-impl SignatoryRecord {
+impl Fields<SignatoryRecord> {
     fn address(&self) -> &PublicKey<SignatoryRecord> {
         &self.address
     }
@@ -924,9 +936,11 @@ pub struct ProposalV1 {
     pub description_link: String,
 }
 
-#[graphql_object(Context = AppContext)]
+traced_object!(ProposalV1);
+
+#[graphql_object(Context = AppContext, name = "ProposalV1")]
 #[graphql(description = "SPLGovernance ProposalV1 account")]
-impl ProposalV1 {
+impl Fields<ProposalV1> {
     fn address(&self) -> &PublicKey<ProposalV1> {
         &self.address
     }
@@ -1133,9 +1147,11 @@ pub struct ProposalV2 {
     pub description_link: String,
 }
 
-#[graphql_object(Context = AppContext)]
+traced_object!(ProposalV2);
+
+#[graphql_object(Context = AppContext, name = "ProposalV2")]
 #[graphql(description = "SPLGovernance ProposalV2 account")]
-impl ProposalV2 {
+impl Fields<ProposalV2> {
     fn address(&self) -> &PublicKey<ProposalV2> {
         &self.address
     }
@@ -13,9 +13,11 @@ pub struct MintStats {
     pub volume_total: Option<U64>,
 }
 
-#[graphql_object(Context = AppContext)]
+traced_object!(MintStats);
+
+#[graphql_object(Context = AppContext, name = "MintStats")]
 #[graphql(description = "Mint Stats")]
-impl MintStats {
+impl Fields<MintStats> {
     fn mint(&self) -> &str {
         &self.mint
     }
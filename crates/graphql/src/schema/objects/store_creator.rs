@@ -9,8 +9,10 @@ pub struct StoreCreator {
     pub twitter_handle: Option<String>,
 }
 
-#[graphql_object(Context = AppContext)]
-impl StoreCreator {
+traced_object!(StoreCreator);
+
+#[graphql_object(Context = AppContext, name = "StoreCreator")]
+impl Fields<StoreCreator> {
     pub fn store_config_address(&self) -> &str {
         &self.store_config_address
     }
@@ -1,3 +1,5 @@
+use objects::marketplace::StoreConfigIssue;
+use scalars::PublicKey;
 use tables::storefronts;
 
 use super::prelude::*;
@@ -14,8 +16,8 @@ pub type StorefrontColumns = (
     storefronts::address,
 );
 
-#[derive(Debug, Clone, GraphQLObject)]
-#[graphql(description = "A Metaplex storefront")]
+#[derive(Debug, Clone)]
+/// A Metaplex storefront
 pub struct Storefront {
     pub address: String,
     pub owner_address: String,
@@ -53,3 +55,48 @@ impl<'a> From<models::Storefront<'a>> for Storefront {
         }
     }
 }
+
+traced_object!(Storefront);
+
+#[graphql_object(Context = AppContext, description = "A Metaplex storefront", name = "Storefront")]
+impl Fields<Storefront> {
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub fn owner_address(&self) -> &str {
+        &self.owner_address
+    }
+
+    pub fn subdomain(&self) -> &str {
+        &self.subdomain
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn favicon_url(&self) -> &str {
+        &self.favicon_url
+    }
+
+    pub fn logo_url(&self) -> &str {
+        &self.logo_url
+    }
+
+    pub fn banner_url(&self) -> &str {
+        &self.banner_url
+    }
+
+    #[graphql(description = "Problems found in the marketplace config JSON for this storefront")]
+    pub async fn config_issues(&self, ctx: &AppContext) -> FieldResult<Vec<StoreConfigIssue>> {
+        ctx.storefront_config_issues_loader
+            .load(PublicKey::from(self.address.clone()))
+            .await
+            .map_err(Into::into)
+    }
+}
@@ -1,14 +1,18 @@
+use std::collections::BTreeSet;
+
 use indexer_core::{
     bigdecimal::BigDecimal,
     db::queries::{self, metadatas::WalletNftOptions},
-    pubkeys,
+    portfolio, pubkeys,
     uuid::Uuid,
 };
 use objects::{
     auction_house::AuctionHouse,
     listing::Bid,
     nft::{Collection, Nft, NftCreator},
+    portfolio::Portfolio,
     profile::TwitterProfile,
+    royalty::RoyaltyStats,
 };
 use scalars::{PublicKey, U64};
 use tables::{bids, graph_connections, wallet_total_rewards};
@@ -73,8 +77,10 @@ impl WalletNftCount {
     }
 }
 
-#[graphql_object(Context = AppContext)]
-impl WalletNftCount {
+traced_object!(WalletNftCount);
+
+#[graphql_object(Context = AppContext, name = "WalletNftCount")]
+impl Fields<WalletNftCount> {
     fn owned(&self, context: &AppContext) -> FieldResult<i32> {
         let conn = context.shared.db.get()?;
 
@@ -154,8 +160,10 @@ impl TryFrom<models::CollectedCollection> for CollectedCollection {
     }
 }
 
-#[graphql_object(Context = AppContext)]
-impl CollectedCollection {
+traced_object!(CollectedCollection);
+
+#[graphql_object(Context = AppContext, name = "CollectedCollection")]
+impl Fields<CollectedCollection> {
     async fn collection(&self, ctx: &AppContext) -> FieldResult<Option<Collection>> {
         ctx.nft_loader
             .load(self.metadata_address.clone())
@@ -186,8 +194,10 @@ impl From<models::CreatedCollection> for CreatedCollection {
     }
 }
 
-#[graphql_object(Context = AppContext)]
-impl CreatedCollection {
+traced_object!(CreatedCollection);
+
+#[graphql_object(Context = AppContext, name = "CreatedCollection")]
+impl Fields<CreatedCollection> {
     async fn collection(&self, ctx: &AppContext) -> FieldResult<Option<Collection>> {
         ctx.nft_loader
             .load(self.address.clone())
@@ -242,8 +252,10 @@ impl TryFrom<models::WalletActivity> for WalletActivity {
     }
 }
 
-#[graphql_object(Context = AppContext)]
-impl WalletActivity {
+traced_object!(WalletActivity);
+
+#[graphql_object(Context = AppContext, name = "WalletActivity")]
+impl Fields<WalletActivity> {
     fn id(&self) -> &Uuid {
         &self.id
     }
@@ -288,8 +300,10 @@ impl WalletActivity {
     }
 }
 
-#[graphql_object(Context = AppContext)]
-impl Wallet {
+traced_object!(Wallet);
+
+#[graphql_object(Context = AppContext, name = "Wallet")]
+impl Fields<Wallet> {
     pub fn address(&self) -> &PublicKey<Wallet> {
         &self.address
     }
@@ -434,6 +448,100 @@ impl Wallet {
         WalletNftCount::new(self.address.clone(), creators)
     }
 
+    #[graphql(
+        description = "Royalties owed to and paid to this wallet as a creator, or null if none of its NFTs have sold with royalties",
+        arguments(
+            start_date(description = "Only include sales made at or after this time"),
+            end_date(description = "Only include sales made before this time"),
+        )
+    )]
+    pub fn creator_earnings(
+        &self,
+        ctx: &AppContext,
+        start_date: Option<DateTime<Utc>>,
+        end_date: Option<DateTime<Utc>>,
+    ) -> FieldResult<Option<RoyaltyStats>> {
+        let conn = ctx.shared.db.get()?;
+
+        queries::royalties::stats(&conn, queries::royalties::RoyaltyOptions {
+            creator: Some(self.address.as_ref()),
+            collection: None,
+            start_date: start_date.map(|d| d.naive_utc()),
+            end_date: end_date.map(|d| d.naive_utc()),
+        })?
+        .map(TryInto::try_into)
+        .transpose()
+        .map_err(Into::into)
+    }
+
+    #[graphql(
+        description = "Valuation and realized and unrealized profit and loss of the NFTs held and traded by this wallet",
+        arguments(history_days(
+            description = "Number of days of history to include, ending today (default 30, at most 365)"
+        ))
+    )]
+    pub fn portfolio(&self, ctx: &AppContext, history_days: Option<i32>) -> FieldResult<Portfolio> {
+        let history_days = history_days.unwrap_or(30);
+
+        if !(1..=365).contains(&history_days) {
+            return Err(FieldError::new(
+                "historyDays must be between 1 and 365",
+                graphql_value!(None),
+            ));
+        }
+
+        let conn = ctx.shared.db.get()?;
+        let now = Utc::now().naive_utc();
+        let today = now.date();
+        let start = today - chrono::Duration::days((history_days - 1).into());
+
+        let holdings: Vec<_> = queries::portfolio::holdings(&conn, &self.address, now)?
+            .into_iter()
+            .map(|h| portfolio::Holding {
+                value: portfolio::estimate_value(h.floor_price, h.trait_floor, h.last_sale_price),
+                nft: h.metadata_address,
+                collection: h.collection_address,
+            })
+            .collect();
+
+        let trades: Vec<_> = queries::portfolio::trades(&conn, &self.address)?
+            .into_iter()
+            .map(|t| portfolio::Trade {
+                nft: t.metadata_address,
+                collection: t.collection_address,
+                side: if t.bought {
+                    portfolio::Side::Buy
+                } else {
+                    portfolio::Side::Sell
+                },
+                price: t.price,
+                at: t.created_at,
+            })
+            .collect();
+
+        let collections: Vec<_> = holdings
+            .iter()
+            .filter_map(|h| h.collection.as_deref())
+            .chain(trades.iter().filter_map(|t| t.collection.as_deref()))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        let mut closes: HashMap<String, Vec<_>> = HashMap::new();
+
+        for close in queries::portfolio::daily_closes(&conn, &collections, start.and_hms(0, 0, 0))?
+        {
+            closes
+                .entry(close.collection_address)
+                .or_default()
+                .push((close.bucket_start.date(), close.close));
+        }
+
+        portfolio::compute(&holdings, &trades, &closes, today, history_days.try_into()?)
+            .try_into()
+            .map_err(Into::into)
+    }
+
     pub fn total_rewards(
         &self,
         ctx: &AppContext,
@@ -459,8 +567,10 @@ pub struct ConnectionCounts {
     pub address: PublicKey<Wallet>,
 }
 
-#[graphql_object(Context = AppContext)]
-impl ConnectionCounts {
+traced_object!(ConnectionCounts);
+
+#[graphql_object(Context = AppContext, name = "ConnectionCounts")]
+impl Fields<ConnectionCounts> {
     pub fn from_count(&self, ctx: &AppContext) -> FieldResult<i32> {
         let db_conn = ctx.shared.db.get()?;
 
@@ -1,18 +1,30 @@
+use std::collections::HashMap;
+
 use indexer_core::{
     db::{
         self,
         expression::dsl::all,
-        queries::{self, collections::TrendingQueryOptions, feed_event::EventType},
+        models::TraitCount,
+        queries::{
+            self,
+            collection_stats::{
+                StatsWindow, TrendsOptions, WindowStatsSort, WindowedTrendsOptions,
+            },
+            feed_event::EventType,
+            nft_search,
+        },
+        Order,
     },
     pubkeys,
 };
 use objects::{
     ah_listing::AhListing,
+    attributes::{AttributeGroup, AttributeVariant},
     auction_house::AuctionHouse,
     bid_receipt::BidReceipt,
     bonding_change::EnrichedBondingChange,
     candy_machine::CandyMachine,
-    chart::PriceChart,
+    chart::{PriceCandle, PriceChart},
     collections::CollectionDocument,
     creator::Creator,
     denylist::Denylist,
@@ -21,16 +33,20 @@ use objects::{
     graph_connection::GraphConnection,
     listing::{Listing, ListingColumns, ListingRow},
     marketplace::Marketplace,
-    nft::{Collection, MetadataJson, Nft, NftActivity, NftCount, NftCreator, NftsStats},
+    nft::{
+        Collection, MetadataJson, Nft, NftActivity, NftCount, NftCreator, NftSearchResults,
+        NftsStats,
+    },
+    owner_history::{self, NftOwnerPeriod, SnapshotHolder},
     profile::{ProfilesStats, TwitterProfile},
+    royalty::RoyaltyStats,
     spl_governance::{
         Governance, Proposal, ProposalV2, Realm, SignatoryRecord, TokenOwnerRecord, VoteRecord,
     },
     storefront::{Storefront, StorefrontColumns},
     wallet::Wallet,
 };
-use scalars::{markers::TokenMint, PublicKey};
-use serde_json::Value;
+use scalars::{markers::TokenMint, PublicKey, U64};
 use tables::{
     auction_caches, auction_datas, auction_datas_ext, auction_houses, bid_receipts,
     candy_machine_datas, candy_machines, current_metadata_owners, geno_habitat_datas, governances,
@@ -39,10 +55,12 @@ use tables::{
 };
 
 use super::{
-    enums::{CollectionInterval, CollectionSort, OrderDirection},
+    enums::{CandleResolution, CollectionInterval, CollectionSort, OrderDirection},
     objects::nft::CollectionTrend,
     prelude::*,
 };
+use crate::search::SearchIndex;
+
 pub struct QueryRoot;
 
 #[derive(GraphQLInputObject, Clone, Debug)]
@@ -58,6 +76,30 @@ impl From<AttributeFilter> for queries::metadatas::AttributeFilter {
     }
 }
 
+#[derive(GraphQLInputObject, Clone, Debug)]
+#[graphql(description = "Filter on an inclusive range of numeric NFT attribute values")]
+pub struct AttributeRangeFilter {
+    trait_type: String,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+impl From<AttributeRangeFilter> for queries::metadatas::AttributeRangeFilter {
+    fn from(
+        AttributeRangeFilter {
+            trait_type,
+            min,
+            max,
+        }: AttributeRangeFilter,
+    ) -> Self {
+        Self {
+            trait_type,
+            min,
+            max,
+        }
+    }
+}
+
 impl QueryRoot {
     fn candy_machine(context: &AppContext, address: String) -> FieldResult<Option<CandyMachine>> {
         let conn = context.shared.db.get()?;
@@ -81,8 +123,10 @@ impl QueryRoot {
     }
 }
 
-#[graphql_object(Context = AppContext)]
-impl QueryRoot {
+traced_object!(QueryRoot);
+
+#[graphql_object(Context = AppContext, name = "QueryRoot")]
+impl Fields<QueryRoot> {
     #[graphql(
         description = "Recommend wallets to follow.",
         arguments(
@@ -240,6 +284,152 @@ impl QueryRoot {
         })
     }
 
+    #[graphql(
+        description = "Open, high, low, and close sale prices of a verified collection, for each time bucket with at least one sale",
+        arguments(
+            collection(description = "Mint address of the collection NFT"),
+            resolution(description = "Width of each time bucket"),
+            from(description = "Start of the time range, rounded down to the start of its bucket"),
+            to(description = "End of the time range, exclusive"),
+            auction_houses(
+                description = "Optional list of auction houses to include sales from, otherwise all"
+            ),
+            exclude_flagged(
+                description = "Leave sales flagged as suspected wash trades out of the volume and sales count of each candle, defaults to false"
+            ),
+        )
+    )]
+    pub async fn collection_candles(
+        &self,
+        context: &AppContext,
+        collection: PublicKey<Nft>,
+        resolution: CandleResolution,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        auction_houses: Option<Vec<PublicKey<AuctionHouse>>>,
+        exclude_flagged: Option<bool>,
+    ) -> FieldResult<Vec<PriceCandle>> {
+        const MAX_CANDLES: i32 = 10_000;
+
+        let resolution: queries::charts::CandleResolution = resolution.into();
+
+        if to <= from {
+            return Err(FieldError::new(
+                "to must be after from",
+                graphql_value!(None),
+            ));
+        }
+
+        if (to - from).num_seconds() / resolution.duration().num_seconds() > MAX_CANDLES.into() {
+            return Err(FieldError::new(
+                "Time range spans too many candles",
+                graphql_value!({ "max": MAX_CANDLES }),
+            ));
+        }
+
+        let conn = context.shared.db.get()?;
+        let rows = queries::charts::collection_candles(
+            &conn,
+            &collection,
+            resolution,
+            &auction_houses,
+            from.naive_utc(),
+            to.naive_utc(),
+            exclude_flagged.unwrap_or(false),
+        )?;
+
+        rows.into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<_>>()
+            .map_err(Into::into)
+    }
+
+    #[graphql(
+        description = "Royalties owed and paid for sales through each marketplace program, including the fraction of royalties paid where payments are known",
+        arguments(
+            collection(
+                description = "Optional mint address of a collection NFT to limit sales to"
+            ),
+            start_date(description = "Only include sales made at or after this time"),
+            end_date(description = "Only include sales made before this time"),
+        )
+    )]
+    pub fn marketplace_royalties(
+        &self,
+        context: &AppContext,
+        collection: Option<PublicKey<Nft>>,
+        start_date: Option<DateTime<Utc>>,
+        end_date: Option<DateTime<Utc>>,
+    ) -> FieldResult<Vec<RoyaltyStats>> {
+        let conn = context.shared.db.get()?;
+        let rows = queries::royalties::by_marketplace(&conn, queries::royalties::RoyaltyOptions {
+            creator: None,
+            collection: collection.as_ref().map(AsRef::as_ref),
+            start_date: start_date.map(|d| d.naive_utc()),
+            end_date: end_date.map(|d| d.naive_utc()),
+        })?;
+
+        rows.into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<_>>()
+            .map_err(Into::into)
+    }
+
+    #[graphql(
+        description = "The number of NFTs of a verified collection held by each wallet at a point in time, largest holdings first.  NFTs are counted by their current verified collection; burned NFTs are left out when looking up by timestamp.",
+        arguments(
+            collection(description = "Mint address of the collection NFT"),
+            slot(description = "Look up holders as of the end of this slot"),
+            timestamp(
+                description = "Look up holders as of this time, compared against the block time of each owner's slot"
+            ),
+        )
+    )]
+    pub fn collection_holders_at(
+        &self,
+        context: &AppContext,
+        collection: PublicKey<Nft>,
+        slot: Option<U64>,
+        timestamp: Option<DateTime<Utc>>,
+    ) -> FieldResult<Vec<SnapshotHolder>> {
+        let at = owner_history::point_in_time(slot, timestamp)?;
+
+        let conn = context.shared.db.get()?;
+        let rows = queries::owner_history::collection_holders_at(&conn, collection.as_ref(), at)?;
+
+        rows.into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<_, _>>()
+            .map_err(Into::into)
+    }
+
+    #[graphql(
+        description = "The owner of an NFT at a point in time, with the period during which it owned the NFT",
+        arguments(
+            mint(description = "Mint address of the NFT"),
+            slot(description = "Look up the owner as of the end of this slot"),
+            timestamp(
+                description = "Look up the owner as of this time, compared against the block time of each owner's slot"
+            ),
+        )
+    )]
+    pub fn nft_owner_at(
+        &self,
+        context: &AppContext,
+        mint: PublicKey<TokenMint>,
+        slot: Option<U64>,
+        timestamp: Option<DateTime<Utc>>,
+    ) -> FieldResult<Option<NftOwnerPeriod>> {
+        let at = owner_history::point_in_time(slot, timestamp)?;
+
+        let conn = context.shared.db.get()?;
+
+        queries::owner_history::owner_at(&conn, mint.as_ref(), at)?
+            .map(TryInto::try_into)
+            .transpose()
+            .map_err(Into::into)
+    }
+
     #[graphql(arguments(
         auction_housese(description = "List of auction houses"),
         creators(description = "Optional list of creators"),
@@ -379,6 +569,9 @@ impl QueryRoot {
             Vec<PublicKey<Wallet>>,
         >,
         #[graphql(description = "Filter on attributes")] attributes: Option<Vec<AttributeFilter>>,
+        #[graphql(description = "Filter on ranges of numeric attributes")] attribute_ranges: Option<
+            Vec<AttributeRangeFilter>,
+        >,
         #[graphql(description = "Filter only listed NFTs")] listed: Option<bool>,
         #[graphql(description = "Allow unverified NFTs")] allow_unverified: Option<bool>,
         #[graphql(
@@ -445,21 +638,22 @@ impl QueryRoot {
 
         let addresses = match term {
             Some(term) => {
-                let search = &context.shared.search;
-                let search_result = search
-                    .index("metadatas")
-                    .search()
-                    .with_query(&term)
-                    .with_limit(context.shared.pre_query_search_limit)
-                    .execute::<Value>()
+                let search_result = context
+                    .shared
+                    .search
+                    .search(
+                        SearchIndex::Metadatas,
+                        &term,
+                        0,
+                        context.shared.pre_query_search_limit,
+                    )
                     .await
-                    .context("failed to load search result for metadata json")?
-                    .hits;
+                    .context("failed to load search result for metadata json")?;
 
                 Some(
                     search_result
                         .into_iter()
-                        .map(|r| MetadataJson::from(r.result).address)
+                        .map(|r| MetadataJson::from(r).address)
                         .collect(),
                 )
             },
@@ -473,6 +667,7 @@ impl QueryRoot {
             update_authorities: update_authorities.map(|a| a.into_iter().map(Into::into).collect()),
             offerers: offerers.map(|o| o.into_iter().map(Into::into).collect()),
             attributes: attributes.map(|a| a.into_iter().map(Into::into).collect()),
+            attribute_ranges: attribute_ranges.map(|r| r.into_iter().map(Into::into).collect()),
             listed,
             allow_unverified,
             with_offers,
@@ -493,6 +688,99 @@ impl QueryRoot {
             .map_err(Into::into)
     }
 
+    #[graphql(description = "Full-text search of NFTs, with counts of matching NFTs by trait")]
+    async fn search_nfts(
+        &self,
+        context: &AppContext,
+        #[graphql(description = "Search term")] term: Option<String>,
+        #[graphql(description = "Filter on one or more collections")] collections: Option<
+            Vec<PublicKey<Nft>>,
+        >,
+        #[graphql(description = "Filter on verified creator address")] creators: Option<
+            Vec<PublicKey<Wallet>>,
+        >,
+        #[graphql(description = "Filter on owner address")] owners: Option<Vec<PublicKey<Wallet>>>,
+        #[graphql(description = "Filter on attributes")] attributes: Option<Vec<AttributeFilter>>,
+        #[graphql(description = "Filter on whether NFTs are listed for sale")] listed: Option<bool>,
+        #[graphql(description = "Sort by lowest listing price")] order_by_price: Option<
+            OrderDirection,
+        >,
+        #[graphql(description = "Query limit")] limit: i32,
+        #[graphql(description = "Query offset")] offset: i32,
+    ) -> FieldResult<NftSearchResults> {
+        let page = context
+            .shared
+            .search
+            .search_nfts(&nft_search::SearchOptions {
+                term,
+                collections: collections.map(|c| c.into_iter().map(Into::into).collect()),
+                creators: creators.map(|c| c.into_iter().map(Into::into).collect()),
+                owners: owners.map(|o| o.into_iter().map(Into::into).collect()),
+                attributes: attributes
+                    .into_iter()
+                    .flatten()
+                    .map(|AttributeFilter { trait_type, values }| (trait_type, values))
+                    .collect(),
+                listed,
+                order_by_price: order_by_price.map(|o| match o {
+                    OrderDirection::Asc => Order::Asc,
+                    OrderDirection::Desc => Order::Desc,
+                }),
+                offset: offset.into(),
+                limit: limit.into(),
+            })
+            .await
+            .context("failed to load search result for NFTs")?;
+
+        let nfts = futures_util::future::try_join_all(
+            page.addresses
+                .into_iter()
+                .map(|a| context.nft_loader.load(a.into())),
+        )
+        .await?
+        .into_iter()
+        .flatten()
+        .collect();
+
+        let mut groups: HashMap<String, Vec<AttributeVariant>> = HashMap::new();
+
+        for TraitCount {
+            trait_type,
+            value,
+            count,
+        } in page.traits
+        {
+            groups
+                .entry(trait_type)
+                .or_default()
+                .push(AttributeVariant {
+                    name: value,
+                    count: count.try_into()?,
+                });
+        }
+
+        let mut traits: Vec<_> = groups
+            .into_iter()
+            .map(|(name, mut variants)| {
+                variants.sort();
+
+                AttributeGroup {
+                    name,
+                    variants,
+                    min_value: None,
+                    max_value: None,
+                }
+            })
+            .collect();
+        traits.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(NftSearchResults {
+            nfts,
+            traits,
+            total: page.total.try_into()?,
+        })
+    }
+
     #[graphql(description = "Stats aggregated across all indexed NFTs")]
     fn nfts_stats(&self) -> NftsStats {
         NftsStats
@@ -685,7 +973,7 @@ impl QueryRoot {
 
     #[graphql(deprecated = "Deprecated alias for candyMachine")]
     fn candymachine(&self, ctx: &AppContext, addr: String) -> FieldResult<Option<CandyMachine>> {
-        Self::candy_machine(ctx, addr)
+        QueryRoot::candy_machine(ctx, addr)
     }
 
     #[graphql(description = "Get a candy machine by the candy machine config address")]
@@ -694,7 +982,7 @@ impl QueryRoot {
         context: &AppContext,
         #[graphql(description = "address of the candy machine config")] address: String,
     ) -> FieldResult<Option<CandyMachine>> {
-        Self::candy_machine(context, address)
+        QueryRoot::candy_machine(context, address)
     }
 
     fn storefronts(&self, context: &AppContext) -> FieldResult<Vec<Storefront>> {
@@ -756,22 +1044,21 @@ impl QueryRoot {
         #[graphql(description = "Query limit")] limit: i32,
         #[graphql(description = "Query offset")] offset: i32,
     ) -> FieldResult<Vec<MetadataJson>> {
-        let search = &context.shared.search;
-
-        let query_result = search
-            .index("metadatas")
-            .search()
-            .with_query(&term)
-            .with_offset(offset.try_into()?)
-            .with_limit(limit.try_into()?)
-            .execute::<Value>()
+        let query_result = context
+            .shared
+            .search
+            .search(
+                SearchIndex::Metadatas,
+                &term,
+                offset.try_into()?,
+                limit.try_into()?,
+            )
             .await
-            .context("failed to load search result for metadata json")?
-            .hits;
+            .context("failed to load search result for metadata json")?;
 
         Ok(query_result
             .into_iter()
-            .map(|r| r.result.into())
+            .map(Into::into)
             .collect::<Vec<MetadataJson>>())
     }
 
@@ -795,25 +1082,70 @@ impl QueryRoot {
         description = "Returns featured collection NFTs ordered by market cap (floor price * number of NFTs in collection)",
         arguments(
             sort_by(description = "Choose sort for trending collections"),
-            time_frame(description = "The desired timeframe to evaluate the trending collection"),
+            time_frame(
+                description = "The desired timeframe to evaluate the trending collection.  Ignored if window is given."
+            ),
+            window(
+                description = "Trailing window to compute statistics over, e.g. `1h`, `24h`, `90d`, or `all`, using the units m, h, d, or w.  Statistics are returned in the `window` field of each trend."
+            ),
             order_direction(
                 description = "Arrange result in ascending or descending order by selected sort_by"
             ),
             limit(description = "Return at most this many results"),
             offset(description = "Return results starting from this index"),
+            exclude_flagged(
+                description = "Leave sales flagged as suspected wash trades out of the volumes and sales counts, defaults to false"
+            ),
         )
     )]
     async fn collection_trends(
         &self,
         context: &AppContext,
         sort_by: CollectionSort,
-        time_frame: CollectionInterval,
+        time_frame: Option<CollectionInterval>,
+        window: Option<String>,
         order_direction: Option<OrderDirection>,
         limit: i32,
         offset: i32,
+        exclude_flagged: Option<bool>,
     ) -> FieldResult<Vec<CollectionTrend>> {
         let conn = context.shared.db.get().context("failed to connect to db")?;
 
+        if let Some(window) = window {
+            let window: StatsWindow = window.parse()?;
+
+            let sort_by = match sort_by {
+                CollectionSort::Volume => WindowStatsSort::Volume,
+                CollectionSort::Floor => WindowStatsSort::FloorPrice,
+                CollectionSort::NumberSales => WindowStatsSort::SalesCount,
+                CollectionSort::Marketcap => WindowStatsSort::Marketcap,
+                CollectionSort::UniqueBuyers => WindowStatsSort::UniqueBuyers,
+            };
+
+            let collections =
+                queries::collection_stats::windowed_trends(&conn, WindowedTrendsOptions {
+                    window,
+                    sort_by,
+                    order: order_direction.map(Into::into),
+                    limit: limit.into(),
+                    offset: offset.into(),
+                    exclude_flagged: exclude_flagged.unwrap_or(false),
+                })?;
+
+            return collections
+                .into_iter()
+                .map(|c| (window, c).try_into())
+                .collect::<Result<_>>()
+                .map_err(Into::into);
+        }
+
+        let time_frame = time_frame.ok_or_else(|| {
+            FieldError::new(
+                "Either timeFrame or window is required",
+                graphql_value!(None),
+            )
+        })?;
+
         let sort = match (time_frame, sort_by) {
             (CollectionInterval::One, CollectionSort::Volume) => {
                 db::custom_types::CollectionSort::OneDayVolume
@@ -846,13 +1178,20 @@ impl QueryRoot {
                 CollectionInterval::One | CollectionInterval::Seven | CollectionInterval::Thirty,
                 CollectionSort::Floor,
             ) => db::custom_types::CollectionSort::FloorPrice,
+            (_, CollectionSort::UniqueBuyers) => {
+                return Err(FieldError::new(
+                    "Sorting by unique buyers requires a window",
+                    graphql_value!(None),
+                ));
+            },
         };
 
-        let collections = queries::collections::trends(&conn, TrendingQueryOptions {
+        let collections = queries::collection_stats::trends(&conn, TrendsOptions {
             sort_by: sort,
             order: order_direction.map(Into::into),
-            limit: limit.try_into()?,
-            offset: offset.try_into()?,
+            limit: limit.into(),
+            offset: offset.into(),
+            exclude_flagged: exclude_flagged.unwrap_or(false),
         })?;
 
         collections
@@ -895,21 +1234,22 @@ impl QueryRoot {
 
         let addresses: Option<Vec<String>> = match term {
             Some(term) => {
-                let search = &context.shared.search;
-                let search_result = search
-                    .index("collections")
-                    .search()
-                    .with_query(&term)
-                    .with_limit(context.shared.pre_query_search_limit)
-                    .execute::<Value>()
+                let search_result = context
+                    .shared
+                    .search
+                    .search(
+                        SearchIndex::Collections,
+                        &term,
+                        0,
+                        context.shared.pre_query_search_limit,
+                    )
                     .await
-                    .context("failed to load search result for collections")?
-                    .hits;
+                    .context("failed to load search result for collections")?;
 
                 Some(
                     search_result
                         .into_iter()
-                        .map(|r| MetadataJson::from(r.result).mint_address)
+                        .map(|r| MetadataJson::from(r).mint_address)
                         .collect(),
                 )
             },
@@ -948,6 +1288,9 @@ impl QueryRoot {
             ),
             limit(description = "Return at most this many results"),
             offset(description = "Return results starting from this index"),
+            exclude_flagged(
+                description = "Leave sales flagged as suspected wash trades out of the volume, defaults to false"
+            ),
         )
     )]
     async fn collections_featured_by_volume(
@@ -959,26 +1302,28 @@ impl QueryRoot {
         end_date: DateTime<Utc>,
         limit: i32,
         offset: i32,
+        exclude_flagged: Option<bool>,
     ) -> FieldResult<Vec<Collection>> {
         let conn = context.shared.db.get().context("failed to connect to db")?;
 
         let addresses: Option<Vec<String>> = match term {
             Some(term) => {
-                let search = &context.shared.search;
-                let search_result = search
-                    .index("collections")
-                    .search()
-                    .with_query(&term)
-                    .with_limit(context.shared.pre_query_search_limit)
-                    .execute::<Value>()
+                let search_result = context
+                    .shared
+                    .search
+                    .search(
+                        SearchIndex::Collections,
+                        &term,
+                        0,
+                        context.shared.pre_query_search_limit,
+                    )
                     .await
-                    .context("failed to load search result for collections")?
-                    .hits;
+                    .context("failed to load search result for collections")?;
 
                 Some(
                     search_result
                         .into_iter()
-                        .map(|r| MetadataJson::from(r.result).mint_address)
+                        .map(|r| MetadataJson::from(r).mint_address)
                         .collect(),
                 )
             },
@@ -991,6 +1336,7 @@ impl QueryRoot {
             order_direction.into(),
             start_date,
             end_date,
+            exclude_flagged.unwrap_or(false),
             limit,
             offset,
         )?;
@@ -1010,22 +1356,21 @@ impl QueryRoot {
         #[graphql(description = "Query limit")] limit: i32,
         #[graphql(description = "Query offset")] offset: i32,
     ) -> FieldResult<Vec<CollectionDocument>> {
-        let search = &context.shared.search;
-
-        let query_result = search
-            .index("mr-collections")
-            .search()
-            .with_query(&term)
-            .with_offset(offset.try_into()?)
-            .with_limit(limit.try_into()?)
-            .execute::<Value>()
+        let query_result = context
+            .shared
+            .search
+            .search(
+                SearchIndex::MrCollections,
+                &term,
+                offset.try_into()?,
+                limit.try_into()?,
+            )
             .await
-            .context("failed to load search result for mr collections")?
-            .hits;
+            .context("failed to load search result for mr collections")?;
 
         Ok(query_result
             .into_iter()
-            .map(|r| r.result.into())
+            .map(Into::into)
             .collect::<Vec<CollectionDocument>>())
     }
 
@@ -1037,22 +1382,21 @@ impl QueryRoot {
         #[graphql(description = "Query limit")] limit: i32,
         #[graphql(description = "Query offset")] offset: i32,
     ) -> FieldResult<Vec<Wallet>> {
-        let search = &context.shared.search;
-
-        let query_result = search
-            .index("name_service")
-            .search()
-            .with_query(&term)
-            .with_offset(offset.try_into()?)
-            .with_limit(limit.try_into()?)
-            .execute::<Value>()
+        let query_result = context
+            .shared
+            .search
+            .search(
+                SearchIndex::NameService,
+                &term,
+                offset.try_into()?,
+                limit.try_into()?,
+            )
             .await
-            .context("failed to load search result for twitter handle")?
-            .hits;
+            .context("failed to load search result for twitter handle")?;
 
         Ok(query_result
             .into_iter()
-            .map(|r| r.result.into())
+            .map(Into::into)
             .collect::<Vec<Wallet>>())
     }
 
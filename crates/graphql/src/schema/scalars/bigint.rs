@@ -60,6 +60,12 @@ impl From<u64> for U64 {
     }
 }
 
+impl From<U64> for u64 {
+    fn from(value: U64) -> Self {
+        value.0
+    }
+}
+
 impl TryFrom<i64> for U64 {
     type Error = std::num::TryFromIntError;
 
@@ -6,7 +6,14 @@ use objects::attributes::{AttributeGroup, AttributeVariant};
 
 use super::prelude::*;
 
-/// groups metadata attributes into attribute groups
+#[derive(Default)]
+struct Group<'a> {
+    variants: HashMap<Option<Cow<'a, str>>, i32>,
+    numeric: Vec<f64>,
+}
+
+/// groups metadata attributes into attribute groups, including the range of
+/// any numeric values for use with range filters
 pub fn group(
     metadata_attributes: Vec<models::MetadataAttribute>,
 ) -> FieldResult<Vec<AttributeGroup>> {
@@ -16,34 +23,46 @@ pub fn group(
             HashMap::new(),
             |mut groups,
              models::MetadataAttribute {
-                 trait_type, value, ..
+                 trait_type,
+                 value,
+                 value_numeric,
+                 ..
              }| {
-                *groups
+                let group: &mut Group = groups
                     .entry(
                         trait_type
                             .ok_or_else(|| anyhow!("Missing trait type from attribute"))?
                             .into_owned(),
                     )
-                    .or_insert_with(HashMap::new)
-                    .entry(value)
-                    .or_insert(0) += 1;
+                    .or_default();
+
+                *group.variants.entry(value).or_insert(0) += 1;
+                group.numeric.extend(value_numeric);
 
                 Result::<_>::Ok(groups)
             },
         )?
         .into_iter()
-        .map(|(name, vars)| AttributeGroup {
-            name,
-            variants: vars
-                .into_iter()
-                .map(|(name, count)| {
-                    let name = name.map_or_else(String::new, Cow::into_owned);
-
-                    AttributeVariant { name, count }
-                })
-                .sorted()
-                .collect(),
+        .map(|(name, Group { variants, numeric })| {
+            let range = numeric.into_iter().fold(None, |range, v| {
+                Some(range.map_or((v, v), |(min, max): (f64, f64)| (min.min(v), max.max(v))))
+            });
+
+            AttributeGroup {
+                name,
+                variants: variants
+                    .into_iter()
+                    .map(|(name, count)| {
+                        let name = name.map_or_else(String::new, Cow::into_owned);
+
+                        AttributeVariant { name, count }
+                    })
+                    .sorted()
+                    .collect(),
+                min_value: range.map(|(min, _)| min),
+                max_value: range.map(|(_, max)| max),
+            }
         })
-        .sorted()
+        .sorted_by(|a, b| a.name.cmp(&b.name))
         .collect::<Vec<_>>())
 }
@@ -0,0 +1,173 @@
+//! Field resolver timing.  Objects are declared with `#[graphql_object]` on
+//! [`Fields<T>`] and exposed as `T` by [`traced_object!`], which records a
+//! span in the request trace for every field juniper resolves on them,
+//! named after the object type and field.
+
+use std::ops::Deref;
+
+use juniper::{
+    Arguments, BoxFuture, ExecutionResult, Executor, GraphQLValue, GraphQLValueAsync, ScalarValue,
+};
+
+use super::prelude::*;
+
+/// The fields of the GraphQL object `T`, resolved without timing
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct Fields<T>(T);
+
+impl<T> Deref for Fields<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Fields<T> {
+    fn of(value: &T) -> &Self {
+        // SAFETY: `Fields` is a transparent wrapper, so it has the same layout
+        // as `T`
+        unsafe { &*(value as *const T).cast() }
+    }
+}
+
+fn span_name(type_name: Option<&str>, field_name: &str) -> String {
+    format!("{}.{}", type_name.unwrap_or("?"), field_name)
+}
+
+/// Resolve a field of `value`, timing it until it is resolved
+pub fn resolve_field<S, T>(
+    value: &T,
+    field_name: &str,
+    arguments: &Arguments<S>,
+    executor: &Executor<AppContext, S>,
+) -> ExecutionResult<S>
+where
+    S: ScalarValue,
+    Fields<T>: GraphQLValue<S, Context = AppContext, TypeInfo = ()>,
+{
+    let fields = Fields::of(value);
+
+    executor
+        .context()
+        .trace
+        .resolver_sync(span_name(fields.type_name(&()), field_name), || {
+            fields.resolve_field(&(), field_name, arguments, executor)
+        })
+}
+
+/// Resolve a field of `value`, timing it until the returned future completes
+pub fn resolve_field_async<'a, S, T>(
+    value: &'a T,
+    field_name: &'a str,
+    arguments: &'a Arguments<S>,
+    executor: &'a Executor<AppContext, S>,
+) -> BoxFuture<'a, ExecutionResult<S>>
+where
+    S: ScalarValue + Send + Sync,
+    Fields<T>: GraphQLValueAsync<S, Context = AppContext, TypeInfo = ()>,
+{
+    let fields = Fields::of(value);
+
+    Box::pin(
+        executor
+            .context()
+            .trace
+            .resolver(span_name(fields.type_name(&()), field_name), || {
+                fields.resolve_field_async(&(), field_name, arguments, executor)
+            }),
+    )
+}
+
+/// Get the concrete GraphQL type name of `value`
+pub fn concrete_type_name<S, T>(value: &T, context: &AppContext) -> String
+where
+    S: ScalarValue,
+    Fields<T>: GraphQLValue<S, Context = AppContext, TypeInfo = ()>,
+{
+    Fields::of(value).concrete_type_name(context, &())
+}
+
+/// Expose each given type as the GraphQL object declared on its
+/// [`Fields`](crate::schema::traced::Fields), timing each field resolved on
+/// it
+macro_rules! traced_object {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl<__S: ::juniper::ScalarValue> ::juniper::marker::IsOutputType<__S> for $ty {
+                fn mark() {
+                    <$crate::schema::traced::Fields<$ty> as ::juniper::marker::IsOutputType<
+                        __S,
+                    >>::mark();
+                }
+            }
+
+            impl<__S: ::juniper::ScalarValue> ::juniper::marker::GraphQLObjectType<__S> for $ty {}
+
+            impl<__S: ::juniper::ScalarValue> ::juniper::GraphQLType<__S> for $ty {
+                fn name(info: &()) -> Option<&str> {
+                    <$crate::schema::traced::Fields<$ty> as ::juniper::GraphQLType<__S>>::name(
+                        info,
+                    )
+                }
+
+                fn meta<'r>(
+                    info: &(),
+                    registry: &mut ::juniper::Registry<'r, __S>,
+                ) -> ::juniper::meta::MetaType<'r, __S>
+                where
+                    __S: 'r,
+                {
+                    <$crate::schema::traced::Fields<$ty> as ::juniper::GraphQLType<__S>>::meta(
+                        info, registry,
+                    )
+                }
+            }
+
+            impl<__S: ::juniper::ScalarValue> ::juniper::GraphQLValue<__S> for $ty {
+                type Context = $crate::schema::AppContext;
+                type TypeInfo = ();
+
+                fn type_name<'i>(&self, info: &'i ()) -> Option<&'i str> {
+                    <Self as ::juniper::GraphQLType<__S>>::name(info)
+                }
+
+                fn resolve_field(
+                    &self,
+                    _: &(),
+                    field_name: &str,
+                    arguments: &::juniper::Arguments<__S>,
+                    executor: &::juniper::Executor<Self::Context, __S>,
+                ) -> ::juniper::ExecutionResult<__S> {
+                    $crate::schema::traced::resolve_field(
+                        self, field_name, arguments, executor,
+                    )
+                }
+
+                fn concrete_type_name(&self, context: &Self::Context, _: &()) -> String {
+                    $crate::schema::traced::concrete_type_name::<__S, _>(self, context)
+                }
+            }
+
+            impl<__S> ::juniper::GraphQLValueAsync<__S> for $ty
+            where
+                __S: ::juniper::ScalarValue + Send + Sync,
+            {
+                fn resolve_field_async<'a>(
+                    &'a self,
+                    _: &'a (),
+                    field_name: &'a str,
+                    arguments: &'a ::juniper::Arguments<__S>,
+                    executor: &'a ::juniper::Executor<Self::Context, __S>,
+                ) -> ::juniper::BoxFuture<'a, ::juniper::ExecutionResult<__S>> {
+                    $crate::schema::traced::resolve_field_async(
+                        self, field_name, arguments, executor,
+                    )
+                }
+            }
+        )+
+    };
+}
+
+pub(super) use traced_object;
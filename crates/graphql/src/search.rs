@@ -0,0 +1,671 @@
+//! Full-text search over the indexed documents, backed by either Meilisearch
+//! or Postgres
+
+use std::{str::FromStr, sync::Arc};
+
+use async_trait::async_trait;
+use indexer_core::{
+    clap,
+    db::{
+        expression::dsl::sql,
+        models::TraitCount,
+        queries::nft_search::{self, SearchOptions},
+        sql_types::{Bool, Text},
+        tables::{
+            collections, geno_habitat_datas, metadata_collection_keys, metadata_jsons, metadatas,
+            twitter_handle_name_services,
+        },
+        Order, Pool,
+    },
+    meilisearch::{self, search::Selectors, NftDocument, NFTS_INDEX},
+    prelude::*,
+};
+use serde_json::{json, Value};
+
+/// A search index queried by the GraphQL server
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchIndex {
+    /// Metaplex metadata accounts
+    Metadatas,
+    /// Verified collection NFTs
+    Collections,
+    /// Collections curated by `MoonRank`
+    MrCollections,
+    /// Twitter handles registered with a name service
+    NameService,
+    /// Genopets habitat NFTs
+    GenoHabitats,
+}
+
+impl SearchIndex {
+    /// The name of this index in Meilisearch
+    #[must_use]
+    pub fn uid(self) -> &'static str {
+        match self {
+            Self::Metadatas => "metadatas",
+            Self::Collections => "collections",
+            Self::MrCollections => "mr-collections",
+            Self::NameService => "name_service",
+            Self::GenoHabitats => "geno_habitats",
+        }
+    }
+}
+
+/// A page of NFT search results
+#[derive(Debug, Default)]
+pub struct NftSearchPage {
+    /// Addresses of the matching metadata accounts on this page, in order
+    pub addresses: Vec<String>,
+    /// The number of matching NFTs with each trait value
+    pub traits: Vec<TraitCount>,
+    /// The total number of matching NFTs
+    pub total: u64,
+}
+
+/// A backend capable of answering full-text search queries.
+///
+/// Results are returned as schemaless documents with the same fields as the
+/// corresponding Meilisearch index.
+#[allow(clippy::module_name_repetitions)]
+#[async_trait]
+pub trait SearchBackend: std::fmt::Debug + Send + Sync {
+    /// Return documents from `index` matching `term`
+    async fn search(
+        &self,
+        index: SearchIndex,
+        term: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Value>>;
+
+    /// Return a page of NFTs matching a search, with counts of the matching
+    /// NFTs by trait
+    async fn search_nfts(&self, opts: &SearchOptions) -> Result<NftSearchPage>;
+}
+
+/// The search backend to use for queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendKind {
+    /// Query Meilisearch, falling back to Postgres if it is unavailable
+    Meilisearch,
+    /// Query Postgres only
+    Postgres,
+}
+
+impl FromStr for BackendKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "meilisearch" => Self::Meilisearch,
+            "postgres" => Self::Postgres,
+            s => bail!(
+                "Invalid search backend {:?}, expected meilisearch or postgres",
+                s
+            ),
+        })
+    }
+}
+
+/// Arguments for configuring search queries
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Backend to answer search queries with, either `meilisearch` or
+    /// `postgres`
+    #[clap(long, env, default_value = "meilisearch")]
+    search_backend: BackendKind,
+
+    /// Meilisearch database endpoint, required for the `meilisearch` backend
+    #[clap(long, env)]
+    meili_url: Option<String>,
+
+    /// Meilisearch database API key, required for the `meilisearch` backend
+    #[clap(long, env)]
+    meili_key: Option<String>,
+}
+
+impl Args {
+    /// Construct the configured search backend
+    ///
+    /// # Errors
+    /// This function fails if the Meilisearch backend is selected without a
+    /// Meilisearch endpoint and API key.
+    pub fn build(self, db: Arc<Pool>) -> Result<Arc<dyn SearchBackend>> {
+        let Self {
+            search_backend,
+            meili_url,
+            meili_key,
+        } = self;
+
+        let meili = match (meili_url, meili_key) {
+            (Some(url), Some(key)) => Some(meilisearch::client::Client::new(url, key)),
+            (None, None) => None,
+            _ => bail!("--meili-url and --meili-key must be provided together"),
+        };
+        let postgres = Postgres(db);
+
+        Ok(match (search_backend, meili) {
+            (BackendKind::Meilisearch, Some(meili)) => Arc::new(Fallback {
+                primary: Meilisearch(meili),
+                fallback: postgres,
+            }),
+            (BackendKind::Meilisearch, None) => {
+                bail!("The meilisearch search backend requires --meili-url and --meili-key")
+            },
+            (BackendKind::Postgres, _) => Arc::new(postgres),
+        })
+    }
+}
+
+/// Search backend querying a Meilisearch instance
+#[derive(Debug)]
+pub struct Meilisearch(meilisearch::client::Client);
+
+#[async_trait]
+impl SearchBackend for Meilisearch {
+    async fn search(
+        &self,
+        index: SearchIndex,
+        term: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Value>> {
+        Ok(self
+            .0
+            .index(index.uid())
+            .search()
+            .with_query(term)
+            .with_offset(offset)
+            .with_limit(limit)
+            .execute::<Value>()
+            .await
+            .with_context(|| format!("Failed to search Meilisearch index {:?}", index.uid()))?
+            .hits
+            .into_iter()
+            .map(|h| h.result)
+            .collect())
+    }
+
+    async fn search_nfts(&self, opts: &SearchOptions) -> Result<NftSearchPage> {
+        const PRICE_ASC: &[&str] = &["listing_price:asc"];
+        const PRICE_DESC: &[&str] = &["listing_price:desc"];
+
+        let mut filters = Vec::new();
+
+        filters.extend(
+            opts.collections
+                .as_ref()
+                .and_then(|c| filter_any("collection_address", c)),
+        );
+        filters.extend(
+            opts.creators
+                .as_ref()
+                .and_then(|c| filter_any("creator_addresses", c)),
+        );
+        filters.extend(
+            opts.owners
+                .as_ref()
+                .and_then(|o| filter_any("owner_address", o)),
+        );
+
+        for (trait_type, values) in &opts.attributes {
+            filters.extend(filter_any(
+                "traits",
+                values
+                    .iter()
+                    .map(|v| NftDocument::format_trait(trait_type, v)),
+            ));
+        }
+
+        if let Some(listed) = opts.listed {
+            filters.push(format!("listed = {}", listed));
+        }
+
+        let filter = filters.join(" AND ");
+
+        let index = self.0.index(NFTS_INDEX);
+        let mut query = index.search();
+        query
+            .with_offset(opts.offset.try_into()?)
+            .with_limit(opts.limit.try_into()?)
+            .with_facets_distribution(Selectors::Some(&["traits"]));
+
+        if let Some(ref term) = opts.term {
+            query.with_query(term);
+        }
+
+        if !filters.is_empty() {
+            query.with_filter(&filter);
+        }
+
+        match opts.order_by_price {
+            Some(Order::Desc) => {
+                query.with_sort(PRICE_DESC);
+            },
+            Some(_) => {
+                query.with_sort(PRICE_ASC);
+            },
+            None => (),
+        }
+
+        let results = query
+            .execute::<NftDocument>()
+            .await
+            .context("Failed to search Meilisearch NFT index")?;
+
+        let traits = results
+            .facets_distribution
+            .iter()
+            .filter_map(|f| f.get("traits"))
+            .flatten()
+            .filter_map(|(value, count)| {
+                let (trait_type, value) = NftDocument::parse_trait(value)?;
+
+                Some(count.to_owned().try_into().map(|count| TraitCount {
+                    trait_type,
+                    value: value.to_owned(),
+                    count,
+                }))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(NftSearchPage {
+            addresses: results
+                .hits
+                .into_iter()
+                .map(|h| h.result.metadata_address)
+                .collect(),
+            traits,
+            total: results.nb_hits.try_into()?,
+        })
+    }
+}
+
+/// Build a Meilisearch filter matching documents where `field` equals any of
+/// `values`, or `None` if `values` is empty
+fn filter_any<I: IntoIterator<Item = S>, S: AsRef<str>>(field: &str, values: I) -> Option<String> {
+    let clauses: Vec<_> = values
+        .into_iter()
+        .map(|v| {
+            format!(
+                "{} = \"{}\"",
+                field,
+                v.as_ref().replace('\\', "\\\\").replace('"', "\\\"")
+            )
+        })
+        .collect();
+
+    if clauses.is_empty() {
+        return None;
+    }
+
+    Some(format!("({})", clauses.join(" OR ")))
+}
+
+/// Search backend using Postgres full-text search over the indexed tables
+pub struct Postgres(Arc<Pool>);
+
+impl std::fmt::Debug for Postgres {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Postgres").finish_non_exhaustive()
+    }
+}
+
+/// Filter on the English full-text vector of a name column matching a web
+/// search query.  The vector expression must match the one in the full-text
+/// indexes created by the `add_name_search_indexes` migration for Postgres to
+/// use them.
+macro_rules! name_matches {
+    ($column:literal, $term:expr) => {
+        sql::<Bool>(concat!(
+            "to_tsvector('english', ",
+            $column,
+            ") @@ websearch_to_tsquery('english', "
+        ))
+        .bind::<Text, _>($term)
+        .sql(")")
+    };
+}
+
+/// Escape `LIKE` wildcards in a search term
+fn like_pattern(term: &str) -> String {
+    let escaped = term
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+
+    format!("%{}%", escaped)
+}
+
+#[async_trait]
+impl SearchBackend for Postgres {
+    #[allow(clippy::too_many_lines)]
+    async fn search(
+        &self,
+        index: SearchIndex,
+        term: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Value>> {
+        let conn = self.0.get().context("Failed to connect to the database")?;
+        let term = term.to_owned();
+        let offset = i64::try_from(offset)?;
+        let limit = i64::try_from(limit)?;
+
+        let docs = match index {
+            SearchIndex::Metadatas | SearchIndex::Collections | SearchIndex::GenoHabitats => {
+                let mut query = metadatas::table
+                    .left_join(
+                        metadata_jsons::table
+                            .on(metadata_jsons::metadata_address.eq(metadatas::address)),
+                    )
+                    .filter(metadatas::burned_at.is_null())
+                    .filter(name_matches!("metadatas.name", term))
+                    .select((
+                        metadatas::address,
+                        metadatas::name,
+                        metadatas::mint_address,
+                        metadata_jsons::image.nullable(),
+                    ))
+                    .order_by(metadatas::name)
+                    .offset(offset)
+                    .limit(limit)
+                    .into_boxed();
+
+                if index == SearchIndex::Collections {
+                    query = query.filter(
+                        metadatas::mint_address.eq_any(
+                            metadata_collection_keys::table
+                                .filter(metadata_collection_keys::verified)
+                                .select(metadata_collection_keys::collection_address),
+                        ),
+                    );
+                } else if index == SearchIndex::GenoHabitats {
+                    query = query.filter(metadatas::mint_address.eq_any(
+                        geno_habitat_datas::table.select(geno_habitat_datas::habitat_mint),
+                    ));
+                }
+
+                query
+                    .load::<(String, String, String, Option<String>)>(&conn)
+                    .context("Failed to search metadatas")?
+                    .into_iter()
+                    .map(|(address, name, mint_address, image)| {
+                        json!({
+                            "id": index_id(index, &address, &mint_address),
+                            "metadata_address": address,
+                            "name": name,
+                            "mint_address": mint_address,
+                            "image": image,
+                        })
+                    })
+                    .collect()
+            },
+            SearchIndex::MrCollections => collections::table
+                .filter(name_matches!("collections.name", term))
+                .select((
+                    collections::id,
+                    collections::name,
+                    collections::image,
+                    collections::magic_eden_id,
+                    collections::verified_collection_address,
+                    collections::twitter_url,
+                    collections::discord_url,
+                    collections::website_url,
+                ))
+                .order_by(collections::name)
+                .offset(offset)
+                .limit(limit)
+                .load::<(
+                    String,
+                    String,
+                    String,
+                    Option<String>,
+                    Option<String>,
+                    Option<String>,
+                    Option<String>,
+                    Option<String>,
+                )>(&conn)
+                .context("Failed to search collections")?
+                .into_iter()
+                .map(
+                    |(id, name, image, magic_eden_id, verified, twitter, discord, website)| {
+                        json!({
+                            "id": id,
+                            "name": name,
+                            "image": image,
+                            "magic_eden_id": magic_eden_id,
+                            "verified_collection_address": verified,
+                            "twitter_url": twitter,
+                            "discord_url": discord,
+                            "website_url": website,
+                        })
+                    },
+                )
+                .collect(),
+            // Handles are single words, so match substrings instead of terms
+            SearchIndex::NameService => twitter_handle_name_services::table
+                .filter(twitter_handle_name_services::twitter_handle.ilike(like_pattern(&term)))
+                .select((
+                    twitter_handle_name_services::address,
+                    twitter_handle_name_services::wallet_address,
+                    twitter_handle_name_services::twitter_handle,
+                ))
+                .order_by(twitter_handle_name_services::twitter_handle)
+                .offset(offset)
+                .limit(limit)
+                .load::<(String, String, String)>(&conn)
+                .context("Failed to search twitter handles")?
+                .into_iter()
+                .map(|(id, owner, handle)| json!({ "id": id, "owner": owner, "handle": handle }))
+                .collect(),
+        };
+
+        Ok(docs)
+    }
+
+    async fn search_nfts(&self, opts: &SearchOptions) -> Result<NftSearchPage> {
+        let conn = self.0.get().context("Failed to connect to the database")?;
+
+        let (addresses, total) = nft_search::search(&conn, opts)?;
+        let traits = nft_search::trait_counts(&conn, opts)?;
+
+        Ok(NftSearchPage {
+            addresses,
+            traits,
+            total: total.try_into()?,
+        })
+    }
+}
+
+/// The primary key Meilisearch documents in the given index use
+fn index_id<'a>(index: SearchIndex, address: &'a str, mint_address: &'a str) -> &'a str {
+    match index {
+        SearchIndex::GenoHabitats => mint_address,
+        _ => address,
+    }
+}
+
+/// Search backend which answers queries with a fallback backend whenever the
+/// primary backend fails
+#[derive(Debug)]
+pub struct Fallback<P, F> {
+    primary: P,
+    fallback: F,
+}
+
+#[async_trait]
+impl<P: SearchBackend, F: SearchBackend> SearchBackend for Fallback<P, F> {
+    async fn search(
+        &self,
+        index: SearchIndex,
+        term: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Value>> {
+        match self.primary.search(index, term, offset, limit).await {
+            Ok(d) => Ok(d),
+            Err(e) => {
+                warn!("Search failed, falling back: {:?}", e);
+
+                self.fallback.search(index, term, offset, limit).await
+            },
+        }
+    }
+
+    async fn search_nfts(&self, opts: &SearchOptions) -> Result<NftSearchPage> {
+        match self.primary.search_nfts(opts).await {
+            Ok(p) => Ok(p),
+            Err(e) => {
+                warn!("NFT search failed, falling back: {:?}", e);
+
+                self.fallback.search_nfts(opts).await
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use futures_util::FutureExt;
+    use indexer_core::{
+        db::{queries::nft_search::SearchOptions, ConnectionManager, Pool},
+        prelude::*,
+    };
+    use serde_json::{json, Value};
+
+    use super::{
+        like_pattern, Args, BackendKind, Fallback, NftSearchPage, SearchBackend, SearchIndex,
+    };
+
+    #[derive(Debug)]
+    struct Failing;
+
+    #[async_trait]
+    impl SearchBackend for Failing {
+        async fn search(&self, _: SearchIndex, _: &str, _: usize, _: usize) -> Result<Vec<Value>> {
+            bail!("Backend unavailable")
+        }
+
+        async fn search_nfts(&self, _: &SearchOptions) -> Result<NftSearchPage> {
+            bail!("Backend unavailable")
+        }
+    }
+
+    #[derive(Debug)]
+    struct Fixed(&'static str);
+
+    #[async_trait]
+    impl SearchBackend for Fixed {
+        async fn search(&self, _: SearchIndex, _: &str, _: usize, _: usize) -> Result<Vec<Value>> {
+            Ok(vec![json!({ "id": self.0 })])
+        }
+
+        async fn search_nfts(&self, _: &SearchOptions) -> Result<NftSearchPage> {
+            Ok(NftSearchPage {
+                addresses: vec![self.0.to_owned()],
+                ..NftSearchPage::default()
+            })
+        }
+    }
+
+    fn build(kind: BackendKind, url: Option<&str>, key: Option<&str>) -> Result<String> {
+        let pool = Pool::builder().build_unchecked(ConnectionManager::new("postgres://"));
+
+        Args {
+            search_backend: kind,
+            meili_url: url.map(ToOwned::to_owned),
+            meili_key: key.map(ToOwned::to_owned),
+        }
+        .build(Arc::new(pool))
+        .map(|b| format!("{:?}", b))
+    }
+
+    #[test]
+    fn test_backend_selection() {
+        let url = Some("http://localhost:7700");
+        let key = Some("key");
+
+        assert!(
+            build(BackendKind::Meilisearch, url, key)
+                .unwrap()
+                .starts_with("Fallback { primary: Meilisearch(")
+        );
+        assert!(
+            build(BackendKind::Postgres, None, None)
+                .unwrap()
+                .starts_with("Postgres {")
+        );
+        assert!(
+            build(BackendKind::Postgres, url, key)
+                .unwrap()
+                .starts_with("Postgres {")
+        );
+        assert!(build(BackendKind::Meilisearch, None, None).is_err());
+        assert!(build(BackendKind::Meilisearch, url, None).is_err());
+        assert!(build(BackendKind::Postgres, None, key).is_err());
+
+        assert_eq!(
+            "meilisearch".parse::<BackendKind>().unwrap(),
+            BackendKind::Meilisearch
+        );
+        assert_eq!(
+            "postgres".parse::<BackendKind>().unwrap(),
+            BackendKind::Postgres
+        );
+        assert!("sqlite".parse::<BackendKind>().is_err());
+    }
+
+    #[test]
+    fn test_fallback() {
+        let search = |b: &dyn SearchBackend| {
+            b.search(SearchIndex::Metadatas, "term", 0, 10)
+                .now_or_never()
+                .unwrap()
+                .unwrap()
+        };
+        let search_nfts = |b: &dyn SearchBackend| {
+            b.search_nfts(&SearchOptions::default())
+                .now_or_never()
+                .unwrap()
+                .unwrap()
+                .addresses
+        };
+
+        let healthy = Fallback {
+            primary: Fixed("primary"),
+            fallback: Fixed("fallback"),
+        };
+        assert_eq!(search(&healthy), vec![json!({ "id": "primary" })]);
+        assert_eq!(search_nfts(&healthy), vec!["primary"]);
+
+        let failing = Fallback {
+            primary: Failing,
+            fallback: Fixed("fallback"),
+        };
+        assert_eq!(search(&failing), vec![json!({ "id": "fallback" })]);
+        assert_eq!(search_nfts(&failing), vec!["fallback"]);
+
+        let down = Fallback {
+            primary: Failing,
+            fallback: Failing,
+        };
+        assert!(
+            down.search(SearchIndex::Metadatas, "term", 0, 10)
+                .now_or_never()
+                .unwrap()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_like_pattern() {
+        assert_eq!(like_pattern("holaplex"), "%holaplex%");
+        assert_eq!(like_pattern("100%_real"), "%100\\%\\_real%");
+        assert_eq!(like_pattern("a\\b"), "%a\\\\b%");
+    }
+}
@@ -0,0 +1,602 @@
+//! Request timing, slow-query logging and OTLP trace export
+
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fs::{File, OpenOptions},
+    future::Future,
+    io::Write,
+    path::PathBuf,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    task::{self, Poll},
+    time::{Duration, Instant},
+};
+
+use indexer_core::{
+    chrono::{DateTime, Utc},
+    clap,
+    prelude::*,
+};
+use juniper::http::GraphQLRequest;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Arguments for configuring request telemetry
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Requests taking at least this many milliseconds are written to the
+    /// slow-query log
+    #[clap(long, env, default_value_t = 5000)]
+    slow_query_threshold_ms: u64,
+
+    /// Number of slowest resolver and loader spans to include in each
+    /// slow-query record
+    #[clap(long, env, default_value_t = 5)]
+    slow_query_top_spans: usize,
+
+    /// File to append slow-query records to as JSON lines.  If omitted,
+    /// records are written to the log instead.
+    #[clap(long, env)]
+    slow_query_log: Option<PathBuf>,
+
+    /// Base URL of an OTLP/HTTP collector to export request traces to, e.g.
+    /// `http://localhost:4318`
+    #[clap(long, env)]
+    otlp_endpoint: Option<String>,
+
+    /// Service name reported to the OTLP collector
+    #[clap(long, env, default_value = "holaplex-indexer-graphql")]
+    otlp_service_name: String,
+}
+
+/// The source of a recorded span
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpanKind {
+    /// A field resolver, including the resolution of the fields selected on
+    /// its value
+    Resolver,
+    /// A single batch call of a dataloader
+    Loader,
+}
+
+/// A single timed operation within a request
+#[derive(Debug, Clone, Serialize)]
+pub struct Span {
+    id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent: Option<u64>,
+    name: Cow<'static, str>,
+    kind: SpanKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keys: Option<usize>,
+    #[serde(serialize_with = "serialize_millis")]
+    start: Duration,
+    #[serde(serialize_with = "serialize_millis")]
+    duration: Duration,
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn serialize_millis<S: serde::Serializer>(d: &Duration, ser: S) -> Result<S::Ok, S::Error> {
+    ser.serialize_f64(d.as_secs_f64() * 1000.0)
+}
+
+#[derive(Debug)]
+struct TraceInner {
+    started_at: DateTime<Utc>,
+    start: Instant,
+    next_id: AtomicU64,
+    /// ID of the resolver span currently being run, or zero outside of any
+    /// resolver
+    current: AtomicU64,
+    /// Parents of the resolver spans which have started but not yet finished
+    open: Mutex<HashMap<u64, Option<u64>>>,
+    spans: Mutex<Vec<Span>>,
+}
+
+/// Per-request collection of timing spans, shared between the request
+/// context and its dataloaders
+#[derive(Debug, Clone)]
+pub struct RequestTrace(Arc<TraceInner>);
+
+/// Guard that records a span into its [`RequestTrace`] when dropped
+#[derive(Debug)]
+pub struct SpanGuard {
+    trace: RequestTrace,
+    id: u64,
+    parent: Option<u64>,
+    name: Cow<'static, str>,
+    kind: SpanKind,
+    keys: Option<usize>,
+    start: Instant,
+}
+
+impl Drop for SpanGuard {
+    fn drop(&mut self) {
+        if self.kind == SpanKind::Resolver {
+            self.trace.open().remove(&self.id);
+        }
+
+        let span = Span {
+            id: self.id,
+            parent: self.parent,
+            name: std::mem::take(&mut self.name),
+            kind: self.kind,
+            keys: self.keys,
+            start: self.start.saturating_duration_since(self.trace.0.start),
+            duration: self.start.elapsed(),
+        };
+
+        match self.trace.0.spans.lock() {
+            Ok(mut spans) => spans.push(span),
+            Err(e) => error!("Request trace lock was poisoned: {}", e),
+        }
+    }
+}
+
+/// Future timing a resolver, which marks its span as the parent of any spans
+/// started while it is being polled
+#[derive(Debug)]
+pub struct Instrumented<F> {
+    fut: F,
+    span: SpanGuard,
+}
+
+impl<F: Future + Unpin> Future for Instrumented<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let span = &this.span;
+
+        span.trace
+            .enter(span.id, || Pin::new(&mut this.fut).poll(cx))
+    }
+}
+
+impl Default for RequestTrace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RequestTrace {
+    /// Begin tracing a new request
+    #[must_use]
+    pub fn new() -> Self {
+        Self(Arc::new(TraceInner {
+            started_at: Utc::now(),
+            start: Instant::now(),
+            next_id: AtomicU64::new(1),
+            current: AtomicU64::new(0),
+            open: Mutex::new(HashMap::new()),
+            spans: Mutex::new(vec![]),
+        }))
+    }
+
+    fn open(&self) -> MutexGuard<'_, HashMap<u64, Option<u64>>> {
+        self.0.open.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Run `f` with the given span marked as the current resolver.  Polls of
+    /// nested resolvers happen within the poll of their parent, so restoring
+    /// the previous span afterwards keeps the marker correct no matter the
+    /// order sibling resolvers are polled in.
+    fn enter<T>(&self, id: u64, f: impl FnOnce() -> T) -> T {
+        let prev = self.0.current.swap(id, Ordering::Relaxed);
+        let res = f();
+        self.0.current.store(prev, Ordering::Relaxed);
+
+        res
+    }
+
+    fn current(&self) -> Option<u64> {
+        Some(self.0.current.load(Ordering::Relaxed)).filter(|i| *i != 0)
+    }
+
+    fn span(
+        &self,
+        name: Cow<'static, str>,
+        kind: SpanKind,
+        parent: Option<u64>,
+        keys: Option<usize>,
+    ) -> SpanGuard {
+        let id = self.0.next_id.fetch_add(1, Ordering::Relaxed);
+
+        if kind == SpanKind::Resolver {
+            self.open().insert(id, parent);
+        }
+
+        SpanGuard {
+            trace: self.clone(),
+            id,
+            parent,
+            name,
+            kind,
+            keys,
+            start: Instant::now(),
+        }
+    }
+
+    /// Time a field resolver, which starts by calling `resolve` and finishes
+    /// once the future it returns completes.  Spans started by either are
+    /// recorded as children of the resolver.
+    pub fn resolver<F: Future + Unpin>(
+        &self,
+        name: impl Into<Cow<'static, str>>,
+        resolve: impl FnOnce() -> F,
+    ) -> Instrumented<F> {
+        let span = self.span(name.into(), SpanKind::Resolver, self.current(), None);
+        let fut = self.enter(span.id, resolve);
+
+        Instrumented { fut, span }
+    }
+
+    /// Time a synchronous field resolver, recording spans started by
+    /// `resolve` as its children
+    pub fn resolver_sync<T>(
+        &self,
+        name: impl Into<Cow<'static, str>>,
+        resolve: impl FnOnce() -> T,
+    ) -> T {
+        let span = self.span(name.into(), SpanKind::Resolver, self.current(), None);
+
+        self.enter(span.id, resolve)
+    }
+
+    /// The span a dataloader batch belongs to.  A batch carries the keys of
+    /// every resolver waiting on the loader, but runs in whichever of them is
+    /// polled last, so it is recorded under the closest common ancestor of
+    /// the resolvers still waiting on a result rather than the one running
+    /// it.
+    fn batch_parent(&self) -> Option<u64> {
+        let open = self.open();
+        let parents: HashSet<u64> = open.values().filter_map(|p| *p).collect();
+        let ancestors =
+            |id: u64| std::iter::successors(Some(id), |i| open.get(i).copied().flatten());
+
+        let mut common: Option<Vec<u64>> = None;
+
+        for leaf in open.keys().filter(|i| !parents.contains(i)) {
+            match common {
+                None => common = Some(ancestors(*leaf).collect()),
+                Some(ref mut common) => {
+                    match ancestors(*leaf).find_map(|a| common.iter().position(|c| *c == a)) {
+                        Some(i) => drop(common.drain(..i)),
+                        None => common.clear(),
+                    }
+                },
+            }
+        }
+
+        common.map_or_else(|| self.current(), |c| c.first().copied())
+    }
+
+    /// Time a dataloader batch for `keys` keys of type `K` loading values of
+    /// type `V` until the returned guard is dropped
+    #[must_use]
+    pub fn loader<K: ?Sized, V: ?Sized>(&self, keys: usize) -> SpanGuard {
+        let name = format!(
+            "Loader<{}, {}>",
+            short_type_name::<K>(),
+            short_type_name::<V>()
+        );
+
+        self.span(
+            name.into(),
+            SpanKind::Loader,
+            self.batch_parent(),
+            Some(keys),
+        )
+    }
+
+    fn spans(&self) -> Vec<Span> {
+        self.0
+            .spans
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+/// Strip module paths from a type name, e.g. `PublicKey<Nft>` rather than
+/// `holaplex_indexer_graphql::schema::scalars::PublicKey<...>`
+fn short_type_name<T: ?Sized>() -> String {
+    let full = std::any::type_name::<T>();
+    let mut out = String::with_capacity(full.len());
+    let mut ident = String::new();
+
+    for c in full.chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            ident.push(c);
+        } else {
+            out.push_str(ident.rsplit("::").next().unwrap_or_default());
+            ident.clear();
+            out.push(c);
+        }
+    }
+
+    out.push_str(ident.rsplit("::").next().unwrap_or_default());
+
+    out
+}
+
+/// Structured record written for each request exceeding the slow-query
+/// threshold
+#[derive(Debug, Serialize)]
+struct SlowQueryRecord<'a> {
+    timestamp: DateTime<Utc>,
+    operation_name: Option<&'a str>,
+    variables_hash: Option<String>,
+    duration_ms: f64,
+    remote_addr: Option<&'a str>,
+    query: &'a str,
+    slowest_spans: Vec<Span>,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+struct RequestBody {
+    #[serde(default)]
+    query: String,
+    #[serde(default, rename = "operationName")]
+    operation_name: Option<String>,
+    #[serde(default)]
+    variables: Option<Value>,
+}
+
+#[derive(Debug)]
+struct OtlpExporter {
+    url: String,
+    service_name: String,
+    http: reqwest::Client,
+}
+
+/// Shared request telemetry configuration
+#[derive(Debug)]
+pub struct Telemetry {
+    slow_threshold: Duration,
+    top_spans: usize,
+    slow_log: Option<Mutex<File>>,
+    otlp: Option<OtlpExporter>,
+}
+
+impl Telemetry {
+    /// Construct request telemetry from the provided arguments
+    ///
+    /// # Errors
+    /// This function fails if the slow-query log cannot be opened for writing.
+    pub fn new(args: Args) -> Result<Self> {
+        let Args {
+            slow_query_threshold_ms,
+            slow_query_top_spans,
+            slow_query_log,
+            otlp_endpoint,
+            otlp_service_name,
+        } = args;
+
+        let slow_log = slow_query_log
+            .map(|p| {
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&p)
+                    .with_context(|| format!("Failed to open slow-query log {:?}", p))
+                    .map(Mutex::new)
+            })
+            .transpose()?;
+
+        let otlp = otlp_endpoint.map(|e| OtlpExporter {
+            url: format!("{}/v1/traces", e.trim_end_matches('/')),
+            service_name: otlp_service_name,
+            http: reqwest::Client::new(),
+        });
+
+        Ok(Self {
+            slow_threshold: Duration::from_millis(slow_query_threshold_ms),
+            top_spans: slow_query_top_spans,
+            slow_log,
+            otlp,
+        })
+    }
+
+    /// Process the trace of a completed request, writing a slow-query record
+    /// and exporting its spans if configured to do so
+    pub fn finish(
+        &self,
+        req: &GraphQLRequest,
+        remote_addr: Option<&str>,
+        trace: &RequestTrace,
+        duration: Duration,
+    ) {
+        let slow = duration >= self.slow_threshold;
+
+        if !slow && self.otlp.is_none() {
+            return;
+        }
+
+        let body: RequestBody = match serde_json::to_value(req).and_then(serde_json::from_value) {
+            Ok(b) => b,
+            Err(e) => {
+                error!("Failed to read GraphQL request for tracing: {}", e);
+                RequestBody::default()
+            },
+        };
+        let spans = trace.spans();
+
+        if slow {
+            self.write_slow_query(&body, remote_addr, trace, &spans, duration);
+        }
+
+        if let Some(ref otlp) = self.otlp {
+            let payload = otlp.payload(&body, trace, &spans, duration);
+            let http = otlp.http.clone();
+            let url = otlp.url.clone();
+
+            actix_web::rt::spawn(async move {
+                match http.post(&url).json(&payload).send().await {
+                    Ok(r) if r.status().is_success() => (),
+                    Ok(r) => warn!("OTLP collector responded with {}", r.status()),
+                    Err(e) => warn!("Failed to export request trace: {}", e),
+                }
+            });
+        }
+    }
+
+    fn write_slow_query(
+        &self,
+        body: &RequestBody,
+        remote_addr: Option<&str>,
+        trace: &RequestTrace,
+        spans: &[Span],
+        duration: Duration,
+    ) {
+        let mut slowest_spans = spans.to_vec();
+        slowest_spans.sort_by(|a, b| b.duration.cmp(&a.duration));
+        slowest_spans.truncate(self.top_spans);
+
+        let record = SlowQueryRecord {
+            timestamp: trace.0.started_at,
+            operation_name: body.operation_name.as_deref(),
+            variables_hash: body
+                .variables
+                .as_ref()
+                .map(|v| format!("{:x}", md5::compute(v.to_string()))),
+            duration_ms: duration.as_secs_f64() * 1000.0,
+            remote_addr,
+            query: &body.query,
+            slowest_spans,
+        };
+
+        let line = match serde_json::to_string(&record) {
+            Ok(l) => l,
+            Err(e) => {
+                error!("Failed to serialize slow-query record: {}", e);
+                return;
+            },
+        };
+
+        if let Some(ref log) = self.slow_log {
+            let res = log
+                .lock()
+                .map_err(|e| anyhow!("Slow-query log lock was poisoned: {}", e))
+                .and_then(|mut f| writeln!(f, "{}", line).context("Failed to write record"));
+
+            if let Err(e) = res {
+                error!("Failed to write to slow-query log: {:?}", e);
+            }
+        } else {
+            warn!("Slow GraphQL request: {}", line);
+        }
+    }
+}
+
+impl OtlpExporter {
+    fn payload(
+        &self,
+        body: &RequestBody,
+        trace: &RequestTrace,
+        spans: &[Span],
+        duration: Duration,
+    ) -> Value {
+        static TRACE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let start_nanos = trace.0.started_at.timestamp_nanos();
+        let trace_id = md5::compute(format!(
+            "{}:{}",
+            start_nanos,
+            TRACE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let trace_id_hex = format!("{:x}", trace_id);
+        let span_id = |i: u64| {
+            md5::compute(format!("{}:{}", trace_id_hex, i))[..8]
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>()
+        };
+        let nanos = |d: Duration| {
+            (start_nanos + i64::try_from(d.as_nanos()).unwrap_or(i64::MAX)).to_string()
+        };
+
+        let root_id = span_id(0);
+        let root = json!({
+            "traceId": trace_id_hex,
+            "spanId": root_id,
+            "name": body.operation_name.as_deref().unwrap_or("graphql"),
+            "kind": 2,
+            "startTimeUnixNano": nanos(Duration::ZERO),
+            "endTimeUnixNano": nanos(duration),
+        });
+
+        let children = spans.iter().map(|s| {
+            json!({
+                "traceId": trace_id_hex,
+                "spanId": span_id(s.id),
+                "parentSpanId": s.parent.map_or_else(|| root_id.clone(), span_id),
+                "name": s.name,
+                "kind": 1,
+                "startTimeUnixNano": nanos(s.start),
+                "endTimeUnixNano": nanos(s.start + s.duration),
+                "attributes": [
+                    { "key": "graphql.span.kind", "value": { "stringValue": s.kind } },
+                    {
+                        "key": "graphql.loader.keys",
+                        "value": { "intValue": s.keys.unwrap_or_default().to_string() },
+                    },
+                ],
+            })
+        });
+
+        json!({
+            "resourceSpans": [{
+                "resource": {
+                    "attributes": [{
+                        "key": "service.name",
+                        "value": { "stringValue": self.service_name },
+                    }],
+                },
+                "scopeSpans": [{
+                    "scope": { "name": env!("CARGO_PKG_NAME") },
+                    "spans": std::iter::once(root).chain(children).collect::<Vec<_>>(),
+                }],
+            }],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future;
+
+    use super::RequestTrace;
+
+    #[test]
+    fn test_span_parents() {
+        let trace = RequestTrace::new();
+
+        trace.resolver_sync("Query.nft", || {
+            let owner = trace.resolver("Nft.owner", || {
+                drop(trace.loader::<str, str>(1));
+                future::pending::<()>()
+            });
+            let creators = trace.resolver("Nft.creators", || {
+                drop(trace.loader::<str, bool>(2));
+                future::pending::<()>()
+            });
+
+            drop((owner, creators));
+        });
+
+        let spans = trace.spans();
+        let span = |name: &str| spans.iter().find(|s| s.name == name).unwrap();
+
+        assert_eq!(span("Query.nft").parent, None);
+        assert_eq!(span("Nft.owner").parent, Some(span("Query.nft").id));
+        assert_eq!(span("Nft.creators").parent, Some(span("Query.nft").id));
+        assert_eq!(span("Loader<str, str>").parent, Some(span("Nft.owner").id));
+        assert_eq!(span("Loader<str, bool>").parent, Some(span("Query.nft").id));
+    }
+}
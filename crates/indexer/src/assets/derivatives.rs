@@ -0,0 +1,178 @@
+use std::io::Cursor;
+
+use image::{
+    codecs::{
+        avif::AvifEncoder,
+        webp::{WebPEncoder, WebPQuality},
+    },
+    imageops::FilterType,
+    io::{Limits, Reader},
+    ColorType, ImageFormat,
+};
+use indexer_core::assets::ImageSize;
+
+use crate::prelude::*;
+
+/// The width buckets derivatives are generated for, matching the sizes served
+/// by the asset proxy
+pub const SIZES: [ImageSize; 5] = [
+    ImageSize::Tiny,
+    ImageSize::XSmall,
+    ImageSize::Small,
+    ImageSize::Medium,
+    ImageSize::Large,
+];
+
+/// An output format for generated derivatives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Lossy WebP
+    WebP,
+    /// AVIF
+    Avif,
+}
+
+impl Format {
+    /// All supported output formats
+    pub const ALL: [Format; 2] = [Format::WebP, Format::Avif];
+
+    /// The MIME type of this format, as recorded in the `files` table
+    #[must_use]
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            Self::WebP => "image/webp",
+            Self::Avif => "image/avif",
+        }
+    }
+
+    /// The file extension used for this format in bucket keys
+    #[must_use]
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::WebP => "webp",
+            Self::Avif => "avif",
+        }
+    }
+}
+
+/// Encoder settings for generated derivatives
+#[derive(Debug, Clone, Copy)]
+pub struct Quality {
+    /// Lossy WebP quality, from 0 to 100
+    pub webp: u8,
+    /// AVIF quality, from 0 to 100
+    pub avif: u8,
+    /// AVIF encoder speed, from 0 (slowest) to 10 (fastest)
+    pub avif_speed: u8,
+}
+
+/// Limits on the original images accepted for decoding, since a small file can
+/// describe an image far too large to hold in memory
+#[derive(Debug, Clone, Copy)]
+pub struct DecodeLimits {
+    /// Maximum width or height of an image, in pixels
+    pub max_dimension: u32,
+    /// Maximum memory the decoder may allocate, in bytes
+    pub max_alloc: u64,
+}
+
+/// A single encoded derivative image
+#[derive(Debug)]
+pub struct Derivative {
+    /// The width bucket this derivative was generated for
+    pub size: ImageSize,
+    /// The encoding of `data`
+    pub format: Format,
+    /// The encoded image
+    pub data: Vec<u8>,
+}
+
+/// Check that a downloaded file is an image in a supported format, returning
+/// the detected format
+///
+/// # Errors
+/// This function fails if the server reported a non-image content type or the
+/// data is not a supported image format
+pub fn validate(content_type: Option<&str>, data: &[u8]) -> Result<ImageFormat> {
+    if let Some(ty) = content_type {
+        let ty = ty.split(';').next().unwrap_or_default().trim();
+
+        ensure!(
+            ty.starts_with("image/") || ty == "application/octet-stream",
+            "Unexpected content type {:?} for image",
+            ty
+        );
+    }
+
+    let format = image::guess_format(data).context("Unrecognized image data")?;
+
+    ensure!(
+        matches!(
+            format,
+            ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::Gif | ImageFormat::WebP
+        ),
+        "Unsupported image format {:?}",
+        format
+    );
+
+    Ok(format)
+}
+
+/// Decode an image and encode a derivative for each supported width bucket
+/// and output format.  Images are never upscaled; buckets wider than the
+/// original are encoded at the original size.
+///
+/// This function is CPU-intensive and should be run on a blocking thread.
+///
+/// # Errors
+/// This function fails if the image cannot be decoded or encoded, or exceeds
+/// the given decoding limits
+pub fn generate(
+    data: &[u8],
+    format: ImageFormat,
+    quality: Quality,
+    limits: DecodeLimits,
+) -> Result<Vec<Derivative>> {
+    let mut decode_limits = Limits::default();
+    decode_limits.max_image_width = Some(limits.max_dimension);
+    decode_limits.max_image_height = Some(limits.max_dimension);
+    decode_limits.max_alloc = Some(limits.max_alloc);
+
+    let mut reader = Reader::with_format(Cursor::new(data), format);
+    reader.limits(decode_limits);
+    let image = reader.decode().context("Failed to decode image")?;
+
+    let mut out = Vec::with_capacity(SIZES.len() * Format::ALL.len());
+
+    for size in SIZES {
+        let width = u32::try_from(size as i32).unwrap_or_else(|_| unreachable!());
+
+        let resized = if image.width() > width {
+            image.resize(width, image.height(), FilterType::Lanczos3)
+        } else {
+            image.clone()
+        };
+        let rgba = resized.to_rgba8();
+        let (w, h) = rgba.dimensions();
+
+        for format in Format::ALL {
+            let mut data = vec![];
+
+            match format {
+                Format::WebP => {
+                    WebPEncoder::new_with_quality(&mut data, WebPQuality::lossy(quality.webp))
+                        .encode(rgba.as_raw(), w, h, ColorType::Rgba8)
+                },
+                Format::Avif => {
+                    AvifEncoder::new_with_speed_quality(&mut data, quality.avif_speed, quality.avif)
+                        .write_image(rgba.as_raw(), w, h, ColorType::Rgba8)
+                },
+            }
+            .with_context(|| format!("Failed to encode {:?} derivative at {:?}", format, size))?;
+
+            out.push(Derivative { size, format, data });
+        }
+    }
+
+    Ok(out)
+}
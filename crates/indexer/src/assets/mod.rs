@@ -0,0 +1,417 @@
+//! Local image derivative worker.  Downloads NFT images directly from their
+//! permaweb gateways, generates resized WebP and AVIF copies at each
+//! [`ImageSize`](indexer_core::assets::ImageSize) width, uploads them to an
+//! S3-compatible bucket, and records them in the `files` table.
+
+mod derivatives;
+mod storage;
+
+use std::{sync::Arc, time::Duration};
+
+use futures_util::StreamExt;
+use indexer_core::{
+    assets::AssetIdentifier,
+    clap,
+    db::{
+        delete, insert_into,
+        models::{File as DbFile, ImageDerivativeFailure},
+        tables::{files, image_derivative_failures, metadata_jsons},
+    },
+    url::Url,
+};
+pub use storage::{Args as StorageArgs, Storage};
+
+use crate::{db::Pool, prelude::*, reqwest};
+
+/// Arguments for the image derivative worker
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    #[clap(flatten)]
+    storage: StorageArgs,
+
+    /// Gateway used to download IPFS images
+    #[clap(long, env, default_value = "https://ipfs.io/")]
+    ipfs_gateway: Url,
+
+    /// Gateway used to download Arweave images
+    #[clap(long, env, default_value = "https://arweave.net/")]
+    arweave_gateway: Url,
+
+    /// HTTP request timeout, in seconds
+    #[clap(long, env = "ASSET_WORKER_TIMEOUT", default_value_t = 30.0)]
+    timeout: f64,
+
+    /// Maximum size of an original image to process, in bytes
+    #[clap(long, env, default_value_t = 50 * 1024 * 1024)]
+    max_image_bytes: usize,
+
+    /// Maximum width or height of an original image to decode, in pixels
+    #[clap(long, env, default_value_t = 8192)]
+    max_image_dimension: u32,
+
+    /// Maximum memory to allocate while decoding an original image, in bytes
+    #[clap(long, env, default_value_t = 512 * 1024 * 1024)]
+    max_decode_bytes: u64,
+
+    /// Number of images to select per batch
+    #[clap(long, env, default_value_t = 64)]
+    batch_size: i64,
+
+    /// Time to wait before checking for new images once none remain, in
+    /// seconds
+    #[clap(long, env, default_value_t = 30)]
+    poll_interval: u64,
+
+    /// Number of failed attempts after which an image is no longer retried
+    #[clap(long, env, default_value_t = 5)]
+    max_attempts: i32,
+
+    /// Minimum time between attempts for a failing image, in seconds
+    #[clap(long, env, default_value_t = 3600)]
+    retry_interval: i64,
+
+    /// Lossy WebP quality, from 0 to 100
+    #[clap(long, env, default_value_t = 80)]
+    webp_quality: u8,
+
+    /// AVIF quality, from 0 to 100
+    #[clap(long, env, default_value_t = 70)]
+    avif_quality: u8,
+
+    /// AVIF encoder speed, from 0 (slowest) to 10 (fastest)
+    #[clap(long, env, default_value_t = 8)]
+    avif_speed: u8,
+}
+
+/// A metadata JSON row whose image has no derivatives yet
+#[derive(Debug)]
+struct Job {
+    metadata_address: String,
+    image: String,
+    slot: i64,
+    write_version: i64,
+}
+
+/// Shared state for the image derivative worker
+#[derive(Debug)]
+pub struct Worker {
+    db: Pool,
+    http: reqwest::Client,
+    storage: Storage,
+    ipfs_gateway: Url,
+    arweave_gateway: Url,
+    max_image_bytes: usize,
+    batch_size: i64,
+    poll_interval: Duration,
+    max_attempts: i32,
+    retry_interval: indexer_core::chrono::Duration,
+    quality: derivatives::Quality,
+    limits: derivatives::DecodeLimits,
+}
+
+impl Worker {
+    /// Construct a new worker
+    ///
+    /// # Errors
+    /// This function fails if the HTTP or storage clients cannot be created
+    pub fn new(args: Args, db: Pool) -> Result<Self> {
+        let Args {
+            storage,
+            ipfs_gateway,
+            arweave_gateway,
+            timeout,
+            max_image_bytes,
+            max_image_dimension,
+            max_decode_bytes,
+            batch_size,
+            poll_interval,
+            max_attempts,
+            retry_interval,
+            webp_quality,
+            avif_quality,
+            avif_speed,
+        } = args;
+
+        Ok(Self {
+            db,
+            http: reqwest::Client::new(Duration::from_secs_f64(timeout))?,
+            storage: Storage::new(storage)?,
+            ipfs_gateway,
+            arweave_gateway,
+            max_image_bytes,
+            batch_size,
+            poll_interval: Duration::from_secs(poll_interval),
+            max_attempts,
+            retry_interval: indexer_core::chrono::Duration::seconds(retry_interval),
+            quality: derivatives::Quality {
+                webp: webp_quality,
+                avif: avif_quality,
+                avif_speed,
+            },
+            limits: derivatives::DecodeLimits {
+                max_dimension: max_image_dimension,
+                max_alloc: max_decode_bytes,
+            },
+        })
+    }
+
+    /// Process images until interrupted, running up to `concurrency` jobs at
+    /// once
+    ///
+    /// # Errors
+    /// This function fails if the database cannot be queried for new jobs
+    pub async fn run(self: Arc<Self>, concurrency: usize) -> Result<()> {
+        loop {
+            let jobs = tokio::select! {
+                j = self.next_batch() => j?,
+                r = tokio::signal::ctrl_c() => {
+                    r.context("Failed to wait for interrupt")?;
+                    warn!("Interrupt received, shutting down...");
+
+                    break Ok(());
+                },
+            };
+
+            if jobs.is_empty() {
+                trace!("No images awaiting derivatives, sleeping");
+                tokio::time::sleep(self.poll_interval).await;
+                continue;
+            }
+
+            debug!("Generating derivatives for {} image(s)", jobs.len());
+
+            futures_util::stream::iter(jobs)
+                .for_each_concurrent(concurrency, |job| {
+                    let worker = Arc::clone(&self);
+                    async move { worker.process(job).await }
+                })
+                .await;
+        }
+    }
+
+    async fn next_batch(&self) -> Result<Vec<Job>> {
+        let batch_size = self.batch_size;
+        let max_attempts = self.max_attempts;
+        let retry_cutoff = Local::now().naive_utc() - self.retry_interval;
+
+        let rows: Vec<(String, Option<String>, i64, i64)> = self
+            .db
+            .run(move |db| {
+                metadata_jsons::table
+                    .filter(metadata_jsons::image.is_not_null())
+                    .filter(not(exists(
+                        files::table
+                            .filter(files::metadata_address.eq(metadata_jsons::metadata_address))
+                            .filter(files::source_uri.eq(metadata_jsons::image)),
+                    )))
+                    .filter(not(exists(
+                        image_derivative_failures::table
+                            .filter(
+                                image_derivative_failures::metadata_address
+                                    .eq(metadata_jsons::metadata_address),
+                            )
+                            .filter(
+                                image_derivative_failures::source_uri
+                                    .nullable()
+                                    .eq(metadata_jsons::image),
+                            )
+                            .filter(
+                                image_derivative_failures::attempts
+                                    .ge(max_attempts)
+                                    .or(image_derivative_failures::updated_at.gt(retry_cutoff)),
+                            ),
+                    )))
+                    .select((
+                        metadata_jsons::metadata_address,
+                        metadata_jsons::image,
+                        metadata_jsons::slot,
+                        metadata_jsons::write_version,
+                    ))
+                    .order_by(metadata_jsons::updated_at.desc())
+                    .limit(batch_size)
+                    .load(db)
+            })
+            .await
+            .context("Failed to load images awaiting derivatives")?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(metadata_address, image, slot, write_version)| {
+                image.map(|image| Job {
+                    metadata_address,
+                    image,
+                    slot,
+                    write_version,
+                })
+            })
+            .collect())
+    }
+
+    async fn process(&self, job: Job) {
+        if let Err(e) = self.try_process(&job).await {
+            warn!(
+                "Failed to generate derivatives for {} ({:?}): {:?}",
+                job.metadata_address, job.image, e
+            );
+
+            if let Err(e) = self.record_failure(job, format!("{:?}", e)).await {
+                error!("{:?}", e);
+            }
+        }
+    }
+
+    async fn try_process(&self, job: &Job) -> Result<()> {
+        let url = Url::parse(&job.image).context("Invalid image URL")?;
+        let id = AssetIdentifier::new(&url);
+        let src = id
+            .gateway_url(None, &self.ipfs_gateway, &self.arweave_gateway)
+            .unwrap_or_else(|| url.clone());
+
+        let (content_type, mut resp) = self
+            .http
+            .run(|h| async move {
+                let resp = h.get(src).send().await?.error_for_status()?;
+                let content_type = resp
+                    .headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .map(ToOwned::to_owned);
+
+                Ok((content_type, resp))
+            })
+            .await
+            .context("Failed to download original image")?;
+
+        if let Some(len) = resp.content_length() {
+            ensure!(
+                len <= self.max_image_bytes as u64,
+                "Image is too large ({} bytes)",
+                len
+            );
+        }
+
+        // Servers may omit or misreport Content-Length, so cap the body as it
+        // streams in rather than trusting the header
+        let mut data = Vec::new();
+
+        while let Some(chunk) = resp
+            .chunk()
+            .await
+            .context("Failed to download original image")?
+        {
+            ensure!(
+                data.len() + chunk.len() <= self.max_image_bytes,
+                "Image is too large (over {} bytes)",
+                self.max_image_bytes
+            );
+
+            data.extend_from_slice(&chunk);
+        }
+
+        let format = derivatives::validate(content_type.as_deref(), &data)?;
+        let quality = self.quality;
+        let limits = self.limits;
+        let derivatives = tokio::task::spawn_blocking(move || {
+            derivatives::generate(&data, format, quality, limits)
+        })
+        .await
+        .context("Image encoding task failed")??;
+
+        // Key derivatives by a digest of the source URL so a changed image
+        // never overwrites the derivatives of the previous one
+        let key = id
+            .fingerprints_hinted()
+            .last()
+            .map(|(f, _)| bs58::encode(f).into_string())
+            .unwrap_or_else(|| unreachable!());
+
+        let mut rows = Vec::with_capacity(derivatives.len());
+
+        for derivatives::Derivative { size, format, data } in derivatives {
+            let width = size as i32;
+            let uri = self
+                .storage
+                .put(
+                    &format!("{}/{}.{}", key, width, format.extension()),
+                    data,
+                    format.mime_type(),
+                )
+                .await?;
+
+            rows.push(DbFile {
+                metadata_address: Owned(job.metadata_address.clone()),
+                uri: Owned(uri.into()),
+                file_type: Borrowed(format.mime_type()),
+                slot: job.slot,
+                write_version: job.write_version,
+                source_uri: Some(Owned(job.image.clone())),
+                width: Some(width),
+            });
+        }
+
+        let addr = job.metadata_address.clone();
+
+        self.db
+            .run(move |db| {
+                db.build_transaction().read_write().run(|| {
+                    delete(
+                        files::table
+                            .filter(files::metadata_address.eq(&addr))
+                            .filter(files::source_uri.is_not_null()),
+                    )
+                    .execute(db)
+                    .context("Failed to delete stale derivatives")?;
+
+                    insert_into(files::table)
+                        .values(&rows)
+                        .execute(db)
+                        .context("Failed to insert derivatives")?;
+
+                    delete(
+                        image_derivative_failures::table
+                            .filter(image_derivative_failures::metadata_address.eq(&addr)),
+                    )
+                    .execute(db)
+                    .context("Failed to clear derivative failure")?;
+
+                    Result::<_>::Ok(())
+                })
+            })
+            .await
+    }
+
+    async fn record_failure(&self, job: Job, error: String) -> Result<()> {
+        self.db
+            .run(move |db| {
+                db.build_transaction().read_write().run(|| {
+                    let prev = image_derivative_failures::table
+                        .filter(
+                            image_derivative_failures::metadata_address.eq(&job.metadata_address),
+                        )
+                        .filter(image_derivative_failures::source_uri.eq(&job.image))
+                        .select(image_derivative_failures::attempts)
+                        .first::<i32>(db)
+                        .optional()?;
+
+                    let row = ImageDerivativeFailure {
+                        metadata_address: Owned(job.metadata_address),
+                        source_uri: Owned(job.image),
+                        attempts: prev.unwrap_or(0).saturating_add(1),
+                        error: Owned(error),
+                        updated_at: Local::now().naive_utc(),
+                    };
+
+                    insert_into(image_derivative_failures::table)
+                        .values(&row)
+                        .on_conflict(image_derivative_failures::metadata_address)
+                        .do_update()
+                        .set(&row)
+                        .execute(db)?;
+
+                    Result::<_>::Ok(())
+                })
+            })
+            .await
+            .context("Failed to record derivative failure")
+    }
+}
@@ -0,0 +1,120 @@
+use std::fmt;
+
+use indexer_core::{clap, url::Url};
+use rusoto_core::{credential::ChainProvider, HttpClient, Region};
+use rusoto_s3::{PutObjectRequest, S3Client, S3};
+
+use crate::prelude::*;
+
+/// Arguments for connecting to an S3-compatible bucket
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Name of the bucket to store image derivatives in
+    #[clap(long, env)]
+    asset_bucket: String,
+
+    /// Region of the bucket
+    #[clap(long, env, default_value = "us-east-1")]
+    asset_bucket_region: String,
+
+    /// Custom S3 API endpoint, e.g. `http://localhost:9000` for a local MinIO
+    /// instance
+    #[clap(long, env)]
+    asset_bucket_endpoint: Option<String>,
+
+    /// Public base URL under which objects in the bucket are served
+    #[clap(long, env)]
+    asset_public_url: Url,
+
+    /// Prefix prepended to the key of each stored object
+    #[clap(long, env, default_value = "derivatives")]
+    asset_key_prefix: String,
+}
+
+/// Handle to an S3-compatible bucket.  Credentials are read from the standard
+/// AWS environment variables and profile files.
+pub struct Storage {
+    client: S3Client,
+    bucket: String,
+    public_url: Url,
+    prefix: String,
+}
+
+impl fmt::Debug for Storage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Storage")
+            .field("bucket", &self.bucket)
+            .field("public_url", &self.public_url)
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Storage {
+    /// Construct a new bucket handle
+    ///
+    /// # Errors
+    /// This function fails if the underlying HTTP client cannot be created
+    pub fn new(args: Args) -> Result<Self> {
+        let Args {
+            asset_bucket,
+            asset_bucket_region,
+            asset_bucket_endpoint,
+            mut asset_public_url,
+            asset_key_prefix,
+        } = args;
+
+        let region = match asset_bucket_endpoint {
+            Some(endpoint) => Region::Custom {
+                name: asset_bucket_region,
+                endpoint,
+            },
+            None => asset_bucket_region
+                .parse()
+                .context("Invalid bucket region")?,
+        };
+
+        let http = HttpClient::new().context("Failed to create S3 HTTP client")?;
+
+        // Ensure URL joins append to the path rather than replacing its last
+        // segment
+        if !asset_public_url.path().ends_with('/') {
+            asset_public_url.set_path(&format!("{}/", asset_public_url.path()));
+        }
+
+        Ok(Self {
+            client: S3Client::new_with(http, ChainProvider::new(), region),
+            bucket: asset_bucket,
+            public_url: asset_public_url,
+            prefix: asset_key_prefix.trim_matches('/').to_owned(),
+        })
+    }
+
+    /// Upload an object to the bucket, returning its public URL
+    ///
+    /// # Errors
+    /// This function fails if the upload request fails
+    pub async fn put(&self, key: &str, body: Vec<u8>, content_type: &str) -> Result<Url> {
+        let key = if self.prefix.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{}/{}", self.prefix, key)
+        };
+
+        self.client
+            .put_object(PutObjectRequest {
+                bucket: self.bucket.clone(),
+                key: key.clone(),
+                body: Some(body.into()),
+                content_type: Some(content_type.to_owned()),
+                cache_control: Some("public, max-age=31536000, immutable".to_owned()),
+                ..PutObjectRequest::default()
+            })
+            .await
+            .with_context(|| format!("Failed to upload {:?} to bucket", key))?;
+
+        self.public_url
+            .join(&key)
+            .context("Failed to format public URL for object")
+    }
+}
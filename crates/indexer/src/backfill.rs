@@ -0,0 +1,146 @@
+//! One-off maintenance tasks for repairing previously-indexed data
+
+use std::{
+    fs::File,
+    io::{self, BufReader},
+    path::Path,
+};
+
+use indexer_core::db::{
+    models::Currency,
+    mutations::{
+        collection_holders as holders, currency_rates as rates, metadata_json,
+        price_candles as candles, royalties, wash_trades as flags,
+    },
+};
+
+use crate::{db::Pool, prelude::*};
+
+/// Remove files, attributes, and collections left behind by metadata JSON
+/// documents which have since been replaced, in batches of up to
+/// `batch_size` rows of each type
+///
+/// # Errors
+/// This function fails if a batch cannot be processed
+pub async fn stale_metadata_json_rows(db: &Pool, batch_size: i64) -> Result<()> {
+    let mut total = metadata_json::StaleRows::default();
+
+    loop {
+        let batch = db
+            .run(move |db| metadata_json::remove_stale_rows(db, batch_size))
+            .await?;
+
+        if batch.total() == 0 {
+            break;
+        }
+
+        debug!("Cleaned up {:?}", batch);
+
+        total.files += batch.files;
+        total.attributes += batch.attributes;
+        total.collections += batch.collections;
+    }
+
+    info!(
+        "Removed {} stale file(s) and {} stale attribute(s), and repaired {} collection(s)",
+        total.files, total.attributes, total.collections
+    );
+
+    Ok(())
+}
+
+/// Rebuild every collection price candle from the `purchases` table
+///
+/// # Errors
+/// This function fails if the candles cannot be rebuilt
+pub async fn price_candles(db: &Pool) -> Result<()> {
+    let count = db.run(|db| candles::rebuild(db)).await?;
+
+    info!("Rebuilt {} price candle(s)", count);
+
+    Ok(())
+}
+
+/// Register a currency so its sales can be normalized once rates are loaded
+///
+/// # Errors
+/// This function fails if the currency cannot be written
+pub async fn currency(db: &Pool, mint: String, symbol: String, decimals: i16) -> Result<()> {
+    info!(
+        "Registering {} ({}) with {} decimal(s)",
+        symbol, mint, decimals
+    );
+
+    db.run(move |db| {
+        rates::upsert_currency(db, &Currency {
+            mint: Owned(mint),
+            symbol: Owned(symbol),
+            decimals,
+        })
+    })
+    .await
+}
+
+/// Load historical currency rates from a `mint,effective_at,sol_price` CSV
+/// file, or from standard input if the path is `-`
+///
+/// # Errors
+/// This function fails if the file cannot be parsed or the rates cannot be
+/// written
+pub async fn currency_rates(db: &Pool, path: &Path) -> Result<()> {
+    let loaded = if path == Path::new("-") {
+        rates::parse(io::stdin().lock())
+    } else {
+        File::open(path)
+            .with_context(|| format!("Failed to open {:?}", path))
+            .and_then(|f| rates::parse(BufReader::new(f)))
+    }?;
+
+    let count = db.run(move |db| rates::insert(db, &loaded)).await?;
+
+    info!(
+        "Loaded {} currency rate(s); run price-candles to apply them to past sales",
+        count
+    );
+
+    Ok(())
+}
+
+/// Rebuild the royalties owed for every sale from the `purchases` table,
+/// leaving their payments to be looked up again by the royalties worker
+///
+/// # Errors
+/// This function fails if the royalties cannot be rebuilt
+pub async fn sale_royalties(db: &Pool) -> Result<()> {
+    let count = db.run(|db| royalties::rebuild(db)).await?;
+
+    info!("Rebuilt {} sale royalt(ies)", count);
+
+    Ok(())
+}
+
+/// Re-run wash trade detection over every sale in the `purchases` table,
+/// replacing the existing flags
+///
+/// # Errors
+/// This function fails if the flags cannot be rebuilt
+pub async fn wash_trades(db: &Pool) -> Result<()> {
+    let count = db.run(|db| flags::rebuild(db)).await?;
+
+    info!("Flagged {} sale(s) as suspected wash trades", count);
+
+    Ok(())
+}
+
+/// Recount the NFTs of each verified collection held by each wallet from the
+/// `current_metadata_owners` table
+///
+/// # Errors
+/// This function fails if the holdings cannot be rebuilt
+pub async fn collection_holders(db: &Pool) -> Result<()> {
+    let count = db.run(|db| holders::rebuild(db)).await?;
+
+    info!("Rebuilt {} collection holding(s)", count);
+
+    Ok(())
+}
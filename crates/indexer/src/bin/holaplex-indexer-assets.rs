@@ -0,0 +1,12 @@
+use std::sync::Arc;
+
+use holaplex_indexer::assets::{Args, Worker};
+use indexer_core::prelude::*;
+
+fn main() {
+    holaplex_indexer::run(|args: Args, params, db| async move {
+        let worker = Worker::new(args, db).context("Failed to construct asset worker")?;
+
+        Arc::new(worker).run(params.concurrency()).await
+    });
+}
@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+
+use indexer_core::clap;
+
+#[derive(Debug, clap::Args)]
+struct Args {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Remove files, attributes, and collections left behind by metadata JSON
+    /// documents which have since been replaced
+    StaleMetadataJsonRows {
+        /// Maximum number of rows of each type to process per transaction
+        #[clap(long, env, default_value_t = 1000)]
+        batch_size: i64,
+    },
+    /// Rebuild every collection price candle from the purchases table
+    PriceCandles,
+    /// Register an SPL token used as an auction house treasury mint
+    Currency {
+        /// Address of the token mint
+        mint: String,
+        /// Display symbol of the token
+        symbol: String,
+        /// Number of decimal places in one whole token
+        decimals: i16,
+    },
+    /// Load historical SOL prices of registered currencies from a CSV file
+    /// of `mint,effective_at,sol_price` rows
+    CurrencyRates {
+        /// Path to the CSV file, or - to read from standard input
+        file: PathBuf,
+    },
+    /// Rebuild the royalties owed for every sale from the purchases table
+    SaleRoyalties,
+    /// Re-run wash trade detection over every sale in the purchases table
+    WashTrades,
+    /// Recount the NFTs of each verified collection held by each wallet
+    CollectionHolders,
+}
+
+fn main() {
+    holaplex_indexer::run(|Args { command }, _params, db| async move {
+        match command {
+            Command::StaleMetadataJsonRows { batch_size } => {
+                holaplex_indexer::backfill::stale_metadata_json_rows(&db, batch_size).await
+            },
+            Command::PriceCandles => holaplex_indexer::backfill::price_candles(&db).await,
+            Command::Currency {
+                mint,
+                symbol,
+                decimals,
+            } => holaplex_indexer::backfill::currency(&db, mint, symbol, decimals).await,
+            Command::CurrencyRates { file } => {
+                holaplex_indexer::backfill::currency_rates(&db, &file).await
+            },
+            Command::SaleRoyalties => holaplex_indexer::backfill::sale_royalties(&db).await,
+            Command::WashTrades => holaplex_indexer::backfill::wash_trades(&db).await,
+            Command::CollectionHolders => holaplex_indexer::backfill::collection_holders(&db).await,
+        }
+    });
+}
@@ -0,0 +1,32 @@
+use std::path::PathBuf;
+
+use indexer_core::{
+    clap,
+    export::{Dataset, Format},
+};
+
+#[derive(Debug, clap::Args)]
+struct Args {
+    /// Output format, either csv or parquet
+    #[clap(long, short, default_value_t = Format::Csv)]
+    format: Format,
+
+    /// Path to write the export to, or - to write to standard output
+    #[clap(long, short, default_value = "-")]
+    output: PathBuf,
+
+    #[clap(subcommand)]
+    dataset: Dataset,
+}
+
+fn main() {
+    holaplex_indexer::run(|args: Args, _params, db| async move {
+        let Args {
+            format,
+            output,
+            dataset,
+        } = args;
+
+        holaplex_indexer::export::run(&db, dataset, format, &output).await
+    });
+}
@@ -70,6 +70,8 @@ async fn run<E: Send + holaplex_indexer::http::Process + 'static>(
         .await
         .context("Failed to create queue consumer")?;
 
+    E::start_tasks(&client, &conn, &queue_type).await?;
+
     holaplex_indexer::amqp_consume(
         &params,
         conn,
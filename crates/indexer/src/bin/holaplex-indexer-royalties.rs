@@ -0,0 +1,11 @@
+use std::sync::Arc;
+
+use holaplex_indexer::royalties::{Args, Worker};
+
+fn main() {
+    holaplex_indexer::run(|args: Args, params, db| async move {
+        Arc::new(Worker::new(args, db)?)
+            .run(params.concurrency())
+            .await
+    });
+}
@@ -4,13 +4,16 @@ use indexer_rabbitmq::search_indexer;
 
 #[derive(Debug, clap::Parser)]
 struct Args {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     /// The address of an AMQP server to connect to
     #[clap(long, env)]
-    amqp_url: String,
+    amqp_url: Option<String>,
 
     /// The ID of the indexer sending events to listen for
     #[clap(long, env)]
-    sender: String,
+    sender: Option<String>,
 
     #[clap(flatten)]
     queue_suffix: indexer_rabbitmq::suffix::Suffix,
@@ -19,9 +22,24 @@ struct Args {
     client: ClientArgs,
 }
 
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Rebuild a search index from the database into a shadow index and
+    /// atomically swap it into place
+    Reindex {
+        /// The name of the index to rebuild
+        index: String,
+
+        /// Number of documents to upload to the shadow index at once
+        #[clap(long, env, default_value_t = 1000)]
+        batch_size: i64,
+    },
+}
+
 fn main() {
     holaplex_indexer::run(
         |Args {
+             command,
              amqp_url,
              sender,
              queue_suffix,
@@ -29,30 +47,16 @@ fn main() {
          },
          params,
          db| async move {
-            let conn = holaplex_indexer::amqp_connect(amqp_url, env!("CARGO_BIN_NAME")).await?;
-
-            let queue_type = search_indexer::QueueType::new(&sender, &queue_suffix)?;
-            let consumer =
-                search_indexer::Consumer::new(&conn, queue_type.clone(), "search-consumer")
-                    .await
-                    .context("Failed to create queue consumer")?;
-
             let (client, upsert_task, stop_upsert) = Client::new_rc(db, client)
                 .await
                 .context("Failed to construct Client")?;
 
-            let ret = holaplex_indexer::amqp_consume(
-                &params,
-                conn,
-                consumer,
-                queue_type,
-                StdDuration::from_millis(500),
-                move |m| {
-                    let client = client.clone();
-                    async move { holaplex_indexer::search::process_message(m, &*client).await }
+            let ret = match command {
+                Some(Command::Reindex { index, batch_size }) => {
+                    holaplex_indexer::search::reindex(&client, &index, batch_size).await
                 },
-            )
-            .await;
+                None => consume(&params, amqp_url, sender, queue_suffix, client).await,
+            };
 
             if let Err(()) = stop_upsert.send(()) {
                 error!("Failed to stop upsert task");
@@ -80,3 +84,34 @@ fn main() {
         },
     );
 }
+
+async fn consume(
+    params: &holaplex_indexer::Params,
+    amqp_url: Option<String>,
+    sender: Option<String>,
+    queue_suffix: indexer_rabbitmq::suffix::Suffix,
+    client: std::sync::Arc<Client>,
+) -> Result<()> {
+    let amqp_url = amqp_url.context("--amqp-url is required to consume search messages")?;
+    let sender = sender.context("--sender is required to consume search messages")?;
+
+    let conn = holaplex_indexer::amqp_connect(amqp_url, env!("CARGO_BIN_NAME")).await?;
+
+    let queue_type = search_indexer::QueueType::new(&sender, &queue_suffix)?;
+    let consumer = search_indexer::Consumer::new(&conn, queue_type.clone(), "search-consumer")
+        .await
+        .context("Failed to create queue consumer")?;
+
+    holaplex_indexer::amqp_consume(
+        params,
+        conn,
+        consumer,
+        queue_type,
+        StdDuration::from_millis(500),
+        move |m| {
+            let client = client.clone();
+            async move { holaplex_indexer::search::process_message(m, &*client).await }
+        },
+    )
+    .await
+}
@@ -0,0 +1,7 @@
+use holaplex_indexer::trends::{Args, Worker};
+
+fn main() {
+    holaplex_indexer::run(
+        |args: Args, _params, db| async move { Worker::new(args, db).run().await },
+    );
+}
@@ -0,0 +1,132 @@
+//! Block time worker.  Looks up the time each slot with owner history or
+//! burns was produced, so ingestion only has to record the slot.
+
+use std::{sync::Arc, time::Duration};
+
+use futures_util::StreamExt;
+use indexer_core::{clap, db::mutations::block_times};
+use serde_json::{json, Value};
+
+use crate::{db::Pool, prelude::*, reqwest};
+
+/// Arguments for the block time worker
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Solana RPC endpoint to look up block times from
+    #[clap(long, env)]
+    solana_endpoint: String,
+
+    /// Number of slots to select per batch
+    #[clap(long, env, default_value_t = 256)]
+    batch_size: i64,
+
+    /// Time to wait before checking for new slots once none remain, in
+    /// seconds
+    #[clap(long, env, default_value_t = 30)]
+    poll_interval: u64,
+}
+
+/// Shared state for the block time worker
+#[derive(Debug)]
+pub struct Worker {
+    db: Pool,
+    http: reqwest::Client,
+    endpoint: String,
+    batch_size: i64,
+    poll_interval: Duration,
+}
+
+impl Worker {
+    /// Construct a new worker
+    ///
+    /// # Errors
+    /// This function fails if the HTTP client cannot be constructed
+    pub fn new(args: Args, db: Pool) -> Result<Self> {
+        let Args {
+            solana_endpoint,
+            batch_size,
+            poll_interval,
+        } = args;
+
+        Ok(Self {
+            db,
+            http: reqwest::Client::new(Duration::from_secs(10))?,
+            endpoint: solana_endpoint,
+            batch_size,
+            poll_interval: Duration::from_secs(poll_interval),
+        })
+    }
+
+    /// Record the block times of new slots until interrupted, looking up to
+    /// `concurrency` slots at once
+    ///
+    /// # Errors
+    /// This function fails if the database cannot be queried for new slots
+    pub async fn run(self: Arc<Self>, concurrency: usize) -> Result<()> {
+        // Slots are walked from the highest down, so a slot whose block time
+        // cannot be looked up is retried on the next pass rather than
+        // blocking the slots below it
+        let mut before = None;
+
+        loop {
+            let batch_size = self.batch_size;
+            let slots = tokio::select! {
+                s = self.db.run(move |db| block_times::pending_slots(db, before, batch_size)) => s?,
+                r = tokio::signal::ctrl_c() => {
+                    r.context("Failed to wait for interrupt")?;
+                    warn!("Interrupt received, shutting down...");
+
+                    break Ok(());
+                },
+            };
+
+            if let Some(&slot) = slots.last() {
+                before = Some(slot);
+            } else {
+                trace!("No slots awaiting block times, sleeping");
+                before = None;
+                tokio::time::sleep(self.poll_interval).await;
+                continue;
+            }
+
+            debug!("Looking up block times for {} slot(s)", slots.len());
+
+            futures_util::stream::iter(slots)
+                .for_each_concurrent(concurrency, |slot| {
+                    let worker = Arc::clone(&self);
+
+                    async move {
+                        if let Err(e) = worker.record_slot(slot).await {
+                            error!("Failed to record block time of slot {}: {:?}", slot, e);
+                        }
+                    }
+                })
+                .await;
+        }
+    }
+
+    async fn record_slot(&self, slot: i64) -> Result<()> {
+        let time = self
+            .http
+            .json_rpc(&self.endpoint, "getBlockTime", json!([slot]))
+            .await?;
+
+        let time = match time {
+            Value::Null => {
+                debug!("No block time available for slot {}", slot);
+
+                return Ok(());
+            },
+            t => t
+                .as_i64()
+                .ok_or_else(|| anyhow!("Invalid block time response: {}", t))?,
+        };
+
+        let block_time = NaiveDateTime::from_timestamp(time, 0);
+
+        self.db
+            .run(move |db| block_times::record(db, slot, block_time))
+            .await
+            .map(|_| ())
+    }
+}
@@ -0,0 +1,95 @@
+//! Currency rate worker.  Polls a price feed serving currency rates in the
+//! format accepted by the `currency-rates` backfill, so sales in non-SOL
+//! currencies can be normalized to SOL as they are indexed.
+
+use std::time::Duration;
+
+use indexer_core::{clap, db::mutations::currency_rates};
+
+use crate::{db::Pool, prelude::*, reqwest};
+
+/// Arguments for the currency rate worker
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// URL of a price feed serving lines of the form
+    /// `mint,effective_at,sol_price`
+    #[clap(long, env)]
+    price_feed_url: String,
+
+    /// Time to wait between polls of the price feed, in seconds
+    #[clap(long, env, default_value_t = 300)]
+    poll_interval: u64,
+}
+
+/// Shared state for the currency rate worker
+#[derive(Debug)]
+pub struct Worker {
+    db: Pool,
+    http: reqwest::Client,
+    url: String,
+    poll_interval: Duration,
+}
+
+impl Worker {
+    /// Construct a new worker
+    ///
+    /// # Errors
+    /// This function fails if the HTTP client cannot be constructed
+    pub fn new(args: Args, db: Pool) -> Result<Self> {
+        let Args {
+            price_feed_url,
+            poll_interval,
+        } = args;
+
+        Ok(Self {
+            db,
+            http: reqwest::Client::new(Duration::from_secs(10))?,
+            url: price_feed_url,
+            poll_interval: Duration::from_secs(poll_interval),
+        })
+    }
+
+    async fn poll(&self) -> Result<usize> {
+        let body = self
+            .http
+            .run(|h| async move {
+                h.get(&self.url)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .text()
+                    .await
+            })
+            .await?;
+
+        let rates = currency_rates::parse(body.as_bytes()).context("Invalid price feed")?;
+
+        self.db
+            .run(move |db| currency_rates::insert(db, &rates))
+            .await
+    }
+
+    /// Load rates from the price feed until interrupted.  Failed polls are
+    /// logged and retried after the poll interval.
+    ///
+    /// # Errors
+    /// This function fails if waiting for an interrupt fails
+    pub async fn run(&self) -> Result<()> {
+        loop {
+            match self.poll().await {
+                Ok(n) => debug!("Loaded {} currency rate(s) from the price feed", n),
+                Err(e) => error!("Failed to load currency rates: {:?}", e),
+            }
+
+            tokio::select! {
+                () = tokio::time::sleep(self.poll_interval) => (),
+                r = tokio::signal::ctrl_c() => {
+                    r.context("Failed to wait for interrupt")?;
+                    warn!("Interrupt received, shutting down...");
+
+                    break Ok(());
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! Export of analytics query results to CSV or Parquet files
+
+use std::{
+    fs::File,
+    io::{self, BufWriter},
+    path::Path,
+};
+
+use indexer_core::export::{self, Dataset, Format};
+
+use crate::{db::Pool, prelude::*};
+
+/// Export the rows of a dataset to the file at `path`, or to standard output
+/// if `path` is `-`
+///
+/// # Errors
+/// This function fails if the output cannot be opened or written, or the
+/// query fails
+pub async fn run(db: &Pool, dataset: Dataset, format: Format, path: &Path) -> Result<()> {
+    let out: Box<dyn io::Write + Send> = if path == Path::new("-") {
+        Box::new(BufWriter::new(io::stdout()))
+    } else {
+        Box::new(BufWriter::new(
+            File::create(path).with_context(|| format!("Failed to create {:?}", path))?,
+        ))
+    };
+
+    let rows = db
+        .run(move |db| export::export(db, &dataset, format, out))
+        .await?;
+
+    info!("Exported {} row(s)", rows);
+
+    Ok(())
+}
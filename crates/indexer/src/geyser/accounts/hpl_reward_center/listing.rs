@@ -58,6 +58,7 @@ pub(crate) async fn process(
         slot: slot.try_into()?,
         write_version: write_version.try_into()?,
     };
+    let metadata_address = row.metadata.to_string();
 
     client
         .db()
@@ -144,6 +145,8 @@ pub(crate) async fn process(
                     canceled_at: row.canceled_at,
                     write_version: Some(row.write_version),
                     slot: row.slot,
+                    currency_mint: Some(auction_houses.treasury_mint),
+                    currency_decimals: None,
                 };
 
                 let listing_exists = select(exists(
@@ -195,5 +198,7 @@ pub(crate) async fn process(
         .await
         .context("Failed to insert rewards listing")?;
 
+    client.dispatch_nft_document(metadata_address).await?;
+
     Ok(())
 }
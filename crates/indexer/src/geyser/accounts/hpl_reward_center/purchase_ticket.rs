@@ -105,6 +105,8 @@ pub(crate) async fn process(
         .await
         .context("Failed to insert rewards payout")?;
 
+    let metadata_address = row.metadata.to_string();
+
     client
         .db()
         .run(move |db| {
@@ -129,6 +131,8 @@ pub(crate) async fn process(
                 created_at: row.created_at,
                 slot: row.slot,
                 write_version: Some(row.write_version),
+                currency_mint: Some(auction_house.treasury_mint),
+                currency_decimals: None,
             };
 
             let purchase_exists = select(exists(
@@ -227,6 +231,8 @@ pub(crate) async fn process(
         .await
         .context("Failed to insert rewards listing")?;
 
+    client.dispatch_nft_document(metadata_address).await?;
+
     Ok(())
 }
 
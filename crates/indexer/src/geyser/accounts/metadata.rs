@@ -1,7 +1,7 @@
 use indexer_core::{
     db::{
         custom_types::TokenStandardEnum,
-        insert_into,
+        delete, insert_into,
         models::{FeedEventWallet, Metadata, MetadataCollectionKey, MetadataCreator, MintEvent},
         select,
         tables::{
@@ -118,9 +118,13 @@ pub(crate) async fn process(
             .context("Failed to insert metadata creator")?;
     }
 
-    if meta.collection.is_some() {
-        index_metadata_collection_key(client, addr, meta.collection.context("err!")?).await?;
-    }
+    index_metadata_collection_key(client, addr, meta.collection).await?;
+
+    client
+        .search()
+        .upsert_nft(false, meta.mint)
+        .await
+        .context("Failed to dispatch NFT search document")?;
 
     Ok(())
 }
@@ -177,29 +181,90 @@ async fn insert_with_event(
 async fn index_metadata_collection_key(
     client: &Client,
     addr: String,
-    collection: Collection,
+    collection: Option<Collection>,
 ) -> Result<()> {
-    let row = MetadataCollectionKey {
-        metadata_address: Owned(addr),
-        collection_address: Owned(collection.key.to_string()),
-        verified: collection.verified,
-    };
+    let row = collection.map(|c| MetadataCollectionKey {
+        metadata_address: Owned(addr.clone()),
+        collection_address: Owned(c.key.to_string()),
+        verified: c.verified,
+    });
 
-    client
+    let emptied = client
         .db()
         .run(move |db| {
-            insert_into(metadata_collection_keys::table)
-                .values(&row)
-                .on_conflict((
-                    metadata_collection_keys::metadata_address,
-                    metadata_collection_keys::collection_address,
-                ))
-                .do_update()
-                .set(&row)
-                .execute(db)
+            db.build_transaction().read_write().run(|| {
+                // Collections this NFT may no longer be a verified member of
+                let mut removed: Vec<String> = delete(
+                    metadata_collection_keys::table
+                        .filter(metadata_collection_keys::metadata_address.eq(&addr))
+                        .filter(
+                            metadata_collection_keys::collection_address.ne_all(
+                                row.iter()
+                                    .map(|r| r.collection_address.to_string())
+                                    .collect::<Vec<_>>(),
+                            ),
+                        ),
+                )
+                .returning(metadata_collection_keys::collection_address)
+                .get_results(db)
+                .context("Failed to remove stale collection keys")?;
+
+                if let Some(ref row) = row {
+                    insert_into(metadata_collection_keys::table)
+                        .values(row)
+                        .on_conflict((
+                            metadata_collection_keys::metadata_address,
+                            metadata_collection_keys::collection_address,
+                        ))
+                        .do_update()
+                        .set(row)
+                        .execute(db)
+                        .context("Failed to insert into metadata_collection_keys")?;
+
+                    if !row.verified {
+                        removed.push(row.collection_address.to_string());
+                    }
+                }
+
+                // Collections left without any verified member are removed
+                // from the collections search index
+                let mut emptied = Vec::new();
+
+                for collection_address in removed {
+                    let has_members = select(exists(
+                        metadata_collection_keys::table
+                            .filter(
+                                metadata_collection_keys::collection_address
+                                    .eq(&collection_address),
+                            )
+                            .filter(metadata_collection_keys::verified),
+                    ))
+                    .get_result::<bool>(db)
+                    .context("Failed to check for verified collection members")?;
+
+                    if !has_members {
+                        emptied.extend(
+                            metadatas::table
+                                .filter(metadatas::mint_address.eq(collection_address))
+                                .select(metadatas::address)
+                                .load::<String>(db)
+                                .context("Failed to load collection metadata address")?,
+                        );
+                    }
+                }
+
+                Result::<_>::Ok(emptied)
+            })
         })
-        .await
-        .context("Failed to insert into metadata_collection_keys")?;
+        .await?;
+
+    for address in emptied {
+        client
+            .search()
+            .delete_collection(address)
+            .await
+            .context("Failed to dispatch collection document deletion")?;
+    }
 
     Ok(())
 }
@@ -56,22 +56,36 @@ pub(crate) async fn process(
     };
 
     let search_backfill;
+    let mut replaced = vec![];
 
     match rows.get(0) {
         Some(indexed) if (incoming_slot, write_version) > (indexed.slot, indexed.write_version) => {
             search_backfill = Some(false);
 
-            client
+            replaced = client
                 .db()
                 .run(move |db| {
-                    update(twitter_handle_name_services::table.filter(
-                        twitter_handle_name_services::wallet_address.eq(wallet.to_string()),
-                    ))
-                    .set(&values)
-                    .execute(db)
+                    db.build_transaction().read_write().run(|| {
+                        let replaced = twitter_handle_name_services::table
+                            .filter(
+                                twitter_handle_name_services::wallet_address.eq(wallet.to_string()),
+                            )
+                            .filter(twitter_handle_name_services::address.ne(key.to_string()))
+                            .select(twitter_handle_name_services::address)
+                            .load::<String>(db)
+                            .context("Failed to load replaced twitter handles")?;
+
+                        update(twitter_handle_name_services::table.filter(
+                            twitter_handle_name_services::wallet_address.eq(wallet.to_string()),
+                        ))
+                        .set(&values)
+                        .execute(db)
+                        .context("Failed to update twitter handle")?;
+
+                        Result::<_>::Ok(replaced)
+                    })
                 })
-                .await
-                .context("failed to update twitter handle")?;
+                .await?;
         },
         Some(_) => search_backfill = None,
         None => {
@@ -100,5 +114,14 @@ pub(crate) async fn process(
             .context("Failed to dispatch upsert twitter handle document job")?;
     }
 
+    // The wallet's handle was previously registered under another account
+    for address in replaced {
+        client
+            .search()
+            .delete_twitter_handle(address)
+            .await
+            .context("Failed to dispatch twitter handle document deletion")?;
+    }
+
     Ok(())
 }
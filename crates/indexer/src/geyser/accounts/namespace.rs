@@ -138,11 +138,16 @@ async fn process_twitter_namespace(
         .context("failed to load twitter handle name services accounts!")?;
 
     let search_backfill;
+    let mut replaced = None;
 
     match rows.get(0) {
         Some(indexed) if (slot, write_version) > (indexed.slot, indexed.write_version) => {
             search_backfill = Some(false);
 
+            if indexed.address != values.address {
+                replaced = Some(indexed.address.to_string());
+            }
+
             client
                 .db()
                 .run(move |db| {
@@ -184,5 +189,14 @@ async fn process_twitter_namespace(
             .context("Failed to dispatch upsert twitter handle document job")?;
     }
 
+    // The wallet's handle was previously registered under another account
+    if let Some(address) = replaced {
+        client
+            .search()
+            .delete_twitter_handle(address)
+            .await
+            .context("Failed to dispatch twitter handle document deletion")?;
+    }
+
     Ok(())
 }
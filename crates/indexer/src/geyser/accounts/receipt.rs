@@ -84,6 +84,8 @@ pub(crate) async fn process_listing_receipt(
                 slot: row.slot,
                 write_version: Some(row.write_version),
                 expiry: None,
+                currency_mint: None,
+                currency_decimals: None,
             };
 
             let listing_id = insert_into(listings::table)
@@ -131,6 +133,10 @@ pub(crate) async fn process_listing_receipt(
         .await
         .context("Failed to insert listing receipt!")?;
 
+    client
+        .dispatch_nft_document(listing.metadata.to_string())
+        .await?;
+
     Ok(())
 }
 
@@ -189,6 +195,10 @@ pub(crate) async fn process_purchase_receipt(
 
     let purchase_id = upsert_into_purchases_table(client, row.clone()).await?;
 
+    client
+        .dispatch_nft_document(purchase.metadata.to_string())
+        .await?;
+
     if purchase_exists {
         return Ok(());
     }
@@ -416,6 +426,8 @@ async fn upsert_into_purchases_table<'a>(
         created_at: row.created_at,
         slot: row.slot,
         write_version: Some(row.write_version),
+        currency_mint: None,
+        currency_decimals: None,
     };
     let purchase_id = client
         .db()
@@ -1,5 +1,10 @@
 use indexer_core::{
-    db::{insert_into, models::CurrentMetadataOwner, tables::current_metadata_owners, update},
+    db::{
+        insert_into,
+        models::{CurrentMetadataOwner, MetadataOwnerHistory},
+        tables::{current_metadata_owners, metadata_owner_history},
+        update, Connection,
+    },
     prelude::*,
 };
 use spl_token::state::Account as TokenAccount;
@@ -24,6 +29,7 @@ pub async fn process(
         return Ok(());
     }
 
+    let mint = token_account.mint;
     let owner = token_account.owner.to_string();
     let mint_address = token_account.mint.to_string();
     let incoming_slot: i64 = slot.try_into()?;
@@ -35,53 +41,99 @@ pub async fn process(
         slot: incoming_slot,
     };
 
-    client
+    let history = MetadataOwnerHistory {
+        mint_address: values.mint_address.clone(),
+        slot: incoming_slot,
+        owner_address: values.owner_address.clone(),
+        token_account_address: values.token_account_address.clone(),
+    };
+
+    let updated = client
         .db()
         .run(move |db| {
-            let rows = current_metadata_owners::table
-                .select((
-                    current_metadata_owners::mint_address,
-                    current_metadata_owners::owner_address,
-                    current_metadata_owners::token_account_address,
-                    current_metadata_owners::slot,
-                ))
-                .filter(current_metadata_owners::mint_address.eq(token_account.mint.to_string()))
-                .load::<CurrentMetadataOwner>(db)
-                .context("failed to load metadata owner!")?;
-
-            match rows.get(0) {
-                Some(r) if incoming_slot > r.slot => {
-                    db.build_transaction().read_write().run(|| {
-                        update(
-                            current_metadata_owners::table
-                                .filter(current_metadata_owners::mint_address.eq(values.clone().mint_address)),
-                        )
+            db.build_transaction().read_write().run(|| {
+                // Only append a row when the owner or token account changed as
+                // of the incoming slot, so unrelated account updates don't pile
+                // up
+                if owner_changed(db, &values)? {
+                    insert_into(metadata_owner_history::table)
+                        .values(&history)
+                        .on_conflict_do_nothing()
+                        .execute(db)
+                        .context("failed to record metadata owner history")?;
+                }
+
+                let rows = current_metadata_owners::table
+                    .select((
+                        current_metadata_owners::mint_address,
+                        current_metadata_owners::owner_address,
+                        current_metadata_owners::token_account_address,
+                        current_metadata_owners::slot,
+                    ))
+                    .filter(
+                        current_metadata_owners::mint_address.eq(token_account.mint.to_string()),
+                    )
+                    .load::<CurrentMetadataOwner>(db)
+                    .context("failed to load metadata owner!")?;
+
+                match rows.get(0) {
+                    Some(r) if incoming_slot > r.slot => {
+                        update(current_metadata_owners::table.filter(
+                            current_metadata_owners::mint_address.eq(values.clone().mint_address),
+                        ))
                         .set(&values)
                         .execute(db)
-                        .context("transaction failed! unable to update metadata_owners when incoming slot > indexed slot")
-                        .map(|_| ())
-                    })
-                },
-                Some(_) => Ok(()),
-                None => {
-                    db.build_transaction()
-                        .read_write()
-                        .run(|| {
-                            insert_into(current_metadata_owners::table)
-                                .values(&values)
-                                .on_conflict(current_metadata_owners::mint_address)
-                                .do_update()
-                                .set(&values)
-                                .execute(db)
-                                .map(|_| ())
-                        })
-                        .context("transaction failed! unable to insert metadata owner")?;
-
-                    Ok(())
-                },
-            }
+                        .context(
+                            "unable to update metadata_owners when incoming slot > indexed slot",
+                        )?;
+
+                        Ok(true)
+                    },
+                    Some(_) => Ok(false),
+                    None => {
+                        insert_into(current_metadata_owners::table)
+                            .values(&values)
+                            .on_conflict(current_metadata_owners::mint_address)
+                            .do_update()
+                            .set(&values)
+                            .execute(db)
+                            .context("unable to insert metadata owner")?;
+
+                        Ok(true)
+                    },
+                }
+            })
         })
         .await
         .context("failed to insert token metadata owner!")?;
+
+    if updated {
+        client
+            .search()
+            .upsert_nft(false, mint)
+            .await
+            .context("Failed to dispatch NFT search document")?;
+    }
+
     Ok(())
 }
+
+/// Check whether the owner or token account of a mint as of the given slot
+/// differs from the last recorded in the owner history
+fn owner_changed(db: &Connection, values: &CurrentMetadataOwner) -> Result<bool> {
+    let previous = metadata_owner_history::table
+        .select((
+            metadata_owner_history::owner_address,
+            metadata_owner_history::token_account_address,
+        ))
+        .filter(metadata_owner_history::mint_address.eq(&*values.mint_address))
+        .filter(metadata_owner_history::slot.le(values.slot))
+        .order(metadata_owner_history::slot.desc())
+        .first::<(String, String)>(db)
+        .optional()
+        .context("failed to load previous metadata owner")?;
+
+    Ok(previous.map_or(true, |(owner, account)| {
+        owner != values.owner_address || account != values.token_account_address
+    }))
+}
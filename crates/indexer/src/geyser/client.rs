@@ -1,6 +1,6 @@
 use std::{sync::Arc, time::Duration};
 
-use indexer_core::clap;
+use indexer_core::{clap, db::tables::metadatas};
 use indexer_rabbitmq::{http_indexer, search_indexer};
 
 use crate::{db::Pool, prelude::*, reqwest, search_dispatch};
@@ -100,6 +100,34 @@ impl Client {
         &self.search
     }
 
+    /// Dispatch an update of the NFT search document for the given metadata
+    /// address, if the metadata has been indexed
+    ///
+    /// # Errors
+    /// This function fails if the mint address cannot be loaded or the AMQP
+    /// payload cannot be sent.
+    pub async fn dispatch_nft_document(&self, metadata_address: String) -> Result<()> {
+        let mint = self
+            .db
+            .run(move |db| {
+                metadatas::table
+                    .filter(metadatas::address.eq(metadata_address))
+                    .select(metadatas::mint_address)
+                    .first::<String>(db)
+                    .optional()
+            })
+            .await
+            .context("Failed to load mint address for NFT document")?;
+
+        if let Some(mint) = mint {
+            self.search
+                .upsert_nft(false, mint.parse().context("Invalid mint address")?)
+                .await?;
+        }
+
+        Ok(())
+    }
+
     /// Dispatch an AMQP message to the HTTP indexer to request off-chain
     /// metadata JSON
     ///
@@ -40,7 +40,7 @@ pub(crate) async fn process(
         slot: slot.try_into()?,
     };
 
-    client
+    let canceled_listings = client
         .db()
         .run(move |db| {
             insert_into(cancel_instructions::table)
@@ -70,7 +70,8 @@ pub(crate) async fn process(
                         listings::canceled_at.eq(Some(row.created_at)),
                         listings::slot.eq(row.slot),
                     ))
-                    .execute(db)
+                    .returning(listings::metadata)
+                    .get_results::<String>(db)
                 } else {
                     update(
                         offers::table.filter(
@@ -85,11 +86,16 @@ pub(crate) async fn process(
                         offers::slot.eq(row.slot),
                     ))
                     .execute(db)
+                    .map(|_| Vec::new())
                 }
             })
         })
         .await
         .context("failed to insert cancel instruction ")?;
 
+    for metadata_address in canceled_listings {
+        client.dispatch_nft_document(metadata_address).await?;
+    }
+
     Ok(())
 }
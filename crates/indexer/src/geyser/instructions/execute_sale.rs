@@ -59,6 +59,7 @@ pub(crate) async fn process(
         token_size: params.token_size.try_into()?,
         created_at: Utc::now().naive_utc(),
         slot: slot.try_into()?,
+        creator_accounts: accts[21..].to_vec(),
     };
 
     upsert_into_purchases_table(
@@ -75,6 +76,8 @@ pub(crate) async fn process(
             created_at: row.created_at,
             slot: row.slot,
             write_version: None,
+            currency_mint: Some(row.treasury_mint.clone()),
+            currency_decimals: None,
         },
         accts[13].clone(),
         accts[14].clone(),
@@ -100,6 +103,8 @@ pub(crate) async fn upsert_into_purchases_table<'a>(
     buyer_trade_state: String,
     seller_trade_state: String,
 ) -> Result<()> {
+    let metadata_address = data.metadata.to_string();
+
     client
         .db()
         .run(move |db| {
@@ -187,5 +192,7 @@ pub(crate) async fn upsert_into_purchases_table<'a>(
         .await
         .context("Failed to insert purchase!")?;
 
+    client.dispatch_nft_document(metadata_address).await?;
+
     Ok(())
 }
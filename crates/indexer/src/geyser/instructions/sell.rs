@@ -97,6 +97,8 @@ pub(crate) async fn process(
         slot: row.slot,
         write_version: None,
         expiry: None,
+        currency_mint: None,
+        currency_decimals: None,
     })
     .await
     .context("failed to insert listing!")?;
@@ -114,6 +116,8 @@ pub(crate) async fn process(
 }
 
 pub async fn upsert_into_listings_table<'a>(client: &Client, row: Listing<'static>) -> Result<()> {
+    let metadata_address = row.metadata.to_string();
+
     client
         .db()
         .run(move |db| {
@@ -171,5 +175,7 @@ pub async fn upsert_into_listings_table<'a>(client: &Client, row: Listing<'stati
         .await
         .context("Failed to insert listing!")?;
 
+    client.dispatch_nft_document(metadata_address).await?;
+
     Ok(())
 }
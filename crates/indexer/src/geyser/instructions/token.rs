@@ -1,5 +1,10 @@
 use indexer_core::{
-    db::{tables::metadatas, update},
+    db::{
+        insert_into,
+        models::MetadataBurn,
+        tables::{metadata_burns, metadatas},
+        update,
+    },
     prelude::*,
 };
 
@@ -11,21 +16,53 @@ pub(crate) async fn process_burn_instruction(
     accounts: &[Pubkey],
     slot: u64,
 ) -> Result<()> {
-    let mint = accounts[1].to_string();
+    let mint = accounts[1];
     let slot = i64::try_from(slot)?;
 
-    client
+    let burn = MetadataBurn {
+        mint_address: Owned(mint.to_string()),
+        slot,
+    };
+
+    let metadata_addresses: Vec<String> = client
         .db()
         .run(move |db| {
-            update(metadatas::table.filter(metadatas::mint_address.eq(mint)))
-                .set((
-                    metadatas::burned_at.eq(Some(Local::now().naive_utc())),
-                    metadatas::slot.eq(slot),
-                ))
-                .execute(db)
+            db.build_transaction().read_write().run(|| {
+                insert_into(metadata_burns::table)
+                    .values(&burn)
+                    .on_conflict_do_nothing()
+                    .execute(db)?;
+
+                update(metadatas::table.filter(metadatas::mint_address.eq(&*burn.mint_address)))
+                    .set((
+                        metadatas::burned_at.eq(Some(Local::now().naive_utc())),
+                        metadatas::slot.eq(slot),
+                    ))
+                    .returning(metadatas::address)
+                    .get_results(db)
+            })
         })
         .await
         .context("failed to update metadata")?;
 
+    if metadata_addresses.is_empty() {
+        return Ok(());
+    }
+
+    client
+        .search()
+        .delete_nft(mint)
+        .await
+        .context("Failed to dispatch NFT document deletion")?;
+
+    // A burned NFT may have been the mint of a collection
+    for address in metadata_addresses {
+        client
+            .search()
+            .delete_collection(address)
+            .await
+            .context("Failed to dispatch collection document deletion")?;
+    }
+
     Ok(())
 }
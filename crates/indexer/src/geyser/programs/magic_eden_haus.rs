@@ -58,6 +58,9 @@ async fn process_execute_sale(
             created_at: Utc::now().naive_utc(),
             slot: slot.try_into()?,
             write_version: None,
+            // Magic Eden only trades in SOL
+            currency_mint: Some(Owned(pubkeys::SOL.to_string())),
+            currency_decimals: None,
         },
         accts[11].clone(),
         accts[13].clone(),
@@ -130,6 +133,8 @@ async fn process_sale(
             e if e <= 0 => None,
             _ => Some(util::unix_timestamp(params.expiry)?),
         },
+        currency_mint: Some(Owned(pubkeys::SOL.to_string())),
+        currency_decimals: None,
     })
     .await
     .context("failed to insert listing!")?;
@@ -219,7 +224,7 @@ async fn process_cancel_sale(client: &Client, accounts: &[Pubkey], slot: u64) ->
     let trade_state = accts[6].clone();
     let slot = i64::try_from(slot)?;
 
-    client
+    let canceled_listings = client
         .db()
         .run(move |db| {
             update(
@@ -234,11 +239,16 @@ async fn process_cancel_sale(client: &Client, accounts: &[Pubkey], slot: u64) ->
                 listings::canceled_at.eq(Some(canceled_at)),
                 listings::slot.eq(slot),
             ))
-            .execute(db)
+            .returning(listings::metadata)
+            .get_results::<String>(db)
         })
         .await
         .context("failed to cancel ME listing ")?;
 
+    for metadata_address in canceled_listings {
+        client.dispatch_nft_document(metadata_address).await?;
+    }
+
     Ok(())
 }
 
@@ -3,6 +3,7 @@ use std::{sync::Arc, time::Duration};
 use indexer_core::{assets::AssetProxyArgs, clap};
 use indexer_rabbitmq::search_indexer;
 
+use super::{refetch, strategy, Strategy};
 use crate::{db::Pool, prelude::*, reqwest, search_dispatch};
 
 /// Common arguments for internal HTTP indexer usage
@@ -15,6 +16,12 @@ pub struct Args {
     #[clap(flatten)]
     search: search_dispatch::Args,
 
+    #[clap(flatten)]
+    refetch: refetch::Args,
+
+    #[clap(flatten)]
+    fetch: strategy::Args,
+
     /// HTTP request timeout, in seconds
     #[clap(long, env = "HTTP_INDEXER_TIMEOUT")]
     timeout: f64,
@@ -27,6 +34,8 @@ pub struct Client {
     http: reqwest::Client,
     asset_proxy: AssetProxyArgs,
     search: search_dispatch::Client,
+    refetch: refetch::Args,
+    strategies: Vec<Strategy>,
 }
 
 impl Client {
@@ -45,6 +54,8 @@ impl Client {
             asset_proxy,
             timeout,
             search,
+            refetch,
+            fetch,
         } = args;
 
         let timeout = Duration::from_secs_f64(timeout);
@@ -54,6 +65,8 @@ impl Client {
             http: reqwest::Client::new(timeout)?,
             asset_proxy,
             search: search_dispatch::Client::new(conn, search_queue, search).await?,
+            refetch,
+            strategies: Strategy::from_args(fetch, timeout),
         }))
     }
 
@@ -80,4 +93,16 @@ impl Client {
     pub fn proxy_args(&self) -> &AssetProxyArgs {
         &self.asset_proxy
     }
+
+    /// Get the configured fetch strategies, in the order they should be tried
+    #[inline]
+    pub fn fetch_strategies(&self) -> &[Strategy] {
+        &self.strategies
+    }
+
+    /// Get a reference to the metadata JSON re-fetch arguments
+    #[inline]
+    pub fn refetch_args(&self) -> &refetch::Args {
+        &self.refetch
+    }
 }
@@ -1,17 +1,16 @@
 use std::fmt::{self, Debug, Display};
 
 use indexer_core::{
-    assets::{proxy_non_permaweb_url, proxy_url, proxy_url_hinted, AssetIdentifier},
+    assets::{content_hash, proxy_url, verify_ipfs_content, AssetHint, AssetIdentifier},
     db::{
         delete, insert_into,
         models::{
             File as DbFile, MetadataAttributeWrite, MetadataCollection,
-            MetadataJson as DbMetadataJson,
+            MetadataJson as DbMetadataJson, MetadataJsonIssue,
         },
-        select,
         tables::{
-            attributes, files, metadata_collection_keys, metadata_collections, metadata_jsons,
-            metadatas,
+            attributes, files, metadata_collection_keys, metadata_collections, metadata_creators,
+            metadata_json_issues, metadata_jsons, metadatas,
         },
         update, Connection,
     },
@@ -25,7 +24,7 @@ use indexer_core::{
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use super::Client;
+use super::{refetch, validation, Client, Strategy};
 use crate::{prelude::*, search_dispatch::CollectionDocument};
 
 type SlotInfo = (i64, i64);
@@ -53,14 +52,42 @@ struct Property {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
 enum ValueDataType {
-    Number(i64),
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
     String(String),
 }
 
+impl ValueDataType {
+    /// The value recorded in the `value_type` column of `attributes`
+    fn value_type(&self) -> &'static str {
+        match self {
+            Self::Boolean(_) => "boolean",
+            Self::Integer(_) | Self::Float(_) => "number",
+            Self::String(_) => "string",
+        }
+    }
+
+    /// The numeric value of this attribute, including strings containing a
+    /// number, so that they can be filtered by range
+    #[allow(clippy::cast_precision_loss)]
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Boolean(_) => None,
+            Self::Integer(i) => Some(*i as f64),
+            Self::Float(f) => Some(*f),
+            Self::String(s) => s.trim().parse().ok(),
+        }
+        .filter(|f: &f64| f.is_finite())
+    }
+}
+
 impl Display for ValueDataType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::Number(n) => Display::fmt(n, f),
+            Self::Boolean(b) => Display::fmt(b, f),
+            Self::Integer(i) => Display::fmt(i, f),
+            Self::Float(n) => Display::fmt(n, f),
             Self::String(s) => Display::fmt(s, f),
         }
     }
@@ -70,6 +97,8 @@ impl Display for ValueDataType {
 struct Attribute {
     trait_type: Option<String>,
     value: Option<ValueDataType>,
+    display_type: Option<String>,
+    max_value: Option<ValueDataType>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -115,6 +144,54 @@ struct MetadataJsonMinimal {
 struct FetchJsonExtra {
     url: Url,
     raw: Value,
+    content_hash: Vec<u8>,
+    content_source: &'static str,
+    cid_verified: Option<bool>,
+    fetch_strategy: String,
+}
+
+/// Content integrity information from a previously-indexed document
+struct PreviousContent {
+    fingerprint: Vec<u8>,
+    content_hash: Option<Vec<u8>>,
+    content_changed_at: Option<NaiveDateTime>,
+}
+
+impl PreviousContent {
+    /// Determine when the document at a URI was last observed to change,
+    /// given the fingerprint and content hash of a new fetch.  A new URI
+    /// starts with no recorded change.
+    fn changed_at(
+        prev: Option<&Self>,
+        meta_key: &str,
+        extra: &FetchJsonExtra,
+        fingerprint: &[u8],
+    ) -> Option<NaiveDateTime> {
+        let prev = prev.filter(|p| p.fingerprint == fingerprint)?;
+
+        match prev.content_hash {
+            Some(ref hash) if *hash != extra.content_hash => {
+                warn!(
+                    "Content of metadata JSON {:?} for {} changed since it was last fetched",
+                    extra.url.as_str(),
+                    meta_key,
+                );
+
+                Some(Local::now().naive_utc())
+            },
+            _ => prev.content_changed_at,
+        }
+    }
+
+    /// Returns true if a new fetch has the same content as the previously
+    /// indexed document, even if it was fetched from a different URI.
+    /// Attributes are derived only from the content, so they need not be
+    /// replaced for such a document.
+    fn unchanged(prev: Option<&Self>, extra: &FetchJsonExtra) -> bool {
+        prev.map_or(false, |p| {
+            p.content_hash.as_ref() == Some(&extra.content_hash)
+        })
+    }
 }
 
 enum MetadataJsonResult {
@@ -131,24 +208,37 @@ struct MetadataJsonParams<'a> {
     extra: FetchJsonExtra,
     fingerprint: Vec<u8>,
     slot_info: SlotInfo,
+    previous: Option<PreviousContent>,
 }
 
 async fn fetch_json(
     client: &Client,
     meta_key: Pubkey,
-    url: Result<Url>,
+    strategy: &Strategy,
+    url: Url,
+    hint: Option<AssetHint>,
+    id: &AssetIdentifier<'_>,
 ) -> Result<(MetadataJsonResult, FetchJsonExtra)> {
     let start_time = Local::now();
-    let url = url.context("Failed to create asset URL")?;
 
-    let bytes = client
+    let res = client
         .http()
         .run(|h| {
             let url = url.clone();
-            async move { h.get(url).send().await?.bytes().await }
+            let timeout = strategy.timeout();
+            async move {
+                h.get(url)
+                    .timeout(timeout)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .bytes()
+                    .await
+            }
         })
-        .await
-        .context("Failed to download metadata JSON")?;
+        .await;
+    strategy.record(&res);
+    let bytes = res.context("Failed to download metadata JSON")?;
 
     let end_time = Local::now();
 
@@ -162,13 +252,41 @@ async fn fetch_json(
     let raw =
         serde_json::from_slice(&bytes).context("Metadata JSON response was not valid JSON")?;
 
+    let (content_source, cid_verified) = match hint {
+        Some(AssetHint::Ipfs) => (
+            "ipfs",
+            id.ipfs
+                .as_ref()
+                .and_then(|(cid, path)| verify_ipfs_content(cid, path, &bytes)),
+        ),
+        Some(AssetHint::Arweave) => ("arweave", None),
+        None => ("http", None),
+    };
+
+    if cid_verified == Some(false) {
+        warn!(
+            "Metadata JSON {:?} for {} does not match its IPFS CID",
+            url.as_str(),
+            meta_key
+        );
+    }
+
+    let extra = FetchJsonExtra {
+        url,
+        raw,
+        content_hash: content_hash(&bytes),
+        content_source,
+        cid_verified,
+        fetch_strategy: strategy.name().to_owned(),
+    };
+
     let full_err;
     match serde_json::from_slice(&bytes) {
-        Ok(f) => return Ok((MetadataJsonResult::Full(f), FetchJsonExtra { url, raw })),
+        Ok(f) => return Ok((MetadataJsonResult::Full(f), extra)),
         Err(e) => {
             trace!(
                 "Failed to parse full metadata JSON for {:?}: {:?}",
-                url.as_str(),
+                extra.url.as_str(),
                 e
             );
             full_err = e;
@@ -177,15 +295,12 @@ async fn fetch_json(
 
     match serde_json::from_slice(&bytes) {
         Ok(value) => {
-            return Ok((
-                MetadataJsonResult::Minimal { value, full_err },
-                FetchJsonExtra { url, raw },
-            ));
+            return Ok((MetadataJsonResult::Minimal { value, full_err }, extra));
         },
         Err(e) => {
             trace!(
                 "Failed to parse minimal metadata JSON for {:?}: {:?}",
-                url.as_str(),
+                extra.url.as_str(),
                 e
             );
         },
@@ -193,7 +308,7 @@ async fn fetch_json(
 
     Err(anyhow!(
         "Failed to parse JSON response from {:?}",
-        url.as_str()
+        extra.url.as_str()
     ))
 }
 
@@ -202,73 +317,72 @@ async fn try_locate_json(
     id: &AssetIdentifier<'_>,
     meta_key: Pubkey,
 ) -> Result<Option<(MetadataJsonResult, Vec<u8>, FetchJsonExtra)>> {
-    // Set to true for fallback
-    const TRY_LAST_RESORT: bool = false;
-    // Set to true to fetch links with no fingerprint
-    const FETCH_NON_PERMAWEB: bool = true;
-
-    let mut resp = Ok(None);
-
-    for (fingerprint, hint) in id.fingerprints_hinted() {
-        let url = if let Some(hint) = hint {
-            proxy_url_hinted(client.proxy_args(), id, hint, None)
-                .map(|u| u.unwrap_or_else(|| unreachable!()))
-        } else if FETCH_NON_PERMAWEB {
-            Ok(proxy_non_permaweb_url(client.proxy_args(), id.url.clone())?)
-        } else {
-            continue;
-        };
-        let url_str = url.as_ref().map_or("???", Url::as_str).to_owned();
-
-        match fetch_json(client, meta_key, url).await {
-            Ok((json, extra)) => {
-                trace!("Using fetch from {:?} for metadata {}", url_str, meta_key);
-                resp = Ok(Some((json, fingerprint, extra)));
-                break;
-            },
-            Err(e) => {
-                warn!(
-                    "Metadata fetch {:?} for {} failed: {:?}",
-                    url_str, meta_key, e
+    let mut failed = false;
+
+    for strategy in client.fetch_strategies() {
+        for (fingerprint, hint) in id.fingerprints_hinted() {
+            let url = match strategy.url(client, id, hint) {
+                Ok(Some(u)) => u,
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!(
+                        "Failed to create {} URL for metadata {}: {:?}",
+                        strategy, meta_key, e
+                    );
+                    failed = true;
+                    continue;
+                },
+            };
+
+            if !strategy.available() {
+                trace!(
+                    "Skipping fetch strategy {} for metadata {} (circuit open)",
+                    strategy,
+                    meta_key
                 );
+                failed = true;
+                continue;
+            }
 
-                resp = Err(());
-            },
+            let url_str = url.as_str().to_owned();
+
+            match fetch_json(client, meta_key, strategy, url, hint, id).await {
+                Ok((json, extra)) => {
+                    trace!(
+                        "Using fetch from {:?} ({}) for metadata {}",
+                        url_str,
+                        strategy,
+                        meta_key
+                    );
+
+                    return Ok(Some((json, fingerprint.into_owned(), extra)));
+                },
+                Err(e) => {
+                    warn!(
+                        "Metadata fetch {:?} ({}) for {} failed: {:?}",
+                        url_str, strategy, meta_key, e
+                    );
+                    failed = true;
+                },
+            }
         }
     }
 
-    Ok(match resp {
-        Ok(Some((res, fingerprint, extra))) => Some((res, fingerprint.into_owned(), extra)),
-        Ok(None) => {
-            trace!(
-                "Not fetching unparseable url {:?} for {}",
-                id.url.as_str(),
-                meta_key
-            );
+    if failed {
+        bail!(
+            "All fetch strategies for metadata {:?} for {} failed",
+            id.url.as_str(),
+            meta_key
+        );
+    }
 
-            None
-        },
-        Err(()) if TRY_LAST_RESORT => {
-            let (json, extra) = fetch_json(client, meta_key, Ok(id.url.clone()))
-                .await
-                .with_context(|| {
-                    format!(
-                        "Last-resort metadata fetch {:?} for {} failed",
-                        id.url.as_str(),
-                        meta_key,
-                    )
-                })?;
+    trace!(
+        "Not fetching unparseable url {:?} for {}",
+        id.url.as_str(),
+        meta_key
+    );
 
-            Some((json, vec![], extra))
-        },
-        Err(()) => {
-            bail!(
-                "Cached metadata fetch {:?} for {} failed (not trying last-resort)",
-                id.url.as_str(),
-                meta_key
-            )
-        },
-    })
+    Ok(None)
 }
 
 async fn process_full(
@@ -277,9 +391,10 @@ async fn process_full(
     MetadataJsonParams {
         client,
         addr,
-        extra: FetchJsonExtra { url, raw },
+        extra,
         fingerprint,
         slot_info,
+        previous,
     }: MetadataJsonParams<'_>,
 ) -> Result<()> {
     dispatch_metadata_document(client, false, addr.clone())
@@ -305,6 +420,17 @@ async fn process_full(
     );
 
     let (slot, write_version) = slot_info;
+    let content_changed_at =
+        PreviousContent::changed_at(previous.as_ref(), &addr, &extra, &fingerprint);
+    let content_unchanged = PreviousContent::unchanged(previous.as_ref(), &extra);
+    let FetchJsonExtra {
+        url,
+        raw,
+        content_hash,
+        content_source,
+        cid_verified,
+        fetch_strategy,
+    } = extra;
     let row = DbMetadataJson {
         metadata_address: Owned(addr.clone()),
         fingerprint: Owned(fingerprint),
@@ -320,31 +446,43 @@ async fn process_full(
         slot,
         write_version,
         name: Some(Owned(name)),
+        content_hash: Some(Owned(content_hash)),
+        content_source: Some(Borrowed(content_source)),
+        cid_verified,
+        content_changed_at,
+        fetch_strategy: Some(Owned(fetch_strategy)),
     };
 
     client
         .db()
         .run(move |db| {
-            insert_into(metadata_jsons::table)
-                .values(&row)
-                .on_conflict(metadata_jsons::metadata_address)
-                .do_update()
-                .set(&row)
-                .execute(db)
-                .context("Failed to insert metadata")?;
-
-            // TODO: if the row updates the following functions do not clear the
-            //       previous rows from the old metadata JSON:
-
-            process_files(db, &addr, files, slot_info)?;
-            process_attributes(
-                db,
-                &addr,
-                first_verified_creator.as_deref(),
-                json.attributes,
-                slot_info,
-            )?;
-            process_collection(db, &addr, json.collection, slot_info)
+            db.build_transaction().read_write().run(|| {
+                if !upsert_json(db, &row)? {
+                    return Ok(());
+                }
+
+                process_issues(db, &addr, &row.raw_content, None, slot_info)?;
+                process_files(db, &addr, files, slot_info)?;
+
+                if content_unchanged {
+                    update_attribute_creators(
+                        db,
+                        &addr,
+                        first_verified_creator.as_deref(),
+                        slot_info,
+                    )?;
+                } else {
+                    process_attributes(
+                        db,
+                        &addr,
+                        first_verified_creator.as_deref(),
+                        json.attributes,
+                        slot_info,
+                    )?;
+                }
+
+                process_collection(db, &addr, json.collection, slot_info)
+            })
         })
         .await
 }
@@ -355,9 +493,10 @@ async fn process_minimal(
     MetadataJsonParams {
         client,
         addr,
-        extra: FetchJsonExtra { url, raw },
+        extra,
         fingerprint,
         slot_info,
+        previous,
     }: MetadataJsonParams<'_>,
 ) -> Result<()> {
     fn to_opt_string(v: &Value) -> Option<Cow<'static, str>> {
@@ -385,6 +524,18 @@ async fn process_minimal(
     } = json;
 
     let (slot, write_version) = slot_info;
+    let parse_err = full_err.to_string();
+    let content_changed_at =
+        PreviousContent::changed_at(previous.as_ref(), &addr, &extra, &fingerprint);
+    let content_unchanged = PreviousContent::unchanged(previous.as_ref(), &extra);
+    let FetchJsonExtra {
+        url,
+        raw,
+        content_hash,
+        content_source,
+        cid_verified,
+        fetch_strategy,
+    } = extra;
     let row = DbMetadataJson {
         metadata_address: Owned(addr.clone()),
         fingerprint: Owned(fingerprint),
@@ -395,25 +546,119 @@ async fn process_minimal(
         external_url: to_opt_string(&external_url),
         category: to_opt_string(&category),
         raw_content: Owned(raw),
-        model: Some(Owned(format!("minimal ({})", full_err))),
+        model: Some(Owned(format!("minimal ({})", parse_err))),
         fetch_uri: Owned(url.to_string()),
         slot,
         write_version,
         name: to_opt_string(&name),
+        content_hash: Some(Owned(content_hash)),
+        content_source: Some(Borrowed(content_source)),
+        cid_verified,
+        content_changed_at,
+        fetch_strategy: Some(Owned(fetch_strategy)),
     };
 
     client
         .db()
         .run(move |db| {
-            insert_into(metadata_jsons::table)
-                .values(&row)
-                .on_conflict(metadata_jsons::metadata_address)
-                .do_update()
-                .set(&row)
-                .execute(db)
+            db.build_transaction().read_write().run(|| {
+                if !upsert_json(db, &row)? {
+                    return Ok(());
+                }
+
+                process_issues(db, &addr, &row.raw_content, Some(&parse_err), slot_info)?;
+
+                // The minimal model carries no files, attributes or
+                // collection, so clear any left by a previous document
+                process_files(db, &addr, None, slot_info)?;
+
+                if !content_unchanged {
+                    process_attributes(db, &addr, None, None, slot_info)?;
+                }
+
+                process_collection(db, &addr, None, slot_info)
+            })
         })
         .await
-        .context("Failed to insert minimal metadata")?;
+        .context("Failed to insert minimal metadata")
+}
+
+/// Upsert a metadata JSON row, returning false without modifying anything if a
+/// document at a later slot and write version has already been indexed.  The
+/// existing row is locked until the end of the current transaction, so
+/// callers may safely replace rows derived from the previous document.
+fn upsert_json(db: &Connection, row: &DbMetadataJson) -> Result<bool> {
+    let existing: Option<SlotInfo> = metadata_jsons::table
+        .filter(metadata_jsons::metadata_address.eq(&*row.metadata_address))
+        .select((metadata_jsons::slot, metadata_jsons::write_version))
+        .for_update()
+        .first(db)
+        .optional()
+        .context("Failed to lock existing metadata JSON")?;
+
+    if let Some(existing) = existing {
+        if existing > (row.slot, row.write_version) {
+            trace!(
+                "Skipping stale metadata JSON for {} (seen at slot_info={:?})",
+                row.metadata_address,
+                existing
+            );
+
+            return Ok(false);
+        }
+    }
+
+    insert_into(metadata_jsons::table)
+        .values(row)
+        .on_conflict(metadata_jsons::metadata_address)
+        .do_update()
+        .set(row)
+        .execute(db)
+        .context("Failed to insert metadata")?;
+
+    Ok(true)
+}
+
+fn process_issues(
+    db: &Connection,
+    addr: &str,
+    raw: &Value,
+    parse_err: Option<&str>,
+    (slot, write_version): SlotInfo,
+) -> Result<()> {
+    let creators: Vec<(String, i32)> = metadata_creators::table
+        .filter(metadata_creators::metadata_address.eq(addr))
+        .select((metadata_creators::creator_address, metadata_creators::share))
+        .load(db)
+        .context("Failed to load on-chain creators")?;
+
+    delete(metadata_json_issues::table.filter(metadata_json_issues::metadata_address.eq(addr)))
+        .execute(db)
+        .context("Failed to delete stale metadata JSON issues")?;
+
+    let rows: Vec<_> = validation::validate(raw, parse_err, &creators)
+        .into_iter()
+        .map(
+            |validation::Issue {
+                 code,
+                 path,
+                 message,
+             }| MetadataJsonIssue {
+                id: None,
+                metadata_address: Borrowed(addr),
+                code: Borrowed(code.as_str()),
+                path: Owned(path),
+                message: Owned(message),
+                slot,
+                write_version,
+            },
+        )
+        .collect();
+
+    insert_into(metadata_json_issues::table)
+        .values(&rows)
+        .execute(db)
+        .context("Failed to insert metadata JSON issues")?;
 
     Ok(())
 }
@@ -422,31 +667,42 @@ fn process_files(
     db: &Connection,
     addr: &str,
     files: Option<Vec<File>>,
-    slot_info: SlotInfo,
+    (slot, write_version): SlotInfo,
 ) -> Result<()> {
-    for File { uri, ty } in files.unwrap_or_else(Vec::new) {
-        let (uri, ty) = if let Some(v) = uri.zip(ty) {
-            v
-        } else {
-            trace!("Skipping malformed file in JSON");
-            continue;
-        };
-
-        let (slot, write_version) = slot_info;
-        let row = DbFile {
-            metadata_address: Borrowed(addr),
-            uri: Owned(uri),
-            file_type: Owned(ty),
-            slot,
-            write_version,
-        };
+    // Derivatives generated by the asset worker are managed separately
+    delete(
+        files::table
+            .filter(files::metadata_address.eq(addr))
+            .filter(files::source_uri.is_null()),
+    )
+    .execute(db)
+    .context("Failed to delete stale files")?;
+
+    let rows: Vec<_> = files
+        .unwrap_or_else(Vec::new)
+        .into_iter()
+        .filter_map(|File { uri, ty }| {
+            let (uri, ty) = uri.zip(ty).or_else(|| {
+                trace!("Skipping malformed file in JSON");
+                None
+            })?;
+
+            Some(DbFile {
+                metadata_address: Borrowed(addr),
+                uri: Owned(uri),
+                file_type: Owned(ty),
+                slot,
+                write_version,
+                source_uri: None,
+                width: None,
+            })
+        })
+        .collect();
 
-        insert_into(files::table)
-            .values(&row)
-            .on_conflict_do_nothing()
-            .execute(db)
-            .context("Failed to insert file!")?;
-    }
+    insert_into(files::table)
+        .values(&rows)
+        .execute(db)
+        .context("Failed to insert files!")?;
 
     Ok(())
 }
@@ -457,38 +713,43 @@ fn process_attributes(
     addr: &str,
     first_verified_creator: Option<&str>,
     attributes: Option<Vec<Attribute>>,
-    slot_info: SlotInfo,
+    (slot, write_version): SlotInfo,
 ) -> Result<()> {
-    let (slot, write_version) = slot_info;
-    let attributes_exists = select(exists(
-        attributes::table.filter(
-            attributes::metadata_address
-                .eq(addr)
-                .and(attributes::slot.lt(slot)),
-        ),
-    ))
-    .get_result::<bool>(db);
-
-    if let Ok(true) = attributes_exists {
-        delete(attributes::table.filter(attributes::metadata_address.eq(addr))).execute(db)?;
-    }
-
-    for Attribute { trait_type, value } in attributes.unwrap_or_else(Vec::new) {
-        let row = MetadataAttributeWrite {
-            metadata_address: Borrowed(addr),
-            trait_type: trait_type.map(Owned),
-            value: value.as_ref().map(|v| Owned(v.to_string())),
-            first_verified_creator: first_verified_creator.map(Borrowed),
-            slot,
-            write_version,
-        };
+    delete(attributes::table.filter(attributes::metadata_address.eq(addr)))
+        .execute(db)
+        .context("Failed to delete stale attributes")?;
+
+    let rows: Vec<_> = attributes
+        .unwrap_or_else(Vec::new)
+        .into_iter()
+        .map(
+            |Attribute {
+                 trait_type,
+                 value,
+                 display_type,
+                 max_value,
+             }| MetadataAttributeWrite {
+                metadata_address: Borrowed(addr),
+                trait_type: trait_type.map(Owned),
+                value: value.as_ref().map(|v| Owned(v.to_string())),
+                first_verified_creator: first_verified_creator.map(Borrowed),
+                slot,
+                write_version,
+                value_type: value.as_ref().map(|v| Borrowed(v.value_type())),
+                display_type: display_type.map(Owned),
+                value_numeric: value.as_ref().and_then(ValueDataType::as_f64),
+                max_value: max_value.as_ref().and_then(ValueDataType::as_f64),
+            },
+        )
+        .collect();
 
-        insert_into(attributes::table)
-            .values(&row)
-            .on_conflict_do_nothing()
-            .execute(db)
-            .context("Failed to insert attribute!")?;
-    }
+    // Documents may repeat an attribute, which would otherwise violate the
+    // unique constraint and abort the whole replacement
+    insert_into(attributes::table)
+        .values(&rows)
+        .on_conflict_do_nothing()
+        .execute(db)
+        .context("Failed to insert attributes!")?;
 
     Ok(())
 }
@@ -498,10 +759,13 @@ fn process_collection(
     db: &Connection,
     addr: &str,
     collection: Option<Collection>,
-    slot_info: SlotInfo,
+    (slot, write_version): SlotInfo,
 ) -> Result<()> {
+    delete(metadata_collections::table.filter(metadata_collections::metadata_address.eq(addr)))
+        .execute(db)
+        .context("Failed to delete stale collection")?;
+
     if let Some(Collection { name, family }) = collection {
-        let (slot, write_version) = slot_info;
         let row = MetadataCollection {
             metadata_address: Borrowed(addr),
             name: name.map(Owned),
@@ -512,7 +776,6 @@ fn process_collection(
 
         insert_into(metadata_collections::table)
             .values(&row)
-            .on_conflict_do_nothing()
             .execute(db)
             .context("Failed to insert collection!")?;
     }
@@ -520,31 +783,43 @@ fn process_collection(
     Ok(())
 }
 
+/// Set the first verified creator of attributes indexed before the given slot
+/// and write version, leaving attributes which already match untouched
+fn update_attribute_creators(
+    db: &Connection,
+    addr: &str,
+    first_verified_creator: Option<&str>,
+    (slot, write_version): SlotInfo,
+) -> Result<()> {
+    update(
+        attributes::table
+            .filter(attributes::metadata_address.eq(addr))
+            .filter(attributes::first_verified_creator.is_distinct_from(first_verified_creator))
+            .filter(
+                attributes::slot.lt(slot).or(attributes::slot
+                    .eq(slot)
+                    .and(attributes::write_version.lt(write_version))),
+            ),
+    )
+    .set(attributes::first_verified_creator.eq(first_verified_creator))
+    .execute(db)
+    .context("Failed to update attributes")?;
+
+    Ok(())
+}
+
 async fn reprocess_attributes(
     client: &Client,
     addr: String,
     first_verified_creator: Option<String>,
-    (slot, write_version): SlotInfo,
+    slot_info: SlotInfo,
 ) -> Result<()> {
     client
         .db()
         .run(move |db| {
-            update(
-                attributes::table
-                    .filter(attributes::metadata_address.eq(addr))
-                    .filter(
-                        attributes::slot.lt(slot).or(attributes::slot
-                            .eq(slot)
-                            .and(attributes::write_version.lt(write_version))),
-                    ),
-            )
-            .set(attributes::first_verified_creator.eq(first_verified_creator))
-            .execute(db)
+            update_attribute_creators(db, &addr, first_verified_creator.as_deref(), slot_info)
         })
         .await
-        .context("Failed to update attributes")?;
-
-    Ok(())
 }
 
 pub async fn process<'a>(
@@ -559,17 +834,25 @@ pub async fn process<'a>(
         i64::try_from(write_version).context("Write version was too big to store")?,
     );
 
+    let addr = bs58::encode(meta_key).into_string();
     let url = match Url::parse(&uri_str) {
         Ok(u) => u,
         Err(e) => {
             // Don't return an error because this happens A Lot.
             trace!("Couldn't parse metadata URL: {:?}", e);
+
+            // An unparseable URI can't be re-fetched, so drop any schedule
+            // rather than leaving it pending
+            refetch::reschedule(client, addr, false, false, false).await?;
+
             return Ok(());
         },
     };
     let id = AssetIdentifier::new(&url);
+    // IPFS documents are checked against their CID, but Arweave gateways serve
+    // unverified content, so re-fetch those too in order to detect changes
+    let refetchable = id.ipfs.is_none();
 
-    let addr = bs58::encode(meta_key).into_string();
     let existing_row = client
         .db()
         .run({
@@ -580,43 +863,100 @@ pub async fn process<'a>(
                     .select((
                         metadata_jsons::fingerprint,
                         (metadata_jsons::slot, metadata_jsons::write_version),
+                        metadata_jsons::content_hash,
+                        metadata_jsons::content_changed_at,
                     ))
-                    .first::<(Cow<[u8]>, SlotInfo)>(db)
+                    .first::<(Vec<u8>, SlotInfo, Option<Vec<u8>>, Option<NaiveDateTime>)>(db)
                     .optional()
             }
         })
         .await
         .context("Failed to check for already-indexed metadata JSON")?;
+    let pending = refetch::is_pending(client, addr.clone()).await?;
 
     let first_verified_creator =
         first_verified_creator.map(|address| bs58::encode(address).into_string());
 
-    if let Some((fingerprint, existing_slot_info)) = existing_row {
-        if existing_slot_info > slot_info || id.fingerprints_hinted().any(|(f, _)| fingerprint == f)
+    let previous =
+        if let Some((fingerprint, existing_slot_info, content_hash, content_changed_at)) =
+            existing_row
         {
-            trace!(
-                "Skipping already-indexed metadata JSON for {} (seen at slot_info={:?})",
-                meta_key,
-                existing_slot_info
-            );
+            if existing_slot_info > slot_info
+                || (!pending && id.fingerprints_hinted().any(|(f, _)| *fingerprint == *f))
+            {
+                trace!(
+                    "Skipping already-indexed metadata JSON for {} (seen at slot_info={:?})",
+                    meta_key,
+                    existing_slot_info
+                );
 
-            reprocess_attributes(client, addr.clone(), first_verified_creator, slot_info).await?;
+                reprocess_attributes(client, addr.clone(), first_verified_creator, slot_info)
+                    .await?;
+
+                if pending {
+                    refetch::clear_pending(client, addr.clone()).await?;
+                } else if refetchable {
+                    refetch::enroll(client, addr.clone()).await?;
+                }
+
+                dispatch_metadata_document(client, true, addr).await?;
 
+                return Ok(());
+            }
+
+            Some(PreviousContent {
+                fingerprint,
+                content_hash,
+                content_changed_at,
+            })
+        } else {
+            None
+        };
+
+    trace!("{:?} -> {:?}", url.as_str(), id);
+
+    let located = try_locate_json(client, &id, meta_key).await;
+
+    if refetchable || pending {
+        let (fetched, changed) = match located {
+            Ok(Some((_, ref fingerprint, ref extra))) => (
+                true,
+                previous.as_ref().map_or(false, |p| {
+                    p.fingerprint == *fingerprint
+                        && p.content_hash
+                            .as_ref()
+                            .map_or(false, |h| *h != extra.content_hash)
+                }),
+            ),
+            _ => (false, false),
+        };
+
+        refetch::reschedule(client, addr.clone(), refetchable, fetched, changed).await?;
+    }
+
+    if let Some((json, fingerprint, extra)) = located? {
+        // A re-fetch returning the document already indexed has nothing new
+        // to write, so leave its files and attributes untouched
+        let unchanged = previous.as_ref().map_or(false, |p| {
+            p.fingerprint == fingerprint && p.content_hash.as_ref() == Some(&extra.content_hash)
+        });
+
+        if unchanged {
+            trace!("Metadata JSON for {} is unchanged, skipping", meta_key);
+
+            reprocess_attributes(client, addr.clone(), first_verified_creator, slot_info).await?;
             dispatch_metadata_document(client, true, addr).await?;
 
             return Ok(());
         }
-    }
-
-    trace!("{:?} -> {:?}", url.as_str(), id);
 
-    if let Some((json, fingerprint, extra)) = try_locate_json(client, &id, meta_key).await? {
         let params = MetadataJsonParams {
             client,
             addr,
             extra,
             fingerprint,
             slot_info,
+            previous,
         };
 
         match json {
@@ -637,7 +977,7 @@ async fn dispatch_metadata_document(
     is_for_backfill: bool,
     addr: String,
 ) -> Result<()> {
-    if let Ok(Some(collection_address)) = client
+    if let Ok((mint_address, collection_address)) = client
         .db()
         .run({
             let addr = addr.clone();
@@ -648,17 +988,31 @@ async fn dispatch_metadata_document(
                             .on(metadatas::address.eq(metadata_collection_keys::metadata_address)),
                     )
                     .filter(metadatas::address.eq(&addr))
-                    .select(metadata_collection_keys::collection_address.nullable())
-                    .first::<Option<String>>(db)
+                    .select((
+                        metadatas::mint_address,
+                        metadata_collection_keys::collection_address.nullable(),
+                    ))
+                    .first::<(String, Option<String>)>(db)
                     .context("failed to load mint and name for search doc")
             }
         })
         .await
         .map_err(|e| warn!("Failed to get search document data for metadata: {:?}", e))
     {
-        upsert_collection_metadata(client, collection_address, is_for_backfill)
+        client
+            .search()
+            .upsert_nft(
+                is_for_backfill,
+                mint_address.parse().context("Invalid mint address")?,
+            )
             .await
-            .context("failed to index collection metadata")?;
+            .context("failed to dispatch NFT search document")?;
+
+        if let Some(collection_address) = collection_address {
+            upsert_collection_metadata(client, collection_address, is_for_backfill)
+                .await
+                .context("failed to index collection metadata")?;
+        }
     }
 
     Ok(())
@@ -2,12 +2,19 @@
 
 pub(self) mod client;
 mod metadata_json;
+pub mod refetch;
 mod store_config;
+pub mod strategy;
+mod validation;
 
-use std::fmt;
+use std::{fmt, sync::Arc};
 
 pub use client::{Args as ClientArgs, Client};
-use indexer_rabbitmq::http_indexer::{Entity, MetadataJson, StoreConfig};
+use indexer_rabbitmq::{
+    http_indexer::{Entity, MetadataJson, Producer, QueueType, StoreConfig},
+    lapin::Connection,
+};
+pub use strategy::Strategy;
 
 use crate::prelude::*;
 
@@ -31,9 +38,21 @@ impl fmt::Display for MessageId {
 
 /// Define processing logic for an incoming entity type
 #[async_trait::async_trait]
-pub trait Process: Entity {
+pub trait Process: Entity + Sync {
     /// Process and consume an incoming entity
     async fn process(self, client: &Client) -> MessageResult<MessageId>;
+
+    /// Start any background tasks run alongside the consumer for this entity
+    ///
+    /// # Errors
+    /// This function fails if a background task cannot be started.
+    async fn start_tasks(
+        _client: &Arc<Client>,
+        _conn: &Connection,
+        _queue_type: &QueueType<Self>,
+    ) -> Result<()> {
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
@@ -50,6 +69,25 @@ impl Process for MetadataJson {
             .await
             .map_err(|e| MessageError::new(e, MessageId::MetadataJson(meta_address)))
     }
+
+    async fn start_tasks(
+        client: &Arc<Client>,
+        conn: &Connection,
+        queue_type: &QueueType<Self>,
+    ) -> Result<()> {
+        if !client.refetch_args().scheduler_enabled() {
+            warn!("Metadata JSON re-fetch scheduler disabled");
+            return Ok(());
+        }
+
+        let producer = Producer::new(conn, queue_type.clone())
+            .await
+            .context("Couldn't create AMQP metadata JSON re-fetch producer")?;
+
+        tokio::spawn(refetch::run_scheduler(Arc::clone(client), producer));
+
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
@@ -0,0 +1,325 @@
+//! Periodic re-fetching of metadata JSON documents which may change.
+//! Documents whose URIs contain no IPFS CID, including those on Arweave, are
+//! re-fetched on a schedule which halves its interval each time the document
+//! is observed to change and doubles it each time it is not.
+
+use std::{str::FromStr, sync::Arc};
+
+use indexer_core::{
+    clap,
+    db::{
+        delete, insert_into,
+        models::MetadataJsonRefetch,
+        tables::{metadata_creators, metadata_json_refetches, metadata_jsons, metadatas},
+        update,
+    },
+    hash::HashMap,
+};
+use indexer_rabbitmq::http_indexer;
+
+use super::Client;
+use crate::prelude::*;
+
+/// Arguments for scheduling metadata JSON re-fetches
+#[derive(Debug, Clone, Copy, clap::Args)]
+pub struct Args {
+    /// Disable the metadata JSON re-fetch scheduler.  Documents are still
+    /// re-fetched if dispatched by another scheduler.
+    #[clap(long, env)]
+    no_refetch: bool,
+
+    /// Minimum time between re-fetches of a mutable metadata JSON document, in
+    /// seconds
+    #[clap(long, env, default_value_t = 3600)]
+    refetch_min_interval: i32,
+
+    /// Maximum time between re-fetches of a mutable metadata JSON document, in
+    /// seconds
+    #[clap(long, env, default_value_t = 7 * 24 * 3600)]
+    refetch_max_interval: i32,
+
+    /// Maximum number of re-fetches to dispatch at once
+    #[clap(long, env, default_value_t = 256)]
+    refetch_batch_size: i64,
+
+    /// Time to wait before checking for due re-fetches once none remain, in
+    /// seconds
+    #[clap(long, env, default_value_t = 60)]
+    refetch_poll_interval: u64,
+}
+
+impl Args {
+    /// Returns true if this process should run the re-fetch scheduler
+    #[must_use]
+    pub fn scheduler_enabled(&self) -> bool {
+        !self.no_refetch
+    }
+
+    fn next_interval(&self, interval: i32, changed: bool) -> i32 {
+        let interval = if changed {
+            interval / 2
+        } else {
+            interval.saturating_mul(2)
+        };
+
+        interval.clamp(self.refetch_min_interval, self.refetch_max_interval)
+    }
+}
+
+/// Returns true if the document for the given metadata has been dispatched by
+/// the scheduler and should be fetched even if its URI is unchanged
+pub(super) async fn is_pending(client: &Client, addr: String) -> Result<bool> {
+    client
+        .db()
+        .run(move |db| {
+            metadata_json_refetches::table
+                .filter(metadata_json_refetches::metadata_address.eq(addr))
+                .select(metadata_json_refetches::pending)
+                .first(db)
+                .optional()
+        })
+        .await
+        .context("Failed to check for pending metadata JSON re-fetch")
+        .map(|p| p.unwrap_or(false))
+}
+
+/// Ensure a schedule exists for a re-fetchable document indexed before
+/// re-fetching was introduced
+pub(super) async fn enroll(client: &Client, addr: String) -> Result<()> {
+    let args = *client.refetch_args();
+    let row = MetadataJsonRefetch {
+        metadata_address: Owned(addr),
+        interval_seconds: args.refetch_min_interval,
+        next_fetch_at: Local::now().naive_utc()
+            + chrono::Duration::seconds(args.refetch_min_interval.into()),
+        pending: false,
+        last_fetched_at: None,
+        last_changed_at: None,
+    };
+
+    client
+        .db()
+        .run(move |db| {
+            insert_into(metadata_json_refetches::table)
+                .values(&row)
+                .on_conflict_do_nothing()
+                .execute(db)
+        })
+        .await
+        .context("Failed to enroll metadata JSON for re-fetching")?;
+
+    Ok(())
+}
+
+/// Mark a dispatched re-fetch as handled without fetching the document, e.g.
+/// because a later version of it has already been indexed
+pub(super) async fn clear_pending(client: &Client, addr: String) -> Result<()> {
+    client
+        .db()
+        .run(move |db| {
+            update(
+                metadata_json_refetches::table
+                    .filter(metadata_json_refetches::metadata_address.eq(addr)),
+            )
+            .set(metadata_json_refetches::pending.eq(false))
+            .execute(db)
+        })
+        .await
+        .context("Failed to clear pending metadata JSON re-fetch")?;
+
+    Ok(())
+}
+
+/// Update the re-fetch schedule for a document after an attempt to fetch it.
+/// Schedules for documents verified by their IPFS CID are removed.
+pub(super) async fn reschedule(
+    client: &Client,
+    addr: String,
+    refetchable: bool,
+    fetched: bool,
+    changed: bool,
+) -> Result<()> {
+    let args = *client.refetch_args();
+
+    client
+        .db()
+        .run(move |db| {
+            if !refetchable {
+                return delete(
+                    metadata_json_refetches::table
+                        .filter(metadata_json_refetches::metadata_address.eq(addr)),
+                )
+                .execute(db)
+                .map(|_| ());
+            }
+
+            let existing: Option<(i32, Option<NaiveDateTime>, Option<NaiveDateTime>)> =
+                metadata_json_refetches::table
+                    .filter(metadata_json_refetches::metadata_address.eq(&addr))
+                    .select((
+                        metadata_json_refetches::interval_seconds,
+                        metadata_json_refetches::last_fetched_at,
+                        metadata_json_refetches::last_changed_at,
+                    ))
+                    .first(db)
+                    .optional()?;
+
+            let now = Local::now().naive_utc();
+            let (interval, last_fetched_at, last_changed_at) = existing.map_or(
+                (args.refetch_min_interval, None, None),
+                |(interval, fetched_at, changed_at)| {
+                    (
+                        args.next_interval(interval, changed),
+                        fetched_at,
+                        changed_at,
+                    )
+                },
+            );
+
+            let row = MetadataJsonRefetch {
+                metadata_address: Owned(addr),
+                interval_seconds: interval,
+                next_fetch_at: now + chrono::Duration::seconds(interval.into()),
+                pending: false,
+                last_fetched_at: if fetched { Some(now) } else { last_fetched_at },
+                last_changed_at: if changed { Some(now) } else { last_changed_at },
+            };
+
+            insert_into(metadata_json_refetches::table)
+                .values(&row)
+                .on_conflict(metadata_json_refetches::metadata_address)
+                .do_update()
+                .set(&row)
+                .execute(db)
+                .map(|_| ())
+        })
+        .await
+        .context("Failed to update metadata JSON re-fetch schedule")
+}
+
+/// Claim a batch of due re-fetches, marking them pending and pushing their
+/// next fetch time back so lost messages are eventually retried
+async fn claim_batch(client: &Client) -> Result<Vec<http_indexer::MetadataJson>> {
+    let args = *client.refetch_args();
+
+    let (rows, creators) = client
+        .db()
+        .run(move |db| {
+            db.build_transaction().read_write().run(|| {
+                let now = Local::now().naive_utc();
+
+                let addrs: Vec<String> = metadata_json_refetches::table
+                    .filter(metadata_json_refetches::next_fetch_at.le(now))
+                    .select(metadata_json_refetches::metadata_address)
+                    .order_by(metadata_json_refetches::next_fetch_at)
+                    .limit(args.refetch_batch_size)
+                    .for_update()
+                    .skip_locked()
+                    .load(db)
+                    .context("Failed to load due re-fetches")?;
+
+                update(
+                    metadata_json_refetches::table
+                        .filter(metadata_json_refetches::metadata_address.eq(any(&addrs))),
+                )
+                .set((
+                    metadata_json_refetches::pending.eq(true),
+                    metadata_json_refetches::next_fetch_at
+                        .eq(now + chrono::Duration::seconds(args.refetch_min_interval.into())),
+                ))
+                .execute(db)
+                .context("Failed to mark re-fetches as pending")?;
+
+                let rows: Vec<(String, String, Option<i64>, Option<i64>, Option<i64>)> =
+                    metadatas::table
+                        .left_join(
+                            metadata_jsons::table
+                                .on(metadatas::address.eq(metadata_jsons::metadata_address)),
+                        )
+                        .filter(metadatas::address.eq(any(&addrs)))
+                        .select((
+                            metadatas::address,
+                            metadatas::uri,
+                            metadatas::slot,
+                            metadata_jsons::slot.nullable(),
+                            metadata_jsons::write_version.nullable(),
+                        ))
+                        .load(db)
+                        .context("Failed to load metadata for re-fetches")?;
+
+                let creators: Vec<(String, String)> = metadata_creators::table
+                    .filter(metadata_creators::metadata_address.eq(any(&addrs)))
+                    .filter(metadata_creators::verified)
+                    .select((
+                        metadata_creators::metadata_address,
+                        metadata_creators::creator_address,
+                    ))
+                    .order_by(metadata_creators::position.desc())
+                    .load(db)
+                    .context("Failed to load verified creators for re-fetches")?;
+
+                Result::<_>::Ok((rows, creators))
+            })
+        })
+        .await?;
+
+    // Creators are sorted by descending position, so the first verified
+    // creator is inserted last
+    let creators: HashMap<_, _> = creators.into_iter().collect();
+
+    rows.into_iter()
+        .map(
+            |(address, uri, meta_slot, json_slot, json_write_version)| -> Result<_> {
+                let slot_info = match json_slot.zip(json_write_version) {
+                    Some((slot, write_version)) => (slot.try_into()?, write_version.try_into()?),
+                    None => (meta_slot.unwrap_or(0).try_into()?, 0),
+                };
+
+                Ok(http_indexer::MetadataJson {
+                    meta_address: Pubkey::from_str(&address)?,
+                    first_verified_creator: creators
+                        .get(&address)
+                        .map(|c| Pubkey::from_str(c))
+                        .transpose()?,
+                    uri,
+                    slot_info,
+                })
+            },
+        )
+        .collect()
+}
+
+/// Periodically dispatch due metadata JSON re-fetches to the given producer
+/// until the process exits
+pub async fn run_scheduler(
+    client: Arc<Client>,
+    producer: http_indexer::Producer<http_indexer::MetadataJson>,
+) {
+    let poll_interval = StdDuration::from_secs(client.refetch_args().refetch_poll_interval);
+
+    loop {
+        let msgs = match claim_batch(&client).await {
+            Ok(m) => m,
+            Err(e) => {
+                error!("Failed to claim metadata JSON re-fetches: {:?}", e);
+                Vec::new()
+            },
+        };
+
+        if msgs.is_empty() {
+            trace!("No metadata JSON re-fetches due, sleeping");
+            tokio::time::sleep(poll_interval).await;
+            continue;
+        }
+
+        debug!("Dispatching {} metadata JSON re-fetch(es)", msgs.len());
+
+        for msg in msgs {
+            let addr = msg.meta_address;
+
+            if let Err(e) = producer.write(msg).await {
+                warn!("Failed to dispatch re-fetch for metadata {}: {:?}", addr, e);
+            }
+        }
+    }
+}
@@ -1,18 +1,27 @@
-use std::collections::HashMap;
+//! Parsing of versioned store config JSON documents.  Documents without a
+//! `version` field are parsed as version 1.  Unknown fields are ignored, and
+//! any problems found are recorded in `store_config_json_issues` rather than
+//! discarding the document.
 
 use indexer_core::{
     db::{
         delete, insert_into,
-        models::{StoreAuctionHouse, StoreConfigJson, StoreCreator},
-        tables::{store_auction_houses, store_config_jsons, store_creators},
+        models::{StoreAuctionHouse, StoreConfigJson, StoreConfigJsonIssue, StoreCreator},
+        tables::{
+            store_auction_houses, store_config_json_issues, store_config_jsons, store_creators,
+        },
     },
     url::Url,
 };
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use super::Client;
 use crate::prelude::*;
 
+/// The newest store config schema version understood by the indexer
+const LATEST_VERSION: i32 = 2;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Creator {
     pub address: String,
@@ -23,47 +32,326 @@ pub struct AuctionHouse {
     pub address: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
 pub struct Metadata {
-    pub name: String,
-    pub description: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
 pub struct Address {
-    pub owner: String,
+    pub owner: Option<String>,
     pub store: Option<String>,
-    pub store_config: String,
+    pub store_config: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
 pub struct Upload {
-    pub url: String,
+    pub url: Option<String>,
     pub name: Option<String>,
     #[serde(rename = "type")]
     pub ty: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct Theme {
-    pub banner: Upload,
-    pub logo: Upload,
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct ThemeV1 {
+    banner: Upload,
+    logo: Upload,
+    primary_color: Option<String>,
+    background_color: Option<String>,
+    text_color: Option<String>,
+    title_font: Option<String>,
+    text_font: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+struct ColorsV2 {
+    primary: Option<String>,
+    background: Option<String>,
+    text: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+struct FontsV2 {
+    title: Option<String>,
+    text: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(rename_all = "camelCase")]
-struct SettingUri {
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+struct ThemeV2 {
+    banner: Upload,
+    logo: Upload,
+    colors: ColorsV2,
+    fonts: FontsV2,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct SettingUriV1 {
     meta: Metadata,
-    theme: Theme,
-    subdomain: String,
+    theme: ThemeV1,
+    subdomain: Option<String>,
     address: Address,
     creators: Option<Vec<Creator>>,
-    #[serde(flatten)]
-    extra: HashMap<String, serde_json::Value>,
     auction_houses: Option<Vec<AuctionHouse>>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct SettingUriV2 {
+    meta: Metadata,
+    theme: ThemeV2,
+    subdomain: Option<String>,
+    custom_domain: Option<String>,
+    address: Address,
+    creators: Option<Vec<Creator>>,
+    auction_houses: Option<Vec<AuctionHouse>>,
+}
+
+/// A store config document normalized from any supported schema version
+#[derive(Debug, Default)]
+struct StoreConfig {
+    version: i32,
+    meta: Metadata,
+    logo: Upload,
+    banner: Upload,
+    primary_color: Option<String>,
+    background_color: Option<String>,
+    text_color: Option<String>,
+    title_font: Option<String>,
+    text_font: Option<String>,
+    subdomain: Option<String>,
+    custom_domain: Option<String>,
+    address: Address,
+    creators: Option<Vec<Creator>>,
+    auction_houses: Option<Vec<AuctionHouse>>,
+}
+
+impl From<SettingUriV1> for StoreConfig {
+    fn from(
+        SettingUriV1 {
+            meta,
+            theme,
+            subdomain,
+            address,
+            creators,
+            auction_houses,
+        }: SettingUriV1,
+    ) -> Self {
+        Self {
+            version: 1,
+            meta,
+            logo: theme.logo,
+            banner: theme.banner,
+            primary_color: theme.primary_color,
+            background_color: theme.background_color,
+            text_color: theme.text_color,
+            title_font: theme.title_font,
+            text_font: theme.text_font,
+            subdomain,
+            custom_domain: None,
+            address,
+            creators,
+            auction_houses,
+        }
+    }
+}
+
+impl From<SettingUriV2> for StoreConfig {
+    fn from(
+        SettingUriV2 {
+            meta,
+            theme,
+            subdomain,
+            custom_domain,
+            address,
+            creators,
+            auction_houses,
+        }: SettingUriV2,
+    ) -> Self {
+        Self {
+            version: 2,
+            meta,
+            logo: theme.logo,
+            banner: theme.banner,
+            primary_color: theme.colors.primary,
+            background_color: theme.colors.background,
+            text_color: theme.colors.text,
+            title_font: theme.fonts.title,
+            text_font: theme.fonts.text,
+            subdomain,
+            custom_domain,
+            address,
+            creators,
+            auction_houses,
+        }
+    }
+}
+
+/// A problem found in a store config document
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Issue {
+    path: String,
+    message: String,
+}
+
+#[derive(Debug, Default)]
+struct Report(Vec<Issue>);
+
+impl Report {
+    fn push(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.0.push(Issue {
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    fn check_required(&mut self, path: &str, value: Option<&String>) {
+        if value.map_or(true, |v| v.trim().is_empty()) {
+            self.push(path, "required field is missing");
+        }
+    }
+
+    fn check_url(&mut self, path: &str, value: Option<&String>) {
+        match value {
+            None => self.push(path, "URL is missing"),
+            Some(s) => {
+                if let Err(e) = Url::parse(s) {
+                    self.push(path, format!("URL {:?} is invalid: {}", s, e));
+                }
+            },
+        }
+    }
+
+    fn check_color(&mut self, path: &str, value: Option<&String>) {
+        if let Some(color) = value {
+            let hex = color.strip_prefix('#').unwrap_or("");
+
+            if !matches!(hex.len(), 3 | 4 | 6 | 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                self.push(path, format!("{:?} is not a hex color", color));
+            }
+        }
+    }
+}
+
+/// Parse a store config document according to its `version` field,
+/// returning `None` if it could not be parsed at all
+fn parse(raw: Value) -> (Option<StoreConfig>, Vec<Issue>) {
+    let mut report = Report::default();
+
+    let version = match raw.get("version") {
+        None | Some(Value::Null) => 1,
+        Some(v) => match v.as_i64().and_then(|v| i32::try_from(v).ok()) {
+            Some(v) if (1..=LATEST_VERSION).contains(&v) => v,
+            _ => {
+                report.push(
+                    "version",
+                    format!(
+                        "unsupported schema version {}, parsing as version {}",
+                        v, LATEST_VERSION
+                    ),
+                );
+                LATEST_VERSION
+            },
+        },
+    };
+
+    let parsed = if version == 1 {
+        serde_json::from_value::<SettingUriV1>(raw).map(StoreConfig::from)
+    } else {
+        serde_json::from_value::<SettingUriV2>(raw).map(StoreConfig::from)
+    };
+
+    let config = match parsed {
+        Ok(c) => c,
+        Err(e) => {
+            report.push(
+                "",
+                format!("document does not match schema version {}: {}", version, e),
+            );
+            return (None, report.0);
+        },
+    };
+
+    report.check_required("meta.name", config.meta.name.as_ref());
+    report.check_required("subdomain", config.subdomain.as_ref());
+    report.check_required("address.owner", config.address.owner.as_ref());
+    report.check_required("address.storeConfig", config.address.store_config.as_ref());
+    report.check_url("theme.logo.url", config.logo.url.as_ref());
+    report.check_url("theme.banner.url", config.banner.url.as_ref());
+
+    let (primary, background, text) = if config.version == 1 {
+        (
+            "theme.primaryColor",
+            "theme.backgroundColor",
+            "theme.textColor",
+        )
+    } else {
+        (
+            "theme.colors.primary",
+            "theme.colors.background",
+            "theme.colors.text",
+        )
+    };
+
+    report.check_color(primary, config.primary_color.as_ref());
+    report.check_color(background, config.background_color.as_ref());
+    report.check_color(text, config.text_color.as_ref());
+
+    if let Some(ref domain) = config.custom_domain {
+        if Url::parse(&format!("https://{}/", domain))
+            .ok()
+            .and_then(|u| u.domain().map(|d| d == domain))
+            != Some(true)
+        {
+            report.push(
+                "customDomain",
+                format!("{:?} is not a valid domain name", domain),
+            );
+        }
+    }
+
+    (Some(config), report.0)
+}
+
+async fn record_issues(client: &Client, addr: String, issues: Vec<Issue>) -> Result<()> {
+    client
+        .db()
+        .run(move |db| {
+            db.build_transaction().read_write().run(|| {
+                delete(
+                    store_config_json_issues::table
+                        .filter(store_config_json_issues::config_address.eq(&addr)),
+                )
+                .execute(db)?;
+
+                let rows: Vec<_> = issues
+                    .into_iter()
+                    .map(|Issue { path, message }| StoreConfigJsonIssue {
+                        id: None,
+                        config_address: Borrowed(&addr),
+                        path: Owned(path),
+                        message: Owned(message),
+                    })
+                    .collect();
+
+                insert_into(store_config_json_issues::table)
+                    .values(&rows)
+                    .execute(db)
+                    .map(|_| ())
+            })
+        })
+        .await
+        .context("Failed to record store config issues")
+}
+
 #[allow(clippy::too_many_lines)]
 pub async fn process(client: &Client, config_key: Pubkey, uri_str: String) -> Result<()> {
     let url = Url::parse(&uri_str).context("Couldn't parse store config URL")?;
@@ -73,29 +361,66 @@ pub async fn process(client: &Client, config_key: Pubkey, uri_str: String) -> Re
         config_key, uri_str
     );
 
-    // TODO: parse failure shouldn't be an error, this stuff will be unstructured
-    let json = client
+    let bytes = client
         .http()
-        .run(|h| async move { h.get(url).send().await?.json::<SettingUri>().await })
+        .run(|h| async move { h.get(url).send().await?.error_for_status()?.bytes().await })
         .await
         .context("Store config JSON request failed")?;
 
     let addr = bs58::encode(config_key).into_string();
 
-    if addr != json.address.store_config {
-        info!("store config address does not match setting uri JSON config address");
-        return Ok(());
+    let (json, mut issues) = match serde_json::from_slice(&bytes) {
+        Ok(raw) => parse(raw),
+        Err(e) => (None, vec![Issue {
+            path: String::new(),
+            message: format!("document is not valid JSON: {}", e),
+        }]),
+    };
+
+    if let Some(ref j) = json {
+        if j.address.store_config.as_ref() != Some(&addr) {
+            info!("store config address does not match setting uri JSON config address");
+            issues.push(Issue {
+                path: "address.storeConfig".into(),
+                message: "does not match the store config account address".into(),
+            });
+        }
+    }
+
+    if !issues.is_empty() {
+        debug!("Store config {} has {} issue(s)", addr, issues.len());
     }
 
+    record_issues(client, addr.clone(), issues).await?;
+
+    // Documents which could not be parsed at all leave the previous row in
+    // place.  Otherwise the row is stored even if incomplete, so that the
+    // marketplace and its recorded issues remain reachable.
+    let json = match json {
+        Some(j) => j,
+        None => return Ok(()),
+    };
+
+    let non_empty = |s: Option<String>| s.filter(|s| !s.trim().is_empty());
+    let subdomain = non_empty(json.subdomain);
+    let owner_address = non_empty(json.address.owner);
+
     let row = StoreConfigJson {
         config_address: Owned(addr.clone()),
-        name: Owned(json.meta.name),
-        description: Owned(json.meta.description),
-        logo_url: Owned(json.theme.logo.url),
-        banner_url: Owned(json.theme.banner.url),
-        subdomain: Owned(json.subdomain),
-        owner_address: Owned(json.address.owner),
+        name: Owned(json.meta.name.unwrap_or_default()),
+        description: Owned(json.meta.description.unwrap_or_default()),
+        logo_url: Owned(json.logo.url.unwrap_or_default()),
+        banner_url: Owned(json.banner.url.unwrap_or_default()),
+        subdomain: subdomain.map(Owned),
+        owner_address: owner_address.map(Owned),
         store_address: json.address.store.map(Owned),
+        schema_version: json.version,
+        primary_color: json.primary_color.map(Owned),
+        background_color: json.background_color.map(Owned),
+        text_color: json.text_color.map(Owned),
+        title_font: json.title_font.map(Owned),
+        text_font: json.text_font.map(Owned),
+        custom_domain: json.custom_domain.map(Owned),
     };
 
     client
@@ -218,3 +543,52 @@ pub async fn process(client: &Client, config_key: Pubkey, uri_str: String) -> Re
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::parse;
+
+    #[test]
+    fn test_versions() {
+        let (v1, issues) = parse(json!({
+            "meta": { "name": "Store", "description": "" },
+            "theme": {
+                "logo": { "url": "https://arweave.net/logo" },
+                "banner": { "url": "https://arweave.net/banner" },
+                "primaryColor": "#fff",
+            },
+            "subdomain": "store",
+            "address": { "owner": "A", "storeConfig": "B" },
+            "someNewField": true,
+        }));
+        let v1 = v1.unwrap();
+
+        assert_eq!(issues, vec![]);
+        assert_eq!(v1.version, 1);
+        assert_eq!(v1.primary_color.as_deref(), Some("#fff"));
+
+        let (v2, issues) = parse(json!({
+            "version": 2,
+            "meta": { "name": "Store" },
+            "theme": {
+                "logo": { "url": "https://arweave.net/logo" },
+                "colors": { "primary": "red" },
+                "fonts": { "title": "Inter" },
+            },
+            "subdomain": "store",
+            "customDomain": "store.example.com",
+            "address": { "owner": "A", "storeConfig": "B" },
+        }));
+        let v2 = v2.unwrap();
+
+        assert_eq!(v2.version, 2);
+        assert_eq!(v2.title_font.as_deref(), Some("Inter"));
+        assert_eq!(v2.custom_domain.as_deref(), Some("store.example.com"));
+        assert_eq!(
+            issues.into_iter().map(|i| i.path).collect::<Vec<_>>(),
+            vec!["theme.banner.url", "theme.colors.primary"]
+        );
+    }
+}
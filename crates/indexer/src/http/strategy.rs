@@ -0,0 +1,225 @@
+//! Configurable strategies for fetching off-chain documents, each guarded by a
+//! circuit breaker which skips the strategy after repeated failures.
+
+use std::{
+    fmt,
+    str::FromStr,
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use indexer_core::{
+    assets::{proxy_non_permaweb_url, proxy_url_hinted, AssetHint, AssetIdentifier},
+    clap,
+    url::Url,
+};
+
+use super::Client;
+use crate::{prelude::*, reqwest};
+
+/// Arguments for configuring fetch strategies
+#[derive(Debug, Clone, clap::Args)]
+pub struct Args {
+    /// Ordered list of strategies used to fetch off-chain documents.  Valid
+    /// strategies are `proxy` (the Holaplex asset proxy), `ipfs=<gateway URL>`,
+    /// `arweave=<gateway URL>`, and `direct` (the raw URI).  Each may be
+    /// suffixed with `@<seconds>` to override the request timeout.
+    #[clap(
+        long,
+        env,
+        use_value_delimiter(true),
+        default_value = "proxy,ipfs=https://ipfs.io/,arweave=https://arweave.net/,direct"
+    )]
+    fetch_strategies: Vec<StrategyConfig>,
+
+    /// Number of consecutive failures after which a fetch strategy is skipped
+    #[clap(long, env, default_value_t = 10)]
+    fetch_breaker_threshold: u32,
+
+    /// Time to skip a failing fetch strategy before retrying it, in seconds
+    #[clap(long, env, default_value_t = 60)]
+    fetch_breaker_cooldown: u64,
+}
+
+/// The source a strategy fetches documents from
+#[derive(Debug, Clone)]
+enum Kind {
+    Proxy,
+    Ipfs(Url),
+    Arweave(Url),
+    Direct,
+}
+
+/// A parsed fetch strategy argument
+#[derive(Debug, Clone)]
+pub struct StrategyConfig {
+    name: String,
+    kind: Kind,
+    timeout: Option<Duration>,
+}
+
+impl FromStr for StrategyConfig {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, timeout) = match s
+            .rsplit_once('@')
+            .and_then(|(name, secs)| secs.parse().ok().map(|s| (name, s)))
+        {
+            Some((name, secs)) => (name, Some(Duration::from_secs_f64(secs))),
+            None => (s, None),
+        };
+
+        let kind = match name.split_once('=') {
+            None if name == "proxy" => Kind::Proxy,
+            None if name == "direct" => Kind::Direct,
+            Some(("ipfs", url)) => Kind::Ipfs(url.parse().context("Invalid IPFS gateway URL")?),
+            Some(("arweave", url)) => {
+                Kind::Arweave(url.parse().context("Invalid Arweave gateway URL")?)
+            },
+            _ => bail!("Invalid fetch strategy {:?}", name),
+        };
+
+        Ok(Self {
+            name: name.to_owned(),
+            kind,
+            timeout,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    failures: u32,
+    open_until: Option<Instant>,
+}
+
+/// A fetch strategy with its own timeout and circuit breaker
+#[derive(Debug)]
+pub struct Strategy {
+    name: String,
+    kind: Kind,
+    timeout: Duration,
+    threshold: u32,
+    cooldown: Duration,
+    breaker: Mutex<BreakerState>,
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl Strategy {
+    /// Construct the configured list of strategies, using `default_timeout`
+    /// for any strategy without its own
+    pub(super) fn from_args(args: Args, default_timeout: Duration) -> Vec<Self> {
+        let Args {
+            fetch_strategies,
+            fetch_breaker_threshold,
+            fetch_breaker_cooldown,
+        } = args;
+
+        fetch_strategies
+            .into_iter()
+            .map(
+                |StrategyConfig {
+                     name,
+                     kind,
+                     timeout,
+                 }| Self {
+                    name,
+                    kind,
+                    timeout: timeout.unwrap_or(default_timeout),
+                    threshold: fetch_breaker_threshold,
+                    cooldown: Duration::from_secs(fetch_breaker_cooldown),
+                    breaker: Mutex::default(),
+                },
+            )
+            .collect()
+    }
+
+    /// The name of this strategy, as recorded in the `fetch_strategy` column
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The request timeout for this strategy
+    #[must_use]
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Construct the URL to request for the given asset and hint, or `None`
+    /// if this strategy does not apply
+    ///
+    /// # Errors
+    /// This function fails if a proxy URL cannot be constructed
+    pub fn url(
+        &self,
+        client: &Client,
+        id: &AssetIdentifier,
+        hint: Option<AssetHint>,
+    ) -> Result<Option<Url>> {
+        Ok(match (&self.kind, hint) {
+            (Kind::Proxy, Some(hint)) => proxy_url_hinted(client.proxy_args(), id, hint, None)?,
+            (Kind::Proxy, None) => {
+                Some(proxy_non_permaweb_url(client.proxy_args(), id.url.clone())?)
+            },
+            (Kind::Ipfs(gateway), Some(AssetHint::Ipfs))
+            | (Kind::Arweave(gateway), Some(AssetHint::Arweave)) => {
+                id.gateway_url(hint, gateway, gateway)
+            },
+            (Kind::Direct, None) => Some(id.url.clone()),
+            _ => None,
+        })
+    }
+
+    fn state(&self) -> MutexGuard<'_, BreakerState> {
+        self.breaker.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns false if the circuit breaker for this strategy is open
+    #[must_use]
+    pub fn available(&self) -> bool {
+        self.state()
+            .open_until
+            .map_or(true, |t| Instant::now() >= t)
+    }
+
+    /// Record the outcome of a request made using this strategy
+    pub fn record<T>(&self, res: &Result<T>) {
+        let mut state = self.state();
+
+        match res {
+            Ok(_) => *state = BreakerState::default(),
+            Err(e) if is_gateway_failure(e) => {
+                state.failures = state.failures.saturating_add(1);
+
+                if state.failures >= self.threshold {
+                    if state.failures == self.threshold {
+                        warn!(
+                            "Fetch strategy {:?} failed {} times, skipping for {:?}",
+                            self.name, state.failures, self.cooldown
+                        );
+                    }
+
+                    state.open_until = Some(Instant::now() + self.cooldown);
+                }
+            },
+            Err(_) => (),
+        }
+    }
+}
+
+/// Returns true if an error indicates the strategy itself is unhealthy, rather
+/// than the requested document being unavailable
+fn is_gateway_failure(e: &Error) -> bool {
+    e.downcast_ref::<reqwest::Error>().map_or(false, |e| {
+        e.status().map_or(true, |s| {
+            s.is_server_error() || s == reqwest::StatusCode::TOO_MANY_REQUESTS
+        })
+    })
+}
@@ -0,0 +1,310 @@
+//! Validation of metadata JSON documents against the Metaplex token metadata
+//! JSON standard
+
+use indexer_core::{hash::HashMap, url::Url};
+use serde_json::Value;
+
+/// Categories defined by the Metaplex standard
+const CATEGORIES: &[&str] = &["image", "video", "audio", "vr", "html"];
+
+/// Maximum value of `seller_fee_basis_points`, representing a 100% royalty
+const MAX_SELLER_FEE_BASIS_POINTS: u64 = 10_000;
+
+/// The type of a validation issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Code {
+    ParseError,
+    MissingImage,
+    InvalidSellerFeeBasisPoints,
+    CreatorShareMismatch,
+    InvalidFile,
+    UnsupportedCategory,
+}
+
+impl Code {
+    /// The value recorded in the `code` column of `metadata_json_issues`
+    pub(super) fn as_str(self) -> &'static str {
+        match self {
+            Self::ParseError => "parse_error",
+            Self::MissingImage => "missing_image",
+            Self::InvalidSellerFeeBasisPoints => "invalid_seller_fee_basis_points",
+            Self::CreatorShareMismatch => "creator_share_mismatch",
+            Self::InvalidFile => "invalid_file",
+            Self::UnsupportedCategory => "unsupported_category",
+        }
+    }
+}
+
+/// A problem found in a metadata JSON document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Issue {
+    pub code: Code,
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+struct Report(Vec<Issue>);
+
+impl Report {
+    fn push(&mut self, code: Code, path: impl Into<String>, message: impl Into<String>) {
+        self.0.push(Issue {
+            code,
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+}
+
+fn check_uri(report: &mut Report, code: Code, path: &str, value: Option<&Value>) {
+    match value {
+        None | Some(Value::Null) => report.push(code, path, "URI is missing"),
+        Some(Value::String(s)) if s.trim().is_empty() => report.push(code, path, "URI is empty"),
+        Some(Value::String(s)) => {
+            if let Err(e) = Url::parse(s) {
+                report.push(
+                    Code::InvalidFile,
+                    path,
+                    format!("URI {:?} is invalid: {}", s, e),
+                );
+            }
+        },
+        Some(_) => report.push(code, path, "URI is not a string"),
+    }
+}
+
+fn check_seller_fee(report: &mut Report, value: Option<&Value>) {
+    const PATH: &str = "seller_fee_basis_points";
+
+    match value {
+        None | Some(Value::Null) => (),
+        Some(Value::Number(n)) => match n.as_u64() {
+            Some(n) if n <= MAX_SELLER_FEE_BASIS_POINTS => (),
+            _ => report.push(
+                Code::InvalidSellerFeeBasisPoints,
+                PATH,
+                format!(
+                    "{} is not an integer between 0 and {}",
+                    n, MAX_SELLER_FEE_BASIS_POINTS
+                ),
+            ),
+        },
+        Some(v) => report.push(
+            Code::InvalidSellerFeeBasisPoints,
+            PATH,
+            format!("{} is not a number", v),
+        ),
+    }
+}
+
+fn check_creators(report: &mut Report, value: Option<&Value>, on_chain: &[(String, i32)]) {
+    const PATH: &str = "properties.creators";
+
+    let listed = match value {
+        None | Some(Value::Null) => return,
+        Some(Value::Array(a)) => a,
+        Some(_) => {
+            report.push(Code::CreatorShareMismatch, PATH, "creators is not an array");
+            return;
+        },
+    };
+
+    // Creators may not have been indexed yet, in which case there is nothing
+    // to compare against
+    if on_chain.is_empty() {
+        return;
+    }
+
+    let mut on_chain: HashMap<_, _> = on_chain.iter().map(|(a, s)| (a.as_str(), *s)).collect();
+
+    for (i, creator) in listed.iter().enumerate() {
+        let path = format!("{}[{}]", PATH, i);
+        let address = creator.get("address").and_then(Value::as_str);
+        let share = creator.get("share").and_then(Value::as_i64);
+
+        let address = if let Some(a) = address {
+            a
+        } else {
+            report.push(
+                Code::CreatorShareMismatch,
+                path,
+                "creator address is missing",
+            );
+            continue;
+        };
+
+        match (on_chain.remove(address), share) {
+            (None, _) => report.push(
+                Code::CreatorShareMismatch,
+                path,
+                format!("creator {} is not listed on-chain", address),
+            ),
+            (Some(expected), Some(share)) if i64::from(expected) == share => (),
+            (Some(expected), share) => report.push(
+                Code::CreatorShareMismatch,
+                path,
+                format!(
+                    "share {} of creator {} does not match on-chain share {}",
+                    share.map_or_else(|| "(missing)".to_owned(), |s| s.to_string()),
+                    address,
+                    expected
+                ),
+            ),
+        }
+    }
+
+    let mut missing: Vec<_> = on_chain.into_iter().collect();
+    missing.sort_unstable();
+
+    for (address, share) in missing {
+        report.push(
+            Code::CreatorShareMismatch,
+            PATH,
+            format!(
+                "on-chain creator {} with share {} is not listed",
+                address, share
+            ),
+        );
+    }
+}
+
+fn check_files(report: &mut Report, value: Option<&Value>) {
+    const PATH: &str = "properties.files";
+
+    let files = match value {
+        None | Some(Value::Null) => return,
+        Some(Value::Array(a)) => a,
+        Some(_) => {
+            report.push(Code::InvalidFile, PATH, "files is not an array");
+            return;
+        },
+    };
+
+    for (i, file) in files.iter().enumerate() {
+        check_uri(
+            report,
+            Code::InvalidFile,
+            &format!("{}[{}].uri", PATH, i),
+            file.get("uri"),
+        );
+
+        if !file.get("type").map_or(false, Value::is_string) {
+            report.push(
+                Code::InvalidFile,
+                format!("{}[{}].type", PATH, i),
+                "file type is missing",
+            );
+        }
+    }
+}
+
+fn check_category(report: &mut Report, value: Option<&Value>) {
+    const PATH: &str = "properties.category";
+
+    match value {
+        None | Some(Value::Null) => (),
+        Some(Value::String(s)) if CATEGORIES.contains(&s.as_str()) => (),
+        Some(v) => report.push(
+            Code::UnsupportedCategory,
+            PATH,
+            format!(
+                "{} is not one of {}",
+                v,
+                CATEGORIES
+                    .iter()
+                    .map(|c| format!("{:?}", c))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ),
+    }
+}
+
+/// Validate a metadata JSON document, comparing its listed creators against
+/// the `(address, share)` pairs of its on-chain creators.  `parse_err` should
+/// contain the error raised if the document failed to parse as the full
+/// metadata model.
+pub(super) fn validate(
+    raw: &Value,
+    parse_err: Option<&str>,
+    on_chain_creators: &[(String, i32)],
+) -> Vec<Issue> {
+    let mut report = Report::default();
+
+    if let Some(err) = parse_err {
+        report.push(Code::ParseError, "", err);
+    }
+
+    if !raw.is_object() {
+        report.push(Code::ParseError, "", "document is not an object");
+        return report.0;
+    }
+
+    check_uri(&mut report, Code::MissingImage, "image", raw.get("image"));
+    check_seller_fee(&mut report, raw.get("seller_fee_basis_points"));
+
+    if let Some(props) = raw.get("properties").filter(|p| !p.is_null()) {
+        check_creators(&mut report, props.get("creators"), on_chain_creators);
+        check_files(&mut report, props.get("files"));
+        check_category(&mut report, props.get("category"));
+    }
+
+    report.0
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::{validate, Code};
+
+    fn codes(raw: &serde_json::Value, creators: &[(String, i32)]) -> Vec<(Code, String)> {
+        validate(raw, None, creators)
+            .into_iter()
+            .map(|i| (i.code, i.path))
+            .collect()
+    }
+
+    #[test]
+    fn test_valid() {
+        let raw = json!({
+            "name": "Test",
+            "image": "https://arweave.net/abc",
+            "seller_fee_basis_points": 500,
+            "properties": {
+                "category": "image",
+                "files": [{ "uri": "https://arweave.net/abc", "type": "image/png" }],
+                "creators": [{ "address": "A", "share": 100 }],
+            },
+        });
+
+        assert_eq!(codes(&raw, &[("A".into(), 100)]), vec![]);
+    }
+
+    #[test]
+    fn test_issues() {
+        let raw = json!({
+            "name": "Test",
+            "seller_fee_basis_points": "500",
+            "properties": {
+                "category": "meme",
+                "files": [{ "uri": "not a url" }],
+                "creators": [{ "address": "A", "share": 60 }, { "address": "C", "share": 40 }],
+            },
+        });
+
+        assert_eq!(codes(&raw, &[("A".into(), 50), ("B".into(), 50)]), vec![
+            (Code::MissingImage, "image".into()),
+            (
+                Code::InvalidSellerFeeBasisPoints,
+                "seller_fee_basis_points".into()
+            ),
+            (Code::CreatorShareMismatch, "properties.creators[0]".into()),
+            (Code::CreatorShareMismatch, "properties.creators[1]".into()),
+            (Code::CreatorShareMismatch, "properties.creators".into()),
+            (Code::InvalidFile, "properties.files[0].uri".into()),
+            (Code::InvalidFile, "properties.files[0].type".into()),
+            (Code::UnsupportedCategory, "properties.category".into()),
+        ]);
+    }
+}
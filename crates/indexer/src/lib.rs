@@ -9,20 +9,33 @@
 )]
 #![warn(clippy::pedantic, clippy::cargo, missing_docs)]
 
+#[cfg(feature = "assets")]
+pub mod assets;
+pub mod backfill;
+#[cfg(feature = "block-times")]
+pub mod block_times;
+#[cfg(feature = "currency-rates")]
+pub mod currency_rates;
 pub mod db;
+#[cfg(feature = "export")]
+pub mod export;
 #[cfg(feature = "geyser")]
 pub mod geyser;
 #[cfg(feature = "http")]
 pub mod http;
 #[cfg(feature = "job-runner")]
 pub mod jobs;
+pub mod rarity;
 #[cfg(feature = "reqwest")]
 pub(crate) mod reqwest;
+#[cfg(feature = "royalties")]
+pub mod royalties;
 #[cfg(feature = "search")]
 pub mod search;
 #[cfg(feature = "search-dispatch")]
 /// Search dispatch module for creating client and dispatching AMQP messages to the search indexer
 pub mod search_dispatch;
+pub mod trends;
 pub(crate) mod util;
 
 pub use runtime::*;
@@ -83,6 +96,14 @@ mod runtime {
         concurrency: usize,
     }
 
+    impl Params {
+        /// The number of jobs to run concurrently
+        #[must_use]
+        pub fn concurrency(&self) -> usize {
+            self.concurrency
+        }
+    }
+
     /// Entrypoint for `holaplex-indexer` binaries
     pub fn run<T: Debug + Args, F: Future<Output = Result<()>>>(
         f: impl FnOnce(T, Params, Pool) -> F,
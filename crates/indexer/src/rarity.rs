@@ -0,0 +1,124 @@
+//! Rarity worker.  Recomputes the rarity scores of verified collections
+//! whenever their members' attributes change, as recorded in the
+//! `rarity_dirty_collections` table.  Collections which fail to score are
+//! retried with exponential backoff.
+
+use std::{sync::Arc, time::Duration};
+
+use futures_util::StreamExt;
+use indexer_core::{clap, db::mutations::rarity};
+
+use crate::{db::Pool, prelude::*};
+
+/// Arguments for the rarity worker
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Number of collections to select per batch
+    #[clap(long, env, default_value_t = 16)]
+    batch_size: i64,
+
+    /// Time to wait before checking for changed collections once none
+    /// remain, in seconds
+    #[clap(long, env, default_value_t = 30)]
+    poll_interval: u64,
+}
+
+/// Shared state for the rarity worker
+#[derive(Debug)]
+pub struct Worker {
+    db: Pool,
+    batch_size: i64,
+    poll_interval: Duration,
+}
+
+impl Worker {
+    /// Construct a new worker
+    #[must_use]
+    pub fn new(args: Args, db: Pool) -> Self {
+        let Args {
+            batch_size,
+            poll_interval,
+        } = args;
+
+        Self {
+            db,
+            batch_size,
+            poll_interval: Duration::from_secs(poll_interval),
+        }
+    }
+
+    /// Recompute changed collections until interrupted, scoring up to
+    /// `concurrency` collections at once
+    ///
+    /// # Errors
+    /// This function fails if the database cannot be queried for changed
+    /// collections
+    pub async fn run(self: Arc<Self>, concurrency: usize) -> Result<()> {
+        loop {
+            let batch_size = self.batch_size;
+            let collections = tokio::select! {
+                c = self.db.run(move |db| rarity::dirty_collections(db, batch_size)) => c?,
+                r = tokio::signal::ctrl_c() => {
+                    r.context("Failed to wait for interrupt")?;
+                    warn!("Interrupt received, shutting down...");
+
+                    break Ok(());
+                },
+            };
+
+            if collections.is_empty() {
+                trace!("No collections awaiting rarity scores, sleeping");
+                tokio::time::sleep(self.poll_interval).await;
+                continue;
+            }
+
+            debug!("Recomputing rarity for {} collection(s)", collections.len());
+
+            futures_util::stream::iter(collections)
+                .for_each_concurrent(concurrency, |(address, marked_at)| {
+                    let worker = Arc::clone(&self);
+
+                    async move {
+                        let res = worker
+                            .db
+                            .run({
+                                let address = address.clone();
+                                move |db| rarity::recompute_collection(db, &address, marked_at)
+                            })
+                            .await;
+
+                        match res {
+                            Ok(n) => debug!("Scored {} NFT(s) in collection {}", n, address),
+                            Err(e) => {
+                                error!("Failed to score collection {}: {:?}", address, e);
+
+                                // Postpone the collection so it does not block
+                                // the collections marked after it
+                                let error = format!("{:?}", e);
+                                let res = worker
+                                    .db
+                                    .run({
+                                        let address = address.clone();
+                                        move |db| rarity::mark_failed(db, &address, &error)
+                                    })
+                                    .await;
+
+                                match res {
+                                    Ok(Some(n)) => warn!(
+                                        "Collection {} has failed to score {} time(s), backing off",
+                                        address, n
+                                    ),
+                                    Ok(None) => (),
+                                    Err(e) => error!(
+                                        "Failed to record scoring failure for collection {}: {:?}",
+                                        address, e
+                                    ),
+                                }
+                            },
+                        }
+                    }
+                })
+                .await;
+        }
+    }
+}
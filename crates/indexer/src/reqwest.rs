@@ -5,7 +5,7 @@ use std::time::Duration;
 pub use ::reqwest::*;
 use indexer_core::{
     error::Result as IResult,
-    prelude::{anyhow, error, warn, Context},
+    prelude::{anyhow, bail, error, warn, Context},
 };
 use tokio::sync::Mutex;
 
@@ -78,4 +78,42 @@ impl Client {
             },
         }
     }
+
+    /// Call a Solana JSON-RPC method and return its result
+    ///
+    /// # Errors
+    /// This function fails if the request fails or the node returns an error
+    #[cfg(feature = "serde_json")]
+    pub async fn json_rpc(
+        &self,
+        endpoint: &str,
+        method: &str,
+        params: serde_json::Value,
+    ) -> IResult<serde_json::Value> {
+        let mut res: serde_json::Value = self
+            .run(|h| async move {
+                h.post(endpoint)
+                    .json(&serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": 1,
+                        "method": method,
+                        "params": params,
+                    }))
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await
+            })
+            .await?;
+
+        if let Some(err) = res.get("error") {
+            bail!("RPC call {} failed: {}", method, err);
+        }
+
+        match res.get_mut("result") {
+            Some(result) => Ok(result.take()),
+            None => bail!("Missing result in {} response", method),
+        }
+    }
 }
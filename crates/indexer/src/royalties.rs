@@ -0,0 +1,378 @@
+//! Royalties worker.  Looks up the transaction of each sale recorded in the
+//! `sale_royalties` table and records the royalty each creator was actually
+//! paid, read from the balance changes of the transaction.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use futures_util::StreamExt;
+use indexer_core::{
+    clap,
+    db::mutations::royalties::{self, UncheckedSale},
+    pubkeys,
+};
+use serde_json::{json, Value};
+
+use crate::{db::Pool, prelude::*, reqwest};
+
+/// Maximum number of signatures returned by one `getSignaturesForAddress`
+/// call
+const SIGNATURE_PAGE_SIZE: usize = 1000;
+
+/// Arguments for the royalties worker
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Solana RPC endpoint to load sale transactions from
+    #[clap(long, env)]
+    solana_endpoint: String,
+
+    /// Number of sales to select per batch
+    #[clap(long, env, default_value_t = 64)]
+    batch_size: i64,
+
+    /// Time to wait before checking for new sales once none remain, in
+    /// seconds
+    #[clap(long, env, default_value_t = 30)]
+    poll_interval: u64,
+
+    /// Minimum age of a sale before its transaction is looked up, in
+    /// seconds, so the transaction has been confirmed
+    #[clap(long, env, default_value_t = 60)]
+    min_age: u64,
+
+    /// Maximum number of pages of signatures to search for the transaction
+    /// of a sale before giving up
+    #[clap(long, env, default_value_t = 10)]
+    max_signature_pages: usize,
+}
+
+/// Shared state for the royalties worker
+#[derive(Debug)]
+pub struct Worker {
+    db: Pool,
+    http: reqwest::Client,
+    endpoint: String,
+    batch_size: i64,
+    poll_interval: Duration,
+    min_age: chrono::Duration,
+    max_signature_pages: usize,
+}
+
+impl Worker {
+    /// Construct a new worker
+    ///
+    /// # Errors
+    /// This function fails if the HTTP client cannot be constructed
+    pub fn new(args: Args, db: Pool) -> Result<Self> {
+        let Args {
+            solana_endpoint,
+            batch_size,
+            poll_interval,
+            min_age,
+            max_signature_pages,
+        } = args;
+
+        Ok(Self {
+            db,
+            http: reqwest::Client::new(Duration::from_secs(30))?,
+            endpoint: solana_endpoint,
+            batch_size,
+            poll_interval: Duration::from_secs(poll_interval),
+            min_age: chrono::Duration::seconds(min_age.try_into()?),
+            max_signature_pages,
+        })
+    }
+
+    /// Record the royalties paid for new sales until interrupted, checking
+    /// up to `concurrency` sales at once
+    ///
+    /// # Errors
+    /// This function fails if the database cannot be queried for new sales
+    pub async fn run(self: Arc<Self>, concurrency: usize) -> Result<()> {
+        loop {
+            let batch_size = self.batch_size;
+            let before = Utc::now().naive_utc() - self.min_age;
+            let sales = tokio::select! {
+                s = self.db.run(move |db| royalties::unchecked_sales(db, before, batch_size)) => s?,
+                r = tokio::signal::ctrl_c() => {
+                    r.context("Failed to wait for interrupt")?;
+                    warn!("Interrupt received, shutting down...");
+
+                    break Ok(());
+                },
+            };
+
+            if sales.is_empty() {
+                trace!("No sales awaiting royalty checks, sleeping");
+                tokio::time::sleep(self.poll_interval).await;
+                continue;
+            }
+
+            debug!("Checking royalties for {} sale(s)", sales.len());
+
+            let checked = futures_util::stream::iter(sales)
+                .map(|sale| {
+                    let worker = Arc::clone(&self);
+
+                    async move {
+                        let id = sale.purchase_id;
+
+                        match worker.check_sale(sale).await {
+                            Ok(()) => true,
+                            Err(e) => {
+                                error!("Failed to check royalties for sale {}: {:?}", id, e);
+                                false
+                            },
+                        }
+                    }
+                })
+                .buffer_unordered(concurrency)
+                .filter(|ok| futures_util::future::ready(*ok))
+                .count()
+                .await;
+
+            // Back off rather than spin on a batch which cannot be checked,
+            // e.g. while the RPC node is unavailable
+            if checked == 0 {
+                tokio::time::sleep(self.poll_interval).await;
+            }
+        }
+    }
+
+    async fn check_sale(&self, sale: UncheckedSale) -> Result<()> {
+        let payments = match self.find_transaction(&sale).await? {
+            Some(tx) => {
+                let changes = match sale.currency_mint {
+                    Some(ref mint) => balance_changes(&tx, mint)?,
+                    None => HashMap::new(),
+                };
+
+                sale.creators
+                    .iter()
+                    .map(|creator| {
+                        // Payments to a creator who is also a party to the sale
+                        // cannot be told apart from the sale itself, and the
+                        // currency must be known to read the right balances
+                        let paid = if sale.currency_mint.is_none()
+                            || *creator == sale.buyer
+                            || *creator == sale.seller
+                        {
+                            None
+                        } else {
+                            Some(
+                                changes
+                                    .get(creator)
+                                    .copied()
+                                    .unwrap_or(0)
+                                    .max(0)
+                                    .try_into()
+                                    .unwrap_or(i64::MAX),
+                            )
+                        };
+
+                        (creator.clone(), paid)
+                    })
+                    .collect::<Vec<_>>()
+            },
+            None => {
+                debug!("No transaction found for sale {}", sale.purchase_id);
+
+                sale.creators.iter().map(|c| (c.clone(), None)).collect()
+            },
+        };
+
+        let id = sale.purchase_id;
+        self.db
+            .run(move |db| royalties::record_payments(db, id, &payments))
+            .await
+    }
+
+    /// Find the transaction of a sale among the transactions referencing the
+    /// sold NFT's metadata in the slot of the sale
+    async fn find_transaction(&self, sale: &UncheckedSale) -> Result<Option<Value>> {
+        let mut before: Option<String> = None;
+
+        for _ in 0..self.max_signature_pages {
+            let mut config = json!({
+                "limit": SIGNATURE_PAGE_SIZE,
+                "commitment": "confirmed",
+            });
+
+            if let Some(ref before) = before {
+                config["before"] = json!(before);
+            }
+
+            let page = self
+                .call("getSignaturesForAddress", json!([sale.metadata, config]))
+                .await?;
+            let page = page
+                .as_array()
+                .ok_or_else(|| anyhow!("Invalid signatures response: {}", page))?;
+
+            for sig in page {
+                let slot = sig.get("slot").and_then(Value::as_i64);
+
+                if slot != Some(sale.slot) || !sig.get("err").map_or(true, Value::is_null) {
+                    continue;
+                }
+
+                let signature = sig
+                    .get("signature")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| anyhow!("Missing signature in response: {}", sig))?;
+
+                let tx = self
+                    .call(
+                        "getTransaction",
+                        json!([signature, {
+                            "encoding": "json",
+                            "commitment": "confirmed",
+                            "maxSupportedTransactionVersion": 0,
+                        }]),
+                    )
+                    .await?;
+
+                let keys = account_keys(&tx)?;
+                if keys.contains(&sale.buyer.as_str()) && keys.contains(&sale.seller.as_str()) {
+                    return Ok(Some(tx));
+                }
+            }
+
+            // Signatures are returned newest first, so stop once the page
+            // reaches past the slot of the sale
+            match page.last() {
+                Some(last)
+                    if page.len() == SIGNATURE_PAGE_SIZE
+                        && last.get("slot").and_then(Value::as_i64) >= Some(sale.slot) =>
+                {
+                    before = last
+                        .get("signature")
+                        .and_then(Value::as_str)
+                        .map(ToOwned::to_owned);
+                },
+                _ => return Ok(None),
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value> {
+        self.http.json_rpc(&self.endpoint, method, params).await
+    }
+}
+
+/// The accounts referenced by a transaction, in the order its balances are
+/// listed
+fn account_keys(tx: &Value) -> Result<Vec<&str>> {
+    let keys = tx
+        .pointer("/transaction/message/accountKeys")
+        .and_then(Value::as_array)
+        .ok_or_else(|| anyhow!("Missing account keys in transaction"))?;
+    let loaded = ["writable", "readonly"].into_iter().flat_map(|k| {
+        tx.pointer(&format!("/meta/loadedAddresses/{}", k))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+    });
+
+    keys.iter()
+        .chain(loaded)
+        .map(|k| {
+            k.as_str()
+                .ok_or_else(|| anyhow!("Invalid account key in transaction"))
+        })
+        .collect()
+}
+
+/// The net change over a transaction in each wallet's balance of the given
+/// currency, in its smallest unit, from a `getTransaction` response
+fn balance_changes(tx: &Value, currency_mint: &str) -> Result<HashMap<String, i128>> {
+    let mut changes = HashMap::new();
+
+    if currency_mint == pubkeys::SOL.to_string() {
+        let balances = |k: &str| {
+            tx.pointer(&format!("/meta/{}", k))
+                .and_then(Value::as_array)
+                .ok_or_else(|| anyhow!("Missing {} in transaction", k))
+        };
+
+        let keys = account_keys(tx)?;
+        let pre = balances("preBalances")?;
+        let post = balances("postBalances")?;
+
+        for ((key, pre), post) in keys.into_iter().zip(pre).zip(post) {
+            let change = i128::from(post.as_u64().unwrap_or_default())
+                - i128::from(pre.as_u64().unwrap_or_default());
+
+            *changes.entry(key.to_owned()).or_default() += change;
+        }
+    } else {
+        for (k, sign) in [("preTokenBalances", -1), ("postTokenBalances", 1)] {
+            let balances = tx
+                .pointer(&format!("/meta/{}", k))
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten();
+
+            for balance in balances {
+                if balance.get("mint").and_then(Value::as_str) != Some(currency_mint) {
+                    continue;
+                }
+
+                let owner = match balance.get("owner").and_then(Value::as_str) {
+                    Some(o) => o,
+                    None => continue,
+                };
+                let amount: i128 = balance
+                    .pointer("/uiTokenAmount/amount")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| anyhow!("Missing token amount in transaction"))?
+                    .parse()
+                    .context("Invalid token amount in transaction")?;
+
+                *changes.entry(owner.to_owned()).or_default() += sign * amount;
+            }
+        }
+    }
+
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::balance_changes;
+
+    #[test]
+    fn test_balance_changes() {
+        let sol = "So11111111111111111111111111111111111111112";
+        let usdc = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+        let tx = json!({
+            "transaction": { "message": { "accountKeys": ["buyer", "seller", "creator"] } },
+            "meta": {
+                "preBalances": [1000, 0, 50],
+                "postBalances": [0, 900, 150],
+                "preTokenBalances": [
+                    { "mint": usdc, "owner": "buyer", "uiTokenAmount": { "amount": "500" } },
+                    { "mint": usdc, "owner": "creator", "uiTokenAmount": { "amount": "0" } },
+                ],
+                "postTokenBalances": [
+                    { "mint": usdc, "owner": "buyer", "uiTokenAmount": { "amount": "0" } },
+                    { "mint": usdc, "owner": "seller", "uiTokenAmount": { "amount": "475" } },
+                    { "mint": usdc, "owner": "creator", "uiTokenAmount": { "amount": "25" } },
+                ],
+            },
+        });
+
+        let changes = balance_changes(&tx, sol).unwrap();
+        assert_eq!(changes["buyer"], -1000);
+        assert_eq!(changes["seller"], 900);
+        assert_eq!(changes["creator"], 100);
+
+        let changes = balance_changes(&tx, usdc).unwrap();
+        assert_eq!(changes["buyer"], -500);
+        assert_eq!(changes["seller"], 475);
+        assert_eq!(changes["creator"], 25);
+    }
+}
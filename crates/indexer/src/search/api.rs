@@ -0,0 +1,119 @@
+//! Direct calls to Meilisearch API endpoints not exposed by the Meilisearch
+//! SDK
+
+use std::time::Duration;
+
+use indexer_core::meilisearch;
+
+use crate::{prelude::*, reqwest};
+
+/// Delay between polls of an outstanding task
+const TASK_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A minimal HTTP client for the Meilisearch API
+#[derive(Debug)]
+pub struct Api {
+    http: reqwest::Client,
+    base: String,
+    key: String,
+}
+
+impl Api {
+    /// Construct a new API client from the Meilisearch connection arguments
+    ///
+    /// # Errors
+    /// This function fails if the HTTP client cannot be constructed.
+    pub fn new(args: &meilisearch::Args) -> Result<Self> {
+        Ok(Self {
+            http: reqwest::Client::new(Duration::from_secs(30))?,
+            base: args.url().trim_end_matches('/').to_owned(),
+            key: args.key().to_owned(),
+        })
+    }
+
+    /// Send a request which enqueues a task and return the status code and
+    /// response body
+    ///
+    /// # Errors
+    /// This function fails if the request fails or the response is not JSON.
+    pub async fn send(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: serde_json::Value,
+    ) -> Result<(reqwest::StatusCode, serde_json::Value)> {
+        self.http
+            .run(|h| {
+                let url = format!("{}{}", self.base, path);
+                let key = self.key.clone();
+
+                async move {
+                    let res = h
+                        .request(method, url)
+                        .bearer_auth(key)
+                        .json(&body)
+                        .send()
+                        .await?;
+                    let status = res.status();
+
+                    Ok((status, res.json::<serde_json::Value>().await?))
+                }
+            })
+            .await
+    }
+
+    /// Send a request which enqueues a task and wait for the task to succeed
+    ///
+    /// # Errors
+    /// This function fails if the request is rejected or the task fails.
+    pub async fn run_task(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: serde_json::Value,
+    ) -> Result<()> {
+        let (status, task) = self.send(method, path, body).await?;
+        ensure!(status.is_success(), "Request to {} failed: {}", path, task);
+
+        self.wait_for_task(&task).await
+    }
+
+    /// Wait for the task described by an API response to finish, failing if
+    /// the task failed
+    ///
+    /// # Errors
+    /// This function fails if the task cannot be polled or did not succeed.
+    pub async fn wait_for_task(&self, task: &serde_json::Value) -> Result<()> {
+        let uid = task
+            .get("taskUid")
+            .or_else(|| task.get("uid"))
+            .and_then(serde_json::Value::as_u64)
+            .ok_or_else(|| anyhow!("Missing task ID in response: {}", task))?;
+
+        loop {
+            let task = self
+                .http
+                .run(|h| {
+                    let url = format!("{}/tasks/{}", self.base, uid);
+                    let key = self.key.clone();
+
+                    async move {
+                        h.get(url)
+                            .bearer_auth(key)
+                            .send()
+                            .await?
+                            .error_for_status()?
+                            .json::<serde_json::Value>()
+                            .await
+                    }
+                })
+                .await?;
+
+            match task.get("status").and_then(serde_json::Value::as_str) {
+                Some("succeeded") => break Ok(()),
+                Some("failed" | "canceled") => bail!("Meilisearch task failed: {}", task),
+                _ => tokio::time::sleep(TASK_POLL_INTERVAL).await,
+            }
+        }
+    }
+}
@@ -42,13 +42,31 @@ pub struct Args {
     asset_proxy: AssetProxyArgs,
 }
 
+/// A pending change to a single document
+#[derive(Debug)]
+enum Write {
+    Upsert(super::Document),
+    Delete(String),
+}
+
+impl Write {
+    fn id(&self) -> &str {
+        match self {
+            Self::Upsert(d) => &d.id,
+            Self::Delete(i) => i,
+        }
+    }
+}
+
 /// Wrapper for handling network logic
 #[derive(Debug)]
 pub struct Client {
     db: Pool,
+    meili: MeiliClient,
+    meili_args: meilisearch::Args,
     upsert_batch: usize,
     asset_proxy: AssetProxyArgs,
-    upsert_queue: RwLock<SegQueue<(String, super::Document)>>,
+    upsert_queue: RwLock<SegQueue<(String, Write)>>,
     trigger_upsert: mpsc::Sender<()>,
 }
 
@@ -69,29 +87,24 @@ impl Client {
             asset_proxy,
         } = args;
 
-        let meili = meili.into_client();
-
-        create_index(meili.clone(), "geno_habitats", "id")
-            .await
-            .context("Failed to create Genopets habitat index")?;
-
-        create_index(meili.clone(), "name_service", "id")
-            .await
-            .context("Failed to create name service index")?;
-
-        create_index(meili.clone(), "collections", "id")
-            .await
-            .context("Failed to create collections index")?;
+        let meili_args = meili;
+        let meili = meili_args.clone().into_client();
+        let api = super::api::Api::new(&meili_args)?;
 
-        create_index(meili.clone(), "mr-collections", "id")
-            .await
-            .context("Failed to create mr-collections index")?;
+        for settings in super::settings::INDEXES {
+            settings
+                .apply(&meili, &api, settings.name)
+                .await
+                .with_context(|| format!("Failed to configure index {:?}", settings.name))?;
+        }
 
         let (trigger_upsert, upsert_rx) = mpsc::channel(1);
         let (stop_tx, stop_rx) = oneshot::channel();
 
         let arc_self = Arc::new(Self {
             db,
+            meili: meili.clone(),
+            meili_args,
             upsert_batch,
             asset_proxy,
             upsert_queue: RwLock::new(SegQueue::new()),
@@ -292,27 +305,66 @@ impl Client {
 
             debug!("Ticking document upsert for {} document(s)...", queue.len());
 
+            // Only the last write queued for each document is kept
             let map =
                 std::iter::from_fn(|| queue.pop()).fold(HashMap::default(), |mut h, (k, v)| {
-                    h.entry(k).or_insert_with(Vec::new).push(v);
+                    h.entry(k)
+                        .or_insert_with(HashMap::default)
+                        .insert(v.id().to_owned(), v);
                     h
                 });
 
             let mut futures = futures_util::stream::FuturesUnordered::new();
 
-            for (idx, docs) in &map {
+            for (idx, writes) in map {
+                let (upserts, deletes) = writes.into_values().fold(
+                    (vec![], vec![]),
+                    |(mut upserts, mut deletes), write| {
+                        match write {
+                            Write::Upsert(d) => upserts.push(d),
+                            Write::Delete(i) => deletes.push(i),
+                        }
+
+                        (upserts, deletes)
+                    },
+                );
+
                 debug!(
-                    "{} document(s) in upsert queue flagged for {:?}",
-                    docs.len(),
+                    "{} document(s) in upsert queue and {} in delete queue flagged for {:?}",
+                    upserts.len(),
+                    deletes.len(),
                     idx
                 );
 
                 if dry_run {
-                    info!("Upsert to {:?} of {:#?}", idx, serde_json::to_value(&docs));
+                    info!(
+                        "Upsert to {:?} of {:#?}",
+                        idx,
+                        serde_json::to_value(&upserts)
+                    );
+                    info!("Delete from {:?} of {:?}", idx, deletes);
                 } else {
                     let meili = meili.clone();
-                    futures
-                        .push(async move { meili.index(idx).add_or_replace(&*docs, None).await });
+                    futures.push(async move {
+                        // Mirror writes to the shadow index of a running
+                        // reindex so they are not lost when it is swapped in
+                        let shadow = meili
+                            .get_index(super::reindex::shadow_index(&idx))
+                            .await
+                            .ok();
+
+                        for index in std::iter::once(meili.index(idx)).chain(shadow) {
+                            if !upserts.is_empty() {
+                                index.add_or_replace(&upserts, None).await?;
+                            }
+
+                            if !deletes.is_empty() {
+                                index.delete_documents(&deletes).await?;
+                            }
+                        }
+
+                        Ok::<_, meilisearch::errors::Error>(())
+                    });
                 }
             }
 
@@ -341,6 +393,18 @@ impl Client {
         &self.db
     }
 
+    /// Get a reference to the Meilisearch client
+    #[must_use]
+    pub fn meili(&self) -> &MeiliClient {
+        &self.meili
+    }
+
+    /// Get a reference to the Meilisearch connection arguments
+    #[must_use]
+    pub fn meili_args(&self) -> &meilisearch::Args {
+        &self.meili_args
+    }
+
     /// Get a reference to the asset proxy arguments, used by
     /// [`proxy_url`](indexer_core::assets::proxy_url)
     #[inline]
@@ -356,9 +420,31 @@ impl Client {
         &self,
         idx: String,
         docs: D,
+    ) -> Result<()> {
+        self.queue_writes(idx, docs.into_iter().map(Write::Upsert))
+            .await
+    }
+
+    /// Delete documents by ID from the given index
+    ///
+    /// # Errors
+    /// This function fails if the HTTP call returns an error
+    pub async fn delete_documents<I: IntoIterator<Item = String>>(
+        &self,
+        idx: String,
+        ids: I,
+    ) -> Result<()> {
+        self.queue_writes(idx, ids.into_iter().map(Write::Delete))
+            .await
+    }
+
+    async fn queue_writes<W: IntoIterator<Item = Write>>(
+        &self,
+        idx: String,
+        writes: W,
     ) -> Result<()> {
         let q = self.upsert_queue.read().await;
-        std::iter::repeat(idx).zip(docs).for_each(|p| q.push(p));
+        std::iter::repeat(idx).zip(writes).for_each(|p| q.push(p));
 
         if q.len() >= self.upsert_batch {
             use mpsc::error::TrySendError;
@@ -373,21 +459,3 @@ impl Client {
         Ok(())
     }
 }
-
-async fn create_index(meili: MeiliClient, index_name: &str, primary_key: &str) -> Result<()> {
-    if let Ok(mut idx) = meili.get_index(index_name).await {
-        ensure!(
-            idx.get_primary_key()
-                .await
-                .context("Failed to check primary key name")?
-                .map_or(false, |k| k == primary_key),
-            "Primary key mismatch for index {}",
-            index_name
-        );
-    } else {
-        let task = meili.create_index(index_name, Some(primary_key)).await?;
-        meili.wait_for_task(task, None, None).await?;
-    };
-
-    Ok(())
-}
@@ -1,6 +1,9 @@
 //! Support features for the search indexer
 
+mod api;
 mod client;
+mod reindex;
+pub mod settings;
 
 use std::fmt;
 
@@ -8,13 +11,14 @@ pub use client::{Args as ClientArgs, Client};
 use indexer_core::{
     assets::AssetIdentifier,
     db::tables::{
-        metadata_collection_keys, metadata_creators, metadata_jsons, metadatas,
-        twitter_handle_name_services,
+        attributes, current_metadata_owners, listings, metadata_collection_keys, metadata_creators,
+        metadata_jsons, metadatas, twitter_handle_name_services,
     },
-    meilisearch::IndirectMetadataDocument,
+    meilisearch::{IndirectMetadataDocument, NftDocument, NFTS_INDEX},
     url::Url,
 };
 use indexer_rabbitmq::search_indexer::{self, Message};
+pub use reindex::reindex;
 
 use crate::prelude::*;
 
@@ -23,6 +27,8 @@ use crate::prelude::*;
 pub enum MessageId {
     /// The message was a direct document upsert
     Upsert,
+    /// The message was a direct document deletion
+    Delete,
     /// The message was an indirect upsert for a metadata account with the given
     /// mint
     IndirectMetadata(Pubkey),
@@ -32,6 +38,7 @@ impl fmt::Display for MessageId {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::Upsert => write!(f, "document upsert"),
+            Self::Delete => write!(f, "document deletion"),
             Self::IndirectMetadata(k) => write!(f, "indirect upsert of metadata at {}", k),
         }
     }
@@ -57,6 +64,14 @@ impl From<search_indexer::Document> for Document {
 /// This function fails if an error occurs processing the message body.
 pub async fn process_message(msg: Message, client: &Client) -> MessageResult<MessageId> {
     match msg {
+        Message::Delete { index, id } => {
+            client
+                .delete_documents(index, Some(id))
+                .await
+                .map_err(|e| MessageError::new(e, MessageId::Delete))?;
+
+            Ok(())
+        },
         Message::Upsert { index, document } => {
             client
                 .upsert_documents(index, Some(document.into()))
@@ -68,22 +83,38 @@ pub async fn process_message(msg: Message, client: &Client) -> MessageResult<Mes
         Message::IndirectMetadata { index, mint } => {
             let mint_address = mint.to_string();
             let msg_id = MessageId::IndirectMetadata(mint);
-            let doc = get_indirect_metadata(client, mint_address.clone())
-                .await
-                .map_err(|e| MessageError::new(e, msg_id))?;
-
-            client
-                .upsert_documents(
-                    index,
-                    Some(Document {
-                        id: mint_address,
-                        body: serde_json::to_value(doc)
+            let body = if index == NFTS_INDEX {
+                get_nft(client, mint_address.clone()).await.and_then(|d| {
+                    d.map(serde_json::to_value)
+                        .transpose()
+                        .context("Failed to serialize NFT document")
+                })
+            } else {
+                get_indirect_metadata(client, mint_address.clone())
+                    .await
+                    .and_then(|d| {
+                        d.map(serde_json::to_value)
+                            .transpose()
                             .context("Failed to serialize metadata document")
-                            .map_err(|e| MessageError::new(e, msg_id))?,
-                    }),
-                )
-                .await
-                .map_err(|e| MessageError::new(e, msg_id))?;
+                    })
+            }
+            .map_err(|e| MessageError::new(e, msg_id))?;
+
+            // Burned NFTs are removed rather than re-indexed
+            if let Some(body) = body {
+                client
+                    .upsert_documents(
+                        index,
+                        Some(Document {
+                            id: mint_address,
+                            body,
+                        }),
+                    )
+                    .await
+            } else {
+                client.delete_documents(index, Some(mint_address)).await
+            }
+            .map_err(|e| MessageError::new(e, msg_id))?;
 
             Ok(())
         },
@@ -93,9 +124,10 @@ pub async fn process_message(msg: Message, client: &Client) -> MessageResult<Mes
 async fn get_indirect_metadata(
     client: &Client,
     mint_address: String,
-) -> Result<IndirectMetadataDocument> {
+) -> Result<Option<IndirectMetadataDocument>> {
     let (
         metadata_address,
+        burned_at,
         name,
         image,
         collection_address,
@@ -129,6 +161,7 @@ async fn get_indirect_metadata(
                     .filter(metadata_creators::position.eq(0))
                     .select((
                         metadatas::address,
+                        metadatas::burned_at,
                         metadatas::name,
                         metadata_jsons::image,
                         metadata_collection_keys::collection_address.nullable(),
@@ -137,6 +170,7 @@ async fn get_indirect_metadata(
                     ))
                     .first::<(
                         String,
+                        Option<NaiveDateTime>,
                         String,
                         Option<String>,
                         Option<String>,
@@ -148,7 +182,25 @@ async fn get_indirect_metadata(
         })
         .await?;
 
-    let image = image
+    if burned_at.is_some() {
+        return Ok(None);
+    }
+
+    let image = proxy_image(client, image)?;
+
+    Ok(Some(IndirectMetadataDocument {
+        metadata_address,
+        mint_address,
+        name,
+        image,
+        creator_address,
+        creator_twitter_handle,
+        collection_address,
+    }))
+}
+
+fn proxy_image(client: &Client, image: Option<String>) -> Result<Option<String>> {
+    image
         .as_ref()
         .and_then(|i| Url::parse(i).ok())
         .and_then(|u| {
@@ -159,15 +211,121 @@ async fn get_indirect_metadata(
                 .transpose()
         })
         .or_else(|| image.map(Ok))
-        .transpose()?;
+        .transpose()
+}
+
+async fn get_nft(client: &Client, mint_address: String) -> Result<Option<NftDocument>> {
+    let nft = client
+        .db()
+        .run({
+            let mint_address = mint_address.clone();
+
+            move |conn| {
+                let (address, burned_at, name, description, image): (
+                    String,
+                    Option<NaiveDateTime>,
+                    String,
+                    Option<String>,
+                    Option<String>,
+                ) = metadatas::table
+                    .left_join(
+                        metadata_jsons::table
+                            .on(metadata_jsons::metadata_address.eq(metadatas::address)),
+                    )
+                    .filter(metadatas::mint_address.eq(&mint_address))
+                    .select((
+                        metadatas::address,
+                        metadatas::burned_at,
+                        metadatas::name,
+                        metadata_jsons::description.nullable(),
+                        metadata_jsons::image.nullable(),
+                    ))
+                    .first(conn)
+                    .context("Failed to load metadata")?;
+
+                if burned_at.is_some() {
+                    return Ok(None);
+                }
+
+                let traits: Vec<(Option<String>, Option<String>)> = attributes::table
+                    .filter(attributes::metadata_address.eq(&address))
+                    .select((attributes::trait_type, attributes::value))
+                    .load(conn)
+                    .context("Failed to load attributes")?;
 
-    Ok(IndirectMetadataDocument {
+                let collection: Option<String> = metadata_collection_keys::table
+                    .filter(metadata_collection_keys::metadata_address.eq(&address))
+                    .filter(metadata_collection_keys::verified)
+                    .select(metadata_collection_keys::collection_address)
+                    .first(conn)
+                    .optional()
+                    .context("Failed to load collection")?;
+
+                let creators: Vec<String> = metadata_creators::table
+                    .filter(metadata_creators::metadata_address.eq(&address))
+                    .filter(metadata_creators::verified)
+                    .select(metadata_creators::creator_address)
+                    .order_by(metadata_creators::position)
+                    .load(conn)
+                    .context("Failed to load creators")?;
+
+                let owner: Option<String> = current_metadata_owners::table
+                    .filter(current_metadata_owners::mint_address.eq(&mint_address))
+                    .select(current_metadata_owners::owner_address)
+                    .first(conn)
+                    .optional()
+                    .context("Failed to load owner")?;
+
+                let price: Option<i64> = listings::table
+                    .filter(listings::metadata.eq(&address))
+                    .filter(listings::purchase_id.is_null())
+                    .filter(listings::canceled_at.is_null())
+                    .filter(
+                        listings::expiry
+                            .is_null()
+                            .or(listings::expiry.gt(Local::now().naive_utc())),
+                    )
+                    .select(listings::price)
+                    .order_by(listings::price)
+                    .first(conn)
+                    .optional()
+                    .context("Failed to load listing price")?;
+
+                Result::<_>::Ok(Some((
+                    address,
+                    name,
+                    description,
+                    image,
+                    traits,
+                    collection,
+                    creators,
+                    owner,
+                    price,
+                )))
+            }
+        })
+        .await?;
+
+    let (metadata_address, name, description, image, traits, collection, creators, owner, price) =
+        match nft {
+            Some(n) => n,
+            None => return Ok(None),
+        };
+
+    Ok(Some(NftDocument {
         metadata_address,
         mint_address,
         name,
-        image,
-        creator_address,
-        creator_twitter_handle,
-        collection_address,
-    })
+        description,
+        image: proxy_image(client, image)?,
+        traits: traits
+            .into_iter()
+            .filter_map(|(t, v)| Some(NftDocument::format_trait(&t?, &v?)))
+            .collect(),
+        collection_address: collection,
+        creator_addresses: creators,
+        owner_address: owner,
+        listing_price: price,
+        listed: price.is_some(),
+    }))
 }
@@ -0,0 +1,370 @@
+//! Zero-downtime rebuilding of search indexes from Postgres
+//!
+//! Documents are written to a shadow index configured with the same settings
+//! as the live index, which is then atomically swapped with the live index.
+//! While the shadow index exists the search indexer writes every update to
+//! both indexes, so updates made during the rebuild survive the swap.
+
+use futures_util::StreamExt;
+use indexer_core::{
+    db::tables::{
+        collections, geno_habitat_datas, metadata_collection_keys, metadata_jsons, metadatas,
+        twitter_handle_name_services,
+    },
+    meilisearch::{tasks::Task, NFTS_INDEX},
+};
+
+use super::{api::Api, settings, Client, Document};
+use crate::{prelude::*, reqwest};
+
+/// Number of documents to build from the database concurrently
+const CONCURRENCY: usize = 16;
+
+/// The name of the shadow index used to rebuild the given index
+pub(super) fn shadow_index(index: &str) -> String {
+    format!("{}_reindex", index)
+}
+
+/// An index which can be rebuilt from the database
+#[derive(Debug, Clone, Copy)]
+enum Source {
+    Nfts,
+    GenoHabitats,
+    NameService,
+    Collections,
+    MrCollections,
+}
+
+impl Source {
+    fn for_index(index: &str) -> Result<Self> {
+        Ok(match index {
+            NFTS_INDEX => Self::Nfts,
+            "geno_habitats" => Self::GenoHabitats,
+            "name_service" => Self::NameService,
+            "collections" => Self::Collections,
+            "mr-collections" => Self::MrCollections,
+            i => bail!("Unknown search index {:?}", i),
+        })
+    }
+
+    async fn count(self, client: &Client) -> Result<i64> {
+        client
+            .db()
+            .run(move |db| match self {
+                Self::Nfts => metadatas::table
+                    .filter(metadatas::burned_at.is_null())
+                    .count()
+                    .get_result(db),
+                Self::GenoHabitats => geno_habitat_datas::table.count().get_result(db),
+                Self::NameService => twitter_handle_name_services::table.count().get_result(db),
+                Self::Collections => metadatas::table
+                    .filter(metadatas::burned_at.is_null())
+                    .filter(
+                        metadatas::mint_address.eq_any(
+                            metadata_collection_keys::table
+                                .filter(metadata_collection_keys::verified)
+                                .select(metadata_collection_keys::collection_address),
+                        ),
+                    )
+                    .count()
+                    .get_result(db),
+                Self::MrCollections => collections::table.count().get_result(db),
+            })
+            .await
+            .context("Failed to count source rows")
+    }
+
+    /// Load a page of document IDs, ordered by ID, following the given ID
+    async fn page(self, client: &Client, after: String, limit: i64) -> Result<Vec<String>> {
+        client
+            .db()
+            .run(move |db| match self {
+                Self::Nfts => metadatas::table
+                    .filter(metadatas::burned_at.is_null())
+                    .filter(metadatas::mint_address.gt(after))
+                    .select(metadatas::mint_address)
+                    .order_by(metadatas::mint_address)
+                    .limit(limit)
+                    .load(db),
+                Self::GenoHabitats => geno_habitat_datas::table
+                    .filter(geno_habitat_datas::habitat_mint.gt(after))
+                    .select(geno_habitat_datas::habitat_mint)
+                    .order_by(geno_habitat_datas::habitat_mint)
+                    .limit(limit)
+                    .load(db),
+                Self::NameService => twitter_handle_name_services::table
+                    .filter(twitter_handle_name_services::address.gt(after))
+                    .select(twitter_handle_name_services::address)
+                    .order_by(twitter_handle_name_services::address)
+                    .limit(limit)
+                    .load(db),
+                Self::Collections => metadatas::table
+                    .filter(metadatas::burned_at.is_null())
+                    .filter(
+                        metadatas::mint_address.eq_any(
+                            metadata_collection_keys::table
+                                .filter(metadata_collection_keys::verified)
+                                .select(metadata_collection_keys::collection_address),
+                        ),
+                    )
+                    .filter(metadatas::address.gt(after))
+                    .select(metadatas::address)
+                    .order_by(metadatas::address)
+                    .limit(limit)
+                    .load(db),
+                Self::MrCollections => collections::table
+                    .filter(collections::id.gt(after))
+                    .select(collections::id)
+                    .order_by(collections::id)
+                    .limit(limit)
+                    .load(db),
+            })
+            .await
+            .context("Failed to load source rows")
+    }
+
+    /// Build the document with the given ID, or `None` if it should not be
+    /// indexed
+    async fn document(self, client: &Client, id: String) -> Result<Option<Document>> {
+        let body = match self {
+            Self::Nfts => match super::get_nft(client, id.clone()).await? {
+                Some(d) => serde_json::to_value(d),
+                None => return Ok(None),
+            },
+            Self::GenoHabitats => match super::get_indirect_metadata(client, id.clone()).await? {
+                Some(d) => serde_json::to_value(d),
+                None => return Ok(None),
+            },
+            Self::NameService => {
+                let (owner, handle): (String, String) = client
+                    .db()
+                    .run({
+                        let id = id.clone();
+                        move |db| {
+                            twitter_handle_name_services::table
+                                .filter(twitter_handle_name_services::address.eq(id))
+                                .select((
+                                    twitter_handle_name_services::wallet_address,
+                                    twitter_handle_name_services::twitter_handle,
+                                ))
+                                .first(db)
+                        }
+                    })
+                    .await
+                    .context("Failed to load twitter handle")?;
+
+                Ok(serde_json::json!({ "owner": owner, "handle": handle }))
+            },
+            Self::Collections => {
+                let (mint_address, name, image): (String, String, Option<String>) = client
+                    .db()
+                    .run({
+                        let id = id.clone();
+                        move |db| {
+                            metadatas::table
+                                    .left_join(metadata_jsons::table.on(
+                                        metadata_jsons::metadata_address.eq(metadatas::address),
+                                    ))
+                                    .filter(metadatas::address.eq(id))
+                                    .select((
+                                        metadatas::mint_address,
+                                        metadatas::name,
+                                        metadata_jsons::image.nullable(),
+                                    ))
+                                    .first(db)
+                        }
+                    })
+                    .await
+                    .context("Failed to load collection metadata")?;
+
+                Ok(serde_json::json!({
+                    "name": name,
+                    "image": super::proxy_image(client, image)?,
+                    "mint_address": mint_address,
+                }))
+            },
+            Self::MrCollections => {
+                let (name, image, magic_eden_id, verified, twitter, discord, website): (
+                    String,
+                    String,
+                    Option<String>,
+                    Option<String>,
+                    Option<String>,
+                    Option<String>,
+                    Option<String>,
+                ) = client
+                    .db()
+                    .run({
+                        let id = id.clone();
+                        move |db| {
+                            collections::table
+                                .filter(collections::id.eq(id))
+                                .select((
+                                    collections::name,
+                                    collections::image,
+                                    collections::magic_eden_id,
+                                    collections::verified_collection_address,
+                                    collections::twitter_url,
+                                    collections::discord_url,
+                                    collections::website_url,
+                                ))
+                                .first(db)
+                        }
+                    })
+                    .await
+                    .context("Failed to load collection")?;
+
+                Ok(serde_json::json!({
+                    "name": name,
+                    "image": super::proxy_image(client, Some(image))?,
+                    "magic_eden_id": magic_eden_id,
+                    "verified_collection_address": verified,
+                    "twitter_url": twitter,
+                    "discord_url": discord,
+                    "website_url": website,
+                }))
+            },
+        }
+        .context("Failed to serialize document")?;
+
+        Ok(Some(Document { id, body }))
+    }
+}
+
+/// Rebuild the given search index from the database without interrupting
+/// searches against it.
+///
+/// # Errors
+/// This function fails if the index cannot be rebuilt from the database or if
+/// a database query or Meilisearch API call fails.
+pub async fn reindex(client: &Client, index: &str, batch_size: i64) -> Result<()> {
+    let source = Source::for_index(index)?;
+    let settings =
+        settings::find(index).ok_or_else(|| anyhow!("Missing settings for index {:?}", index))?;
+    let meili = client.meili();
+    let api = Api::new(client.meili_args())?;
+    let shadow = shadow_index(index);
+
+    // Clear out any shadow index left behind by an interrupted reindex
+    delete_index(client, &shadow).await?;
+
+    settings
+        .apply(meili, &api, &shadow)
+        .await
+        .context("Failed to create shadow index")?;
+
+    let total = source.count(client).await?;
+    info!("Reindexing {:?} with {} source row(s)", index, total);
+
+    let mut after = String::new();
+    let mut done = 0_i64;
+    let mut skipped = 0_usize;
+
+    loop {
+        let ids = source.page(client, after.clone(), batch_size).await?;
+
+        let last = match ids.last() {
+            Some(l) => l.clone(),
+            None => break,
+        };
+        let count = ids.len();
+        done += i64::try_from(count)?;
+
+        let docs: Vec<_> = futures_util::stream::iter(ids)
+            .map(|id| async move {
+                let res = source.document(client, id.clone()).await;
+                (id, res)
+            })
+            .buffer_unordered(CONCURRENCY)
+            .filter_map(|(id, res)| async move {
+                res.map_err(|e| warn!("Skipping document {:?}: {:?}", id, e))
+                    .ok()
+                    .flatten()
+            })
+            .collect()
+            .await;
+
+        skipped += count - docs.len();
+
+        let task = meili
+            .index(&shadow)
+            .add_or_replace(&docs, Some(settings.primary_key))
+            .await
+            .context("Failed to upload documents to shadow index")?;
+        let task = meili.wait_for_task(task, None, None).await?;
+        ensure!(
+            !matches!(task, Task::Failed { .. }),
+            "Meilisearch failed to index documents: {:?}",
+            task
+        );
+
+        #[allow(clippy::cast_precision_loss)]
+        let pct = if total > 0 {
+            done as f64 * 100.0 / total as f64
+        } else {
+            100.0
+        };
+        info!(
+            "Reindexing {:?}: {}/{} ({:.1}%) row(s) processed",
+            index, done, total, pct
+        );
+
+        after = last;
+    }
+
+    if skipped > 0 {
+        warn!(
+            "Skipped {} source row(s) with no indexable document",
+            skipped
+        );
+    }
+
+    swap_indexes(&api, index, &shadow)
+        .await
+        .context("Failed to swap shadow index into place")?;
+    info!("Swapped rebuilt index into {:?}", index);
+
+    // The shadow index now holds the previous documents
+    delete_index(client, &shadow).await?;
+
+    Ok(())
+}
+
+async fn delete_index(client: &Client, uid: &str) -> Result<()> {
+    let meili = client.meili();
+
+    if meili.get_index(uid).await.is_ok() {
+        let task = meili
+            .delete_index(uid)
+            .await
+            .with_context(|| format!("Failed to delete index {:?}", uid))?;
+        meili.wait_for_task(task, None, None).await?;
+    }
+
+    Ok(())
+}
+
+/// Atomically exchange the documents and settings of two indexes.
+///
+/// This calls the `/swap-indexes` endpoint directly, as it is not exposed by
+/// the Meilisearch SDK.
+async fn swap_indexes(api: &Api, a: &str, b: &str) -> Result<()> {
+    let (status, task) = api
+        .send(
+            reqwest::Method::POST,
+            "/swap-indexes",
+            serde_json::json!([{ "indexes": [a, b] }]),
+        )
+        .await?;
+
+    ensure!(
+        status != reqwest::StatusCode::NOT_FOUND,
+        "This Meilisearch server does not support swapping indexes (v0.30 or newer is \
+         required); the rebuilt index has been left in place"
+    );
+    ensure!(status.is_success(), "Index swap failed: {}", task);
+
+    api.wait_for_task(&task)
+        .await
+        .context("Index swap task failed")
+}
@@ -0,0 +1,197 @@
+//! Declarative settings for the Meilisearch indexes maintained by the search
+//! indexer
+
+use indexer_core::meilisearch::{
+    client::Client as MeiliClient, settings::Settings, tasks::Task, NftDocument, NFTS_INDEX,
+};
+
+use super::api::Api;
+use crate::{prelude::*, reqwest};
+
+/// The default Meilisearch ranking rules, in order
+pub const DEFAULT_RANKING_RULES: &[&str] = &[
+    "words",
+    "typo",
+    "proximity",
+    "attribute",
+    "sort",
+    "exactness",
+];
+
+/// Settings for a single Meilisearch index
+#[derive(Debug, Clone, Copy)]
+pub struct IndexSettings {
+    /// The name (UID) of the index
+    pub name: &'static str,
+    /// The primary key of the index's documents
+    pub primary_key: &'static str,
+    /// Ranking rules applied to search results, in order
+    pub ranking_rules: &'static [&'static str],
+    /// Attributes searched by full-text queries, in order of importance
+    pub searchable_attributes: &'static [&'static str],
+    /// Attributes usable in search filters and facets
+    pub filterable_attributes: &'static [&'static str],
+    /// Attributes usable for sorting search results
+    pub sortable_attributes: &'static [&'static str],
+    /// Groups of words treated as equivalent by full-text queries
+    pub synonyms: &'static [(&'static str, &'static [&'static str])],
+    /// Words ignored by full-text queries
+    pub stop_words: &'static [&'static str],
+    /// Maximum number of values returned for each facet, or the Meilisearch
+    /// default if `None`
+    pub max_values_per_facet: Option<usize>,
+}
+
+/// Settings for every index managed by the search indexer
+pub const INDEXES: &[IndexSettings] = &[
+    IndexSettings {
+        name: "geno_habitats",
+        primary_key: "id",
+        ranking_rules: DEFAULT_RANKING_RULES,
+        searchable_attributes: &["name", "creator_twitter_handle"],
+        filterable_attributes: &["mint_address", "creator_address", "collection_address"],
+        sortable_attributes: &["name"],
+        synonyms: &[],
+        stop_words: &[],
+        max_values_per_facet: None,
+    },
+    IndexSettings {
+        name: "name_service",
+        primary_key: "id",
+        ranking_rules: DEFAULT_RANKING_RULES,
+        searchable_attributes: &["handle"],
+        filterable_attributes: &["owner"],
+        sortable_attributes: &["handle"],
+        synonyms: &[],
+        stop_words: &[],
+        max_values_per_facet: None,
+    },
+    IndexSettings {
+        name: "collections",
+        primary_key: "id",
+        ranking_rules: DEFAULT_RANKING_RULES,
+        searchable_attributes: &["name"],
+        filterable_attributes: &["mint_address"],
+        sortable_attributes: &["name"],
+        synonyms: &[],
+        stop_words: &[],
+        max_values_per_facet: None,
+    },
+    IndexSettings {
+        name: "mr-collections",
+        primary_key: "id",
+        ranking_rules: DEFAULT_RANKING_RULES,
+        searchable_attributes: &["name"],
+        filterable_attributes: &["magic_eden_id", "verified_collection_address"],
+        sortable_attributes: &["name"],
+        synonyms: &[],
+        stop_words: &[],
+        max_values_per_facet: None,
+    },
+    IndexSettings {
+        name: NFTS_INDEX,
+        primary_key: "id",
+        ranking_rules: DEFAULT_RANKING_RULES,
+        searchable_attributes: NftDocument::SEARCHABLE_ATTRIBUTES,
+        filterable_attributes: NftDocument::FILTERABLE_ATTRIBUTES,
+        sortable_attributes: NftDocument::SORTABLE_ATTRIBUTES,
+        synonyms: &[("pfp", &["profile picture", "avatar"])],
+        stop_words: &["the", "a", "an", "of"],
+        // Every trait of every NFT is a value of the single `traits` facet, so
+        // the default limit of 100 truncates the trait counts of most
+        // collections
+        max_values_per_facet: Some(10_000),
+    },
+];
+
+/// Look up the settings for the index with the given name
+#[must_use]
+pub fn find(name: &str) -> Option<&'static IndexSettings> {
+    INDEXES.iter().find(|i| i.name == name)
+}
+
+impl IndexSettings {
+    /// Convert these settings into a Meilisearch settings object
+    #[must_use]
+    pub fn to_settings(&self) -> Settings {
+        Settings::new()
+            .with_ranking_rules(self.ranking_rules)
+            .with_searchable_attributes(self.searchable_attributes)
+            .with_filterable_attributes(self.filterable_attributes)
+            .with_sortable_attributes(self.sortable_attributes)
+            .with_synonyms(
+                self.synonyms
+                    .iter()
+                    .flat_map(|(word, syns)| {
+                        // Meilisearch synonyms are one-way, so register every
+                        // word in the group against the rest
+                        let group: Vec<_> =
+                            std::iter::once(*word).chain(syns.iter().copied()).collect();
+
+                        group
+                            .clone()
+                            .into_iter()
+                            .map(move |w| (w, group.iter().copied().filter(|o| *o != w).collect()))
+                    })
+                    .collect::<std::collections::HashMap<&str, Vec<&str>>>(),
+            )
+            .with_stop_words(self.stop_words)
+    }
+
+    /// Create the index named `uid` if it does not exist and apply these
+    /// settings to it, waiting for Meilisearch to finish processing them
+    ///
+    /// # Errors
+    /// This function fails if the index exists with a different primary key or
+    /// if any Meilisearch API call or task fails.
+    pub async fn apply(&self, meili: &MeiliClient, api: &Api, uid: &str) -> Result<()> {
+        if let Ok(mut idx) = meili.get_index(uid).await {
+            ensure!(
+                idx.get_primary_key()
+                    .await
+                    .context("Failed to check primary key name")?
+                    .map_or(false, |k| k == self.primary_key),
+                "Primary key mismatch for index {}",
+                uid
+            );
+        } else {
+            let task = meili
+                .create_index(uid, Some(self.primary_key))
+                .await
+                .with_context(|| format!("Failed to create index {:?}", uid))?;
+            let task = meili.wait_for_task(task, None, None).await?;
+            ensure!(
+                !matches!(task, Task::Failed { .. }),
+                "Meilisearch failed to create index {:?}: {:?}",
+                uid,
+                task
+            );
+        }
+
+        let task = meili
+            .index(uid)
+            .set_settings(&self.to_settings())
+            .await
+            .with_context(|| format!("Failed to update settings for index {:?}", uid))?;
+        let task = meili.wait_for_task(task, None, None).await?;
+        ensure!(
+            !matches!(task, Task::Failed { .. }),
+            "Meilisearch failed to update settings for index {:?}: {:?}",
+            uid,
+            task
+        );
+
+        // Faceting settings are not exposed by the Meilisearch SDK
+        if let Some(max) = self.max_values_per_facet {
+            api.run_task(
+                reqwest::Method::PATCH,
+                &format!("/indexes/{}/settings/faceting", uid),
+                serde_json::json!({ "maxValuesPerFacet": max }),
+            )
+            .await
+            .with_context(|| format!("Failed to update faceting settings for index {:?}", uid))?;
+        }
+
+        Ok(())
+    }
+}
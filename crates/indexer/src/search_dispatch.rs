@@ -1,4 +1,7 @@
-use indexer_core::{clap, meilisearch};
+use indexer_core::{
+    clap,
+    meilisearch::{self, NFTS_INDEX},
+};
 use indexer_rabbitmq::search_indexer::{Document, Message, Producer, QueueType};
 use serde::Serialize;
 
@@ -36,12 +39,6 @@ pub struct MRCollectionDocument {
 /// Arguments to build the ``search_dispatch`` client
 #[derive(Debug, Clone, clap::Parser)]
 pub struct Args {
-    /// Pass this flag to run backfill search upsert jobs
-    ///
-    /// Be aware that this can have severe performance implications.
-    #[clap(long, env)]
-    backfill_search: bool,
-
     /// Meilisearch arguments
     /// Contains Key and URL
     #[clap(flatten)]
@@ -52,7 +49,6 @@ pub struct Args {
 #[derive(Debug)]
 pub struct Client {
     producer: Producer,
-    backfill: bool,
     meili_client: meilisearch::client::Client,
 }
 
@@ -65,16 +61,12 @@ impl Client {
     pub async fn new(
         conn: &indexer_rabbitmq::lapin::Connection,
         queue: QueueType,
-        Args {
-            backfill_search,
-            search,
-        }: Args,
+        Args { search }: Args,
     ) -> Result<Self> {
         Ok(Self {
             producer: Producer::new(conn, queue)
                 .await
                 .context("Couldn't create AMQP search producer")?,
-            backfill: backfill_search,
             meili_client: search.into_client(),
         })
     }
@@ -101,7 +93,8 @@ impl Client {
         id: impl std::fmt::Display,
         body: impl Serialize,
     ) -> Result<()> {
-        if is_for_backfill && !self.backfill {
+        // Backfills are performed by the search indexer's `reindex` command
+        if is_for_backfill {
             return Ok(());
         }
 
@@ -117,6 +110,17 @@ impl Client {
             .context("Failed to send upsert message")
     }
 
+    #[inline]
+    async fn dispatch_delete(&self, index: &'static str, id: impl std::fmt::Display) -> Result<()> {
+        self.producer
+            .write(Message::Delete {
+                index: index.to_owned(),
+                id: id.to_string(),
+            })
+            .await
+            .context("Failed to send delete message")
+    }
+
     #[inline]
     async fn dispatch_indirect_meta(
         &self,
@@ -124,7 +128,8 @@ impl Client {
         index: &'static str,
         mint: Pubkey,
     ) -> Result<()> {
-        if is_for_backfill && !self.backfill {
+        // Backfills are performed by the search indexer's `reindex` command
+        if is_for_backfill {
             return Ok(());
         }
 
@@ -146,6 +151,15 @@ impl Client {
             .await
     }
 
+    /// Dispatches NFT document message to the AMQP queue
+    ///
+    /// # Errors
+    /// This function fails if the AMQP payload cannot be sent.
+    pub async fn upsert_nft(&self, is_for_backfill: bool, mint: Pubkey) -> Result<()> {
+        self.dispatch_indirect_meta(is_for_backfill, NFTS_INDEX, mint)
+            .await
+    }
+
     /// Dispatches collection document message to the AMQP queue
     ///
     /// # Errors
@@ -189,4 +203,42 @@ impl Client {
         self.dispatch_upsert(is_for_backfill, "name_service", key, body)
             .await
     }
+
+    /// Dispatches deletions of the NFT and Genopets habitat documents for a
+    /// mint to the AMQP queue
+    ///
+    /// # Errors
+    /// This function fails if the AMQP payload cannot be sent.
+    pub async fn delete_nft(&self, mint: Pubkey) -> Result<()> {
+        self.dispatch_delete(NFTS_INDEX, mint).await?;
+        self.dispatch_delete("geno_habitats", mint).await
+    }
+
+    /// Dispatches deletion of a collection document to the AMQP queue
+    ///
+    /// # Errors
+    /// This function fails if the AMQP payload cannot be sent.
+    pub async fn delete_collection(&self, key: String) -> Result<()> {
+        debug_assert!(key.parse::<Pubkey>().is_ok());
+
+        self.dispatch_delete("collections", key).await
+    }
+
+    /// Dispatches deletion of a moonrank collection document to the AMQP
+    /// queue
+    ///
+    /// # Errors
+    /// This function fails if the AMQP payload cannot be sent.
+    pub async fn delete_mr_collection(&self, key: String) -> Result<()> {
+        self.dispatch_delete("mr-collections", key).await
+    }
+
+    /// Dispatches deletion of a twitter name service document to the AMQP
+    /// queue
+    ///
+    /// # Errors
+    /// This function fails if the AMQP payload cannot be sent.
+    pub async fn delete_twitter_handle(&self, key: String) -> Result<()> {
+        self.dispatch_delete("name_service", key).await
+    }
 }
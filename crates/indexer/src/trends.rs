@@ -0,0 +1,70 @@
+//! Collection trends worker.  Periodically refreshes the normalized and
+//! flagged volumes of the `collection_trends` table from the price candles,
+//! so trend queries can sort on them without aggregating candles per request.
+
+use std::time::Duration;
+
+use indexer_core::{clap, db::mutations::collection_trends};
+
+use crate::{db::Pool, prelude::*};
+
+/// Arguments for the collection trends worker
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Time to wait between refreshes of the trend volumes, in seconds
+    #[clap(long, env, default_value_t = 300)]
+    refresh_interval: u64,
+}
+
+/// Shared state for the collection trends worker
+#[derive(Debug)]
+pub struct Worker {
+    db: Pool,
+    refresh_interval: Duration,
+}
+
+impl Worker {
+    /// Construct a new worker
+    #[must_use]
+    pub fn new(args: Args, db: Pool) -> Self {
+        let Args { refresh_interval } = args;
+
+        Self {
+            db,
+            refresh_interval: Duration::from_secs(refresh_interval),
+        }
+    }
+
+    /// Refresh the trend volumes until interrupted.  Failed refreshes are
+    /// logged and retried after the refresh interval.
+    ///
+    /// # Errors
+    /// This function fails if waiting for an interrupt fails
+    pub async fn run(&self) -> Result<()> {
+        loop {
+            let start = std::time::Instant::now();
+
+            match self
+                .db
+                .run(|db| collection_trends::refresh_volumes(db))
+                .await
+            {
+                Ok(()) => debug!(
+                    "Refreshed collection trend volumes in {:?}",
+                    start.elapsed()
+                ),
+                Err(e) => error!("Failed to refresh collection trend volumes: {:?}", e),
+            }
+
+            tokio::select! {
+                () = tokio::time::sleep(self.refresh_interval) => (),
+                r = tokio::signal::ctrl_c() => {
+                    r.context("Failed to wait for interrupt")?;
+                    warn!("Interrupt received, shutting down...");
+
+                    break Ok(());
+                },
+            }
+        }
+    }
+}
@@ -212,6 +212,8 @@ async fn process() -> Result<()> {
         .json::<Vec<Data>>()
         .await?;
 
+    remove_unlisted_collections(&pool, &search, &collections).await?;
+
     dispatch_documents(collections.clone(), search, asset_proxy).await?;
 
     futures_util::stream::iter(collections.into_iter().map(|data| {
@@ -337,6 +339,41 @@ async fn upsert_collection_data(
     Ok(())
 }
 
+/// Remove collections no longer listed by MoonRank from the database and the
+/// search index
+async fn remove_unlisted_collections(
+    pool: &Pool,
+    search: &search_dispatch::Client,
+    listed: &[Data],
+) -> Result<()> {
+    // Never treat an empty response as every collection being delisted
+    if listed.is_empty() {
+        return Ok(());
+    }
+
+    let conn = pool.get()?;
+    let listed: Vec<&str> = listed.iter().map(|d| d.collection.id.as_str()).collect();
+
+    let removed: Vec<String> = conn.build_transaction().read_write().run(|| {
+        let removed = delete(collections::table.filter(collections::id.ne_all(&listed)))
+            .returning(collections::id)
+            .get_results::<String>(&conn)?;
+
+        delete(collection_mints::table.filter(collection_mints::collection_id.eq_any(&removed)))
+            .execute(&conn)?;
+
+        Result::<_>::Ok(removed)
+    })?;
+
+    for id in removed {
+        info!("Removing delisted collection {:?}", id);
+
+        search.delete_mr_collection(id).await?;
+    }
+
+    Ok(())
+}
+
 async fn dispatch_documents(
     collections: Vec<Data>,
     search: search_dispatch::Client,
@@ -16,5 +16,7 @@ done
 export DATABASE_NAME
 export DATABASE_PASSWD
 export DATABASE_URL
+export AWS_ACCESS_KEY_ID
+export AWS_SECRET_ACCESS_KEY
 
 docker-compose "$@"
@@ -0,0 +1,3 @@
+#!/bin/sh
+
+bin/holaplex-indexer-trends